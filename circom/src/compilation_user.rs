@@ -1,7 +1,10 @@
-use crate::VERSION;
 use ansi_term::Colour;
+use circom::Circuit;
 use compiler::compiler_interface;
-use compiler::compiler_interface::{Config, VCP};
+use program_structure::diagnostics::print_progress;
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::Report;
+use program_structure::file_definition::FileLibrary;
 
 pub struct CompilerConfig {
     pub c_folder: String,
@@ -9,50 +12,179 @@ pub struct CompilerConfig {
     pub c_file: String,
     pub dat_file: String,
     pub c_flag: bool,
+    pub c_library_flag: bool,
+    pub js_folder: String,
+    pub wat_file: String,
+    pub wasm_file: String,
+    pub wasm_flag: bool,
+    pub wat_flag: bool,
+    pub rust_folder: String,
+    pub rust_file: String,
+    pub rust_dat_file: String,
+    pub rust_flag: bool,
+    pub wtns_flag: bool,
+    pub wtns_input: String,
+    pub wtns_file: String,
     pub debug_output: bool,
-    pub produce_input_log: bool,
-    pub vcp: VCP,
 }
 
-pub fn compile(config: CompilerConfig) -> Result<(), ()> {
+// Writes the witness generators of the circuit that the user asked for
+pub fn compile(circuit: &Circuit, config: CompilerConfig) -> Result<(), ()> {
+    if config.debug_output {
+        compiler_interface::write_debug_output(circuit).map_err(|_| output_error("ir_log"))?;
+    }
+
     if config.c_flag {
-        let circuit = compiler_interface::run_compiler(
-            config.vcp,
-            Config {
-                debug_output: config.debug_output,
-                produce_input_log: config.produce_input_log,
-            },
-            VERSION,
-        )?;
-
-        if config.c_flag {
-            compiler_interface::write_c(
-                &circuit,
-                &config.c_folder,
-                &config.c_run_name,
-                &config.c_file,
-                &config.dat_file,
-            )?;
-            println!(
-                "{} {} and {}",
+        compiler_interface::check_c_support(circuit)
+            .map_err(|msg| Report::print_error(msg, ReportCode::InvalidOption))?;
+        compiler_interface::write_c(
+            circuit,
+            &config.c_folder,
+            &config.c_run_name,
+            &config.c_file,
+            &config.dat_file,
+            config.c_library_flag,
+        )
+        .map_err(|_| output_error(&config.c_folder))?;
+        print_progress(&format!(
+            "{} {} and {}",
+            Colour::Green.paint("Written successfully:"),
+            config.c_file,
+            config.dat_file
+        ));
+        let entry = if config.c_library_flag {
+            "circom_witness.h, circom_witness.cpp"
+        } else {
+            "main.cpp"
+        };
+        print_progress(&format!(
+            "{} {}/{}, {}, {}, {}, {}, {}, and {}",
+            Colour::Green.paint("Written successfully:"),
+            &config.c_folder,
+            entry,
+            "circom.hpp".to_string(),
+            "calcwit.hpp".to_string(),
+            "calcwit.cpp".to_string(),
+            "fr.hpp".to_string(),
+            "fr.cpp".to_string(),
+            "Makefile".to_string()
+        ));
+    }
+
+    if config.wasm_flag || config.wat_flag {
+        compiler_interface::write_wasm(circuit, &config.js_folder, &config.wat_file)
+            .map_err(|_| output_error(&config.js_folder))?;
+        if config.wasm_flag {
+            wat_to_wasm(&config.wat_file, &config.wasm_file)?;
+            if !config.wat_flag {
+                let _ = std::fs::remove_file(&config.wat_file);
+            }
+            print_progress(&format!(
+                "{} {}",
                 Colour::Green.paint("Written successfully:"),
-                config.c_file,
-                config.dat_file
-            );
-            println!(
-                "{} {}/{}, {}, {}, {}, {}, {}, and {}",
+                config.wasm_file
+            ));
+        }
+        if config.wat_flag {
+            print_progress(&format!(
+                "{} {}",
                 Colour::Green.paint("Written successfully:"),
-                &config.c_folder,
-                "main.cpp".to_string(),
-                "circom.hpp".to_string(),
-                "calcwit.hpp".to_string(),
-                "calcwit.cpp".to_string(),
-                "fr.hpp".to_string(),
-                "fr.cpp".to_string(),
-                "Makefile".to_string()
-            );
+                config.wat_file
+            ));
         }
+        print_progress(&format!(
+            "{} {}/generate_witness.js and witness_calculator.js",
+            Colour::Green.paint("Written successfully:"),
+            &config.js_folder
+        ));
+    }
+
+    if config.rust_flag {
+        compiler_interface::write_rust(
+            circuit,
+            &config.rust_folder,
+            &config.c_run_name,
+            &config.rust_file,
+            &config.rust_dat_file,
+        )
+        .map_err(|_| output_error(&config.rust_folder))?;
+        print_progress(&format!(
+            "{} {} and {}",
+            Colour::Green.paint("Written successfully:"),
+            config.rust_file,
+            config.rust_dat_file
+        ));
+        print_progress(&format!(
+            "{} {}/Cargo.toml, src/lib.rs, src/main.rs, src/calcwit.rs, and src/fr.rs",
+            Colour::Green.paint("Written successfully:"),
+            &config.rust_folder
+        ));
+    }
+
+    if config.wtns_flag {
+        compiler_interface::write_wtns(circuit, &config.wtns_input, &config.wtns_file)
+            .map_err(|msg| Report::print_error(msg, ReportCode::WitnessError))?;
+        print_progress(&format!(
+            "{} {}",
+            Colour::Green.paint("Written successfully:"),
+            config.wtns_file
+        ));
     }
 
     Ok(())
 }
+
+fn output_error(path: &str) {
+    Report::print_error(
+        format!("Could not write the output in the given path: {}", path),
+        ReportCode::OutputError,
+    );
+}
+
+fn wat_to_wasm(wat_file: &str, wasm_file: &str) -> Result<(), ()> {
+    use std::fs::File;
+    use std::io::BufWriter;
+    use std::io::Write;
+    use wast::parser::{self, ParseBuffer};
+    use wast::Wat;
+
+    let wat_contents = std::fs::read_to_string(wat_file).map_err(|_err| output_error(wat_file))?;
+    let buf = ParseBuffer::new(&wat_contents).map_err(|error| {
+        Report::print_error(
+            format!("Error translating the circuit from wat to wasm.\n\nException encountered when reading WAT: {}", error),
+            ReportCode::ErrorWat2Wasm,
+        )
+    })?;
+    let result_wasm_contents = parser::parse::<Wat>(&buf);
+    match result_wasm_contents {
+        Result::Err(error) => {
+            let report = Report::error(
+                format!("Error translating the circuit from wat to wasm.\n\nException encountered when parsing WAT: {}", error),
+                ReportCode::ErrorWat2Wasm,
+            );
+            Report::print_reports(&[report], &FileLibrary::new());
+            Err(())
+        }
+        Result::Ok(mut wat) => {
+            let wasm_contents = wat.module.encode();
+            match wasm_contents {
+                Result::Err(error) => {
+                    let report = Report::error(
+                        format!("Error translating the circuit from wat to wasm.\n\nException encountered when encoding WASM: {}", error),
+                        ReportCode::ErrorWat2Wasm,
+                    );
+                    Report::print_reports(&[report], &FileLibrary::new());
+                    Err(())
+                }
+                Result::Ok(wasm_contents) => {
+                    let file = File::create(wasm_file).map_err(|_err| output_error(wasm_file))?;
+                    let mut writer = BufWriter::new(file);
+                    writer
+                        .write_all(&wasm_contents)
+                        .map_err(|_err| output_error(wasm_file))?;
+                    writer.flush().map_err(|_err| output_error(wasm_file))
+                }
+            }
+        }
+    }
+}
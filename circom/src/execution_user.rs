@@ -1,66 +1,96 @@
 use ansi_term::Colour;
-use compiler::hir::very_concrete_program::VCP;
+use circom::{CompilationArtifacts, VCP};
 use constraint_writers::debug_writer::DebugWriter;
+use constraint_writers::log_writer::Log;
 use constraint_writers::ConstraintExporter;
-use program_structure::program_archive::ProgramArchive;
+use program_structure::diagnostics::print_progress;
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::Report;
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::Write;
 
 pub struct ExecutionConfig {
     pub r1cs: String,
+    pub smt2: String,
     pub sym: String,
-    pub input_map: String,
+    pub io_map: String,
     pub json_constraints: String,
     pub json_substitutions: String,
-    pub no_rounds: usize,
-    pub flag_s: bool,
+    pub stats: String,
+    pub air: String,
+    pub air_columns: String,
     pub flag_f: bool,
-    pub flag_p: bool,
-    pub flag_old_heuristics: bool,
-    pub flag_verbose: bool,
-    pub inspect_constraints_flag: bool,
     pub sym_flag: bool,
     pub r1cs_flag: bool,
-    pub input_map_flag: bool,
+    pub smt2_flag: bool,
+    pub io_map_flag: bool,
     pub json_substitution_flag: bool,
     pub json_constraint_flag: bool,
-    pub prime: String,
+    pub air_flag: bool,
 }
 
-pub fn execute_project(
-    program_archive: ProgramArchive,
-    config: ExecutionConfig,
-) -> Result<VCP, ()> {
-    use constraint_generation::{build_circuit, BuildConfig};
+// Writes the files of the constraints that the user asked for
+pub fn write_outputs(artifacts: &CompilationArtifacts, config: ExecutionConfig) -> Result<(), ()> {
+    let success = Colour::Green.paint("template instances");
+    print_progress(&format!("{}: {}", success, artifacts.template_instances));
+    if config.json_substitution_flag {
+        if config.flag_f {
+            // without simplification there are no substitutions
+            use constraint_writers::json_writer::SubstitutionJSON;
+            let substitution_log = SubstitutionJSON::new(&config.json_substitutions).unwrap();
+            let _ = substitution_log.end();
+        }
+        print_progress(&format!(
+            "{} {}",
+            Colour::Green.paint("Written successfully:"),
+            config.json_substitutions
+        ));
+    }
+    if let Some(statistics) = &artifacts.statistics {
+        statistics.print();
+        if statistics.write_json(&config.stats).is_err() {
+            Report::print_error(
+                format!(
+                    "Could not write the statistics of the templates: {}",
+                    config.stats
+                ),
+                ReportCode::OutputError,
+            );
+            return Result::Err(());
+        }
+        print_progress(&format!(
+            "{} {}",
+            Colour::Green.paint("Written successfully:"),
+            config.stats
+        ));
+    }
     let debug = DebugWriter::new(config.json_constraints).unwrap();
-    let build_config = BuildConfig {
-        no_rounds: config.no_rounds,
-        flag_json_sub: config.json_substitution_flag,
-        json_substitutions: config.json_substitutions,
-        flag_s: config.flag_s,
-        flag_f: config.flag_f,
-        flag_p: config.flag_p,
-        flag_verbose: config.flag_verbose,
-        inspect_constraints: config.inspect_constraints_flag,
-        flag_old_heuristics: config.flag_old_heuristics,
-        prime: config.prime,
-    };
-    let custom_gates = program_archive.custom_gates;
-    let (exporter, vcp) = build_circuit(program_archive, build_config)?;
+    let exporter = artifacts.constraints.as_ref();
+    let custom_gates = artifacts.program_archive.custom_gates;
+    let public_inputs = artifacts
+        .program_archive
+        .get_public_inputs_main_component();
     if config.r1cs_flag {
-        generate_output_r1cs(&config.r1cs, exporter.as_ref(), custom_gates)?;
+        generate_output_r1cs(&config.r1cs, exporter, custom_gates)?;
+    }
+    if config.smt2_flag {
+        generate_output_smt2(&config.smt2, exporter)?;
     }
     if config.sym_flag {
-        generate_output_sym(&config.sym, exporter.as_ref())?;
+        generate_output_sym(&config.sym, exporter)?;
     }
     if config.json_constraint_flag {
-        generate_json_constraints(&debug, exporter.as_ref())?;
+        generate_json_constraints(&debug, exporter)?;
+    }
+    if config.air_flag {
+        generate_output_air(&config.air, &config.air_columns, exporter)?;
     }
-    if config.input_map_flag {
-        generate_input_map(&config.input_map, &vcp)?;
+    if config.io_map_flag {
+        generate_io_map(&config.io_map, &artifacts.vcp, public_inputs)?;
     }
-    Result::Ok(vcp)
+    Result::Ok(())
 }
 
 fn generate_output_r1cs(
@@ -68,13 +98,35 @@ fn generate_output_r1cs(
     exporter: &dyn ConstraintExporter,
     custom_gates: bool,
 ) -> Result<(), ()> {
-    if let Result::Ok(()) = exporter.r1cs(file, custom_gates) {
-        println!("{} {}", Colour::Green.paint("Written successfully:"), file);
+    if let Result::Ok(log) = exporter.r1cs(file, custom_gates) {
+        Log::print(&log);
+        print_progress(&format!(
+            "{} {}",
+            Colour::Green.paint("Written successfully:"),
+            file
+        ));
+        Result::Ok(())
+    } else {
+        Report::print_error(
+            format!("Could not write the output in the given path: {}", file),
+            ReportCode::OutputError,
+        );
+        Result::Err(())
+    }
+}
+
+fn generate_output_smt2(file: &str, exporter: &dyn ConstraintExporter) -> Result<(), ()> {
+    if let Result::Ok(()) = exporter.smt2(file) {
+        print_progress(&format!(
+            "{} {}",
+            Colour::Green.paint("Written successfully:"),
+            file
+        ));
         Result::Ok(())
     } else {
-        eprintln!(
-            "{}",
-            Colour::Red.paint("Could not write the output in the given path")
+        Report::print_error(
+            format!("Could not write the output in the given path: {}", file),
+            ReportCode::OutputError,
         );
         Result::Err(())
     }
@@ -82,12 +134,41 @@ fn generate_output_r1cs(
 
 fn generate_output_sym(file: &str, exporter: &dyn ConstraintExporter) -> Result<(), ()> {
     if let Result::Ok(()) = exporter.sym(file) {
-        println!("{} {}", Colour::Green.paint("Written successfully:"), file);
+        print_progress(&format!(
+            "{} {}",
+            Colour::Green.paint("Written successfully:"),
+            file
+        ));
+        Result::Ok(())
+    } else {
+        Report::print_error(
+            format!("Could not write the output in the given path: {}", file),
+            ReportCode::OutputError,
+        );
+        Result::Err(())
+    }
+}
+
+fn generate_output_air(
+    file: &str,
+    columns_file: &str,
+    exporter: &dyn ConstraintExporter,
+) -> Result<(), ()> {
+    if let Result::Ok(()) = exporter.air(file, columns_file) {
+        print_progress(&format!(
+            "{} {} and {}",
+            Colour::Green.paint("Written successfully:"),
+            file,
+            columns_file
+        ));
         Result::Ok(())
     } else {
-        eprintln!(
-            "{}",
-            Colour::Red.paint("Could not write the output in the given path")
+        Report::print_error(
+            format!(
+                "Could not write the output in the given path: {} and {}",
+                file, columns_file
+            ),
+            ReportCode::OutputError,
         );
         Result::Err(())
     }
@@ -98,49 +179,111 @@ fn generate_json_constraints(
     exporter: &dyn ConstraintExporter,
 ) -> Result<(), ()> {
     if let Ok(()) = exporter.json_constraints(&debug) {
-        println!(
+        print_progress(&format!(
             "{} {}",
             Colour::Green.paint("Constraints written in:"),
             debug.json_constraints
-        );
+        ));
         Result::Ok(())
     } else {
-        eprintln!(
-            "{}",
-            Colour::Red.paint("Could not write the output in the given path")
+        Report::print_error(
+            format!("Could not write the output in the given path: {}", debug.json_constraints),
+            ReportCode::OutputError,
         );
         Result::Err(())
     }
 }
 
-fn generate_input_map(file: &str, vcp: &VCP) -> Result<(), ()> {
-    let f = |file, vcp: &VCP| {
-        #[derive(Serialize)]
-        pub struct InputMap(pub Vec<(String, usize, usize)>);
-
-        let initial_node = vcp.get_main_id();
+// Entry of the io map, the witness of the signal is given as a range when its
+// elements are consecutive in the witness and one by one otherwise
+#[derive(Serialize)]
+struct IOSignal {
+    name: String,
+    #[serde(rename = "type")]
+    xtype: &'static str,
+    public: bool,
+    dimensions: Vec<usize>,
+    tags: BTreeMap<String, Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    witness_range: Option<(usize, usize)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    witness_indices: Option<Vec<Option<usize>>>,
+}
 
-        use program_structure::ast::SignalType::*;
-        let mut input_list = vec![];
-        for s in &vcp.templates[initial_node].signals {
-            if s.xtype == Input {
-                input_list.push((s.name.clone(), s.dag_local_id, s.size()));
-            }
-        }
+fn build_io_map(vcp: &VCP, public_inputs: &[String]) -> Vec<IOSignal> {
+    use program_structure::ast::SignalType::*;
+    let witness: HashMap<usize, usize> = vcp
+        .get_witness_list()
+        .iter()
+        .enumerate()
+        .map(|(index, signal)| (*signal, index))
+        .collect();
+    let main = &vcp.templates[vcp.get_main_id()];
+    let mut io_map = vec![];
+    for s in &main.signals {
+        let (xtype, public) = match s.xtype {
+            Output => ("output", true),
+            Input => ("input", public_inputs.contains(&s.name)),
+            Intermediate => continue,
+        };
+        let tags = main
+            .signals_to_tags
+            .get(&s.name)
+            .map(|tags| {
+                tags.iter()
+                    .map(|(tag, value)| (tag.clone(), value.as_ref().map(|v| v.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let indices: Vec<Option<usize>> = (s.dag_local_id..s.dag_local_id + s.size())
+            .map(|signal| witness.get(&signal).cloned())
+            .collect();
+        let start = indices.first().cloned().flatten();
+        let consecutive = start.is_some()
+            && indices
+                .iter()
+                .enumerate()
+                .all(|(i, index)| *index == start.map(|start| start + i));
+        let (witness_range, witness_indices) = if consecutive {
+            let start = start.unwrap();
+            (Some((start, start + indices.len())), None)
+        } else {
+            (None, Some(indices))
+        };
+        io_map.push(IOSignal {
+            name: format!("main.{}", s.name),
+            xtype,
+            public,
+            dimensions: s.lengths.clone(),
+            tags,
+            witness_range,
+            witness_indices,
+        });
+    }
+    io_map
+}
 
-        let mut file = File::open(file)?;
-        file.write(&bincode::serialize(&InputMap(input_list)).unwrap())
+fn generate_io_map(file: &str, vcp: &VCP, public_inputs: &[String]) -> Result<(), ()> {
+    let f = |file, vcp: &VCP| -> std::io::Result<()> {
+        let io_map = build_io_map(vcp, public_inputs);
+        let mut file = File::create(file)?;
+        serde_json::to_writer_pretty(&mut file, &io_map)?;
+        file.flush()
     };
 
     let result = f(file, vcp);
 
     if result.is_ok() {
-        println!("{} {}", Colour::Green.paint("Written successfully:"), file);
+        print_progress(&format!(
+            "{} {}",
+            Colour::Green.paint("Written successfully:"),
+            file
+        ));
         Ok(())
     } else {
-        eprintln!(
-            "{}",
-            Colour::Red.paint("Could not write the output in the given path")
+        Report::print_error(
+            format!("Could not write the output in the given path: {}", file),
+            ReportCode::OutputError,
         );
         Err(())
     }
@@ -0,0 +1,52 @@
+use crate::input_user::FormatInput;
+use ansi_term::Colour;
+use program_structure::error_definition::Report;
+use program_structure::file_definition::FileLibrary;
+use std::fs;
+
+// Formats each file on its own, the includes are not followed. With --check
+// nothing is written and the files that are not formatted are listed.
+pub fn format(input: &FormatInput) -> Result<(), ()> {
+    let mut failed = false;
+    let mut unformatted = Vec::new();
+    for file in &input.files {
+        let source = fs::read_to_string(file).map_err(|_err| {
+            eprintln!(
+                "{}",
+                Colour::Red.paint(format!("Could not read the file {}", file.display()))
+            )
+        })?;
+        let mut file_library = FileLibrary::new();
+        let file_id = file_library.add_file(file.display().to_string(), source.clone());
+        let formatted = match parser::format_source(&source, file_id) {
+            Result::Ok(formatted) => formatted,
+            Result::Err(reports) => {
+                Report::print_reports(&reports, &file_library);
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if input.check {
+            unformatted.push(file);
+        } else {
+            fs::write(file, formatted).map_err(|_err| {
+                eprintln!(
+                    "{}",
+                    Colour::Red.paint(format!("Could not write the file {}", file.display()))
+                )
+            })?;
+            println!("{} {}", Colour::Green.paint("Formatted"), file.display());
+        }
+    }
+    for file in &unformatted {
+        eprintln!("{} {}", Colour::Red.paint("Not formatted:"), file.display());
+    }
+    if failed || !unformatted.is_empty() {
+        Result::Err(())
+    } else {
+        Result::Ok(())
+    }
+}
@@ -1,22 +1,153 @@
+use clap::ArgMatches;
+use program_structure::diagnostics::{print_progress, set_diagnostics_format};
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::Report;
 use std::path::PathBuf;
 
+// the compiler runs a circuit by default, the rest of the commands work on
+// the files it outputs
+pub enum Command {
+    Compile(Box<Input>),
+    VerifyWitness(VerifyWitnessInput),
+    Format(FormatInput),
+    Test(TestInput),
+}
+
+impl Command {
+    pub fn new() -> Result<Command, ()> {
+        let matches = input_processing::view();
+        if let Some(verify_matches) = matches.subcommand_matches("verify-witness") {
+            VerifyWitnessInput::new(verify_matches).map(Command::VerifyWitness)
+        } else if let Some(format_matches) = matches.subcommand_matches("fmt") {
+            FormatInput::new(format_matches).map(Command::Format)
+        } else if let Some(test_matches) = matches.subcommand_matches("test") {
+            TestInput::new(test_matches).map(Command::Test)
+        } else {
+            Input::new(&matches).map(|input| Command::Compile(Box::new(input)))
+        }
+    }
+}
+
+pub struct VerifyWitnessInput {
+    pub r1cs: PathBuf,
+    pub witness: PathBuf,
+    pub sym: PathBuf,
+}
+
+impl VerifyWitnessInput {
+    fn new(matches: &ArgMatches) -> Result<VerifyWitnessInput, ()> {
+        set_diagnostics_format(
+            input_processing::get_diagnostics_format(matches),
+            matches.value_of("diagnostics_output").map(String::from),
+        );
+        let r1cs = input_processing::get_file(matches, "r1cs")?;
+        let witness = input_processing::get_file(matches, "witness")?;
+        let sym = match matches.value_of("sym") {
+            Some(sym) => PathBuf::from(sym),
+            None => r1cs.with_extension(SYM),
+        };
+        Result::Ok(VerifyWitnessInput { r1cs, witness, sym })
+    }
+    pub fn r1cs_file(&self) -> &str {
+        self.r1cs.to_str().unwrap()
+    }
+    pub fn witness_file(&self) -> &str {
+        self.witness.to_str().unwrap()
+    }
+    pub fn sym_file(&self) -> &str {
+        self.sym.to_str().unwrap()
+    }
+}
+
+pub struct FormatInput {
+    pub files: Vec<PathBuf>,
+    pub check: bool,
+}
+
+impl FormatInput {
+    fn new(matches: &ArgMatches) -> Result<FormatInput, ()> {
+        use ansi_term::Colour;
+        let mut files = Vec::new();
+        for file in matches.values_of("files").unwrap() {
+            let route = PathBuf::from(file);
+            if !route.is_file() {
+                return Result::Err(eprintln!(
+                    "{}",
+                    Colour::Red.paint(format!("File does not exist: {}", route.display()))
+                ));
+            }
+            files.push(route);
+        }
+        let check = matches.is_present("check");
+        Result::Ok(FormatInput { files, check })
+    }
+}
+
+pub struct TestInput {
+    pub input_program: PathBuf,
+    pub prime: String,
+    pub link_libraries: Vec<PathBuf>,
+}
+
+impl TestInput {
+    fn new(matches: &ArgMatches) -> Result<TestInput, ()> {
+        let input_program = input_processing::get_file(matches, "input")?;
+        let prime = input_processing::get_prime(matches)?;
+        let link_libraries = input_processing::get_link_libraries(matches);
+        Result::Ok(TestInput {
+            input_program,
+            prime,
+            link_libraries,
+        })
+    }
+    pub fn input_file(&self) -> &str {
+        self.input_program.to_str().unwrap()
+    }
+    pub fn prime(&self) -> String {
+        self.prime.clone()
+    }
+    pub fn get_link_libraries(&self) -> &Vec<PathBuf> {
+        &self.link_libraries
+    }
+}
+
 pub struct Input {
     pub input_program: PathBuf,
     pub out_r1cs: PathBuf,
+    pub out_smt2: PathBuf,
     pub out_json_constraints: PathBuf,
     pub out_json_substitutions: PathBuf,
+    pub out_stats: PathBuf,
+    pub out_air: PathBuf,
+    pub out_air_columns: PathBuf,
+    pub out_cache: Option<PathBuf>,
     pub out_c_run_name: String,
     pub out_c_folder: PathBuf,
     pub out_c_code: PathBuf,
     pub out_c_dat: PathBuf,
-    pub out_input_map: PathBuf,
+    pub out_js_folder: PathBuf,
+    pub out_wat_code: PathBuf,
+    pub out_wasm_code: PathBuf,
+    pub out_rust_folder: PathBuf,
+    pub out_rust_code: PathBuf,
+    pub out_rust_dat: PathBuf,
+    pub out_io_map: PathBuf,
     pub out_sym: PathBuf,
+    pub out_wtns: PathBuf,
+    pub wtns_input: Option<PathBuf>,
     pub c_flag: bool,
+    pub c_library_flag: bool,
+    pub wasm_flag: bool,
+    pub wat_flag: bool,
+    pub rust_flag: bool,
     pub r1cs_flag: bool,
+    pub smt2_flag: bool,
     pub sym_flag: bool,
-    pub input_map_flag: bool,
+    pub io_map_flag: bool,
     pub json_constraint_flag: bool,
     pub json_substitution_flag: bool,
+    pub stats_flag: bool,
+    pub air_flag: bool,
     pub main_inputs_flag: bool,
     pub print_ir_flag: bool,
     pub fast_flag: bool,
@@ -24,6 +155,7 @@ pub struct Input {
     pub parallel_simplification_flag: bool,
     pub flag_old_heuristics: bool,
     pub inspect_constraints_flag: bool,
+    pub inspect_deep_flag: bool,
     pub no_rounds: usize,
     pub flag_verbose: bool,
     pub prime: String,
@@ -31,40 +163,76 @@ pub struct Input {
 }
 
 const R1CS: &'static str = "r1cs";
+const SMT2: &'static str = "smt2";
 const CPP: &'static str = "cpp";
 const DAT: &'static str = "dat";
+const JS: &'static str = "js";
+const WAT: &'static str = "wat";
+const WASM: &'static str = "wasm";
+const RUST: &'static str = "rust";
+const RS: &'static str = "rs";
 const SYM: &'static str = "sym";
+const CACHE: &'static str = ".circom_cache";
 const JSON: &'static str = "json";
-const MAP: &'static str = "map";
+const WTNS: &'static str = "wtns";
 
 impl Input {
-    pub fn new() -> Result<Input, ()> {
+    fn new(matches: &ArgMatches) -> Result<Input, ()> {
         use ansi_term::Colour;
         use input_processing::SimplificationStyle;
-        let matches = input_processing::view();
-        let input = input_processing::get_input(&matches)?;
+        // set first, every message after this point follows the format
+        set_diagnostics_format(
+            input_processing::get_diagnostics_format(matches),
+            matches.value_of("diagnostics_output").map(String::from),
+        );
+        let input = input_processing::get_input(matches)?;
         let mut file_name = input.file_stem().unwrap().to_str().unwrap().to_string();
-        let output_path = input_processing::get_output_path(&matches)?;
+        let output_path = input_processing::get_output_path(matches)?;
 
-        let c_flag = input_processing::get_c(&matches);
+        let c_library_flag = input_processing::get_c_library(matches);
+        let c_flag = input_processing::get_c(matches) || c_library_flag;
 
-        if c_flag && (file_name == "main" || file_name == "fr" || file_name == "calcwit") {
-            println!("{}", Colour::Yellow.paint(format!("The name {} is reserved in Circom when using de --c flag. The files generated for your circuit will use the name {}_c instead of {}.", file_name, file_name, file_name)));
+        if c_flag
+            && (file_name == "main"
+                || file_name == "fr"
+                || file_name == "calcwit"
+                || (c_library_flag && file_name == "circom_witness"))
+        {
+            print_progress(&Colour::Yellow.paint(format!("The name {} is reserved in Circom when using de --c flag. The files generated for your circuit will use the name {}_c instead of {}.", file_name, file_name, file_name)).to_string());
             file_name = format!("{}_c", file_name)
         };
         let output_c_path = Input::build_folder(&output_path, &file_name, CPP);
-        let o_style = input_processing::get_simplification_style(&matches)?;
-        let link_libraries = input_processing::get_link_libraries(&matches);
+        let output_js_path = Input::build_folder(&output_path, &file_name, JS);
+        let output_rust_path = Input::build_folder(&output_path, &file_name, RUST);
+        let o_style = input_processing::get_simplification_style(matches)?;
+        let prime = input_processing::get_prime(matches)?;
+        let air_flag = input_processing::get_air(matches);
+        if air_flag && prime != "m31" {
+            Report::print_error(
+                "The --air output is only available for the m31 prime".to_string(),
+                ReportCode::InvalidOption,
+            );
+            return Result::Err(());
+        }
+        let link_libraries = input_processing::get_link_libraries(matches);
         Result::Ok(Input {
             //field: P_BN128,
             input_program: input,
             out_r1cs: Input::build_output(&output_path, &file_name, R1CS),
+            out_smt2: Input::build_output(&output_path, &file_name, SMT2),
             out_c_folder: output_c_path.clone(),
             out_c_run_name: file_name.clone(),
             out_c_code: Input::build_output(&output_c_path, &file_name, CPP),
             out_c_dat: Input::build_output(&output_c_path, &file_name, DAT),
+            out_js_folder: output_js_path.clone(),
+            out_wat_code: Input::build_output(&output_js_path, &file_name, WAT),
+            out_wasm_code: Input::build_output(&output_js_path, &file_name, WASM),
+            out_rust_folder: output_rust_path.clone(),
+            out_rust_code: Input::build_output(&output_rust_path.join("src"), "circuit", RS),
+            out_rust_dat: Input::build_output(&output_rust_path, &file_name, DAT),
             out_sym: Input::build_output(&output_path, &file_name, SYM),
-            out_input_map: Input::build_output(&output_path, &file_name, MAP),
+            out_wtns: Input::build_output(&output_path, &file_name, WTNS),
+            wtns_input: input_processing::get_wtns_input(matches)?,
             out_json_constraints: Input::build_output(
                 &output_path,
                 &format!("{}_constraints", file_name),
@@ -75,14 +243,34 @@ impl Input {
                 &format!("{}_substitutions", file_name),
                 JSON,
             ),
+            out_io_map: Input::build_output(&output_path, &format!("{}_io_map", file_name), JSON),
+            out_stats: Input::build_output(&output_path, &format!("{}_stats", file_name), JSON),
+            out_air: Input::build_output(&output_path, &format!("{}_air", file_name), JSON),
+            out_air_columns: Input::build_output(
+                &output_path,
+                &format!("{}_columns", file_name),
+                JSON,
+            ),
+            out_cache: if input_processing::get_no_cache(matches) {
+                None
+            } else {
+                Some(output_path.join(CACHE))
+            },
             c_flag: c_flag,
-            r1cs_flag: input_processing::get_r1cs(&matches),
-            sym_flag: input_processing::get_sym(&matches),
-            input_map_flag: input_processing::get_input_map(&matches),
-            main_inputs_flag: input_processing::get_main_inputs_log(&matches),
-            json_constraint_flag: input_processing::get_json_constraints(&matches),
-            json_substitution_flag: input_processing::get_json_substitutions(&matches),
-            print_ir_flag: input_processing::get_ir(&matches),
+            c_library_flag,
+            wasm_flag: input_processing::get_wasm(matches),
+            wat_flag: input_processing::get_wat(matches),
+            rust_flag: input_processing::get_rust(matches),
+            r1cs_flag: input_processing::get_r1cs(matches),
+            smt2_flag: input_processing::get_smt2(matches),
+            sym_flag: input_processing::get_sym(matches),
+            io_map_flag: input_processing::get_io_map(matches),
+            main_inputs_flag: input_processing::get_main_inputs_log(matches),
+            json_constraint_flag: input_processing::get_json_constraints(matches),
+            json_substitution_flag: input_processing::get_json_substitutions(matches),
+            stats_flag: input_processing::get_stats(matches),
+            air_flag,
+            print_ir_flag: input_processing::get_ir(matches),
             no_rounds: if let SimplificationStyle::O2(r) = o_style {
                 r
             } else {
@@ -90,11 +278,12 @@ impl Input {
             },
             fast_flag: o_style == SimplificationStyle::O0,
             reduced_simplification_flag: o_style == SimplificationStyle::O1,
-            parallel_simplification_flag: input_processing::get_parallel_simplification(&matches),
-            inspect_constraints_flag: input_processing::get_inspect_constraints(&matches),
-            flag_old_heuristics: input_processing::get_flag_old_heuristics(&matches),
-            flag_verbose: input_processing::get_flag_verbose(&matches),
-            prime: input_processing::get_prime(&matches)?,
+            parallel_simplification_flag: input_processing::get_parallel_simplification(matches),
+            inspect_constraints_flag: input_processing::get_inspect_constraints(matches),
+            inspect_deep_flag: input_processing::get_inspect_deep(matches),
+            flag_old_heuristics: input_processing::get_flag_old_heuristics(matches),
+            flag_verbose: input_processing::get_flag_verbose(matches),
+            prime,
             link_libraries,
         })
     }
@@ -122,12 +311,15 @@ impl Input {
     pub fn r1cs_file(&self) -> &str {
         self.out_r1cs.to_str().unwrap()
     }
+    pub fn smt2_file(&self) -> &str {
+        self.out_smt2.to_str().unwrap()
+    }
     pub fn sym_file(&self) -> &str {
         self.out_sym.to_str().unwrap()
     }
 
-    pub fn input_map_file(&self) -> &str {
-        self.out_input_map.to_str().unwrap()
+    pub fn io_map_file(&self) -> &str {
+        self.out_io_map.to_str().unwrap()
     }
 
     pub fn c_folder(&self) -> &str {
@@ -146,27 +338,89 @@ impl Input {
     pub fn json_constraints_file(&self) -> &str {
         self.out_json_constraints.to_str().unwrap()
     }
+    pub fn air_file(&self) -> &str {
+        self.out_air.to_str().unwrap()
+    }
+    pub fn air_columns_file(&self) -> &str {
+        self.out_air_columns.to_str().unwrap()
+    }
+    pub fn cache_folder(&self) -> Option<&str> {
+        self.out_cache
+            .as_ref()
+            .map(|folder| folder.to_str().unwrap())
+    }
     pub fn json_substitutions_file(&self) -> &str {
         self.out_json_substitutions.to_str().unwrap()
     }
+    pub fn stats_file(&self) -> &str {
+        self.out_stats.to_str().unwrap()
+    }
+    pub fn js_folder(&self) -> &str {
+        self.out_js_folder.to_str().unwrap()
+    }
+    pub fn wat_file(&self) -> &str {
+        self.out_wat_code.to_str().unwrap()
+    }
+    pub fn wasm_file(&self) -> &str {
+        self.out_wasm_code.to_str().unwrap()
+    }
+    pub fn rust_folder(&self) -> &str {
+        self.out_rust_folder.to_str().unwrap()
+    }
+    pub fn rust_file(&self) -> &str {
+        self.out_rust_code.to_str().unwrap()
+    }
+    pub fn rust_dat_file(&self) -> &str {
+        self.out_rust_dat.to_str().unwrap()
+    }
+    pub fn wtns_file(&self) -> &str {
+        self.out_wtns.to_str().unwrap()
+    }
+    pub fn wtns_input_file(&self) -> &str {
+        self.wtns_input.as_ref().unwrap().to_str().unwrap()
+    }
     pub fn c_flag(&self) -> bool {
         self.c_flag
     }
+    pub fn c_library_flag(&self) -> bool {
+        self.c_library_flag
+    }
+    pub fn wasm_flag(&self) -> bool {
+        self.wasm_flag
+    }
+    pub fn wat_flag(&self) -> bool {
+        self.wat_flag
+    }
+    pub fn rust_flag(&self) -> bool {
+        self.rust_flag
+    }
+    pub fn wtns_flag(&self) -> bool {
+        self.wtns_input.is_some()
+    }
     pub fn unsimplified_flag(&self) -> bool {
         self.fast_flag
     }
     pub fn r1cs_flag(&self) -> bool {
         self.r1cs_flag
     }
-    pub fn input_map_flag(&self) -> bool {
-        self.input_map_flag
+    pub fn smt2_flag(&self) -> bool {
+        self.smt2_flag
+    }
+    pub fn io_map_flag(&self) -> bool {
+        self.io_map_flag
     }
     pub fn json_constraints_flag(&self) -> bool {
         self.json_constraint_flag
     }
+    pub fn air_flag(&self) -> bool {
+        self.air_flag
+    }
     pub fn json_substitutions_flag(&self) -> bool {
         self.json_substitution_flag
     }
+    pub fn stats_flag(&self) -> bool {
+        self.stats_flag
+    }
     pub fn main_inputs_flag(&self) -> bool {
         self.main_inputs_flag
     }
@@ -179,6 +433,9 @@ impl Input {
     pub fn inspect_constraints_flag(&self) -> bool {
         self.inspect_constraints_flag
     }
+    pub fn inspect_deep_flag(&self) -> bool {
+        self.inspect_deep_flag
+    }
     pub fn flag_verbose(&self) -> bool {
         self.flag_verbose
     }
@@ -200,8 +457,10 @@ impl Input {
 }
 mod input_processing {
     use crate::VERSION;
-    use ansi_term::Colour;
-    use clap::{App, Arg, ArgMatches};
+    use clap::{App, Arg, ArgMatches, SubCommand};
+    use program_structure::diagnostics::DiagnosticsFormat;
+    use program_structure::error_code::ReportCode;
+    use program_structure::error_definition::Report;
     use std::path::{Path, PathBuf};
 
     pub fn get_input(matches: &ArgMatches) -> Result<PathBuf, ()> {
@@ -214,10 +473,24 @@ mod input_processing {
             } else {
                 "".to_owned()
             };
-            Result::Err(eprintln!(
-                "{}",
-                Colour::Red.paint("Input file does not exist".to_owned() + &route)
-            ))
+            Report::print_error(
+                "Input file does not exist".to_owned() + &route,
+                ReportCode::FileOs,
+            );
+            Result::Err(())
+        }
+    }
+
+    pub fn get_file(matches: &ArgMatches, name: &str) -> Result<PathBuf, ()> {
+        let route = Path::new(matches.value_of(name).unwrap()).to_path_buf();
+        if route.is_file() {
+            Result::Ok(route)
+        } else {
+            Report::print_error(
+                format!("File does not exist: {}", route.display()),
+                ReportCode::FileOs,
+            );
+            Result::Err(())
         }
     }
 
@@ -226,7 +499,8 @@ mod input_processing {
         if route.is_dir() {
             Result::Ok(route)
         } else {
-            Result::Err(eprintln!("{}", Colour::Red.paint("invalid output path")))
+            Report::print_error("invalid output path".to_string(), ReportCode::InvalidOption);
+            Result::Err(())
         }
     }
 
@@ -254,10 +528,11 @@ mod input_processing {
                         Ok(SimplificationStyle::O2(no_rounds))
                     }
                 } else {
-                    Result::Err(eprintln!(
-                        "{}",
-                        Colour::Red.paint("invalid number of rounds")
-                    ))
+                    Report::print_error(
+                        "invalid number of rounds".to_string(),
+                        ReportCode::InvalidOption,
+                    );
+                    Result::Err(())
                 }
             }
 
@@ -266,6 +541,10 @@ mod input_processing {
         }
     }
 
+    pub fn get_air(matches: &ArgMatches) -> bool {
+        matches.is_present("print_air")
+    }
+
     pub fn get_json_constraints(matches: &ArgMatches) -> bool {
         matches.is_present("print_json_c")
     }
@@ -274,22 +553,60 @@ mod input_processing {
         matches.is_present("print_json_sub")
     }
 
+    pub fn get_stats(matches: &ArgMatches) -> bool {
+        matches.is_present("print_stats")
+    }
+
     pub fn get_sym(matches: &ArgMatches) -> bool {
         matches.is_present("print_sym")
     }
 
-    pub fn get_input_map(matches: &ArgMatches) -> bool {
-        matches.is_present("print_input_map")
+    pub fn get_io_map(matches: &ArgMatches) -> bool {
+        matches.is_present("print_io_map")
     }
 
     pub fn get_r1cs(matches: &ArgMatches) -> bool {
         matches.is_present("print_r1cs")
     }
 
+    pub fn get_smt2(matches: &ArgMatches) -> bool {
+        matches.is_present("print_smt2")
+    }
+
     pub fn get_c(matches: &ArgMatches) -> bool {
         matches.is_present("print_c")
     }
 
+    pub fn get_c_library(matches: &ArgMatches) -> bool {
+        matches.is_present("print_c_library")
+    }
+
+    pub fn get_wasm(matches: &ArgMatches) -> bool {
+        matches.is_present("print_wasm")
+    }
+
+    pub fn get_wat(matches: &ArgMatches) -> bool {
+        matches.is_present("print_wat")
+    }
+
+    pub fn get_rust(matches: &ArgMatches) -> bool {
+        matches.is_present("print_rust")
+    }
+
+    pub fn get_wtns_input(matches: &ArgMatches) -> Result<Option<PathBuf>, ()> {
+        match matches.value_of("wtns_input") {
+            Some(route) if Path::new(route).is_file() => Ok(Some(Path::new(route).to_path_buf())),
+            Some(route) => {
+                Report::print_error(
+                    format!("Witness input file does not exist: {}", route),
+                    ReportCode::FileOs,
+                );
+                Result::Err(())
+            }
+            None => Ok(None),
+        }
+    }
+
     pub fn get_main_inputs_log(matches: &ArgMatches) -> bool {
         matches.is_present("main_inputs_log")
     }
@@ -302,13 +619,29 @@ mod input_processing {
         matches.is_present("print_ir")
     }
     pub fn get_inspect_constraints(matches: &ArgMatches) -> bool {
-        matches.is_present("inspect_constraints")
+        matches.is_present("inspect_constraints") || get_inspect_deep(matches)
+    }
+
+    pub fn get_inspect_deep(matches: &ArgMatches) -> bool {
+        matches.is_present("inspect_deep")
+    }
+
+    pub fn get_diagnostics_format(matches: &ArgMatches) -> DiagnosticsFormat {
+        match matches.value_of("diagnostics_format") {
+            Some("json") => DiagnosticsFormat::Json,
+            Some("sarif") => DiagnosticsFormat::Sarif,
+            _ => DiagnosticsFormat::Human,
+        }
     }
 
     pub fn get_flag_verbose(matches: &ArgMatches) -> bool {
         matches.is_present("flag_verbose")
     }
 
+    pub fn get_no_cache(matches: &ArgMatches) -> bool {
+        matches.is_present("no_cache")
+    }
+
     pub fn get_flag_old_heuristics(matches: &ArgMatches) -> bool {
         matches.is_present("flag_old_heuristics")
     }
@@ -327,7 +660,8 @@ mod input_processing {
                 {
                     Ok(String::from(matches.value_of("prime").unwrap()))
                 } else {
-                    Result::Err(eprintln!("{}", Colour::Red.paint("invalid prime number")))
+                    Report::print_error("invalid prime number".to_string(), ReportCode::InvalidOption);
+                    Result::Err(())
                 }
             }
 
@@ -335,6 +669,25 @@ mod input_processing {
         }
     }
 
+    // shared by the compiler and the commands that report diagnostics
+    fn diagnostics_format_arg() -> Arg<'static, 'static> {
+        Arg::with_name("diagnostics_format")
+            .long("diagnostics-format")
+            .takes_value(true)
+            .possible_values(&["human", "json", "sarif"])
+            .default_value("human")
+            .display_order(803)
+            .help("Format of the errors and warnings. With json and sarif they are written as a single document when the compilation ends")
+    }
+
+    fn diagnostics_output_arg() -> Arg<'static, 'static> {
+        Arg::with_name("diagnostics_output")
+            .long("diagnostics-output")
+            .takes_value(true)
+            .display_order(804)
+            .help("File where the json or sarif diagnostics are written, instead of the standard output")
+    }
+
     pub fn view() -> ArgMatches<'static> {
         App::new("circom compiler")
             .version(VERSION)
@@ -394,6 +747,13 @@ mod input_processing {
                     .display_order(120)
                     .help("Outputs the constraints in json format"),
             )
+            .arg(
+                Arg::with_name("print_air")
+                    .long("air")
+                    .takes_value(false)
+                    .display_order(125)
+                    .help("Outputs the constraints as an M31 AIR with one gate per row and the wiring of the witness to the trace cells"),
+            )
             .arg(
                 Arg::with_name("print_ir")
                     .long("irout")
@@ -409,6 +769,15 @@ mod input_processing {
                     .display_order(801)
                     .help("Does an additional check over the constraints produced"),
             )
+            .arg(
+                Arg::with_name("inspect_deep")
+                    .long("inspect_deep")
+                    .takes_value(false)
+                    .display_order(802)
+                    .help("Does the checks of --inspect and also checks that the signals assigned with <-- are uniquely determined by the inputs of their template"),
+            )
+            .arg(diagnostics_format_arg())
+            .arg(diagnostics_output_arg())
             .arg(
                 Arg::with_name("print_json_sub")
                     .long("simplification_substitution")
@@ -416,6 +785,13 @@ mod input_processing {
                     .display_order(980)
                     .help("Outputs the substitution applied in the simplification phase in json format"),
             )
+            .arg(
+                Arg::with_name("print_stats")
+                    .long("stats")
+                    .takes_value(false)
+                    .display_order(985)
+                    .help("Outputs the constraints and signals of every template instance in json format and prints them as a table"),
+            )
             .arg(
                 Arg::with_name("print_sym")
                     .long("sym")
@@ -431,11 +807,18 @@ mod input_processing {
                     .help("Outputs the constraints in r1cs format"),
             )
             .arg(
-                Arg::with_name("print_input_map")
-                    .long("input_map")
+                Arg::with_name("print_smt2")
+                    .long("smt2")
+                    .takes_value(false)
+                    .display_order(35)
+                    .help("Outputs the constraints as SMT-LIB assertions over the finite field"),
+            )
+            .arg(
+                Arg::with_name("print_io_map")
+                    .long("io_map")
                     .takes_value(false)
                     .display_order(40)
-                    .help("Outputs the input map"),
+                    .help("Outputs the inputs and outputs of the main component and their witness indexes in json format"),
             )
             .arg(
                 Arg::with_name("link_libraries")
@@ -446,6 +829,20 @@ mod input_processing {
                 .display_order(330)
                 .help("Adds directory to library search path"),
             )
+            .arg(
+                Arg::with_name("print_wasm")
+                    .long("wasm")
+                    .takes_value(false)
+                    .display_order(130)
+                    .help("Compiles the circuit to wasm"),
+            )
+            .arg(
+                Arg::with_name("print_wat")
+                    .long("wat")
+                    .takes_value(false)
+                    .display_order(140)
+                    .help("Compiles the circuit to wat"),
+            )
             .arg(
                 Arg::with_name("print_c")
                     .long("c")
@@ -454,6 +851,28 @@ mod input_processing {
                     .display_order(150)
                     .help("Compiles the circuit to c"),
             )
+            .arg(
+                Arg::with_name("print_c_library")
+                    .long("c-lib")
+                    .takes_value(false)
+                    .display_order(155)
+                    .help("Compiles the circuit to a c library with a C header to compute witnesses, instead of an executable"),
+            )
+            .arg(
+                Arg::with_name("print_rust")
+                    .long("rust")
+                    .takes_value(false)
+                    .display_order(160)
+                    .help("Compiles the circuit to a Rust crate that computes the witness"),
+            )
+            .arg(
+                Arg::with_name("wtns_input")
+                    .long("wtns")
+                    .takes_value(true)
+                    .value_name("input.json")
+                    .display_order(170)
+                    .help("Computes the witness for the given inputs without the C++ witness generator"),
+            )
             .arg(
                 Arg::with_name("parallel_simplification")
                     .long("parallel")
@@ -477,6 +896,13 @@ mod input_processing {
                     .display_order(800)
                     .help("Shows logs during compilation"),
             )
+            .arg(
+                Arg::with_name("no_cache")
+                    .long("no-cache")
+                    .takes_value(false)
+                    .display_order(805)
+                    .help("Executes every template instance instead of reusing the ones stored in .circom_cache in the output folder"),
+            )
             .arg(
                 Arg::with_name("flag_old_heuristics")
                     .long("use_old_simplification_heuristics")
@@ -493,6 +919,68 @@ mod input_processing {
                     .display_order(300)
                     .help("To choose the prime number to use to generate the circuit. Receives the name of the curve (bn128, bls12381, goldilocks, m31, grumpkin, pallas, vesta, secq256r1)"),
             )
+            .subcommand(
+                SubCommand::with_name("verify-witness")
+                    .about("Checks that a witness satisfies the constraints of a compiled circuit")
+                    .arg(
+                        Arg::with_name("r1cs")
+                            .required(true)
+                            .help("Path to the constraints of the circuit in r1cs format"),
+                    )
+                    .arg(
+                        Arg::with_name("witness")
+                            .required(true)
+                            .help("Path to the witness in wtns format"),
+                    )
+                    .arg(
+                        Arg::with_name("sym")
+                            .long("sym")
+                            .takes_value(true)
+                            .help("Path to the sym file used to name the signals, by default the one next to the r1cs file"),
+                    )
+                    .arg(diagnostics_format_arg())
+                    .arg(diagnostics_output_arg()),
+            )
+            .subcommand(
+                SubCommand::with_name("fmt")
+                    .about("Formats circom files in the canonical style")
+                    .arg(
+                        Arg::with_name("files")
+                            .required(true)
+                            .multiple(true)
+                            .help("Paths to the circom files to format"),
+                    )
+                    .arg(
+                        Arg::with_name("check")
+                            .long("check")
+                            .takes_value(false)
+                            .help("Does not write the files, fails if any of them is not formatted"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("test")
+                    .about("Runs the test blocks of a circom file")
+                    .arg(
+                        Arg::with_name("input")
+                            .required(true)
+                            .help("Path to the circom file with the tests"),
+                    )
+                    .arg(
+                        Arg::with_name("prime")
+                            .long("prime")
+                            .takes_value(true)
+                            .default_value("bn128")
+                            .help("To choose the prime number to use to run the tests"),
+                    )
+                    .arg(
+                        Arg::with_name("link_libraries")
+                            .short("l")
+                            .takes_value(true)
+                            .multiple(true)
+                            .number_of_values(1)
+                            .help("Adds directory to library search path"),
+                    ),
+            )
             .get_matches()
     }
 
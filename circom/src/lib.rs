@@ -0,0 +1,623 @@
+//! Programmatic access to the circom compilation pipeline. `compile` runs the
+//! parser, the type analysis, the constraint generation and the circuit
+//! construction in memory and returns every intermediate product, so build
+//! tools do not need to invoke the binary and read its output files back.
+
+pub use compiler::compiler_interface::{Circuit, VCP};
+pub use constraint_generation::{ConstraintWriter, Statistics};
+pub use program_structure::error_definition::{Report, ReportCollection};
+pub use program_structure::file_definition::FileLibrary;
+pub use program_structure::program_archive::ProgramArchive;
+
+use compiler::compiler_interface::CompilationFlags;
+use constraint_generation::{generate_circuit, BuildConfig};
+use program_structure::constants::UsefulConstants;
+use std::path::PathBuf;
+use type_analysis::check_types::{check_ranges, check_types};
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Prime {
+    Bn128,
+    Bls12381,
+    Goldilocks,
+    Grumpkin,
+    Pallas,
+    Vesta,
+    Secq256r1,
+    M31,
+}
+
+impl Prime {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Prime::Bn128 => "bn128",
+            Prime::Bls12381 => "bls12381",
+            Prime::Goldilocks => "goldilocks",
+            Prime::Grumpkin => "grumpkin",
+            Prime::Pallas => "pallas",
+            Prime::Vesta => "vesta",
+            Prime::Secq256r1 => "secq256r1",
+            Prime::M31 => "m31",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Prime> {
+        match name {
+            "bn128" => Some(Prime::Bn128),
+            "bls12381" => Some(Prime::Bls12381),
+            "goldilocks" => Some(Prime::Goldilocks),
+            "grumpkin" => Some(Prime::Grumpkin),
+            "pallas" => Some(Prime::Pallas),
+            "vesta" => Some(Prime::Vesta),
+            "secq256r1" => Some(Prime::Secq256r1),
+            "m31" => Some(Prime::M31),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Simplification {
+    // --O0, no simplification
+    None,
+    // --O1, only signal to signal and signal to constant equalities
+    Equalities,
+    // --O2, full simplification with the given maximum number of rounds
+    Full { rounds: usize },
+}
+
+#[derive(Clone, Debug)]
+pub struct CompilationOptions {
+    pub prime: Prime,
+    pub link_libraries: Vec<PathBuf>,
+    pub simplification: Simplification,
+    pub parallel_simplification: bool,
+    pub old_heuristics: bool,
+    pub verbose: bool,
+    pub inspect: bool,
+    pub inspect_deep: bool,
+    pub produce_input_log: bool,
+    // builds the Circuit used by the witness generators
+    pub produce_circuit: bool,
+    // instances of templates are reused from this folder between builds
+    pub cache_folder: Option<PathBuf>,
+    // collects the per template statistics
+    pub statistics: bool,
+    // the simplification writes the substitutions it applies to this file
+    pub json_substitutions: Option<PathBuf>,
+}
+
+impl Default for CompilationOptions {
+    fn default() -> Self {
+        CompilationOptions {
+            prime: Prime::Bn128,
+            link_libraries: Vec::new(),
+            simplification: Simplification::Full { rounds: usize::MAX },
+            parallel_simplification: false,
+            old_heuristics: false,
+            verbose: false,
+            inspect: false,
+            inspect_deep: false,
+            produce_input_log: false,
+            produce_circuit: true,
+            cache_folder: None,
+            statistics: false,
+            json_substitutions: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Source {
+    Path(PathBuf),
+    // the includes of the code are resolved relative to name
+    Code { name: String, code: String },
+}
+
+pub struct CompilationArtifacts {
+    pub program_archive: ProgramArchive,
+    pub constraints: ConstraintWriter,
+    pub vcp: VCP,
+    pub circuit: Option<Circuit>,
+    pub template_instances: usize,
+    pub statistics: Option<Statistics>,
+    pub warnings: ReportCollection,
+}
+
+// On failure the reports hold the warnings found before the errors, the files
+// are the ones needed to print them
+pub fn compile(
+    source: Source,
+    options: &CompilationOptions,
+) -> Result<CompilationArtifacts, (FileLibrary, ReportCollection)> {
+    let link_libraries = options.link_libraries.clone();
+    let parsed = match source {
+        Source::Path(path) => {
+            parser::run_parser(path.display().to_string(), VERSION, link_libraries)
+        }
+        Source::Code { name, code } => {
+            parser::run_parser_from_source(name, code, VERSION, link_libraries)
+        }
+    };
+    let (mut program_archive, mut warnings) = parsed?;
+    let files = program_archive.get_file_library().clone();
+    match check_types(&mut program_archive) {
+        Result::Ok(mut type_warnings) => warnings.append(&mut type_warnings),
+        Result::Err(mut errors) => {
+            warnings.append(&mut errors);
+            return Result::Err((files, warnings));
+        }
+    }
+    let prime = options.prime.name().to_string();
+    let field = UsefulConstants::new(&prime).get_p().clone();
+    warnings.append(&mut check_ranges(&program_archive, &field));
+
+    let (flag_f, flag_s, no_rounds) = match options.simplification {
+        Simplification::None => (true, false, 0),
+        Simplification::Equalities => (false, true, 0),
+        Simplification::Full { rounds } => (false, false, rounds),
+    };
+    let build_config = BuildConfig {
+        no_rounds,
+        flag_json_sub: options.json_substitutions.is_some(),
+        json_substitutions: options
+            .json_substitutions
+            .as_ref()
+            .map_or(String::new(), |f| f.display().to_string()),
+        flag_stats: options.statistics,
+        flag_s,
+        flag_f,
+        flag_p: options.parallel_simplification,
+        flag_verbose: options.verbose,
+        flag_old_heuristics: options.old_heuristics,
+        inspect_constraints: options.inspect || options.inspect_deep,
+        inspect_deep: options.inspect_deep,
+        prime,
+        cache_folder: options
+            .cache_folder
+            .as_ref()
+            .map(|f| f.display().to_string()),
+    };
+    let mut output =
+        generate_circuit(program_archive.clone(), &build_config).map_err(|mut r| {
+            warnings.append(&mut r);
+            (files, warnings.clone())
+        })?;
+    warnings.append(&mut output.warnings);
+    let circuit = if options.produce_circuit {
+        let flags = CompilationFlags {
+            main_inputs_log: options.produce_input_log,
+        };
+        Some(Circuit::build(output.vcp.clone(), flags, VERSION))
+    } else {
+        None
+    };
+    Result::Ok(CompilationArtifacts {
+        program_archive,
+        constraints: output.exporter,
+        vcp: output.vcp,
+        circuit,
+        template_instances: output.template_instances,
+        statistics: output.statistics,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::time::SystemTime;
+
+    const MULTIPLIER: &str = "pragma circom 2.0.0;
+
+template Multiplier2() {
+    signal input a;
+    signal input b;
+    signal output c;
+    c <== a * b;
+}
+
+component main = Multiplier2();
+";
+
+    #[test]
+    fn compiles_code_in_memory() {
+        let source = Source::Code {
+            name: "multiplier2.circom".to_string(),
+            code: MULTIPLIER.to_string(),
+        };
+        let artifacts = compile(source, &CompilationOptions::default())
+            .ok()
+            .unwrap();
+        assert_eq!(artifacts.vcp.templates.len(), 1);
+        assert!(artifacts.circuit.is_some());
+
+        let broken = Source::Code {
+            name: "broken.circom".to_string(),
+            code: MULTIPLIER.replace("c <== a * b;", "c <== a * d;"),
+        };
+        let (_, reports) = compile(broken, &CompilationOptions::default())
+            .err()
+            .unwrap();
+        assert!(!reports.is_empty());
+    }
+
+    const TREE: &str = "pragma circom 2.0.0;
+
+function double(x) {
+    return FACTOR * x;
+}
+
+template Square() {
+    signal input in;
+    signal output out;
+    out <== in * in;
+}
+
+template Sum(n) {
+    signal input in[n];
+    signal output out;
+    component squares[n];
+    var acc = 0;
+    for (var i = 0; i < n; i++) {
+        squares[i] = Square();
+        squares[i].in <== in[i];
+        acc += squares[i].out;
+    }
+    out <== acc * double(3);
+}
+
+template Main(n) {
+    signal input a[n];
+    signal output b;
+    signal output c;
+    b <== Sum(n)(a);
+    component s = Sum(n - 1);
+    for (var i = 0; i < n - 1; i++) {
+        s.in[i] <== a[i];
+    }
+    c <== s.out;
+}
+
+component main {public [a]} = Main(3);
+";
+
+    // everything the build produces that a cache could change
+    fn outputs(code: &str, cache: Option<PathBuf>, folder: &Path) -> Vec<String> {
+        let source = Source::Code {
+            name: "tree.circom".to_string(),
+            code: code.to_string(),
+        };
+        let options = CompilationOptions {
+            prime: Prime::M31,
+            cache_folder: cache,
+            ..CompilationOptions::default()
+        };
+        let artifacts = compile(source, &options).ok().unwrap();
+        let r1cs = folder.join("tree.r1cs").display().to_string();
+        let sym = folder.join("tree.sym").display().to_string();
+        artifacts.constraints.r1cs(&r1cs, false).unwrap();
+        artifacts.constraints.sym(&sym).unwrap();
+        let circuit = artifacts.circuit.unwrap();
+        let mut result = vec![
+            format!("{:?}", std::fs::read(&r1cs).unwrap()),
+            std::fs::read_to_string(&sym).unwrap(),
+            format!("{:?}", artifacts.vcp.witness_list),
+        ];
+        for id in 0..circuit.templates.len() {
+            result.push(circuit.produce_ir_string_for_template(id));
+        }
+        for id in 0..circuit.functions.len() {
+            result.push(circuit.produce_ir_string_for_function(id));
+        }
+        result
+    }
+
+    // the records of the cache and their modification times
+    fn records(cache: &Path) -> BTreeMap<PathBuf, SystemTime> {
+        std::fs::read_dir(cache)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+                (path, modified)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cached_builds_match_cold_builds() {
+        let folder = std::env::temp_dir().join(format!("circom_cache_{}", std::process::id()));
+        let cache = folder.join("cache");
+        std::fs::create_dir_all(&folder).unwrap();
+        let code = TREE.replace("FACTOR", "2");
+        let cold = outputs(&code, None, &folder);
+        let filled = outputs(&code, Some(cache.clone()), &folder);
+        // Square, Sum(3), Sum(2) and Main(3)
+        assert_eq!(records(&cache).len(), 4);
+        // a record written again would get a new modification time
+        for record in records(&cache).keys() {
+            let file = std::fs::File::options().write(true).open(record).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        }
+        let stored = records(&cache);
+        let warm = outputs(&code, Some(cache.clone()), &folder);
+        assert!(cold == filled && cold == warm);
+        // every instance was loaded, none was executed and stored
+        assert_eq!(records(&cache), stored);
+
+        // a change in a function invalidates the templates that call it
+        let changed = TREE.replace("FACTOR", "5");
+        let changed_cold = outputs(&changed, None, &folder);
+        let changed_warm = outputs(&changed, Some(cache.clone()), &folder);
+        assert!(changed_cold != cold && changed_cold == changed_warm);
+        // only Square, which does not call double, is loaded
+        let updated = records(&cache);
+        assert_eq!(updated.len(), stored.len() + 3);
+        assert!(stored
+            .iter()
+            .all(|(record, modified)| updated[record] == *modified));
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    // the first signals of the witness of the code, as decimal strings
+    fn witness(code: &str, input: &str, signals: usize) -> Result<Vec<String>, String> {
+        let source = Source::Code {
+            name: "witness.circom".to_string(),
+            code: code.to_string(),
+        };
+        let options = CompilationOptions {
+            prime: Prime::M31,
+            simplification: Simplification::None,
+            ..CompilationOptions::default()
+        };
+        let circuit = compile(source, &options).ok().unwrap().circuit.unwrap();
+        let witness = compiler::witness::calculate_witness(&circuit, input)?;
+        Ok(witness
+            .iter()
+            .take(signals)
+            .map(|v| v.to_string())
+            .collect())
+    }
+
+    #[test]
+    fn witness_of_subcomponents_arrays_and_functions() {
+        let code = TREE.replace("FACTOR", "2");
+        // 1, the outputs b and c and the inputs a
+        let expected = ["1", "84", "30", "1", "2", "3"];
+        assert_eq!(witness(&code, r#"{"a": [1, 2, 3]}"#, 6).unwrap(), expected);
+        let expected = ["1", "0", "0", "0", "0", "0"];
+        assert_eq!(
+            witness(&code, r#"{"a": ["0", "0x0", 0]}"#, 6).unwrap(),
+            expected
+        );
+    }
+
+    const ARITHMETIC: &str = "pragma circom 2.0.0;
+
+template Arithmetic() {
+    signal input a;
+    signal input b;
+    signal output q;
+    signal output r;
+    signal output e;
+    signal output w;
+    q <-- a \\ b;
+    r <-- a % b;
+    a === q * b + r;
+    e <-- a ** b;
+    w <-- 2 ** 31;
+}
+
+component main = Arithmetic();
+";
+
+    #[test]
+    fn witness_of_integer_division_remainder_and_power() {
+        let expected = ["1", "3", "2", "1419857", "1", "17", "5"];
+        assert_eq!(
+            witness(ARITHMETIC, r#"{"a": 17, "b": 5}"#, 7).unwrap(),
+            expected
+        );
+        // the powers are reduced modulo 2^31 - 1
+        let power = witness(ARITHMETIC, r#"{"a": 2, "b": 40}"#, 5).unwrap();
+        assert_eq!(power[3], "512");
+    }
+
+    #[test]
+    fn witness_of_a_failing_assert() {
+        let code = "pragma circom 2.0.0;
+
+template Bounded() {
+    signal input in;
+    signal output out;
+    assert(in < 10);
+    out <== in * in;
+}
+
+component main = Bounded();
+";
+        assert_eq!(witness(code, r#"{"in": 3}"#, 3).unwrap(), ["1", "9", "3"]);
+        let message = witness(code, r#"{"in": 12}"#, 3).err().unwrap();
+        assert!(message.starts_with("Failed assert in template/function Bounded line 6"));
+        assert!(witness(code, "{}", 3)
+            .err()
+            .unwrap()
+            .starts_with("Not all inputs"));
+    }
+
+    const QM31: &str = "pragma circom 2.0.0;
+
+include \"qm31.circom\";
+
+template Main() {
+    signal input a[4];
+    signal input b[4];
+    signal output product[4];
+    signal output inverse[4];
+    product <== QM31Mul()(a, b);
+    inverse <== QM31Inv()(a);
+}
+
+component main = Main();
+";
+
+    #[test]
+    fn qm31_constraints_and_witness() {
+        use circom_algebra::num_bigint::BigInt;
+        use constraint_list::witness_checker::check_witness;
+        let source = Source::Code {
+            name: "qm31.circom".to_string(),
+            code: QM31.to_string(),
+        };
+        let options = CompilationOptions {
+            prime: Prime::M31,
+            ..CompilationOptions::default()
+        };
+        let artifacts = compile(source, &options).ok().unwrap();
+        let field = BigInt::from(2147483647);
+        let constraints = artifacts.constraints.r1cs_constraints();
+
+        let input = r#"{"a": [1, 2, 3, 4], "b": [5, 6, 7, 8]}"#;
+        let circuit = artifacts.circuit.unwrap();
+        let mut witness = compiler::witness::calculate_witness(&circuit, input).unwrap();
+        // (1 + 2i + (3 + 4i)u) * (5 + 6i + (7 + 8i)u) and the inverse of the first
+        let expected = [
+            "2147483566",
+            "109",
+            "2147483629",
+            "60",
+            "1855247052",
+            "856841008",
+            "1588674294",
+            "1863525709",
+        ];
+        let outputs: Vec<String> = witness[1..9].iter().map(|v| v.to_string()).collect();
+        assert_eq!(outputs, expected);
+        assert!(check_witness(&field, &constraints, &witness).unwrap().is_empty());
+
+        // a wrong product and a wrong inverse do not satisfy the constraints
+        witness[1] = BigInt::from(2147483565);
+        assert!(!check_witness(&field, &constraints, &witness).unwrap().is_empty());
+        witness[1] = BigInt::from(2147483566);
+        witness[5] = BigInt::from(1855247053);
+        assert!(!check_witness(&field, &constraints, &witness).unwrap().is_empty());
+    }
+
+    #[test]
+    fn qm31_functions_need_the_m31_prime() {
+        let source = Source::Code {
+            name: "qm31.circom".to_string(),
+            code: QM31.to_string(),
+        };
+        let (_, reports) = compile(source, &CompilationOptions::default())
+            .err()
+            .unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0]
+            .get_message()
+            .ends_with("is a QM31 function, which can only be used with --prime m31"));
+    }
+
+    // number of field overflow and meaningless comparison warnings
+    fn range_warnings(code: &str) -> (usize, usize) {
+        use program_structure::error_code::ReportCode;
+        let source = Source::Code {
+            name: "ranges.circom".to_string(),
+            code: code.to_string(),
+        };
+        let options = CompilationOptions {
+            prime: Prime::M31,
+            simplification: Simplification::None,
+            produce_circuit: false,
+            ..CompilationOptions::default()
+        };
+        let warnings = compile(source, &options).ok().unwrap().warnings;
+        let count =
+            |code: fn(&ReportCode) -> bool| warnings.iter().filter(|w| code(w.get_code())).count();
+        (
+            count(|c| matches!(c, ReportCode::FieldOverflow)),
+            count(|c| matches!(c, ReportCode::MeaninglessComparison)),
+        )
+    }
+
+    const TAGGED: &str = "pragma circom 2.1.0;
+
+template Product() {
+    signal input {maxbit} a;
+    signal input {maxbit} b;
+    signal output c;
+    c <== a * b;
+}
+
+template Forward() {
+    signal input {maxbit} in;
+    signal output out;
+    component p = Product();
+    p.a <== in;
+    p.b <== in;
+    out <== p.c;
+}
+
+template Lesser() {
+    signal input {maxbit} in;
+    signal output out;
+    out <-- in < 1000;
+}
+
+template Main() {
+    signal input x;
+    signal output y;
+    signal output z;
+    signal {maxbit} t;
+    t.maxbit = BITS;
+    t <== x;
+    component f = Forward();
+    f.in <== t;
+    y <== f.out;
+    component l = Lesser();
+    l.in <== t;
+    z <== l.out;
+}
+
+component main = Main();
+";
+
+    #[test]
+    fn maxbit_values_flow_from_the_call_sites() {
+        // the square of a 16 bit value does not fit in 31 bits and a 31 bit
+        // value can be larger than half of the field
+        assert_eq!(range_warnings(&TAGGED.replace("BITS", "16")), (1, 0));
+        assert_eq!(range_warnings(&TAGGED.replace("BITS", "31")), (1, 1));
+        assert_eq!(range_warnings(&TAGGED.replace("BITS", "15")), (0, 0));
+        // a maxbit value that is not a constant leaves the inputs unbounded
+        let unknown = TAGGED.replace(
+            "template Main",
+            "function width() {\n    return 16;\n}\n\ntemplate Main",
+        );
+        assert_eq!(range_warnings(&unknown.replace("BITS", "width()")), (0, 0));
+    }
+
+    #[test]
+    fn loops_that_may_not_run_keep_the_previous_values() {
+        let code = "pragma circom 2.0.0;
+
+template Loop(n) {
+    signal output out;
+    var x = 1 << 30;
+    for (var i = 0; i < n; i++) {
+        x = 1;
+    }
+    out <-- x * x;
+}
+
+component main = Loop(0);
+";
+        assert_eq!(range_warnings(code), (1, 0));
+        assert_eq!(range_warnings(&code.replace("1 << 30", "1 << 15")), (0, 0));
+    }
+}
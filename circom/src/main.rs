@@ -1,62 +1,132 @@
 mod compilation_user;
 mod execution_user;
+mod format_user;
 mod input_user;
-mod parser_user;
-mod type_analysis_user;
-
-const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+mod test_user;
+mod verify_witness_user;
 
 use ansi_term::Colour;
-use input_user::Input;
+use circom::VERSION;
+use input_user::{Command, Input};
+use program_structure::diagnostics::{print_progress, write_diagnostics};
+use program_structure::error_definition::Report;
 fn main() {
     let result = start();
+    if write_diagnostics().is_err() {
+        eprintln!("{}", Colour::Red.paint("could not write the diagnostics"));
+    }
     if result.is_err() {
         eprintln!("{}", Colour::Red.paint("previous errors were found"));
         std::process::exit(1);
     } else {
-        println!("{}", Colour::Green.paint("Everything went okay"));
+        print_progress(&Colour::Green.paint("Everything went okay").to_string());
         //std::process::exit(0);
     }
 }
 
 fn start() -> Result<(), ()> {
+    match Command::new()? {
+        Command::Compile(user_input) => compile(*user_input),
+        Command::VerifyWitness(verify_input) => verify_witness_user::verify_witness(&verify_input),
+        Command::Format(format_input) => format_user::format(&format_input),
+        Command::Test(test_input) => test_user::run_tests(&test_input),
+    }
+}
+
+fn compile(user_input: Input) -> Result<(), ()> {
+    use circom::{CompilationOptions, Prime, Simplification, Source};
     use compilation_user::CompilerConfig;
     use execution_user::ExecutionConfig;
-    let user_input = Input::new()?;
-    let mut program_archive = parser_user::parse_project(&user_input)?;
-    type_analysis_user::analyse_project(&mut program_archive)?;
+    use std::path::PathBuf;
+
+    let simplification = if user_input.unsimplified_flag() {
+        Simplification::None
+    } else if user_input.reduced_simplification_flag() {
+        Simplification::Equalities
+    } else {
+        Simplification::Full {
+            rounds: user_input.no_rounds(),
+        }
+    };
+    let options = CompilationOptions {
+        // the name of the prime is checked when the input is read
+        prime: Prime::from_name(&user_input.prime()).unwrap(),
+        link_libraries: user_input.get_link_libraries().clone(),
+        simplification,
+        parallel_simplification: user_input.parallel_simplification_flag(),
+        old_heuristics: user_input.flag_old_heuristics(),
+        verbose: user_input.flag_verbose(),
+        inspect: user_input.inspect_constraints_flag(),
+        inspect_deep: user_input.inspect_deep_flag(),
+        produce_input_log: user_input.main_inputs_flag(),
+        produce_circuit: user_input.c_flag()
+            || user_input.wasm_flag()
+            || user_input.wat_flag()
+            || user_input.rust_flag()
+            || user_input.wtns_flag(),
+        cache_folder: user_input.cache_folder().map(PathBuf::from),
+        statistics: user_input.stats_flag(),
+        json_substitutions: if user_input.json_substitutions_flag() {
+            Some(PathBuf::from(user_input.json_substitutions_file()))
+        } else {
+            None
+        },
+    };
+    let source = Source::Path(PathBuf::from(user_input.input_file()));
+    let artifacts = circom::compile(source, &options)
+        .map_err(|(files, reports)| Report::print_reports(&reports, &files))?;
+    Report::print_reports(
+        &artifacts.warnings,
+        artifacts.program_archive.get_file_library(),
+    );
 
     let config = ExecutionConfig {
-        no_rounds: user_input.no_rounds(),
-        flag_p: user_input.parallel_simplification_flag(),
-        flag_s: user_input.reduced_simplification_flag(),
         flag_f: user_input.unsimplified_flag(),
-        flag_old_heuristics: user_input.flag_old_heuristics(),
-        flag_verbose: user_input.flag_verbose(),
-        inspect_constraints_flag: user_input.inspect_constraints_flag(),
         r1cs_flag: user_input.r1cs_flag(),
         json_constraint_flag: user_input.json_constraints_flag(),
         json_substitution_flag: user_input.json_substitutions_flag(),
         sym_flag: user_input.sym_flag(),
         sym: user_input.sym_file().to_string(),
         r1cs: user_input.r1cs_file().to_string(),
+        smt2_flag: user_input.smt2_flag(),
+        smt2: user_input.smt2_file().to_string(),
         json_constraints: user_input.json_constraints_file().to_string(),
+        air_flag: user_input.air_flag(),
+        air: user_input.air_file().to_string(),
+        air_columns: user_input.air_columns_file().to_string(),
         json_substitutions: user_input.json_substitutions_file().to_string(),
-        prime: user_input.prime(),
-        input_map: user_input.input_map_file().to_string(),
-        input_map_flag: user_input.input_map_flag(),
+        stats: user_input.stats_file().to_string(),
+        io_map: user_input.io_map_file().to_string(),
+        io_map_flag: user_input.io_map_flag(),
     };
-    let circuit = execution_user::execute_project(program_archive, config)?;
-    let compilation_config = CompilerConfig {
-        vcp: circuit,
-        debug_output: user_input.print_ir_flag(),
-        c_flag: user_input.c_flag(),
-        c_folder: user_input.c_folder().to_string(),
-        c_run_name: user_input.c_run_name().to_string(),
-        c_file: user_input.c_file().to_string(),
-        dat_file: user_input.dat_file().to_string(),
-        produce_input_log: user_input.main_inputs_flag(),
-    };
-    compilation_user::compile(compilation_config)?;
+    execution_user::write_outputs(&artifacts, config)?;
+    if let Some(circuit) = &artifacts.circuit {
+        let compilation_config = CompilerConfig {
+            debug_output: user_input.print_ir_flag(),
+            c_flag: user_input.c_flag(),
+            c_library_flag: user_input.c_library_flag(),
+            wasm_flag: user_input.wasm_flag(),
+            wat_flag: user_input.wat_flag(),
+            rust_flag: user_input.rust_flag(),
+            wtns_flag: user_input.wtns_flag(),
+            wtns_input: if user_input.wtns_flag() {
+                user_input.wtns_input_file().to_string()
+            } else {
+                String::new()
+            },
+            wtns_file: user_input.wtns_file().to_string(),
+            c_folder: user_input.c_folder().to_string(),
+            c_run_name: user_input.c_run_name().to_string(),
+            c_file: user_input.c_file().to_string(),
+            dat_file: user_input.dat_file().to_string(),
+            js_folder: user_input.js_folder().to_string(),
+            wat_file: user_input.wat_file().to_string(),
+            wasm_file: user_input.wasm_file().to_string(),
+            rust_folder: user_input.rust_folder().to_string(),
+            rust_file: user_input.rust_file().to_string(),
+            rust_dat_file: user_input.rust_dat_file().to_string(),
+        };
+        compilation_user::compile(circuit, compilation_config)?;
+    }
     Result::Ok(())
 }
@@ -0,0 +1,94 @@
+use crate::input_user::TestInput;
+use crate::verify_witness_user::{unsatisfied_reports, witness_names};
+use ansi_term::Colour;
+use circom::VERSION;
+use compiler::compiler_interface::{Circuit, CompilationFlags};
+use compiler::witness::calculate_witness;
+use constraint_generation::{generate_circuit, BuildConfig};
+use constraint_list::witness_checker::check_witness;
+use program_structure::constants::UsefulConstants;
+use program_structure::error_definition::Report;
+use program_structure::program_archive::ProgramArchive;
+use type_analysis::check_types::check_types;
+
+pub fn run_tests(input: &TestInput) -> Result<(), ()> {
+    let (tests, warnings) = parser::run_parser_for_tests(
+        input.input_file().to_string(),
+        VERSION,
+        input.get_link_libraries().clone(),
+    )
+    .map_err(|(file_library, reports)| Report::print_reports(&reports, &file_library))?;
+    if let Some((_, program)) = tests.first() {
+        Report::print_reports(&warnings, program.get_file_library());
+    }
+    println!("running {} tests", tests.len());
+    let mut failed = Vec::new();
+    let total = tests.len();
+    for (name, program) in tests {
+        if run_test(program, input.prime()).is_ok() {
+            println!("test {} ... {}", name, Colour::Green.paint("ok"));
+        } else {
+            println!("test {} ... {}", name, Colour::Red.paint("FAILED"));
+            failed.push(name);
+        }
+    }
+    let summary = format!("{} passed; {} failed", total - failed.len(), failed.len());
+    if failed.is_empty() {
+        println!("{} {}", Colour::Green.paint("test result: ok."), summary);
+        Result::Ok(())
+    } else {
+        for name in &failed {
+            eprintln!("    {}", name);
+        }
+        eprintln!("{} {}", Colour::Red.paint("test result: FAILED."), summary);
+        Result::Err(())
+    }
+}
+
+// Compiles the test without simplification, computes its witness, which
+// checks the asserts, and checks every constraint against the witness. The
+// warnings are not shown, they are the ones of a normal compilation.
+fn run_test(mut program: ProgramArchive, prime: String) -> Result<(), ()> {
+    let files = program.get_file_library().clone();
+    check_types(&mut program).map_err(|errors| Report::print_reports(&errors, &files))?;
+    let field = UsefulConstants::new(&prime).get_p().clone();
+    let config = BuildConfig {
+        no_rounds: 0,
+        flag_json_sub: false,
+        json_substitutions: String::new(),
+        flag_stats: false,
+        flag_s: false,
+        flag_f: true,
+        flag_p: false,
+        flag_verbose: false,
+        flag_old_heuristics: false,
+        inspect_constraints: false,
+        inspect_deep: false,
+        prime,
+        cache_folder: None,
+    };
+    let output =
+        generate_circuit(program, &config).map_err(|r| Report::print_reports(&r, &files))?;
+    let flags = CompilationFlags {
+        main_inputs_log: false,
+    };
+    let circuit = Circuit::build(output.vcp, flags, VERSION);
+    let witness = calculate_witness(&circuit, "{}")
+        .map_err(|message| eprintln!("{}", Colour::Red.paint(message)))?;
+
+    // the constraints are the ones of the r1cs file, without writing it
+    let constraints = output.exporter.r1cs_constraints();
+    let unsatisfied = check_witness(&field, &constraints, &witness).map_err(|_err| {
+        eprintln!(
+            "{}",
+            Colour::Red.paint("The constraints use signals that are not in the witness")
+        )
+    })?;
+    if unsatisfied.is_empty() {
+        return Result::Ok(());
+    }
+    let names = witness_names(output.exporter.sym_elems());
+    let reports = unsatisfied_reports(&unsatisfied, constraints.len(), &witness, &names);
+    Report::print_reports(&reports, &files);
+    Result::Err(())
+}
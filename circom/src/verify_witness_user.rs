@@ -0,0 +1,152 @@
+use crate::input_user::VerifyWitnessInput;
+use ansi_term::Colour;
+use circom_algebra::num_bigint::BigInt;
+use constraint_list::witness_checker::{check_witness, UnsatisfiedConstraint};
+use constraint_writers::r1cs_reader::R1CSFile;
+use constraint_writers::sym_reader::read_sym;
+use constraint_writers::sym_writer::SymElem;
+use constraint_writers::wtns_reader::WtnsFile;
+use program_structure::diagnostics::print_progress;
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::{Report, ReportCollection};
+use program_structure::file_definition::FileLibrary;
+use std::collections::HashMap;
+
+// number of unsatisfied constraints that are described
+pub const MAX_REPORTED: usize = 10;
+
+pub fn verify_witness(input: &VerifyWitnessInput) -> Result<(), ()> {
+    let r1cs = R1CSFile::read(input.r1cs_file()).map_err(|_err| {
+        Report::print_error(
+            format!("Could not read the r1cs file {}", input.r1cs_file()),
+            ReportCode::FileOs,
+        )
+    })?;
+    let wtns = WtnsFile::read(input.witness_file()).map_err(|_err| {
+        Report::print_error(
+            format!("Could not read the wtns file {}", input.witness_file()),
+            ReportCode::FileOs,
+        )
+    })?;
+    if wtns.field != r1cs.header.field {
+        Report::print_error(
+            format!(
+                "The witness is over the field {} but the constraints are over the field {}",
+                wtns.field, r1cs.header.field
+            ),
+            ReportCode::WitnessError,
+        );
+        return Result::Err(());
+    }
+    if wtns.witness.len() != r1cs.header.total_wires {
+        Report::print_error(
+            format!(
+                "The witness has {} values but the circuit has {} wires",
+                wtns.witness.len(),
+                r1cs.header.total_wires
+            ),
+            ReportCode::WitnessError,
+        );
+        return Result::Err(());
+    }
+    let (names, mut reports) = signal_names(input.sym_file());
+    let unsatisfied =
+        check_witness(&r1cs.header.field, &r1cs.constraints, &wtns.witness).map_err(|_err| {
+            reports.push(Report::error(
+                "The constraints use signals that are not in the witness".to_string(),
+                ReportCode::WitnessError,
+            ));
+            Report::print_reports(&reports, &FileLibrary::new())
+        })?;
+    if unsatisfied.is_empty() {
+        Report::print_reports(&reports, &FileLibrary::new());
+        print_progress(&format!(
+            "{} {}",
+            Colour::Green.paint("All the constraints are satisfied:"),
+            r1cs.constraints.len()
+        ));
+        return Result::Ok(());
+    }
+    reports.append(&mut unsatisfied_reports(
+        &unsatisfied,
+        r1cs.constraints.len(),
+        &wtns.witness,
+        &names,
+    ));
+    Report::print_reports(&reports, &FileLibrary::new());
+    Result::Err(())
+}
+
+// the reports hold the warning when the sym file cannot be read
+fn signal_names(sym_file: &str) -> (HashMap<usize, String>, ReportCollection) {
+    match read_sym(sym_file) {
+        Result::Ok(elems) => (witness_names(elems), ReportCollection::new()),
+        Result::Err(_) => {
+            let report = Report::warning(
+                format!(
+                    "Could not read the sym file {}, the signals are named by their position in the witness",
+                    sym_file
+                ),
+                ReportCode::FileOs,
+            );
+            (HashMap::new(), vec![report])
+        }
+    }
+}
+
+// the witness is named after the first signal that is placed in it
+pub fn witness_names(elems: Vec<SymElem>) -> HashMap<usize, String> {
+    let mut names = HashMap::new();
+    for elem in elems {
+        if elem.witness >= 0 {
+            names.entry(elem.witness as usize).or_insert(elem.symbol);
+        }
+    }
+    names
+}
+
+// Describes the first MAX_REPORTED unsatisfied constraints, with the values
+// of their signals as notes, and how many of the total are not satisfied
+pub fn unsatisfied_reports(
+    unsatisfied: &[UnsatisfiedConstraint],
+    total: usize,
+    witness: &[BigInt],
+    names: &HashMap<usize, String>,
+) -> ReportCollection {
+    let mut reports: ReportCollection = unsatisfied
+        .iter()
+        .take(MAX_REPORTED)
+        .map(|constraint| constraint_report(constraint, witness, names))
+        .collect();
+    reports.push(Report::error(
+        format!(
+            "{} out of {} constraints are not satisfied",
+            unsatisfied.len(),
+            total
+        ),
+        ReportCode::UnsatisfiedConstraint,
+    ));
+    reports
+}
+
+fn constraint_report(
+    constraint: &UnsatisfiedConstraint,
+    witness: &[BigInt],
+    names: &HashMap<usize, String>,
+) -> Report {
+    let mut report = Report::error(
+        format!(
+            "Constraint {} is not satisfied: A = {}, B = {}, C = {}",
+            constraint.index, constraint.a, constraint.b, constraint.c
+        ),
+        ReportCode::UnsatisfiedConstraint,
+    );
+    for signal in &constraint.signals {
+        let name = match names.get(signal) {
+            Some(name) => name.clone(),
+            None => format!("witness[{}]", signal),
+        };
+        report.add_note(format!("{} = {}", name, witness[*signal]));
+    }
+    report
+}
@@ -0,0 +1,389 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+// b = 0 fails the assert. c is removed by the simplification, so it is only in
+// the outputs with every signal
+const BATCH: &str = "pragma circom 2.1.0;
+
+template Square() {
+    signal input in;
+    signal output out;
+    out <== in * in;
+}
+
+template Batch() {
+    signal input a[2];
+    signal input b;
+    signal output out;
+    assert(b != 0);
+    component square = parallel Square();
+    square.in <== a[0];
+    signal t <== square.out * a[1];
+    signal c <== a[0] + 1;
+    out <== t * b + c;
+}
+
+component main {public [b]} = Batch();
+";
+
+const INPUT: &str = r#"{"a": ["3", "5"], "b": "2"}"#;
+
+const P: u64 = 2147483647;
+
+// Sets the inputs through the C API of the library, with b larger than the
+// prime, and prints the result of every call
+const DRIVER: &str = r#"#include <stdio.h>
+#include <stdlib.h>
+#include "circom_witness.h"
+
+int main(int argc, char **argv) {
+  FILE *file = fopen(argv[1], "rb");
+  fseek(file, 0, SEEK_END);
+  long len = ftell(file);
+  rewind(file);
+  uint8_t *dat = (uint8_t *)malloc(len);
+  fread(dat, 1, len, file);
+  fclose(file);
+
+  circom_circuit *circuit = circom_circuit_load(dat, len);
+  circom_witness *witness = circom_witness_new(circuit);
+  uint32_t a[2] = {3, 5};
+  uint32_t zero = 0;
+  uint32_t b = 2147483649u;
+  printf("set a %d\n", circom_witness_set_input(witness, "a", a, 2));
+  printf("set a twice %d\n", circom_witness_set_input(witness, "a", a, 2));
+  printf("set b with two values %d\n", circom_witness_set_input(witness, "b", a, 2));
+  printf("set x %d\n", circom_witness_set_input(witness, "x", a, 1));
+  printf("run without b %d\n", circom_witness_run(witness));
+  printf("set b to zero %d\n", circom_witness_set_input(witness, "b", &zero, 1));
+  printf("run with b zero %d\n", circom_witness_run(witness));
+
+  circom_witness_reset(witness);
+  printf("set a %d\n", circom_witness_set_input(witness, "a", a, 2));
+  printf("set b %d\n", circom_witness_set_input(witness, "b", &b, 1));
+  printf("run %d\n", circom_witness_run(witness));
+  size_t n = circom_witness_size() * circom_field_words();
+  uint32_t *values = (uint32_t *)malloc(n * sizeof(uint32_t));
+  printf("get %d\n", circom_witness_get(witness, values, n));
+  printf("witness");
+  for (size_t i = 0; i < n; i++) {
+    printf(" %u", values[i]);
+  }
+  printf("\n");
+
+  free(values);
+  circom_witness_free(witness);
+  circom_circuit_free(circuit);
+  free(dat);
+  return 0;
+}
+"#;
+
+// The generated code needs a C++ compiler, gmp and nlohmann/json, so the tests
+// that build it are ignored by default and run with cargo test -- --ignored
+fn check_cpp_toolchain(folder: &Path) {
+    let probe = folder.join("probe.cpp");
+    std::fs::write(
+        &probe,
+        "#include <gmp.h>\n#include <nlohmann/json.hpp>\nint main() { return 0; }\n",
+    )
+    .unwrap();
+    let status = Command::new("c++")
+        .args(["-std=c++20", "-fsyntax-only"])
+        .arg(&probe)
+        .output();
+    assert!(
+        matches!(status, Ok(output) if output.status.success()),
+        "no C++ compiler with gmp and nlohmann/json"
+    );
+}
+
+fn test_folder(name: &str) -> PathBuf {
+    let folder =
+        std::env::temp_dir().join(format!("circom_c_witness_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+// Writes the circuit and its input and computes the witness with the
+// interpreter, which the generated code is checked against
+fn compile(folder: &Path, flag: &str) -> Vec<u32> {
+    let file = folder.join("batch.circom");
+    std::fs::write(&file, BATCH).unwrap();
+    let input = folder.join("input.json");
+    std::fs::write(&input, INPUT).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg(&file)
+        .args(["--O2", flag, "--sym", "--prime", "m31", "--wtns"])
+        .arg(&input)
+        .arg("-o")
+        .arg(folder)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    read_wtns(&folder.join("batch.wtns"))
+}
+
+fn cpp(folder: &Path, args: &[&str]) {
+    let output = Command::new("c++")
+        .current_dir(folder)
+        .arg("-std=c++20")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn run(program: &Path, args: &[&str]) -> Output {
+    Command::new(program).args(args).output().unwrap()
+}
+
+fn read_wtns(path: &Path) -> Vec<u32> {
+    let bytes = std::fs::read(path).unwrap();
+    let word = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    assert_eq!(&bytes[0..4], b"wtns");
+    // magic, version, number of sections, then the header section: id,
+    // length, n8, the prime and the number of values
+    assert_eq!(word(24), 4);
+    assert_eq!(word(28) as u64, P);
+    let values = word(32) as usize;
+    // the id and the length of the values section
+    assert_eq!(word(36), 2);
+    (0..values).map(|i| word(48 + 4 * i)).collect()
+}
+
+fn read_raw(path: &Path) -> Vec<u32> {
+    std::fs::read(path)
+        .unwrap()
+        .chunks(4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect()
+}
+
+// The signal and the witness of every name of the .sym file
+fn read_sym(path: &Path) -> HashMap<String, (i64, i64)> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            (
+                fields[3].to_string(),
+                (fields[0].parse().unwrap(), fields[1].parse().unwrap()),
+            )
+        })
+        .collect()
+}
+
+fn read_json(path: &Path) -> HashMap<String, u32> {
+    let json: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    json.as_object()
+        .unwrap()
+        .iter()
+        .map(|(name, value)| (name.clone(), value.as_str().unwrap().parse().unwrap()))
+        .collect()
+}
+
+fn out(a: [u64; 2], b: u64) -> u32 {
+    ((a[0] * a[0] % P * a[1] % P * b + a[0] + 1) % P) as u32
+}
+
+#[test]
+#[ignore = "needs a C++ compiler with gmp and nlohmann/json"]
+fn runner_outputs_match_the_wtns_file() {
+    let folder = test_folder("runner");
+    check_cpp_toolchain(&folder);
+    let expected = compile(&folder, "--c");
+    assert_eq!(expected[1], out([3, 5], 2));
+    let cpp_folder = folder.join("batch_cpp");
+    cpp(
+        &cpp_folder,
+        &[
+            "batch.cpp",
+            "calcwit.cpp",
+            "fr.cpp",
+            "main.cpp",
+            "-o",
+            "batch",
+            "-lgmp",
+            "-lpthread",
+        ],
+    );
+    let runner = cpp_folder.join("batch");
+    let input = folder.join("input.json");
+    let input = input.to_str().unwrap();
+    let sym = read_sym(&folder.join("batch.sym"));
+    let output = |name: &str, args: &[&str]| {
+        let file = folder.join(name);
+        let result = run(&runner, &[&[input, file.to_str().unwrap()], args].concat());
+        assert!(
+            result.status.success(),
+            "{}",
+            String::from_utf8_lossy(&result.stderr)
+        );
+        file
+    };
+
+    assert_eq!(read_wtns(&output("out.wtns", &[])), expected);
+    assert_eq!(
+        read_raw(&output("out.bin", &["--wtns-format", "raw"])),
+        expected
+    );
+
+    let json = read_json(&output("out.json", &["--wtns-format", "json"]));
+    let in_witness: Vec<_> = sym
+        .iter()
+        .filter(|(_, (_, witness))| *witness >= 0)
+        .collect();
+    assert_eq!(json.len(), in_witness.len());
+    for (name, (_, witness)) in in_witness {
+        assert_eq!(json[name], expected[*witness as usize]);
+    }
+    assert!(!json.contains_key("main.c"));
+
+    let all_json = read_json(&output(
+        "all.json",
+        &["--wtns-format", "json", "--all-signals"],
+    ));
+    let all_raw = read_raw(&output(
+        "all.bin",
+        &["--wtns-format", "raw", "--all-signals"],
+    ));
+    assert_eq!(all_json.len(), sym.len());
+    assert_eq!(all_json["main.c"], 4);
+    for (name, (signal, witness)) in &sym {
+        assert_eq!(all_raw[*signal as usize], all_json[name]);
+        if *witness >= 0 {
+            assert_eq!(all_json[name], expected[*witness as usize]);
+        }
+    }
+
+    // a witness that cannot be written is an error in every format
+    let missing = folder.join("missing").join("out");
+    for format in ["wtns", "json", "raw"] {
+        let result = run(
+            &runner,
+            &[input, missing.to_str().unwrap(), "--wtns-format", format],
+        );
+        assert!(!result.status.success());
+        let errors = String::from_utf8_lossy(&result.stderr);
+        assert!(
+            errors.contains("Could not write the witness file"),
+            "{}",
+            errors
+        );
+    }
+
+    // the second line fails the assert, the others are still written
+    let inputs = folder.join("inputs.jsonl");
+    std::fs::write(
+        &inputs,
+        format!(
+            "{}\n{}\n\n{}\n",
+            INPUT, r#"{"a": ["3", "5"], "b": "0"}"#, r#"{"a": ["7", "1"], "b": "9"}"#
+        ),
+    )
+    .unwrap();
+    let witnesses = folder.join("witnesses");
+    let result = run(
+        &runner,
+        &[
+            "--batch",
+            inputs.to_str().unwrap(),
+            witnesses.to_str().unwrap(),
+            "--threads",
+            "2",
+        ],
+    );
+    assert!(!result.status.success());
+    let errors = String::from_utf8_lossy(&result.stderr);
+    assert!(errors.contains("Error in input 1"));
+    assert!(errors.contains("1 out of 3 inputs failed"));
+    assert_eq!(read_wtns(&witnesses.join("0.wtns")), expected);
+    assert!(!witnesses.join("1.wtns").exists());
+    assert_eq!(read_wtns(&witnesses.join("2.wtns"))[1], out([7, 1], 9));
+
+    let _ = std::fs::remove_dir_all(&folder);
+}
+
+#[test]
+#[ignore = "needs a C++ compiler with gmp and nlohmann/json"]
+fn library_sets_the_inputs_and_recovers_from_a_failed_assert() {
+    let folder = test_folder("library");
+    check_cpp_toolchain(&folder);
+    let expected = compile(&folder, "--c-lib");
+    let cpp_folder = folder.join("batch_cpp");
+    cpp(
+        &cpp_folder,
+        &[
+            "-fPIC",
+            "-shared",
+            "batch.cpp",
+            "calcwit.cpp",
+            "fr.cpp",
+            "circom_witness.cpp",
+            "-o",
+            "libbatch.so",
+            "-lgmp",
+            "-lpthread",
+        ],
+    );
+    std::fs::write(cpp_folder.join("driver.cpp"), DRIVER).unwrap();
+    cpp(
+        &cpp_folder,
+        &[
+            "driver.cpp",
+            "-o",
+            "driver",
+            "-L.",
+            "-lbatch",
+            "-Wl,-rpath,$ORIGIN",
+        ],
+    );
+    let result = run(
+        &cpp_folder.join("driver"),
+        &[cpp_folder.join("batch.dat").to_str().unwrap()],
+    );
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    let printed = String::from_utf8(result.stdout).unwrap();
+    let lines: Vec<&str> = printed.lines().collect();
+    assert_eq!(
+        lines[..lines.len() - 1],
+        [
+            "set a 0",
+            "set a twice 3",
+            "set b with two values 2",
+            "set x 1",
+            "run without b 4",
+            "set b to zero 0",
+            "run with b zero 7",
+            "set a 0",
+            "set b 0",
+            "run 0",
+            "get 0",
+        ]
+    );
+    let witness: Vec<u32> = lines[lines.len() - 1]
+        .split(' ')
+        .skip(1)
+        .map(|value| value.parse().unwrap())
+        .collect();
+    assert_eq!(witness, expected);
+
+    let _ = std::fs::remove_dir_all(&folder);
+}
@@ -0,0 +1,162 @@
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const OVERFLOW: &str = "pragma circom 2.1.0;
+
+template Square() {
+    signal input in;
+    signal output out;
+    signal {maxbit} bounded;
+    bounded.maxbit = 16;
+    bounded <== in;
+    out <== bounded * bounded;
+}
+
+component main = Square();
+";
+
+fn run_circom(name: &str, code: &str, format: &str) -> Output {
+    let folder: PathBuf = std::env::temp_dir().join(format!(
+        "circom_diagnostics_{}_{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&folder).unwrap();
+    let file = folder.join(format!("{}.circom", name));
+    std::fs::write(&file, code).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg(&file)
+        .args([
+            "--r1cs",
+            "--sym",
+            "--O0",
+            "--prime",
+            "m31",
+            "--diagnostics-format",
+            format,
+        ])
+        .arg("-o")
+        .arg(&folder)
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&folder);
+    output
+}
+
+#[test]
+fn json_diagnostics_are_the_only_output_on_stdout() {
+    let output = run_circom("json", OVERFLOW, "json");
+    assert!(output.status.success());
+    let document: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let diagnostics = document["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["severity"], "warning");
+    assert_eq!(diagnostics[0]["name"], "FieldOverflow");
+    assert_eq!(diagnostics[0]["primary"][0]["start"]["line"], 9);
+    // the progress messages are still shown
+    let progress = String::from_utf8_lossy(&output.stderr);
+    assert!(progress.contains("template instances"));
+    assert!(progress.contains("Written successfully"));
+    assert!(progress.contains("Everything went okay"));
+}
+
+#[test]
+fn sarif_diagnostics_of_a_failed_compilation() {
+    let broken = OVERFLOW.replace("out <== bounded * bounded;", "out <== missing;");
+    let output = run_circom("sarif", &broken, "sarif");
+    assert!(!output.status.success());
+    let document: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = document["runs"][0]["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().any(|result| result["level"] == "error"));
+}
+
+#[test]
+fn json_diagnostics_of_a_missing_input_file() {
+    let file = std::env::temp_dir().join(format!("circom_missing_{}.circom", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg(&file)
+        .args(["--r1cs", "--diagnostics-format", "json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let document: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let diagnostics = document["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["severity"], "error");
+    assert_eq!(diagnostics[0]["name"], "FileOs");
+    assert!(diagnostics[0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Input file does not exist"));
+}
+
+#[test]
+fn json_diagnostics_of_an_unsatisfied_witness() {
+    let folder: PathBuf =
+        std::env::temp_dir().join(format!("circom_diagnostics_wtns_{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    let file = folder.join("multiplier.circom");
+    std::fs::write(
+        &file,
+        "pragma circom 2.0.0;
+
+template Multiplier() {
+    signal input a;
+    signal input b;
+    signal output c;
+    c <== a * b;
+}
+
+component main = Multiplier();
+",
+    )
+    .unwrap();
+    let input = folder.join("input.json");
+    std::fs::write(&input, r#"{"a": "3", "b": "11"}"#).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg(&file)
+        .args(["--r1cs", "--sym", "--O0", "--prime", "m31", "--wtns"])
+        .arg(&input)
+        .arg("-o")
+        .arg(&folder)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // c is the first value of the witness after the 1, it becomes 34
+    let wtns = folder.join("multiplier.wtns");
+    let mut bytes = std::fs::read(&wtns).unwrap();
+    assert_eq!(bytes[52], 33);
+    bytes[52] = 34;
+    std::fs::write(&wtns, bytes).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg("verify-witness")
+        .arg(folder.join("multiplier.r1cs"))
+        .arg(&wtns)
+        .args(["--diagnostics-format", "json"])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&folder);
+    assert!(!output.status.success());
+    let document: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let diagnostics = document["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics
+        .iter()
+        .all(|diagnostic| diagnostic["name"] == "UnsatisfiedConstraint"));
+    assert!(diagnostics[0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Constraint 0 is not satisfied"));
+    assert!(diagnostics[0]["notes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|note| note == "main.c = 34"));
+    assert_eq!(
+        diagnostics[1]["message"],
+        "1 out of 1 constraints are not satisfied"
+    );
+}
@@ -0,0 +1,94 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+
+// q is replaced by p in the simplification, so it is not in the witness
+const IO: &str = "pragma circom 2.1.0;
+
+template IO() {
+    signal input a[2][3];
+    signal input p;
+    signal input q;
+    signal output {binary} out;
+    signal output s;
+    q === p;
+    signal t <== a[0][0] * a[1][2];
+    out <== t * p;
+    s <== a[0][1] + 1;
+}
+
+component main {public [a]} = IO();
+";
+
+// the io map and the witness of each signal in the sym file
+fn io_map(name: &str, code: &str) -> (Value, HashMap<String, i64>) {
+    let folder: PathBuf =
+        std::env::temp_dir().join(format!("circom_io_map_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    let file = folder.join(format!("{}.circom", name));
+    std::fs::write(&file, code).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg(&file)
+        .args(["--O2", "--io_map", "--sym", "--prime", "m31"])
+        .arg("-o")
+        .arg(&folder)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let map = std::fs::read_to_string(folder.join(format!("{}_io_map.json", name))).unwrap();
+    let sym = std::fs::read_to_string(folder.join(format!("{}.sym", name))).unwrap();
+    let _ = std::fs::remove_dir_all(&folder);
+    let witness = sym
+        .lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            (fields[3].to_string(), fields[1].parse().unwrap())
+        })
+        .collect();
+    (serde_json::from_str(&map).unwrap(), witness)
+}
+
+fn entry<'a>(map: &'a Value, name: &str) -> &'a Value {
+    map.as_array()
+        .unwrap()
+        .iter()
+        .find(|signal| signal["name"] == name)
+        .unwrap()
+}
+
+#[test]
+fn io_map_matches_the_sym_file() {
+    let (map, witness) = io_map("io", IO);
+    // the intermediate signals are not in the map
+    assert_eq!(map.as_array().unwrap().len(), 5);
+
+    let a = entry(&map, "main.a");
+    assert_eq!(a["type"], "input");
+    assert_eq!(a["public"], true);
+    assert_eq!(a["dimensions"].to_string(), "[2,3]");
+    let start = a["witness_range"][0].as_i64().unwrap();
+    assert_eq!(a["witness_range"][1].as_i64().unwrap(), start + 6);
+    let mut position = start;
+    for i in 0..2 {
+        for j in 0..3 {
+            assert_eq!(witness[&format!("main.a[{}][{}]", i, j)], position);
+            position += 1;
+        }
+    }
+
+    let p = entry(&map, "main.p");
+    assert_eq!(p["public"], false);
+    assert_eq!(p["witness_range"][0].as_i64().unwrap(), witness["main.p"]);
+
+    let out = entry(&map, "main.out");
+    assert_eq!(out["type"], "output");
+    assert_eq!(out["tags"].to_string(), r#"{"binary":null}"#);
+    assert_eq!(out["witness_range"][0].as_i64().unwrap(), witness["main.out"]);
+
+    let q = entry(&map, "main.q");
+    assert_eq!(q["public"], false);
+    assert!(q.get("witness_range").is_none());
+    assert_eq!(q["witness_indices"].to_string(), "[null]");
+    assert_eq!(witness["main.q"], -1);
+}
@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Mul is instantiated with a parameter and its array input is assigned at once
+const ARRAYS: &str = "pragma circom 2.1.0;
+
+template Mul(n) {
+    signal input in[n];
+    signal output out;
+    signal acc[n];
+    acc[0] <== in[0];
+    for (var i = 1; i < n; i++) {
+        acc[i] <== acc[i - 1] * in[i];
+    }
+    out <== acc[n - 1];
+}
+
+template Arrays() {
+    signal input a[3];
+    signal input b[2][2];
+    signal output out[2];
+    component mul = Mul(3);
+    mul.in <== a;
+    out[0] <== mul.out * b[0][1];
+    out[1] <== b[1][0] + b[1][1] * a[2];
+}
+
+component main = Arrays();
+";
+
+fn test_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!(
+        "circom_rust_witness_{}_{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+// Generates the crate of the circuit and computes the witness of the input
+// with the interpreter, which the crate is checked against
+fn generate(folder: &Path, name: &str, code: &str, input: &str) -> (PathBuf, Vec<u8>) {
+    let file = folder.join(format!("{}.circom", name));
+    std::fs::write(&file, code).unwrap();
+    let input_file = folder.join("input.json");
+    std::fs::write(&input_file, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg(&file)
+        .args(["--O2", "--rust", "--wtns"])
+        .arg(&input_file)
+        .arg("-o")
+        .arg(folder)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let expected = std::fs::read(folder.join(format!("{}.wtns", name))).unwrap();
+    (folder.join(format!("{}_rust", name)), expected)
+}
+
+#[test]
+fn generated_crate_matches_the_interpreter() {
+    let folder = test_folder("arrays");
+    let input = r#"{"a": ["2", "3", "4"], "b": [["1", "5"], ["6", "7"]]}"#;
+    let (crate_folder, expected) = generate(&folder, "arrays", ARRAYS, input);
+    let output = Command::new(env!("CARGO"))
+        .arg("build")
+        .current_dir(&crate_folder)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let program = crate_folder.join("target").join("debug").join("arrays");
+
+    let wtns = folder.join("arrays.rust.wtns");
+    let output = Command::new(&program)
+        .arg(folder.join("input.json"))
+        .arg(&wtns)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(std::fs::read(&wtns).unwrap(), expected);
+
+    // a missing input is an error and no witness is written
+    let missing = folder.join("missing.json");
+    std::fs::write(&missing, r#"{"a": ["2", "3", "4"]}"#).unwrap();
+    let missing_wtns = folder.join("missing.wtns");
+    let output = Command::new(&program)
+        .arg(&missing)
+        .arg(&missing_wtns)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(!missing_wtns.exists());
+
+    let _ = std::fs::remove_dir_all(&folder);
+}
@@ -0,0 +1,83 @@
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Command;
+
+// The parent only forwards the signals of its subcomponent, so after the
+// simplification the constraint of Multiplier is written over the signals of
+// Forward
+const FORWARD: &str = "pragma circom 2.1.0;
+
+template Multiplier() {
+    signal input a;
+    signal input b;
+    signal output c;
+    c <== a * b;
+}
+
+template Forward() {
+    signal input a;
+    signal input b;
+    signal output c;
+    component m = Multiplier();
+    m.a <== a;
+    m.b <== b;
+    c <== m.c;
+}
+
+component main = Forward();
+";
+
+fn statistics(name: &str, code: &str) -> Value {
+    let folder: PathBuf =
+        std::env::temp_dir().join(format!("circom_statistics_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    let file = folder.join(format!("{}.circom", name));
+    std::fs::write(&file, code).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg(&file)
+        .args(["--O2", "--stats", "--prime", "m31"])
+        .arg("-o")
+        .arg(&folder)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stats = std::fs::read_to_string(folder.join(format!("{}_stats.json", name))).unwrap();
+    let _ = std::fs::remove_dir_all(&folder);
+    serde_json::from_str(&stats).unwrap()
+}
+
+// the generated constraints and the ones after the simplification
+fn constraints(stats: &Value, template: &str) -> (u64, u64) {
+    let templates = stats["templates"].as_array().unwrap();
+    let entry = templates
+        .iter()
+        .find(|entry| entry["template"] == template)
+        .unwrap();
+    (
+        entry["generated_constraints"].as_u64().unwrap(),
+        entry["constraints"].as_u64().unwrap(),
+    )
+}
+
+#[test]
+fn simplified_constraints_stay_in_the_template_that_emits_them() {
+    let stats = statistics("forward", FORWARD);
+    assert_eq!(stats["constraints"], 1);
+    assert_eq!(constraints(&stats, "Multiplier()"), (1, 1));
+    assert_eq!(constraints(&stats, "Forward()"), (3, 0));
+}
+
+#[test]
+fn the_share_is_computed_from_the_final_constraints() {
+    let stats = statistics("share", FORWARD);
+    let templates = stats["templates"].as_array().unwrap();
+    let total: u64 = templates
+        .iter()
+        .map(|entry| entry["constraints"].as_u64().unwrap())
+        .sum();
+    assert_eq!(stats["constraints"], total);
+    for entry in templates {
+        let share = 100.0 * entry["constraints"].as_f64().unwrap() / total as f64;
+        assert_eq!(entry["share"].as_f64().unwrap(), share);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+// Double does not compute what its constraint says, so the witness of its test
+// breaks the constraint
+const TESTS: &str = "pragma circom 2.1.0;
+
+template Mul() {
+    signal input a;
+    signal input b;
+    signal output c;
+    c <== a * b;
+}
+
+template Double() {
+    signal input a;
+    signal output b;
+    b <-- a + 1;
+    b === a * 2;
+}
+
+test \"product\" {
+    component m = Mul();
+    m.a <== 3;
+    m.b <== 4;
+    assert(m.c == 12);
+}
+
+test \"wrong product\" {
+    component m = Mul();
+    m.a <== 3;
+    m.b <== 4;
+    assert(m.c == 13);
+}
+
+test \"wrong double\" {
+    component d = Double();
+    d.a <== 3;
+}
+";
+
+fn run_tests(name: &str, code: &str) -> Output {
+    let folder: PathBuf = std::env::temp_dir().join(format!(
+        "circom_test_blocks_{}_{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&folder).unwrap();
+    let file = folder.join(format!("{}.circom", name));
+    std::fs::write(&file, code).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg("test")
+        .arg(&file)
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&folder);
+    output
+}
+
+fn without_colours(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+// The result printed for every test, and the summary line
+fn results(output: &Output) -> (HashMap<String, String>, String) {
+    let stdout = without_colours(&String::from_utf8_lossy(&output.stdout));
+    let stderr = without_colours(&String::from_utf8_lossy(&output.stderr));
+    let tests = stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("test ")?.split_once(" ... "))
+        .map(|(name, result)| (name.to_string(), result.to_string()))
+        .collect();
+    let summary = stdout
+        .lines()
+        .chain(stderr.lines())
+        .find(|line| line.starts_with("test result: "))
+        .unwrap()
+        .to_string();
+    (tests, summary)
+}
+
+#[test]
+fn failing_tests_are_reported_and_fail_the_run() {
+    let output = run_tests("mixed", TESTS);
+    assert!(!output.status.success());
+    let (tests, summary) = results(&output);
+    assert_eq!(tests.len(), 3);
+    assert_eq!(tests["product"], "ok");
+    assert_eq!(tests["wrong product"], "FAILED");
+    assert_eq!(tests["wrong double"], "FAILED");
+    assert_eq!(summary, "test result: FAILED. 1 passed; 2 failed");
+
+    let errors = without_colours(&String::from_utf8_lossy(&output.stderr));
+    assert!(errors.contains("Failed assert in template/function test \"wrong product\" line 28"));
+    assert!(errors.contains("Failed assert in template/function Double line 14"));
+}
+
+#[test]
+fn passing_tests_succeed() {
+    let end = TESTS.find("test \"wrong product\"").unwrap();
+    let output = run_tests("passing", &TESTS[..end]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let (tests, summary) = results(&output);
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests["product"], "ok");
+    assert_eq!(summary, "test result: ok. 1 passed; 0 failed");
+}
@@ -0,0 +1,207 @@
+use std::convert::TryInto;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use wasmi::core::Trap;
+use wasmi::{Caller, Engine, Instance, Linker, Module, Store};
+
+// The inverse is computed by the field code of the module and a = 0 fails the
+// assert
+const FIXTURE: &str = "pragma circom 2.1.0;
+
+template Mul(n) {
+    signal input in[n];
+    signal output out;
+    signal acc[n];
+    acc[0] <== in[0];
+    for (var i = 1; i < n; i++) {
+        acc[i] <== acc[i - 1] * in[i];
+    }
+    out <== acc[n - 1];
+}
+
+template Fixture() {
+    signal input a[3];
+    signal input b[2];
+    signal output out[2];
+    signal output inv;
+    assert(a[0] != 0);
+    component mul = Mul(3);
+    mul.in <== a;
+    out[0] <== mul.out * b[0];
+    out[1] <== b[1] + 7;
+    inv <-- 1 / a[0];
+    inv * a[0] === 1;
+}
+
+component main = Fixture();
+";
+
+const INPUT: [(&str, &[u64]); 2] = [("a", &[2, 3, 4]), ("b", &[5, 6])];
+
+const ASSERT_FAILED: i32 = 4;
+
+fn test_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!(
+        "circom_wasm_witness_{}_{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+// Writes the wasm and the wat of the fixture and computes the witness of the
+// input with the interpreter, which the module is checked against
+fn generate(folder: &Path, prime: &str) -> (Vec<u8>, Vec<Vec<u32>>) {
+    let file = folder.join("fixture.circom");
+    std::fs::write(&file, FIXTURE).unwrap();
+    let input = folder.join("input.json");
+    std::fs::write(&input, r#"{"a": ["2", "3", "4"], "b": ["5", "6"]}"#).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_circom"))
+        .arg(&file)
+        .args(["--O2", "--wasm", "--wat", "--prime", prime, "--wtns"])
+        .arg(&input)
+        .arg("-o")
+        .arg(folder)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let js_folder = folder.join("fixture_js");
+    let wasm = std::fs::read(js_folder.join("fixture.wasm")).unwrap();
+
+    // the wasm is the encoding of the wat
+    let wat = std::fs::read_to_string(js_folder.join("fixture.wat")).unwrap();
+    let buffer = wast::parser::ParseBuffer::new(&wat).unwrap();
+    let mut module = wast::parser::parse::<wast::Wat>(&buffer).unwrap().module;
+    assert_eq!(module.encode().unwrap(), wasm);
+
+    (wasm, read_wtns(&folder.join("fixture.wtns")))
+}
+
+// The values of the witness, each of them as its words of 32 bits
+fn read_wtns(path: &Path) -> Vec<Vec<u32>> {
+    let bytes = std::fs::read(path).unwrap();
+    let word = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    assert_eq!(&bytes[0..4], b"wtns");
+    let n32 = word(24) as usize / 4;
+    let values = word(28 + 4 * n32) as usize;
+    let start = 44 + 4 * n32;
+    (0..values)
+        .map(|i| (0..n32).map(|j| word(start + 4 * (n32 * i + j))).collect())
+        .collect()
+}
+
+fn fnv1a(name: &str) -> u64 {
+    let mut hash: u64 = 0xCBF29CE484222325;
+    for byte in name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001B3);
+    }
+    hash
+}
+
+// The runtime imports of the module, the exception codes are kept in the store
+fn instantiate(wasm: &[u8]) -> (Store<Vec<i32>>, Instance) {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm).unwrap();
+    let mut store = Store::new(&engine, Vec::new());
+    let mut linker = <Linker<Vec<i32>>>::new(&engine);
+    linker
+        .func_wrap(
+            "runtime",
+            "exceptionHandler",
+            |mut caller: Caller<'_, Vec<i32>>, code: i32| -> Result<(), Trap> {
+                caller.data_mut().push(code);
+                Err(Trap::new(format!("exception {}", code)))
+            },
+        )
+        .unwrap();
+    for name in [
+        "printErrorMessage",
+        "writeBufferMessage",
+        "showSharedRWMemory",
+    ] {
+        linker.func_wrap("runtime", name, || {}).unwrap();
+    }
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .unwrap()
+        .start(&mut store)
+        .unwrap();
+    (store, instance)
+}
+
+// Same steps as _doCalculateWitness and calculateWitness in
+// witness_calculator.js
+fn calculate_witness(
+    store: &mut Store<Vec<i32>>,
+    instance: &Instance,
+    input: &[(&str, &[u64])],
+) -> Result<Vec<Vec<u32>>, wasmi::Error> {
+    let n32 = instance
+        .get_typed_func::<(), i32>(&*store, "getFieldNumLen32")?
+        .call(&mut *store, ())? as usize;
+    let init = instance.get_typed_func::<i32, ()>(&*store, "init")?;
+    let write = instance.get_typed_func::<(i32, i32), ()>(&*store, "writeSharedRWMemory")?;
+    let read = instance.get_typed_func::<i32, i32>(&*store, "readSharedRWMemory")?;
+    let size = instance.get_typed_func::<(i32, i32), i32>(&*store, "getInputSignalSize")?;
+    let set = instance.get_typed_func::<(i32, i32, i32), ()>(&*store, "setInputSignal")?;
+    let witness_size = instance.get_typed_func::<(), i32>(&*store, "getWitnessSize")?;
+    let get = instance.get_typed_func::<i32, ()>(&*store, "getWitness")?;
+
+    init.call(&mut *store, 0)?;
+    for (name, values) in input {
+        let hash = fnv1a(name);
+        let (msb, lsb) = ((hash >> 32) as i32, hash as i32);
+        assert_eq!(size.call(&mut *store, (msb, lsb))?, values.len() as i32);
+        for (i, value) in values.iter().enumerate() {
+            for j in 0..n32 {
+                let word = if j < 2 { (value >> (32 * j)) as u32 } else { 0 };
+                write.call(&mut *store, (j as i32, word as i32))?;
+            }
+            set.call(&mut *store, (msb, lsb, i as i32))?;
+        }
+    }
+    let mut witness = Vec::new();
+    for i in 0..witness_size.call(&mut *store, ())? {
+        get.call(&mut *store, i)?;
+        let value = (0..n32)
+            .map(|j| read.call(&mut *store, j as i32).map(|word| word as u32))
+            .collect::<Result<_, _>>()?;
+        witness.push(value);
+    }
+    Ok(witness)
+}
+
+fn check_prime(prime: &str) {
+    let folder = test_folder(prime);
+    let (wasm, expected) = generate(&folder, prime);
+    let (mut store, instance) = instantiate(&wasm);
+    let witness = calculate_witness(&mut store, &instance, &INPUT).unwrap();
+    assert_eq!(witness, expected);
+    assert!(store.data().is_empty());
+
+    // the same instance computes another witness after init, and a failed
+    // assert goes through the exception handler
+    let failing: [(&str, &[u64]); 2] = [("a", &[0, 3, 4]), ("b", &[5, 6])];
+    assert!(calculate_witness(&mut store, &instance, &failing).is_err());
+    assert_eq!(store.data(), &[ASSERT_FAILED]);
+    let witness = calculate_witness(&mut store, &instance, &INPUT).unwrap();
+    assert_eq!(witness, expected);
+
+    let _ = std::fs::remove_dir_all(&folder);
+}
+
+#[test]
+fn bn128_module_matches_the_interpreter() {
+    check_prime("bn128");
+}
+
+#[test]
+fn m31_module_matches_the_interpreter() {
+    check_prime("m31");
+}
@@ -17,6 +17,8 @@ pub type ConstraintID = usize;
 pub struct ConstraintStorage {
     field_tracker: FieldTracker,
     constraints: Vec<CompressedConstraint>,
+    // node of the dag that emitted each constraint, when it is known
+    origins: Vec<Option<usize>>,
 }
 
 impl ConstraintStorage {
@@ -24,6 +26,7 @@ impl ConstraintStorage {
         ConstraintStorage {
             field_tracker: FieldTracker::new(),
             constraints: Vec::new(),
+            origins: Vec::new(),
         }
     }
 
@@ -31,9 +34,21 @@ impl ConstraintStorage {
         let id = self.constraints.len();
         let compressed = logic::code_constraint(constraint, &mut self.field_tracker);
         self.constraints.push(compressed);
+        self.origins.push(None);
         id
     }
 
+    pub fn add_constraint_from(&mut self, constraint: C, origin: usize) -> ConstraintID {
+        let id = self.add_constraint(constraint);
+        self.origins[id] = Some(origin);
+        id
+    }
+
+    // The replacements and the substitutions keep the origin of a constraint
+    pub fn read_origin(&self, id: ConstraintID) -> Option<usize> {
+        self.origins.get(id).cloned().flatten()
+    }
+
     pub fn read_constraint(&self, id: ConstraintID) -> Option<C> {
         if id < self.constraints.len() {
             Some(logic::decode_constraint(
@@ -53,13 +68,15 @@ impl ConstraintStorage {
 
     pub fn extract_with(&mut self, filter: &dyn Fn(&C) -> bool) -> LinkedList<C> {
         let old = std::mem::take(&mut self.constraints);
+        let old_origins = std::mem::take(&mut self.origins);
         let mut removed = LinkedList::new();
-        for c in old {
+        for (c, origin) in old.into_iter().zip(old_origins) {
             let decoded = logic::decode_constraint(&c, &self.field_tracker);
             if filter(&decoded) {
                 removed.push_back(decoded);
             } else {
                 self.constraints.push(c);
+                self.origins.push(origin);
             }
         }
         removed
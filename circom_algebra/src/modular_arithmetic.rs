@@ -225,6 +225,85 @@ pub fn greater_eq(left: &BigInt, right: &BigInt, field: &BigInt) -> BigInt {
     bool_or(&greater(left, right, field), &eq(left, right, field), field)
 }
 
+// Degree 4 extension of M31 used by Circle-STARKs:
+// CM31 = F[i] / (i^2 + 1) and QM31 = CM31[u] / (u^2 - 2 - i).
+// The element (a0 + a1 * i) + (a2 + a3 * i) * u is represented as [a0, a1, a2, a3].
+fn cm31_mul(
+    left: (&BigInt, &BigInt),
+    right: (&BigInt, &BigInt),
+    field: &BigInt,
+) -> (BigInt, BigInt) {
+    let real = sub(&(left.0 * right.0), &(left.1 * right.1), field);
+    let imaginary = add(&(left.0 * right.1), &(left.1 * right.0), field);
+    (real, imaginary)
+}
+// Multiplication by the non-residue 2 + i
+fn cm31_mul_by_r(elem: (&BigInt, &BigInt), field: &BigInt) -> (BigInt, BigInt) {
+    let two = BigInt::from(2);
+    let real = sub(&(&two * elem.0), elem.1, field);
+    let imaginary = add(&(&two * elem.1), elem.0, field);
+    (real, imaginary)
+}
+pub fn qm31_add(left: &[BigInt], right: &[BigInt], field: &BigInt) -> Vec<BigInt> {
+    left.iter()
+        .zip(right.iter())
+        .map(|(l, r)| add(l, r, field))
+        .collect()
+}
+pub fn qm31_sub(left: &[BigInt], right: &[BigInt], field: &BigInt) -> Vec<BigInt> {
+    left.iter()
+        .zip(right.iter())
+        .map(|(l, r)| sub(l, r, field))
+        .collect()
+}
+pub fn qm31_mul(left: &[BigInt], right: &[BigInt], field: &BigInt) -> Vec<BigInt> {
+    let xx = cm31_mul((&left[0], &left[1]), (&right[0], &right[1]), field);
+    let yy = cm31_mul((&left[2], &left[3]), (&right[2], &right[3]), field);
+    let xy = cm31_mul((&left[0], &left[1]), (&right[2], &right[3]), field);
+    let yx = cm31_mul((&left[2], &left[3]), (&right[0], &right[1]), field);
+    let ryy = cm31_mul_by_r((&yy.0, &yy.1), field);
+    vec![
+        add(&xx.0, &ryy.0, field),
+        add(&xx.1, &ryy.1, field),
+        add(&xy.0, &yx.0, field),
+        add(&xy.1, &yx.1, field),
+    ]
+}
+pub fn qm31_inv(elem: &[BigInt], field: &BigInt) -> Result<Vec<BigInt>, ArithmeticError> {
+    // (x + y * u)^-1 = (x - y * u) / (x^2 - (2 + i) * y^2)
+    let xx = cm31_mul((&elem[0], &elem[1]), (&elem[0], &elem[1]), field);
+    let yy = cm31_mul((&elem[2], &elem[3]), (&elem[2], &elem[3]), field);
+    let ryy = cm31_mul_by_r((&yy.0, &yy.1), field);
+    let denominator = (sub(&xx.0, &ryy.0, field), sub(&xx.1, &ryy.1, field));
+    // (a + b * i)^-1 = (a - b * i) / (a^2 + b^2)
+    let norm = add(
+        &(&denominator.0 * &denominator.0),
+        &(&denominator.1 * &denominator.1),
+        field,
+    );
+    let norm_inverse = div(&BigInt::from(1), &norm, field)?;
+    let denominator_inverse = (
+        mul(&denominator.0, &norm_inverse, field),
+        mul(&prefix_sub(&denominator.1, field), &norm_inverse, field),
+    );
+    let x = cm31_mul(
+        (&elem[0], &elem[1]),
+        (&denominator_inverse.0, &denominator_inverse.1),
+        field,
+    );
+    let y = cm31_mul(
+        (&elem[2], &elem[3]),
+        (&denominator_inverse.0, &denominator_inverse.1),
+        field,
+    );
+    Result::Ok(vec![
+        x.0,
+        x.1,
+        prefix_sub(&y.0, field),
+        prefix_sub(&y.1, field),
+    ])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -277,4 +356,22 @@ mod tests {
         assert!(zero < two);
         assert!(as_bool(&lesser_eq(&zero, &two, &field), &field));
     }
+    #[test]
+    fn qm31_inverse_check() {
+        let field = BigInt::from(2147483647);
+        let a: Vec<BigInt> = [1, 2, 3, 4].iter().map(|v| BigInt::from(*v)).collect();
+        let b: Vec<BigInt> = [5, 6, 7, 8].iter().map(|v| BigInt::from(*v)).collect();
+        let one: Vec<BigInt> = [1, 0, 0, 0].iter().map(|v| BigInt::from(*v)).collect();
+        // u^2 = 2 + i
+        let u: Vec<BigInt> = [0, 0, 1, 0].iter().map(|v| BigInt::from(*v)).collect();
+        let r: Vec<BigInt> = [2, 1, 0, 0].iter().map(|v| BigInt::from(*v)).collect();
+        assert_eq!(qm31_mul(&u, &u, &field), r);
+        assert_eq!(qm31_mul(&a, &b, &field), qm31_mul(&b, &a, &field));
+        let a_inverse = qm31_inv(&a, &field).ok().unwrap();
+        assert_eq!(qm31_mul(&a, &a_inverse, &field), one);
+        let ab = qm31_mul(&a, &b, &field);
+        assert_eq!(qm31_mul(&ab, &a_inverse, &field), b);
+        let zero = vec![BigInt::from(0); 4];
+        assert!(qm31_inv(&zero, &field).is_err());
+    }
 }
@@ -0,0 +1,282 @@
+use program_structure::ast::{self, SignalType, Statement, VariableType};
+use program_structure::file_definition::FileLocation;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DefinitionKind {
+    Template,
+    Function,
+}
+
+pub struct SignalDeclaration {
+    pub name: String,
+    pub signal_type: SignalType,
+    pub tags: Vec<String>,
+    pub dimensions: Vec<String>,
+    pub location: FileLocation,
+}
+
+impl SignalDeclaration {
+    pub fn describe(&self) -> String {
+        let kind = match self.signal_type {
+            SignalType::Input => "signal input",
+            SignalType::Output => "signal output",
+            SignalType::Intermediate => "signal",
+        };
+        let tags = if self.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" {{{}}}", self.tags.join(", "))
+        };
+        let dimensions: String = self.dimensions.iter().map(|d| format!("[{}]", d)).collect();
+        format!("{}{} {}{}", kind, tags, self.name, dimensions)
+    }
+}
+
+pub struct Definition {
+    pub kind: DefinitionKind,
+    pub name: String,
+    pub params: Vec<String>,
+    pub file: PathBuf,
+    pub location: FileLocation,
+    pub name_location: FileLocation,
+    pub signals: Vec<SignalDeclaration>,
+}
+
+impl Definition {
+    pub fn signature(&self) -> String {
+        let keyword = match self.kind {
+            DefinitionKind::Template => "template",
+            DefinitionKind::Function => "function",
+        };
+        format!("{} {}({})", keyword, self.name, self.params.join(", "))
+    }
+}
+
+// The templates and functions of a file and of the files it includes. Each
+// file is parsed on its own, so files without a main component are indexed
+// too. The files with syntax errors are indexed when the parser recovers
+// from all of them.
+pub struct Index {
+    pub sources: HashMap<PathBuf, String>,
+    pub definitions: Vec<Definition>,
+}
+
+impl Index {
+    // documents holds the content of the files open in the editor, the rest
+    // of the files are read from disk
+    pub fn build(
+        root: &Path,
+        documents: &HashMap<PathBuf, String>,
+        link_libraries: &[PathBuf],
+    ) -> Index {
+        let mut index = Index {
+            sources: HashMap::new(),
+            definitions: Vec::new(),
+        };
+        let mut visited = HashSet::new();
+        let mut pending = VecDeque::new();
+        pending.push_back(root.to_path_buf());
+        while let Some(file) = pending.pop_front() {
+            if !visited.insert(file.clone()) {
+                continue;
+            }
+            let source = match documents.get(&file) {
+                Some(source) => source.clone(),
+                None => match read_to_string(&file) {
+                    Ok(source) => source,
+                    Err(_) => continue,
+                },
+            };
+            if let (Some(program), _) = parser::parse_file_with_recovery(&source, 0) {
+                for include in &program.includes {
+                    if let Some(path) = resolve_include(&file, include, link_libraries) {
+                        pending.push_back(path);
+                    }
+                }
+                for definition in &program.definitions {
+                    // the parser keeps the definitions without a name
+                    if let Some(definition) = index_definition(definition, &file, &source) {
+                        if !definition.name.is_empty() {
+                            index.definitions.push(definition);
+                        }
+                    }
+                }
+            }
+            index.sources.insert(file, source);
+        }
+        index
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Definition> {
+        self.definitions.iter().find(|d| d.name == name)
+    }
+
+    pub fn enclosing(&self, file: &Path, offset: usize) -> Option<&Definition> {
+        self.definitions
+            .iter()
+            .find(|d| d.file == file && d.location.start <= offset && offset < d.location.end)
+    }
+
+    pub fn in_file<'a>(&'a self, file: &'a Path) -> impl Iterator<Item = &'a Definition> + 'a {
+        self.definitions.iter().filter(move |d| d.file == file)
+    }
+}
+
+// Same lookup as the parser: next to the including file and then in the
+// link libraries
+pub fn resolve_include(file: &Path, include: &str, link_libraries: &[PathBuf]) -> Option<PathBuf> {
+    let mut directories = vec![file.parent().map_or(PathBuf::new(), Path::to_path_buf)];
+    directories.extend(link_libraries.iter().cloned());
+    directories
+        .into_iter()
+        .filter_map(|directory| std::fs::canonicalize(directory.join(include)).ok())
+        .find(|path| path.is_file())
+}
+
+// the tests are not indexed, they cannot be called
+fn index_definition(definition: &ast::Definition, file: &Path, source: &str) -> Option<Definition> {
+    let (kind, meta, name, args, arg_location, body) = match definition {
+        ast::Definition::Template {
+            meta,
+            name,
+            args,
+            arg_location,
+            body,
+            ..
+        } => (
+            DefinitionKind::Template,
+            meta,
+            name,
+            args,
+            arg_location,
+            body,
+        ),
+        ast::Definition::Function {
+            meta,
+            name,
+            args,
+            arg_location,
+            body,
+        } => (
+            DefinitionKind::Function,
+            meta,
+            name,
+            args,
+            arg_location,
+            body,
+        ),
+        ast::Definition::Test { .. } => return None,
+    };
+    // the name is the last occurrence before the parameters
+    let header = source.get(meta.start..arg_location.start).unwrap_or("");
+    let name_location = match header.rfind(name.as_str()) {
+        Some(i) => (meta.start + i)..(meta.start + i + name.len()),
+        None => meta.location.clone(),
+    };
+    let mut signals = Vec::new();
+    collect_signals(body, source, &mut signals);
+    Some(Definition {
+        kind,
+        name: name.clone(),
+        params: args.clone(),
+        file: file.to_path_buf(),
+        location: meta.location.clone(),
+        name_location,
+        signals,
+    })
+}
+
+fn collect_signals(statement: &Statement, source: &str, signals: &mut Vec<SignalDeclaration>) {
+    use Statement::*;
+    match statement {
+        Declaration {
+            meta,
+            xtype: VariableType::Signal(signal_type, tags),
+            name,
+            dimensions,
+            ..
+        } => {
+            let dimensions = dimensions
+                .iter()
+                .map(|d| {
+                    let location = &d.get_meta().location;
+                    source
+                        .get(location.clone())
+                        .unwrap_or("")
+                        .trim()
+                        .to_string()
+                })
+                .collect();
+            signals.push(SignalDeclaration {
+                name: name.clone(),
+                signal_type: *signal_type,
+                tags: tags.clone(),
+                dimensions,
+                location: meta.location.clone(),
+            });
+        }
+        InitializationBlock {
+            initializations, ..
+        } => {
+            for s in initializations {
+                collect_signals(s, source, signals);
+            }
+        }
+        Block { stmts, .. } => {
+            for s in stmts {
+                collect_signals(s, source, signals);
+            }
+        }
+        IfThenElse {
+            if_case, else_case, ..
+        } => {
+            collect_signals(if_case, source, signals);
+            if let Some(else_case) = else_case {
+                collect_signals(else_case, source, signals);
+            }
+        }
+        While { stmt, .. } => collect_signals(stmt, source, signals),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_and_signals_are_indexed() {
+        let file = PathBuf::from("/nonexistent/main.circom");
+        let source = "pragma circom 2.0.0;
+
+template Bits(n) {
+    signal input in;
+    signal output {binary} out[n];
+}
+
+function square(x) {
+    return x * x
+}
+
+template (m) {}
+";
+        let mut documents = HashMap::new();
+        documents.insert(file.clone(), source.to_string());
+        let index = Index::build(&file, &documents, &[]);
+        let bits = index.find("Bits").unwrap();
+        assert_eq!(bits.signature(), "template Bits(n)");
+        assert_eq!(&source[bits.name_location.clone()], "Bits");
+        let described: Vec<String> = bits.signals.iter().map(|s| s.describe()).collect();
+        assert_eq!(
+            described,
+            vec!["signal input in", "signal output {binary} out[n]"]
+        );
+        let square = index.find("square").unwrap();
+        assert_eq!(square.kind, DefinitionKind::Function);
+        let offset = source.find("x * x").unwrap();
+        assert_eq!(index.enclosing(&file, offset).unwrap().name, "square");
+    }
+}
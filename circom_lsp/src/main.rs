@@ -0,0 +1,18 @@
+mod index;
+mod server;
+mod text;
+mod transport;
+
+use server::Server;
+
+// Language server for circom over stdio. It publishes the diagnostics of the
+// compiler when a document is opened or saved, and answers go to definition,
+// hover and document symbol requests.
+fn main() {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let result = Server::new().run(&mut stdin.lock(), &mut stdout.lock());
+    if result.is_err() {
+        std::process::exit(1);
+    }
+}
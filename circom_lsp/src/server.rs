@@ -0,0 +1,547 @@
+use crate::index::{DefinitionKind, Index};
+use crate::text::{include_at, path_to_uri, to_offset, to_range, uri_to_path, word_at};
+use crate::transport::{read_message, write_message};
+use program_structure::error_definition::Report;
+use program_structure::file_definition::FileLibrary;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use type_analysis::check_types::check_types;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+// LSP diagnostic severities and symbol kinds
+const SEVERITY_ERROR: u64 = 1;
+const SEVERITY_WARNING: u64 = 2;
+const SYMBOL_CLASS: u64 = 5;
+const SYMBOL_FIELD: u64 = 8;
+const SYMBOL_FUNCTION: u64 = 12;
+
+#[derive(Default)]
+pub struct Server {
+    // content of the open documents, which may not be saved yet
+    documents: HashMap<PathBuf, String>,
+    link_libraries: Vec<PathBuf>,
+    // uris with diagnostics in the client, to clear them once they are fixed
+    published: HashSet<String>,
+    shutdown: bool,
+    exit: bool,
+}
+
+impl Server {
+    pub fn new() -> Server {
+        Server::default()
+    }
+
+    // Serves the messages of the reader until the client sends exit. Fails
+    // when the stream is broken or the client exits without a shutdown.
+    pub fn run<R: BufRead, W: Write>(&mut self, reader: &mut R, writer: &mut W) -> Result<(), ()> {
+        while !self.exit {
+            let message = match read_message(reader)? {
+                Some(message) => message,
+                None => break,
+            };
+            for response in self.handle(&message) {
+                write_message(writer, &response)?;
+            }
+        }
+        if self.shutdown {
+            Result::Ok(())
+        } else {
+            Result::Err(())
+        }
+    }
+
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            None => {
+                return self.notification(method, params);
+            }
+        };
+        let result = match method {
+            "initialize" => Result::Ok(self.initialize(params)),
+            "shutdown" => {
+                self.shutdown = true;
+                Result::Ok(Value::Null)
+            }
+            "textDocument/definition" => self.definition(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            _ => Result::Err((METHOD_NOT_FOUND, format!("unknown method {}", method))),
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        vec![response]
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let document = &params["textDocument"];
+        let path = document["uri"].as_str().and_then(uri_to_path);
+        match (method, path) {
+            ("exit", _) => {
+                self.exit = true;
+                Vec::new()
+            }
+            ("textDocument/didOpen", Some(path)) => {
+                let text = document["text"].as_str().unwrap_or("").to_string();
+                self.documents.insert(path.clone(), text);
+                self.diagnostics(&path)
+            }
+            ("textDocument/didChange", Some(path)) => {
+                // full synchronization, the last change holds the whole text
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|c| c.last())
+                    .and_then(|c| c["text"].as_str())
+                {
+                    self.documents.insert(path, text.to_string());
+                }
+                Vec::new()
+            }
+            ("textDocument/didSave", Some(path)) => self.diagnostics(&path),
+            ("textDocument/didClose", Some(path)) => {
+                self.documents.remove(&path);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        if let Some(libraries) = params["initializationOptions"]["linkLibraries"].as_array() {
+            self.link_libraries = libraries
+                .iter()
+                .filter_map(|l| l.as_str().map(PathBuf::from))
+                .collect();
+        }
+        json!({
+            "capabilities": {
+                "textDocumentSync": {
+                    "openClose": true,
+                    "change": 1,
+                    "save": { "includeText": false },
+                },
+                "definitionProvider": true,
+                "hoverProvider": true,
+                "documentSymbolProvider": true,
+            },
+            "serverInfo": { "name": "circom-lsp", "version": VERSION },
+        })
+    }
+
+    // The saved file is compiled as the main file of a project. Its includes
+    // are reported too, and a file without a main component is a library,
+    // which is checked without one.
+    fn diagnostics(&mut self, path: &Path) -> Vec<Value> {
+        let parsed = parser::run_parser_for_library(
+            path.display().to_string(),
+            VERSION,
+            self.link_libraries.clone(),
+        );
+        let (file_library, reports) = match parsed {
+            Ok((mut program_archive, mut reports)) => {
+                match check_types(&mut program_archive) {
+                    Ok(mut found) | Err(mut found) => reports.append(&mut found),
+                }
+                (program_archive.file_library, reports)
+            }
+            Err(failure) => failure,
+        };
+        let root = path_to_uri(path);
+        let mut diagnostics: HashMap<String, Vec<Value>> = HashMap::new();
+        diagnostics.insert(root.clone(), Vec::new());
+        for report in &reports {
+            let (uri, diagnostic) = to_diagnostic(report, &file_library);
+            diagnostics
+                .entry(uri.unwrap_or_else(|| root.clone()))
+                .or_default()
+                .push(diagnostic);
+        }
+        let mut notifications = Vec::new();
+        let published: HashSet<String> = diagnostics.keys().cloned().collect();
+        for uri in self.published.difference(&published) {
+            diagnostics.entry(uri.clone()).or_default();
+        }
+        let mut uris: Vec<&String> = diagnostics.keys().collect();
+        uris.sort();
+        for uri in uris {
+            notifications.push(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": { "uri": uri, "diagnostics": diagnostics[uri] },
+            }));
+        }
+        self.published = published;
+        notifications
+    }
+
+    fn index(&self, path: &Path) -> Index {
+        Index::build(path, &self.documents, &self.link_libraries)
+    }
+
+    fn definition(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (path, offset) = self.position(params)?;
+        let index = self.index(&path);
+        let source = &index.sources[&path];
+        if let Some(include) = include_at(source, offset) {
+            let libraries = &self.link_libraries;
+            let location = crate::index::resolve_include(&path, &include, libraries)
+                .map(|file| json!({ "uri": path_to_uri(&file), "range": to_range("", 0, 0) }));
+            return Result::Ok(location.unwrap_or(Value::Null));
+        }
+        let (word, _, _) = match word_at(source, offset) {
+            Some(word) => word,
+            None => return Result::Ok(Value::Null),
+        };
+        if let Some(definition) = index.find(&word) {
+            let source = &index.sources[&definition.file];
+            let location = &definition.name_location;
+            return Result::Ok(json!({
+                "uri": path_to_uri(&definition.file),
+                "range": to_range(source, location.start, location.end),
+            }));
+        }
+        let signal = index
+            .enclosing(&path, offset)
+            .and_then(|d| d.signals.iter().find(|s| s.name == word));
+        Result::Ok(match signal {
+            Some(signal) => json!({
+                "uri": path_to_uri(&path),
+                "range": to_range(source, signal.location.start, signal.location.end),
+            }),
+            None => Value::Null,
+        })
+    }
+
+    fn hover(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (path, offset) = self.position(params)?;
+        let index = self.index(&path);
+        let source = &index.sources[&path];
+        let (word, start, end) = match word_at(source, offset) {
+            Some(word) => word,
+            None => return Result::Ok(Value::Null),
+        };
+        let mut lines = Vec::new();
+        if let Some(definition) = index.find(&word) {
+            lines.push(definition.signature());
+            for signal in &definition.signals {
+                if signal.signal_type != program_structure::ast::SignalType::Intermediate {
+                    lines.push(format!("    {};", signal.describe()));
+                }
+            }
+        } else if let Some(definition) = index.enclosing(&path, offset) {
+            if let Some(signal) = definition.signals.iter().find(|s| s.name == word) {
+                lines.push(signal.describe());
+            }
+        }
+        if lines.is_empty() {
+            return Result::Ok(Value::Null);
+        }
+        Result::Ok(json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!("```circom\n{}\n```", lines.join("\n")),
+            },
+            "range": to_range(source, start, end),
+        }))
+    }
+
+    fn document_symbols(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let path = params["textDocument"]["uri"]
+            .as_str()
+            .and_then(uri_to_path)
+            .ok_or((INVALID_PARAMS, "invalid document".to_string()))?;
+        let index = self.index(&path);
+        let source = match index.sources.get(&path) {
+            Some(source) => source,
+            None => return Result::Ok(json!([])),
+        };
+        let symbols: Vec<Value> = index
+            .in_file(&path)
+            .map(|definition| {
+                let signals: Vec<Value> = definition
+                    .signals
+                    .iter()
+                    .map(|signal| {
+                        let range = to_range(source, signal.location.start, signal.location.end);
+                        json!({
+                            "name": signal.name,
+                            "detail": signal.describe(),
+                            "kind": SYMBOL_FIELD,
+                            "range": range,
+                            "selectionRange": range,
+                        })
+                    })
+                    .collect();
+                let kind = match definition.kind {
+                    DefinitionKind::Template => SYMBOL_CLASS,
+                    DefinitionKind::Function => SYMBOL_FUNCTION,
+                };
+                let location = &definition.location;
+                let name_location = &definition.name_location;
+                json!({
+                    "name": definition.name,
+                    "detail": definition.signature(),
+                    "kind": kind,
+                    "range": to_range(source, location.start, location.end),
+                    "selectionRange": to_range(source, name_location.start, name_location.end),
+                    "children": signals,
+                })
+            })
+            .collect();
+        Result::Ok(json!(symbols))
+    }
+
+    // The document and the byte offset of a TextDocumentPositionParams
+    fn position(&self, params: &Value) -> Result<(PathBuf, usize), (i64, String)> {
+        let path = params["textDocument"]["uri"]
+            .as_str()
+            .and_then(uri_to_path)
+            .ok_or((INVALID_PARAMS, "invalid document".to_string()))?;
+        let source = match self.documents.get(&path) {
+            Some(source) => source.clone(),
+            None => std::fs::read_to_string(&path)
+                .map_err(|_| (INVALID_PARAMS, format!("can not read {}", path.display())))?,
+        };
+        let offset = to_offset(&source, &params["position"]);
+        Result::Ok((path, offset))
+    }
+}
+
+// The uri of the file of the first primary label, None when the report has
+// no location
+fn to_diagnostic(report: &Report, file_library: &FileLibrary) -> (Option<String>, Value) {
+    let source_of = |file_id| {
+        file_library
+            .to_storage()
+            .get(file_id)
+            .map_or("", |file| file.source().as_str())
+    };
+    let uri_of = |file_id| {
+        file_library
+            .get_file_name(file_id)
+            .map(|name| path_to_uri(Path::new(name.trim_matches('"'))))
+    };
+    let mut message = report.get_message().clone();
+    for note in report.get_notes() {
+        message.push('\n');
+        message.push_str(note);
+    }
+    let mut diagnostic = json!({
+        "range": to_range("", 0, 0),
+        "severity": if report.is_error() { SEVERITY_ERROR } else { SEVERITY_WARNING },
+        "code": Report::error_code_to_diagnostic_code(report.get_code()),
+        "source": "circom",
+        "message": message,
+    });
+    let mut uri = None;
+    if let Some(primary) = report.get_primary().first() {
+        let source = source_of(primary.file_id);
+        diagnostic["range"] = to_range(source, primary.range.start, primary.range.end);
+        uri = uri_of(primary.file_id);
+    }
+    let related: Vec<Value> = report
+        .get_primary()
+        .iter()
+        .skip(1)
+        .chain(report.get_secondary().iter())
+        .filter_map(|label| {
+            let location = json!({
+                "uri": uri_of(label.file_id)?,
+                "range": to_range(source_of(label.file_id), label.range.start, label.range.end),
+            });
+            Some(json!({ "location": location, "message": label.message }))
+        })
+        .collect();
+    if !related.is_empty() {
+        diagnostic["relatedInformation"] = json!(related);
+    }
+    (uri, diagnostic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn read_all(output: &[u8]) -> Vec<Value> {
+        let mut reader = Cursor::new(output);
+        let mut messages = Vec::new();
+        while let Some(message) = read_message(&mut reader).unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[test]
+    fn scripted_session() {
+        let directory = std::env::temp_dir().join(format!("circom_lsp_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let bits = "pragma circom 2.0.0;
+
+template Bits(n) {
+    signal input in;
+    signal output {binary} out[n];
+    out[0] <== in;
+}
+";
+        let main = "pragma circom 2.0.0;
+include \"bits.circom\";
+
+template Main() {
+    signal input a;
+    component b = Bits(1);
+    b.in <== a;
+    signal c;
+}
+
+component main = Main();
+";
+        std::fs::write(directory.join("bits.circom"), bits).unwrap();
+        std::fs::write(directory.join("main.circom"), main).unwrap();
+        let main_path = std::fs::canonicalize(directory.join("main.circom")).unwrap();
+        let uri = path_to_uri(&main_path);
+        let document = json!({ "uri": uri });
+        let at = |text: &str| {
+            let offset = main.find(text).unwrap();
+            crate::text::to_position(main, offset)
+        };
+        let script = vec![
+            request(1, "initialize", json!({})),
+            notification("initialized", json!({})),
+            notification(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "text": main, "version": 1 } }),
+            ),
+            request(
+                2,
+                "textDocument/definition",
+                json!({ "textDocument": document, "position": at("Bits(1)") }),
+            ),
+            request(
+                3,
+                "textDocument/hover",
+                json!({ "textDocument": document, "position": at("Bits(1)") }),
+            ),
+            request(
+                4,
+                "textDocument/documentSymbol",
+                json!({ "textDocument": document }),
+            ),
+            request(
+                5,
+                "textDocument/definition",
+                json!({ "textDocument": document, "position": at("bits.circom") }),
+            ),
+            request(6, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ];
+        let mut input = Vec::new();
+        for message in &script {
+            write_message(&mut input, message).unwrap();
+        }
+        let mut output = Vec::new();
+        let result = Server::new().run(&mut Cursor::new(input), &mut output);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert!(result.is_ok());
+
+        let messages = read_all(&output);
+        let response = |id: u64| messages.iter().find(|m| m["id"] == id).unwrap();
+        assert_eq!(response(1)["result"]["capabilities"]["hoverProvider"], true);
+        let diagnostics = messages
+            .iter()
+            .find(|m| m["method"] == "textDocument/publishDiagnostics")
+            .unwrap();
+        assert_eq!(diagnostics["params"]["uri"], json!(uri));
+        let codes: Vec<&Value> = diagnostics["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| &d["code"])
+            .collect();
+        assert!(codes.is_empty());
+
+        let bits_uri = path_to_uri(&main_path.with_file_name("bits.circom"));
+        assert_eq!(response(2)["result"]["uri"], json!(bits_uri));
+        assert_eq!(response(2)["result"]["range"]["start"]["line"], 2);
+        let hover = response(3)["result"]["contents"]["value"].as_str().unwrap();
+        assert!(hover.contains("template Bits(n)"));
+        assert!(hover.contains("signal output {binary} out[n];"));
+        let symbols = response(4)["result"].as_array().unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0]["name"], "Main");
+        assert_eq!(symbols[0]["children"].as_array().unwrap().len(), 2);
+        assert_eq!(response(5)["result"]["uri"], json!(bits_uri));
+    }
+
+    #[test]
+    fn library_without_main_is_checked() {
+        let directory =
+            std::env::temp_dir().join(format!("circom_lsp_library_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let library = "pragma circom 2.0.0;
+
+template Product() {
+    signal input a;
+    signal output b;
+    b <== a * c;
+}
+";
+        let path = directory.join("library.circom");
+        std::fs::write(&path, library).unwrap();
+        let uri = path_to_uri(&std::fs::canonicalize(&path).unwrap());
+        let script = vec![
+            request(1, "initialize", json!({})),
+            notification(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "text": library, "version": 1 } }),
+            ),
+            request(2, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ];
+        let mut input = Vec::new();
+        for message in &script {
+            write_message(&mut input, message).unwrap();
+        }
+        let mut output = Vec::new();
+        let result = Server::new().run(&mut Cursor::new(input), &mut output);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert!(result.is_ok());
+
+        let messages = read_all(&output);
+        let published: Vec<&Value> = messages
+            .iter()
+            .filter(|m| m["method"] == "textDocument/publishDiagnostics")
+            .collect();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0]["params"]["uri"], json!(uri));
+        let diagnostics = published[0]["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0]["message"], "Undeclared symbol");
+        assert_eq!(diagnostics[0]["severity"], SEVERITY_ERROR);
+        // the c of a * c
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 5);
+        assert_eq!(diagnostics[0]["range"]["start"]["character"], 14);
+    }
+}
@@ -0,0 +1,137 @@
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+// LSP positions count lines from 0 and columns in UTF-16 code units, the
+// locations of the reports and the AST are byte offsets
+pub fn to_position(source: &str, offset: usize) -> Value {
+    let offset = offset.min(source.len());
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = source[..line_start].matches('\n').count();
+    let character: usize = source[line_start..offset]
+        .chars()
+        .map(char::len_utf16)
+        .sum();
+    json!({ "line": line, "character": character })
+}
+
+pub fn to_range(source: &str, start: usize, end: usize) -> Value {
+    json!({ "start": to_position(source, start), "end": to_position(source, end) })
+}
+
+pub fn to_offset(source: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+    let mut offset = 0;
+    for _ in 0..line {
+        match source[offset..].find('\n') {
+            Some(i) => offset += i + 1,
+            None => return source.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in source[offset..].char_indices() {
+        if units >= character || c == '\n' {
+            return offset + i;
+        }
+        units += c.len_utf16();
+    }
+    source.len()
+}
+
+fn is_identifier(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+// The identifier that contains the offset
+pub fn word_at(source: &str, offset: usize) -> Option<(String, usize, usize)> {
+    let offset = offset.min(source.len());
+    let start = source[..offset]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_identifier(*c))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let end = source[offset..]
+        .find(|c: char| !is_identifier(c))
+        .map_or(source.len(), |i| offset + i);
+    if start < end {
+        Some((source[start..end].to_string(), start, end))
+    } else {
+        None
+    }
+}
+
+// The path of an include directive when the offset is on its line
+pub fn include_at(source: &str, offset: usize) -> Option<String> {
+    let offset = offset.min(source.len());
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i);
+    let line = source[line_start..line_end].trim();
+    let path = line
+        .strip_prefix("include")?
+        .trim_start()
+        .strip_prefix('"')?;
+    path.find('"').map(|end| path[..end].to_string())
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = path
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_and_offsets() {
+        let source = "template A() {\n    signal input \u{e9}a;\n}\n";
+        let offset = source.find("a;").unwrap();
+        let position = to_position(source, offset);
+        assert_eq!(position, json!({ "line": 1, "character": 18 }));
+        assert_eq!(to_offset(source, &position), offset);
+        assert_eq!(
+            word_at(source, offset),
+            Some(("a".to_string(), offset, offset + 1))
+        );
+        assert_eq!(
+            include_at("include \"../bits.circom\";\n", 3),
+            Some("../bits.circom".to_string())
+        );
+        let path = Path::new("/tmp/my circuits/main.circom");
+        assert_eq!(path_to_uri(path), "file:///tmp/my%20circuits/main.circom");
+        assert_eq!(uri_to_path(&path_to_uri(path)).unwrap(), path);
+    }
+}
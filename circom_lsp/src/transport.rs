@@ -0,0 +1,30 @@
+use serde_json::Value;
+use std::io::{BufRead, Write};
+
+// Every message is a Content-Length header, an empty line and the JSON body.
+// Returns None when the client closes the stream.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Value>, ()> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|_| {})? == 0 {
+            return Result::Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = Some(value.trim().parse::<usize>().map_err(|_| {})?);
+        }
+    }
+    let mut body = vec![0; length.ok_or(())?];
+    reader.read_exact(&mut body).map_err(|_| {})?;
+    serde_json::from_slice(&body).map(Some).map_err(|_| {})
+}
+
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> Result<(), ()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body).map_err(|_| {})?;
+    writer.flush().map_err(|_| {})
+}
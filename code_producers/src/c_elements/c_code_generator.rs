@@ -410,7 +410,7 @@ pub fn generate_my_trace() -> String {
 }
 
 pub fn build_failed_assert_message(line: usize) -> String {
-    format!("std::cout << \"Failed assert in template/function \" << {} << \" line {}. \" <<  \"Followed trace of components: \" << {} << std::endl" ,
+    format!("std::string(\"Failed assert in template/function \") + {} + \" line {}. Followed trace of components: \" + {}" ,
         MY_TEMPLATE_NAME,
         line,
         generate_my_trace()
@@ -534,18 +534,26 @@ pub fn generate_dat_witness_to_signal_list(signal_list: &Vec<usize>) -> Vec<u8>
 
 pub fn generate_dat_constant_list(producer: &CProducer, constant_list: &Vec<String>) -> Vec<u8> {
     let mut constant_list_data = vec![];
+    let p = producer.get_prime().parse::<BigInt>().unwrap();
+    let n8 = producer.get_size_32_bit() * 4;
+    // Montgomery based runtimes expect the constants already multiplied by R
+    let factor = match get_fr_implementation(producer) {
+        Ok(FrImplementation::Montgomery) => (BigInt::from(1) << (n8 * 8)) % &p,
+        _ => BigInt::from(1),
+    };
     for s in constant_list {
         let mut n = s.parse::<BigInt>().unwrap();
-        let p = producer.get_prime().parse::<BigInt>().unwrap();
         if n < BigInt::from(0) {
-            n = p + n;
+            n = &p + n;
         }
+        n = (n * &factor) % &p;
         let (snn, bnn) = n.to_bytes_be();
         assert_ne!(snn, Sign::Minus);
+        assert!(bnn.len() <= n8);
         let mut v: Vec<u8> = bnn.to_vec();
         v.reverse();
         constant_list_data.append(&mut v);
-        for _i in 0..4 - bnn.len() {
+        for _i in 0..n8 - bnn.len() {
             constant_list_data.push(0);
         }
     }
@@ -763,6 +771,25 @@ pub fn generate_main_cpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
     Ok(())
 }
 
+// C header and implementation of the library mode, which replace main.cpp
+pub fn generate_witness_library_files(c_folder: &PathBuf) -> std::io::Result<()> {
+    let files = [
+        ("circom_witness.h", include_str!("common/circom_witness.h")),
+        (
+            "circom_witness.cpp",
+            include_str!("common/circom_witness.cpp"),
+        ),
+    ];
+    for (name, code) in files {
+        let mut file_path = c_folder.clone();
+        file_path.push(name);
+        let mut c_file = std::io::BufWriter::new(File::create(file_path)?);
+        c_file.write_all(code.as_bytes())?;
+        c_file.flush()?;
+    }
+    Ok(())
+}
+
 pub fn generate_circom_hpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
     use std::io::BufWriter;
     let mut file_path = c_folder.clone();
@@ -780,21 +807,115 @@ pub fn generate_circom_hpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
     Ok(())
 }
 
-pub fn generate_fr_hpp_file(c_folder: &PathBuf, prime: &String) -> std::io::Result<()> {
+//--------------- field arithmetic (fr.hpp and fr.cpp) ---------------
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FrImplementation {
+    // one 32-bit word, specialised for 2^31 - 1
+    M31,
+    // one 64-bit word, specialised for 2^64 - 2^32 + 1
+    Goldilocks,
+    // size_32_bit limbs of 32 bits in Montgomery form
+    Montgomery,
+}
+
+pub fn get_fr_implementation(producer: &CProducer) -> Result<FrImplementation, String> {
+    let prime = producer
+        .get_prime()
+        .parse::<BigInt>()
+        .map_err(|_| format!("The prime {} is not a valid number", producer.get_prime()))?;
+    let bits = prime.bits();
+    let needed_limbs = bits / 32 + if bits % 32 != 0 { 1 } else { 0 };
+    let implementation = match producer.prime_str.as_str() {
+        "m31" => FrImplementation::M31,
+        "goldilocks" => FrImplementation::Goldilocks,
+        "bn128" | "bls12381" | "grumpkin" | "pallas" | "vesta" | "secq256r1" => {
+            FrImplementation::Montgomery
+        }
+        other => {
+            return Err(format!(
+                "The prime {} is not supported by the C witness generator",
+                other
+            ))
+        }
+    };
+    if needed_limbs != producer.get_size_32_bit() {
+        return Err(format!(
+            "The C witness generator for prime {} needs {} limbs of 32 bits, but {} were requested",
+            producer.prime_str,
+            needed_limbs,
+            producer.get_size_32_bit()
+        ));
+    }
+    Ok(implementation)
+}
+
+// Little endian list of the 32-bit limbs of n
+fn generate_limb_list(n: &BigInt, size_32_bit: usize) -> String {
+    let (_, mut bytes) = n.to_bytes_le();
+    bytes.resize(size_32_bit * 4, 0);
+    let limbs: Vec<String> = bytes
+        .chunks(4)
+        .map(|c| format!("0x{:08x}", u32::from_le_bytes([c[0], c[1], c[2], c[3]])))
+        .collect();
+    limbs.join(", ")
+}
+
+fn generate_montgomery_constants(producer: &CProducer) -> serde_json::Value {
+    let prime = producer.get_prime().parse::<BigInt>().unwrap();
+    let n32 = producer.get_size_32_bit();
+    let one = BigInt::from(1);
+    let r = (&one << (32 * n32)) % &prime;
+    let r2 = (&r * &r) % &prime;
+    let half = &prime >> 1;
+    let mask = (&one << prime.bits()) - &one;
+    // Newton iteration for q^-1 mod 2^32, every step doubles the correct bits
+    let (_, q_bytes) = prime.to_bytes_le();
+    let q0 = u32::from_le_bytes([q_bytes[0], q_bytes[1], q_bytes[2], q_bytes[3]]);
+    let mut inv: u32 = 1;
+    for _i in 0..5 {
+        inv = inv.wrapping_mul(2u32.wrapping_sub(q0.wrapping_mul(inv)));
+    }
+    json!({
+        "n32": n32,
+        "q": generate_limb_list(&prime, n32),
+        "r2": generate_limb_list(&r2, n32),
+        "one": generate_limb_list(&r, n32),
+        "half": generate_limb_list(&half, n32),
+        "mask": generate_limb_list(&mask, n32),
+        "np": format!("0x{:08x}", inv.wrapping_neg()),
+        "bits": prime.bits(),
+    })
+}
+
+fn generate_fr_code(producer: &CProducer, extension: &str) -> std::io::Result<String> {
+    let implementation = get_fr_implementation(producer).map_err(std::io::Error::other)?;
+    let code = match (implementation, extension) {
+        (FrImplementation::M31, "hpp") => include_str!("m31/fr.hpp").to_string(),
+        (FrImplementation::M31, _) => include_str!("m31/fr.cpp").to_string(),
+        (FrImplementation::Goldilocks, "hpp") => include_str!("goldilocks/fr.hpp").to_string(),
+        (FrImplementation::Goldilocks, _) => include_str!("goldilocks/fr.cpp").to_string(),
+        (FrImplementation::Montgomery, _) => {
+            let template = match extension {
+                "hpp" => include_str!("montgomery/fr.hpp"),
+                _ => include_str!("montgomery/fr.cpp"),
+            };
+            handlebars::Handlebars::new()
+                .render_template(template, &generate_montgomery_constants(producer))
+                .expect("must render")
+        }
+    };
+    Ok(code)
+}
+
+pub fn generate_fr_hpp_file(c_folder: &PathBuf, producer: &CProducer) -> std::io::Result<()> {
     use std::io::BufWriter;
     let mut file_path = c_folder.clone();
     file_path.push("fr");
     file_path.set_extension("hpp");
     let file_name = file_path.to_str().unwrap();
+    let code = generate_fr_code(producer, "hpp")?;
     let mut c_file = BufWriter::new(File::create(file_name).unwrap());
-    let mut code = "".to_string();
-    let file = match prime.as_ref() {
-        "m31" => include_str!("m31/fr.hpp"),
-        _ => unreachable!(),
-    };
-    for line in file.lines() {
-        code = format!("{}{}\n", code, line);
-    }
     c_file.write_all(code.as_bytes())?;
     c_file.flush()?;
     Ok(())
@@ -817,21 +938,14 @@ pub fn generate_calcwit_hpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
     Ok(())
 }
 
-pub fn generate_fr_cpp_file(c_folder: &PathBuf, prime: &String) -> std::io::Result<()> {
+pub fn generate_fr_cpp_file(c_folder: &PathBuf, producer: &CProducer) -> std::io::Result<()> {
     use std::io::BufWriter;
     let mut file_path = c_folder.clone();
     file_path.push("fr");
     file_path.set_extension("cpp");
     let file_name = file_path.to_str().unwrap();
+    let code = generate_fr_code(producer, "cpp")?;
     let mut c_file = BufWriter::new(File::create(file_name).unwrap());
-    let mut code = "".to_string();
-    let file = match prime.as_ref() {
-        "m31" => include_str!("m31/fr.cpp"),
-        _ => unreachable!(),
-    };
-    for line in file.lines() {
-        code = format!("{}{}\n", code, line);
-    }
     c_file.write_all(code.as_bytes())?;
     c_file.flush()?;
     Ok(())
@@ -854,7 +968,11 @@ pub fn generate_calcwit_cpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
     Ok(())
 }
 
-pub fn generate_cmake_file(c_folder: &PathBuf, run_name: &str) -> std::io::Result<()> {
+pub fn generate_cmake_file(
+    c_folder: &PathBuf,
+    run_name: &str,
+    library: bool,
+) -> std::io::Result<()> {
     use std::io::BufWriter;
 
     let makefile_template: &str = include_str!("common/CMakeLists.txt");
@@ -865,6 +983,7 @@ pub fn generate_cmake_file(c_folder: &PathBuf, run_name: &str) -> std::io::Resul
             makefile_template,
             &json!({
                 "run_name": run_name,
+                "library": library,
             }),
         )
         .expect("must render");
@@ -977,4 +1096,64 @@ mod tests {
         let _rc = generate_c_file(pathc, &producer);
         assert!(true);
     }
+
+    fn parse_limbs(limbs: &serde_json::Value) -> BigInt {
+        limbs
+            .as_str()
+            .unwrap()
+            .split(", ")
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .fold(BigInt::from(0), |acc, limb| {
+                (acc << 32) + u32::from_str_radix(&limb[2..], 16).unwrap()
+            })
+    }
+
+    #[test]
+    fn montgomery_constants_of_the_supported_primes() {
+        let primes = [
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+            "52435875175126190479447740508185965837690552500527637822603658699938581184513",
+            "21888242871839275222246405745257275088696311157297823662689037894645226208583",
+            "28948022309329048855892746252171976963363056481941560715954676764349967630337",
+            "28948022309329048855892746252171976963363056481941647379679742748393362948097",
+            "115792089210356248762697446949407573530086143415290314195533631308867097853951",
+            "18446744069414584321",
+            "2147483647",
+        ];
+        for prime in primes {
+            let mut producer = create_producer();
+            producer.prime = prime.to_string();
+            let q = prime.parse::<BigInt>().unwrap();
+            producer.size_32_bit = (q.bits() + 31) / 32;
+            let constants = generate_montgomery_constants(&producer);
+            let n32 = producer.size_32_bit;
+            assert_eq!(parse_limbs(&constants["q"]), q);
+
+            let np = u32::from_str_radix(&constants["np"].as_str().unwrap()[2..], 16).unwrap();
+            let q0 = q
+                .to_bytes_le()
+                .1
+                .iter()
+                .take(4)
+                .rev()
+                .fold(0u32, |acc, b| acc << 8 | *b as u32);
+            assert_eq!(
+                q0.wrapping_mul(np),
+                u32::MAX,
+                "q*np != -1 mod 2^32 for {}",
+                prime
+            );
+
+            let r = (BigInt::from(1) << (32 * n32)) % &q;
+            assert_eq!(parse_limbs(&constants["one"]), r);
+            assert_eq!(
+                parse_limbs(&constants["r2"]),
+                (&r * &r) % &q,
+                "r2 of {}",
+                prime
+            );
+        }
+    }
 }
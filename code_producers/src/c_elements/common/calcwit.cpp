@@ -1,6 +1,8 @@
 #include <iomanip>
 #include <sstream>
 #include <assert.h>
+#include <cstring>
+#include <vector>
 #include "calcwit.hpp"
 
 extern void run(Circom_CalcWit* ctx);
@@ -23,6 +25,86 @@ u64 fnv1a(std::string s) {
   return hash;
 }
 
+Circom_Circuit* loadCircuitFromData(const u8* bdata, u64 size) {
+    u64 inisize = 0;
+    u64 dsize = get_size_of_input_hashmap()*sizeof(HashSignalInfo) +
+      get_size_of_witness()*sizeof(u64) + get_size_of_constants()*sizeof(FrElement) +
+      get_size_of_io_map()*sizeof(u32);
+    if (size < dsize) {
+      return NULL;
+    }
+
+    Circom_Circuit *circuit = new Circom_Circuit;
+
+    circuit->InputHashMap = new HashSignalInfo[get_size_of_input_hashmap()];
+    dsize = get_size_of_input_hashmap()*sizeof(HashSignalInfo);
+    memcpy((void *)(circuit->InputHashMap), (void *)bdata, dsize);
+
+    circuit->witness2SignalList = new u64[get_size_of_witness()];
+    inisize = dsize;
+    dsize = get_size_of_witness()*sizeof(u64);
+    memcpy((void *)(circuit->witness2SignalList), (void *)(bdata+inisize), dsize);
+
+    circuit->circuitConstants = new FrElement[get_size_of_constants()];
+    if (get_size_of_constants()>0) {
+      inisize += dsize;
+      dsize = get_size_of_constants()*sizeof(FrElement);
+      memcpy((void *)(circuit->circuitConstants), (void *)(bdata+inisize), dsize);
+    }
+
+    std::map<u32,IODefPair> templateInsId2IOSignalInfo1;
+    if (get_size_of_io_map()>0) {
+      std::vector<u32> index(get_size_of_io_map());
+      inisize += dsize;
+      dsize = get_size_of_io_map()*sizeof(u32);
+      memcpy((void *)index.data(), (void *)(bdata+inisize), dsize);
+      inisize += dsize;
+      if (inisize % sizeof(u32) != 0 || size % sizeof(u32) != 0) {
+        delete []circuit->InputHashMap;
+        delete []circuit->witness2SignalList;
+        delete []circuit->circuitConstants;
+        delete circuit;
+        return NULL;
+      }
+      std::vector<u32> dataiomap((size-inisize)/sizeof(u32));
+      memcpy((void *)dataiomap.data(), (void *)(bdata+inisize), size-inisize);
+      u32* pu32 = dataiomap.data();
+
+      for (int i = 0; i < get_size_of_io_map(); i++) {
+	u32 n = *pu32;
+	IODefPair p;
+	p.len = n;
+	pu32 += 1;
+	p.defs = new IODef[n];
+	for (u32 j = 0; j <n; j++){
+	  p.defs[j].offset=*pu32;
+	  u32 len = *(pu32+1);
+	  p.defs[j].len = len;
+	  p.defs[j].lengths = new u32[len];
+	  memcpy((void *)p.defs[j].lengths,(void *)(pu32+2),len*sizeof(u32));
+	  pu32 += len + 2;
+	}
+	templateInsId2IOSignalInfo1[index[i]] = p;
+      }
+    }
+    circuit->templateInsId2IOSignalInfo = std::move(templateInsId2IOSignalInfo1);
+
+    return circuit;
+}
+
+void freeCircuit(Circom_Circuit* circuit) {
+  for (auto &entry : circuit->templateInsId2IOSignalInfo) {
+    for (u32 j = 0; j < entry.second.len; j++) {
+      delete []entry.second.defs[j].lengths;
+    }
+    delete []entry.second.defs;
+  }
+  delete []circuit->InputHashMap;
+  delete []circuit->witness2SignalList;
+  delete []circuit->circuitConstants;
+  delete circuit;
+}
+
 Circom_CalcWit::Circom_CalcWit (Circom_Circuit *aCircuit, uint maxTh) {
   circuit = aCircuit;
   inputSignalAssignedCounter = get_main_input_signal_no();
@@ -44,32 +126,89 @@ Circom_CalcWit::Circom_CalcWit (Circom_Circuit *aCircuit, uint maxTh) {
 }
 
 Circom_CalcWit::~Circom_CalcWit() {
-  // ...
+  releaseMainComponent();
+  delete []componentMemory;
+  delete []signalValues;
+  delete []inputSignalAssigned;
 }
 
-uint Circom_CalcWit::getInputSignalHashPosition(u64 h) {
+// the subcomponents release their memory when they finish, only the one of
+// the main component is left
+void Circom_CalcWit::releaseMainComponent() {
+  Circom_Component &main = componentMemory[0];
+  if (main.subcomponents) delete []main.subcomponents;
+  if (main.subcomponentsParallel) delete []main.subcomponentsParallel;
+  if (main.outputIsSet) delete []main.outputIsSet;
+  if (main.mutexes) delete []main.mutexes;
+  if (main.cvs) delete []main.cvs;
+  if (main.sbct) delete []main.sbct;
+  main.subcomponents = NULL;
+  main.subcomponentsParallel = NULL;
+  main.outputIsSet = NULL;
+  main.mutexes = NULL;
+  main.cvs = NULL;
+  main.sbct = NULL;
+}
+
+void Circom_CalcWit::reset() {
+  releaseMainComponent();
+  // the memory of the subcomponents is already released, but the pointers
+  // are left as they were
+  for (uint i = 1; i < get_number_of_components(); i++) {
+    componentMemory[i].subcomponents = NULL;
+    componentMemory[i].subcomponentsParallel = NULL;
+    componentMemory[i].outputIsSet = NULL;
+    componentMemory[i].mutexes = NULL;
+    componentMemory[i].cvs = NULL;
+    componentMemory[i].sbct = NULL;
+  }
+  inputSignalAssignedCounter = get_main_input_signal_no();
+  for (int i = 0; i< inputSignalAssignedCounter; i++) {
+    inputSignalAssigned[i] = false;
+  }
+  numThread = 0;
+  failure = nullptr;
+}
+
+int Circom_CalcWit::findInputSignalHashPosition(u64 h) {
   uint n = get_size_of_input_hashmap();
+  if (n == 0) return -1;
   uint pos = (uint)(h % (u64)n);
-  if (circuit->InputHashMap[pos].hash!=h){
-    uint inipos = pos;
-    pos = (pos+1)%n; 
-    while (pos != inipos) {
-      if (circuit->InputHashMap[pos].hash == h) return pos;
-      if (circuit->InputHashMap[pos].signalid == 0) {
-	fprintf(stderr, "Signal not found\n");
-	assert(false);
-      }
-      pos = (pos+1)%n; 
-    }
-    fprintf(stderr, "Signals not found\n");
+  uint inipos = pos;
+  do {
+    if (circuit->InputHashMap[pos].hash == h) return pos;
+    if (circuit->InputHashMap[pos].signalid == 0) return -1;
+    pos = (pos+1)%n;
+  } while (pos != inipos);
+  return -1;
+}
+
+uint Circom_CalcWit::getInputSignalHashPosition(u64 h) {
+  int pos = findInputSignalHashPosition(h);
+  if (pos < 0) {
+    fprintf(stderr, "Signal not found\n");
     assert(false);
   }
   return pos;
 }
 
+void Circom_CalcWit::recordFailure(std::exception_ptr e) {
+  std::lock_guard<std::mutex> lock(failureMutex);
+  if (!failure) {
+    failure = e;
+  }
+}
+
+// A failed assert throws CircuitAssertFailed, the context has to be reset
+// before it computes another witness
 void Circom_CalcWit::tryRunCircuit(){ 
   if (inputSignalAssignedCounter == 0) {
     run(this);
+    if (failure) {
+      std::exception_ptr e = failure;
+      failure = nullptr;
+      std::rethrow_exception(e);
+    }
   }
 }
 
@@ -95,6 +234,29 @@ void Circom_CalcWit::setInputSignal(u64 h, uint i,  FrElement & val){
   tryRunCircuit();
 }
 
+InputResult Circom_CalcWit::checkInputSignal(u64 h, uint i){
+  int pos = findInputSignalHashPosition(h);
+  if (pos < 0) return INPUT_NOT_FOUND;
+  if (i >= circuit->InputHashMap[pos].signalsize) return INPUT_OUT_OF_RANGE;
+  uint si = circuit->InputHashMap[pos].signalid+i;
+  if (inputSignalAssigned[si-get_main_input_signal_start()]) return INPUT_ASSIGNED_TWICE;
+  return INPUT_OK;
+}
+
+InputResult Circom_CalcWit::assignInputSignal(u64 h, uint i, FrElement & val){
+  InputResult result = checkInputSignal(h, i);
+  if (result != INPUT_OK) return result;
+  uint si = circuit->InputHashMap[findInputSignalHashPosition(h)].signalid+i;
+  signalValues[si] = val;
+  inputSignalAssigned[si-get_main_input_signal_start()] = true;
+  inputSignalAssignedCounter--;
+  return INPUT_OK;
+}
+
+bool Circom_CalcWit::hasInputSignal(u64 h) {
+  return findInputSignalHashPosition(h) >= 0;
+}
+
 u64 Circom_CalcWit::getInputSignalSize(u64 h) {
   uint pos = getInputSignalHashPosition(h);
   return circuit->InputHashMap[pos].signalsize;
@@ -6,6 +6,8 @@
 #include <functional>
 #include <atomic>
 #include <memory>
+#include <exception>
+#include <stdexcept>
 
 #include "circom.hpp"
 #include "fr.hpp"
@@ -14,6 +16,24 @@
 
 u64 fnv1a(std::string s);
 
+// Reads the circuit from the contents of its .dat file, NULL if they are not
+// valid
+Circom_Circuit* loadCircuitFromData(const u8* bdata, u64 size);
+void freeCircuit(Circom_Circuit* circuit);
+
+// Thrown by the code of the circuit when one of its asserts fails
+class CircuitAssertFailed : public std::runtime_error {
+public:
+  using std::runtime_error::runtime_error;
+};
+
+enum InputResult {
+  INPUT_OK,
+  INPUT_NOT_FOUND,
+  INPUT_OUT_OF_RANGE,
+  INPUT_ASSIGNED_TWICE
+};
+
 class Circom_CalcWit {
 
   bool *inputSignalAssigned;
@@ -36,15 +56,28 @@ public:
 
   int maxThread;
 
+  // The parallel components run in threads of their own, so they keep the
+  // first exception of the circuit here and tryRunCircuit throws it
+  void recordFailure(std::exception_ptr e);
+
   // Functions called by the circuit
   Circom_CalcWit(Circom_Circuit *aCircuit, uint numTh = NMUTEXES);
   ~Circom_CalcWit();
 
+  // Prepares the context to compute another witness with the same circuit
+  void reset();
+
   // Public functions
   void setInputSignal(u64 h, uint i, FrElement &val);
+  // Same as setInputSignal but returns an error code instead of aborting and
+  // does not run the circuit when the last input is set
+  InputResult assignInputSignal(u64 h, uint i, FrElement &val);
+  // The result assignInputSignal would give, without assigning the signal
+  InputResult checkInputSignal(u64 h, uint i);
   void tryRunCircuit();
   
   u64 getInputSignalSize(u64 h);
+  bool hasInputSignal(u64 h);
 
   inline uint getRemaingInputsToBeSet() {
     return inputSignalAssignedCounter;
@@ -60,7 +93,13 @@ public:
 
 private:
   
+  std::mutex failureMutex;
+  std::exception_ptr failure;
+
   uint getInputSignalHashPosition(u64 h);
+  void releaseMainComponent();
+  // -1 when the signal is not an input of the main component
+  int findInputSignalHashPosition(u64 h);
 
 };
 
@@ -0,0 +1,131 @@
+#include <cstring>
+#include <string>
+#include "calcwit.hpp"
+#include "circom.hpp"
+#include "circom_witness.h"
+
+struct circom_circuit {
+  Circom_Circuit *circuit;
+};
+
+struct circom_witness {
+  Circom_CalcWit *ctx;
+  bool computed;
+};
+
+size_t circom_field_words(void) {
+  return Fr_N32;
+}
+
+size_t circom_witness_size(void) {
+  return get_size_of_witness();
+}
+
+size_t circom_input_count(void) {
+  return get_main_input_signal_no();
+}
+
+uint64_t circom_input_hash(const char *name) {
+  return fnv1a(std::string(name));
+}
+
+const char *circom_result_message(int result) {
+  switch (result) {
+  case CIRCOM_OK: return "ok";
+  case CIRCOM_SIGNAL_NOT_FOUND: return "the signal is not an input of the main component";
+  case CIRCOM_WRONG_SIZE: return "the number of values is not the size of the input";
+  case CIRCOM_ASSIGNED_TWICE: return "the input is assigned twice";
+  case CIRCOM_MISSING_INPUTS: return "not all the inputs have been set";
+  case CIRCOM_NOT_COMPUTED: return "the witness has not been computed";
+  case CIRCOM_BUFFER_TOO_SMALL: return "the buffer is smaller than the witness";
+  case CIRCOM_ASSERT_FAILED: return "an assert of the circuit failed";
+  default: return "unknown result";
+  }
+}
+
+circom_circuit *circom_circuit_load(const uint8_t *dat, size_t len) {
+  Circom_Circuit *circuit = loadCircuitFromData(dat, len);
+  if (circuit == NULL) {
+    return NULL;
+  }
+  return new circom_circuit{circuit};
+}
+
+void circom_circuit_free(circom_circuit *circuit) {
+  if (circuit == NULL) return;
+  freeCircuit(circuit->circuit);
+  delete circuit;
+}
+
+circom_witness *circom_witness_new(const circom_circuit *circuit) {
+  return new circom_witness{new Circom_CalcWit(circuit->circuit), false};
+}
+
+void circom_witness_free(circom_witness *witness) {
+  if (witness == NULL) return;
+  delete witness->ctx;
+  delete witness;
+}
+
+void circom_witness_reset(circom_witness *witness) {
+  witness->ctx->reset();
+  witness->computed = false;
+}
+
+int circom_witness_set_input(circom_witness *witness, const char *name, const uint32_t *values, size_t count) {
+  return circom_witness_set_input_by_hash(witness, circom_input_hash(name), values, count);
+}
+
+int circom_witness_set_input_by_hash(circom_witness *witness, uint64_t hash, const uint32_t *values, size_t count) {
+  Circom_CalcWit *ctx = witness->ctx;
+  if (!ctx->hasInputSignal(hash)) {
+    return CIRCOM_SIGNAL_NOT_FOUND;
+  }
+  if (count != ctx->getInputSignalSize(hash)) {
+    return CIRCOM_WRONG_SIZE;
+  }
+  // every element is checked first so that a failed call assigns nothing
+  for (size_t i = 0; i < count; i++) {
+    if (ctx->checkInputSignal(hash, i) != INPUT_OK) {
+      return CIRCOM_ASSIGNED_TWICE;
+    }
+  }
+  FrElement v;
+  for (size_t i = 0; i < count; i++) {
+    memcpy(v.longVal, values + i * Fr_N32, Fr_N32 * sizeof(uint32_t));
+    Fr_fromLongNormal(&v, &v);
+    ctx->assignInputSignal(hash, i, v);
+  }
+  return CIRCOM_OK;
+}
+
+int circom_witness_run(circom_witness *witness) {
+  if (witness->ctx->getRemaingInputsToBeSet() != 0) {
+    return CIRCOM_MISSING_INPUTS;
+  }
+  if (!witness->computed) {
+    try {
+      witness->ctx->tryRunCircuit();
+    } catch (CircuitAssertFailed &e) {
+      return CIRCOM_ASSERT_FAILED;
+    }
+    witness->computed = true;
+  }
+  return CIRCOM_OK;
+}
+
+int circom_witness_get(const circom_witness *witness, uint32_t *buffer, size_t len) {
+  if (!witness->computed) {
+    return CIRCOM_NOT_COMPUTED;
+  }
+  if (len < get_size_of_witness() * Fr_N32) {
+    return CIRCOM_BUFFER_TOO_SMALL;
+  }
+  FrElement v;
+  for (uint i = 0; i < get_size_of_witness(); i++) {
+    witness->ctx->getWitness(i, &v);
+    Fr_toLongNormal(&v, &v);
+    memcpy(buffer + i * Fr_N32, v.longVal, Fr_N32 * sizeof(uint32_t));
+  }
+  return CIRCOM_OK;
+}
@@ -0,0 +1,75 @@
+#ifndef CIRCOM_WITNESS_H
+#define CIRCOM_WITNESS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+Witness calculator of the circuit as a library.
+
+A circuit is loaded once from the contents of its .dat file and can be shared
+by several witnesses, one for each set of inputs. The values of the signals
+are given and returned in normal form, each of them as circom_field_words()
+words of 32 bits with the least significant word first, as in the .wtns files.
+
+Every witness is independent, so different witnesses can be computed at the
+same time in different threads. When an assert of the circuit fails,
+circom_witness_run returns CIRCOM_ASSERT_FAILED and the witness has to be reset
+before it is computed again.
+*/
+
+typedef struct circom_circuit circom_circuit;
+typedef struct circom_witness circom_witness;
+
+enum circom_result {
+  CIRCOM_OK = 0,
+  // the input is not an input of the main component
+  CIRCOM_SIGNAL_NOT_FOUND = 1,
+  // the number of values is not the size of the input
+  CIRCOM_WRONG_SIZE = 2,
+  CIRCOM_ASSIGNED_TWICE = 3,
+  // run before all the inputs are set
+  CIRCOM_MISSING_INPUTS = 4,
+  // witness read before it is computed
+  CIRCOM_NOT_COMPUTED = 5,
+  // buffer too small for the witness
+  CIRCOM_BUFFER_TOO_SMALL = 6,
+  // an assert of the circuit failed while computing the witness
+  CIRCOM_ASSERT_FAILED = 7
+};
+
+size_t circom_field_words(void);
+size_t circom_witness_size(void);
+size_t circom_input_count(void);
+uint64_t circom_input_hash(const char *name);
+const char *circom_result_message(int result);
+
+// NULL when the data is not a valid .dat file of the circuit
+circom_circuit *circom_circuit_load(const uint8_t *dat, size_t len);
+void circom_circuit_free(circom_circuit *circuit);
+
+// The circuit must outlive its witnesses
+circom_witness *circom_witness_new(const circom_circuit *circuit);
+void circom_witness_free(circom_witness *witness);
+// Clears the inputs and the witness to compute another one, which avoids the
+// allocations of circom_witness_new
+void circom_witness_reset(circom_witness *witness);
+
+// Sets every element of an input, count is the number of elements. When it
+// fails no element of the input is set
+int circom_witness_set_input(circom_witness *witness, const char *name, const uint32_t *values, size_t count);
+int circom_witness_set_input_by_hash(circom_witness *witness, uint64_t hash, const uint32_t *values, size_t count);
+int circom_witness_run(circom_witness *witness);
+// len is the number of words of the buffer, at least
+// circom_witness_size() * circom_field_words()
+int circom_witness_get(const circom_witness *witness, uint32_t *buffer, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // CIRCOM_WITNESS_H
@@ -9,6 +9,10 @@
 #include <nlohmann/json.hpp>
 #include <vector>
 #include <chrono>
+#include <thread>
+#include <atomic>
+#include <filesystem>
+#include <algorithm>
 
 using json = nlohmann::json;
 
@@ -20,8 +24,6 @@ using json = nlohmann::json;
            do { perror(msg); exit(EXIT_FAILURE); } while (0)
 
 Circom_Circuit* loadCircuit(std::string const &datFileName) {
-    Circom_Circuit *circuit = new Circom_Circuit;
-
     int fd;
     struct stat sb;
 
@@ -38,59 +40,15 @@ Circom_Circuit* loadCircuit(std::string const &datFileName) {
     u8* bdata = (u8*)mmap(NULL, sb.st_size, PROT_READ , MAP_PRIVATE, fd, 0);
     close(fd);
 
-    circuit->InputHashMap = new HashSignalInfo[get_size_of_input_hashmap()];
-    uint dsize = get_size_of_input_hashmap()*sizeof(HashSignalInfo);
-    memcpy((void *)(circuit->InputHashMap), (void *)bdata, dsize);
-
-    circuit->witness2SignalList = new u64[get_size_of_witness()];
-    uint inisize = dsize;    
-    dsize = get_size_of_witness()*sizeof(u64);
-    memcpy((void *)(circuit->witness2SignalList), (void *)(bdata+inisize), dsize);
-
-    circuit->circuitConstants = new FrElement[get_size_of_constants()];
-    if (get_size_of_constants()>0) {
-      inisize += dsize;
-      dsize = get_size_of_constants()*sizeof(FrElement);
-      memcpy((void *)(circuit->circuitConstants), (void *)(bdata+inisize), dsize);
-    }
-
-    std::map<u32,IODefPair> templateInsId2IOSignalInfo1;
-    if (get_size_of_io_map()>0) {
-      u32 index[get_size_of_io_map()];
-      inisize += dsize;
-      dsize = get_size_of_io_map()*sizeof(u32);
-      memcpy((void *)index, (void *)(bdata+inisize), dsize);
-      inisize += dsize;
-      assert(inisize % sizeof(u32) == 0);    
-      assert(sb.st_size % sizeof(u32) == 0);
-      u32 dataiomap[(sb.st_size-inisize)/sizeof(u32)];
-      memcpy((void *)dataiomap, (void *)(bdata+inisize), sb.st_size-inisize);
-      u32* pu32 = dataiomap;
-
-      for (int i = 0; i < get_size_of_io_map(); i++) {
-	u32 n = *pu32;
-	IODefPair p;
-	p.len = n;
-	IODef defs[n];
-	pu32 += 1;
-	for (u32 j = 0; j <n; j++){
-	  defs[j].offset=*pu32;
-	  u32 len = *(pu32+1);
-	  defs[j].len = len;
-	  defs[j].lengths = new u32[len];
-	  memcpy((void *)defs[j].lengths,(void *)(pu32+2),len*sizeof(u32));
-	  pu32 += len + 2;
-	}
-	p.defs = (IODef*)calloc(10, sizeof(IODef));
-	for (u32 j = 0; j < p.len; j++){
-	  p.defs[j] = defs[j];
-	}
-	templateInsId2IOSignalInfo1[index[i]] = p;
-      }
-    }
-    circuit->templateInsId2IOSignalInfo = std::move(templateInsId2IOSignalInfo1);
+    Circom_Circuit *circuit = loadCircuitFromData(bdata, sb.st_size);
     
     munmap(bdata, sb.st_size);
+
+    if (circuit == NULL) {
+        std::ostringstream errStrStream;
+        errStrStream << "Invalid .dat file: " << datFileName << "\n";
+        throw std::runtime_error(errStrStream.str() );
+    }
     
     return circuit;
 }
@@ -160,11 +118,7 @@ void json2FrElements (json val, std::vector<FrElement> & vval){
 }
 
 
-void loadJson(Circom_CalcWit *ctx, std::string filename) {
-  std::ifstream inStream(filename);
-  json j;
-  inStream >> j;
-  
+void loadJson(Circom_CalcWit *ctx, json &j) {
   u64 nItems = j.size();
   // printf("Items : %llu\n",nItems);
   if (nItems == 0){
@@ -173,6 +127,11 @@ void loadJson(Circom_CalcWit *ctx, std::string filename) {
   for (json::iterator it = j.begin(); it != j.end(); ++it) {
     // std::cout << it.key() << " => " << it.value() << '\n';
     u64 h = fnv1a(it.key());
+    if (!ctx->hasInputSignal(h)) {
+	std::ostringstream errStrStream;
+	errStrStream << "Error loading signal " << it.key() << ": Not an input of the main component\n";
+	throw std::runtime_error(errStrStream.str() );
+    }
     std::vector<FrElement> v;
     json2FrElements(it.value(),v);
     uint signalSize = ctx->getInputSignalSize(h);
@@ -190,6 +149,8 @@ void loadJson(Circom_CalcWit *ctx, std::string filename) {
       try {
 	// std::cout << it.key() << "," << i << " => " << Fr_element2str(&(v[i])) << '\n';
 	ctx->setInputSignal(h,i,v[i]);
+      } catch (CircuitAssertFailed &e) {
+	throw;
       } catch (std::runtime_error e) {
 	std::ostringstream errStrStream;
 	errStrStream << "Error setting signal: " << it.key() << "\n" << e.what();
@@ -199,10 +160,44 @@ void loadJson(Circom_CalcWit *ctx, std::string filename) {
   }
 }
 
-void writeBinWitness(Circom_CalcWit *ctx, std::string wtnsFileName) {
+void loadJson(Circom_CalcWit *ctx, std::string filename) {
+  std::ifstream inStream(filename);
+  json j;
+  inStream >> j;
+  loadJson(ctx, j);
+}
+
+// The values that are written, the witness or every signal of the circuit
+uint getNumberOfValues(bool allSignals) {
+    return allSignals ? get_total_signal_no() : get_size_of_witness();
+}
+
+void getValue(Circom_CalcWit *ctx, bool allSignals, uint i, PFrElement v) {
+    if (allSignals) {
+        Fr_copy(v, &ctx->signalValues[i]);
+    } else {
+        ctx->getWitness(i, v);
+    }
+}
+
+void throwWitnessFileError(std::string wtnsFileName) {
+    std::ostringstream errStrStream;
+    errStrStream << "Could not write the witness file: " << wtnsFileName << "\n";
+    throw std::runtime_error(errStrStream.str() );
+}
+
+FILE *openWitnessFile(std::string wtnsFileName) {
+    FILE *write_ptr = fopen(wtnsFileName.c_str(),"wb");
+    if (write_ptr == NULL) {
+        throwWitnessFileError(wtnsFileName);
+    }
+    return write_ptr;
+}
+
+void writeBinWitness(Circom_CalcWit *ctx, std::string wtnsFileName, bool allSignals) {
     FILE *write_ptr;
 
-    write_ptr = fopen(wtnsFileName.c_str(),"wb");
+    write_ptr = openWitnessFile(wtnsFileName);
 
     fwrite("wtns", 4, 1, write_ptr);
 
@@ -225,7 +220,7 @@ void writeBinWitness(Circom_CalcWit *ctx, std::string wtnsFileName) {
 
     fwrite(Fr_q.longVal, Fr_N32*4, 1, write_ptr);
 
-    uint Nwtns = get_size_of_witness();
+    uint Nwtns = getNumberOfValues(allSignals);
     
     u32 nVars = (u32)Nwtns;
     fwrite(&nVars, 4, 1, write_ptr);
@@ -240,29 +235,298 @@ void writeBinWitness(Circom_CalcWit *ctx, std::string wtnsFileName) {
     FrElement v;
 
     for (int i=0;i<Nwtns;i++) {
-        ctx->getWitness(i, &v);
+        getValue(ctx, allSignals, i, &v);
         Fr_toLongNormal(&v, &v);
         fwrite(v.longVal, Fr_N32*4, 1, write_ptr);
     }
-    fclose(write_ptr);
+    if (fclose(write_ptr) != 0) {
+        throwWitnessFileError(wtnsFileName);
+    }
+}
+
+// Flat array of little-endian u32 values, without header, only for the
+// fields that fit in one word
+void writeRawWitness(Circom_CalcWit *ctx, std::string wtnsFileName, bool allSignals) {
+#if Fr_N32 == 1
+    FILE *write_ptr;
+
+    write_ptr = openWitnessFile(wtnsFileName);
+
+    uint Nwtns = getNumberOfValues(allSignals);
+
+    FrElement v;
+
+    for (int i=0;i<Nwtns;i++) {
+        getValue(ctx, allSignals, i, &v);
+        Fr_toLongNormal(&v, &v);
+        u32 value = v.longVal[0];
+        u8 bytes[4] = {(u8)value, (u8)(value >> 8), (u8)(value >> 16), (u8)(value >> 24)};
+        fwrite(bytes, 4, 1, write_ptr);
+    }
+    if (fclose(write_ptr) != 0) {
+        throwWitnessFileError(wtnsFileName);
+    }
+#else
+    throw std::runtime_error("The raw witness format is only available for fields of 32 bits\n");
+#endif
+}
+
+struct SymEntry {
+    long long signal;
+    long long witness;
+    std::string name;
+};
+
+// The lines of the .sym file are signal,witness,component,name and the
+// signals that are not in the witness have witness -1
+std::vector<SymEntry> loadSym(std::string symFileName) {
+    std::ifstream symStream(symFileName);
+    if (!symStream.good()) {
+        std::ostringstream errStrStream;
+        errStrStream << ".sym file not found: " << symFileName << "\n";
+        throw std::runtime_error(errStrStream.str() );
+    }
+    std::vector<SymEntry> entries;
+    std::string line;
+    while (std::getline(symStream, line)) {
+        std::istringstream lineStream(line);
+        std::string signal, witness, component, name;
+        if (!std::getline(lineStream, signal, ',') || !std::getline(lineStream, witness, ',') ||
+            !std::getline(lineStream, component, ',') || !std::getline(lineStream, name)) {
+            continue;
+        }
+        entries.push_back({std::stoll(signal), std::stoll(witness), name});
+    }
+    return entries;
+}
+
+// Object from the names of the .sym file to the values of the signals
+void writeJsonWitness(Circom_CalcWit *ctx, std::string wtnsFileName, std::vector<SymEntry> const &sym, bool allSignals) {
+    std::ofstream outStream(wtnsFileName);
+    if (!outStream.good()) {
+        throwWitnessFileError(wtnsFileName);
+    }
+    outStream << "{";
+    bool first = true;
+    for (auto const &entry : sym) {
+        FrElement v;
+        if (allSignals && entry.signal >= 0 && entry.signal < get_total_signal_no()) {
+            getValue(ctx, true, entry.signal, &v);
+        } else if (!allSignals && entry.witness >= 0) {
+            getValue(ctx, false, entry.witness, &v);
+        } else {
+            continue;
+        }
+        char *value = Fr_element2str(&v);
+        outStream << (first ? "\n" : ",\n") << "  \"" << entry.name << "\": \"" << value << "\"";
+        delete[] value;
+        first = false;
+    }
+    outStream << "\n}\n";
+    outStream.close();
+    if (outStream.fail()) {
+        throwWitnessFileError(wtnsFileName);
+    }
+}
+
+struct OutputOptions {
+    std::string format;
+    std::vector<SymEntry> sym;
+    bool allSignals;
+};
+
+void writeWitness(Circom_CalcWit *ctx, std::string wtnsFileName, OutputOptions const &options) {
+    if (options.format == "json") {
+        writeJsonWitness(ctx, wtnsFileName, options.sym, options.allSignals);
+    } else if (options.format == "raw") {
+        writeRawWitness(ctx, wtnsFileName, options.allSignals);
+    } else {
+        writeBinWitness(ctx, wtnsFileName, options.allSignals);
+    }
+}
+
+struct BatchInput {
+    // name of the witness file, without extension
+    std::string name;
+    // a line of the jsonl stream, or the file of the input when it is empty
+    std::string text;
+    std::string path;
+};
+
+std::vector<BatchInput> readBatchInputs(std::string source) {
+    std::vector<BatchInput> inputs;
+    if (std::filesystem::is_directory(source)) {
+        std::vector<std::filesystem::path> files;
+        for (auto const &entry : std::filesystem::directory_iterator(source)) {
+            if (entry.is_regular_file() && entry.path().extension() == ".json") {
+                files.push_back(entry.path());
+            }
+        }
+        std::sort(files.begin(), files.end());
+        for (auto const &file : files) {
+            inputs.push_back({file.stem().string(), "", file.string()});
+        }
+        return inputs;
+    }
+    std::ifstream fileStream;
+    if (source != "-") {
+        fileStream.open(source);
+        if (!fileStream.good()) {
+            std::ostringstream errStrStream;
+            errStrStream << "Inputs not found: " << source << "\n";
+            throw std::runtime_error(errStrStream.str() );
+        }
+    }
+    std::istream &inStream = source == "-" ? std::cin : fileStream;
+    std::string line;
+    while (std::getline(inStream, line)) {
+        if (line.find_first_not_of(" \t\r") == std::string::npos) continue;
+        inputs.push_back({std::to_string(inputs.size()), line, ""});
+    }
+    return inputs;
+}
+
+// Computes a witness for every input with a pool of threads, each of them
+// with its own context that is reset between inputs. The threads of the
+// parallel components are shared among the contexts. Returns the number of
+// inputs that failed.
+uint runBatch(Circom_Circuit *circuit, std::vector<BatchInput> const &inputs, std::string outputFolder, OutputOptions const &options, uint threads) {
+    std::filesystem::create_directories(outputFolder);
+    std::string extension = options.format == "raw" ? "bin" : options.format;
+    std::atomic<size_t> next(0);
+    std::atomic<uint> failed(0);
+    std::mutex errorMutex;
+    uint maxThread = std::max(1u, (uint)NMUTEXES / threads);
+    auto worker = [&]() {
+        Circom_CalcWit *ctx = new Circom_CalcWit(circuit, maxThread);
+        for (size_t i = next++; i < inputs.size(); i = next++) {
+            BatchInput const &input = inputs[i];
+            try {
+                json j;
+                if (input.path.empty()) {
+                    j = json::parse(input.text);
+                } else {
+                    std::ifstream inStream(input.path);
+                    inStream >> j;
+                }
+                loadJson(ctx, j);
+                if (ctx->getRemaingInputsToBeSet()!=0) {
+                    std::ostringstream errStrStream;
+                    errStrStream << "Not all inputs have been set. Only " << get_main_input_signal_no()-ctx->getRemaingInputsToBeSet() << " out of " << get_main_input_signal_no() << "\n";
+                    throw std::runtime_error(errStrStream.str() );
+                }
+                std::filesystem::path file = std::filesystem::path(outputFolder) / (input.name + "." + extension);
+                writeWitness(ctx, file.string(), options);
+            } catch (std::exception &e) {
+                std::lock_guard<std::mutex> lock(errorMutex);
+                std::cerr << "Error in input " << input.name << ": " << e.what() << std::endl;
+                failed++;
+            }
+            ctx->reset();
+        }
+        delete ctx;
+    };
+    std::vector<std::thread> pool;
+    for (uint t = 1; t < threads; t++) {
+        pool.emplace_back(worker);
+    }
+    worker();
+    for (auto &thread : pool) {
+        thread.join();
+    }
+    return failed;
+}
+
+// circom writes the .sym file in the folder that holds the one of the witness
+// calculator, which is named after the circuit
+std::string defaultSym(std::string const &cl) {
+    size_t slash = cl.find_last_of('/');
+    std::string folder = slash == std::string::npos ? "." : cl.substr(0, slash);
+    std::string name = slash == std::string::npos ? cl : cl.substr(slash + 1);
+    return folder + "/../" + name + ".sym";
+}
+
+void usage(std::string const &cl) {
+    std::cout << "Usage: " << cl << " <input.json> <output> [options]\n";
+    std::cout << "       " << cl << " --batch <inputs.jsonl|inputs folder|-> <output folder> [--threads <n>] [options]\n";
+    std::cout << "  --wtns-format  format of the output, wtns by default. json uses the names of the .sym file and raw\n";
+    std::cout << "                 is a flat array of little-endian u32 values, only for fields of 32 bits\n";
+    std::cout << "  --all-signals  writes the value of every signal and not only the ones of the witness\n";
+    std::cout << "  --sym          .sym file of the circuit for the json format, " << defaultSym(cl) << " by default\n";
+    std::cout << "  --batch        computes a witness for every line of a jsonl file, or of the standard input with -,\n";
+    std::cout << "                 or for every .json file of a folder. The witnesses are named after the number of the\n";
+    std::cout << "                 line, from 0, or after the input file\n";
+    std::cout << "  --threads      number of inputs computed at the same time in batch mode, 1 by default\n";
 }
 
 int main (int argc, char *argv[]) {
   std::string cl(argv[0]);
-  if (argc!=3) {
-        std::cout << "Usage: " << cl << " <input.json> <output.wtns>\n";
+  std::vector<std::string> positional;
+  std::string format = "wtns";
+  std::string symfile = defaultSym(cl);
+  bool allSignals = false;
+  bool batch = false;
+  int threads = 1;
+  bool valid = true;
+  for (int i = 1; i < argc; i++) {
+    std::string arg(argv[i]);
+    if (arg == "--wtns-format" && i + 1 < argc) {
+      format = argv[++i];
+    } else if (arg == "--sym" && i + 1 < argc) {
+      symfile = argv[++i];
+    } else if (arg == "--all-signals") {
+      allSignals = true;
+    } else if (arg == "--batch") {
+      batch = true;
+    } else if (arg == "--threads" && i + 1 < argc) {
+      threads = atoi(argv[++i]);
+      valid &= threads > 0;
+    } else if (arg.rfind("--", 0) == 0) {
+      valid = false;
+    } else {
+      positional.push_back(arg);
+    }
+  }
+  valid &= positional.size() == 2 && (format == "wtns" || format == "json" || format == "raw");
+  if (!valid) {
+    usage(cl);
   } else {
     std::string datfile = cl + ".dat";
-    std::string jsonfile(argv[1]);
-    std::string wtnsfile(argv[2]);
+    std::string jsonfile(positional[0]);
+    std::string wtnsfile(positional[1]);
+    OutputOptions options {format, {}, allSignals};
+    if (format == "json") {
+      try {
+        options.sym = loadSym(symfile);
+      } catch (std::runtime_error &e) {
+        std::cerr << e.what() << "The json format needs the .sym file of the circuit, give it with --sym" << std::endl;
+        usage(cl);
+        return EXIT_FAILURE;
+      }
+    }
   
     // auto t_start = std::chrono::high_resolution_clock::now();
 
    Circom_Circuit *circuit = loadCircuit(datfile);
 
+   if (batch) {
+     std::vector<BatchInput> inputs = readBatchInputs(jsonfile);
+     uint failed = runBatch(circuit, inputs, wtnsfile, options, threads);
+     if (failed != 0) {
+       std::cerr << failed << " out of " << inputs.size() << " inputs failed" << std::endl;
+       return EXIT_FAILURE;
+     }
+     return EXIT_SUCCESS;
+   }
+
    Circom_CalcWit *ctx = new Circom_CalcWit(circuit);
   
-   loadJson(ctx, jsonfile);
+   try {
+     loadJson(ctx, jsonfile);
+   } catch (CircuitAssertFailed &e) {
+     std::cerr << e.what() << std::endl;
+     return EXIT_FAILURE;
+   }
    if (ctx->getRemaingInputsToBeSet()!=0) {
      std::cerr << "Not all inputs have been set. Only " << get_main_input_signal_no()-ctx->getRemaingInputsToBeSet() << " out of " << get_main_input_signal_no() << std::endl;
      assert(false);
@@ -278,7 +542,12 @@ int main (int argc, char *argv[]) {
    //auto t_mid = std::chrono::high_resolution_clock::now();
    //std::cout << std::chrono::duration<double, std::milli>(t_mid-t_start).count()<<std::endl;
 
-   writeBinWitness(ctx,wtnsfile);
+   try {
+     writeWitness(ctx, wtnsfile, options);
+   } catch (std::runtime_error &e) {
+     std::cerr << e.what();
+     return EXIT_FAILURE;
+   }
   
    //auto t_end = std::chrono::high_resolution_clock::now();
    //std::cout << std::chrono::duration<double, std::milli>(t_end-t_mid).count()<<std::endl;
@@ -0,0 +1,253 @@
+#include "fr.hpp"
+#include <stdio.h>
+#include <stdlib.h>
+#include <assert.h>
+#include <gmp.h>
+
+// p = 2^64 - 2^32 + 1, elements are kept in normal form
+const uint64_t MOD = 0xFFFFFFFF00000001ULL;
+const uint64_t EPSILON = 0xFFFFFFFFULL;
+const uint64_t HALF = MOD / 2;
+
+static inline uint64_t Fr_canonical(uint64_t a) {
+    return a >= MOD ? a - MOD : a;
+}
+
+// Reduces a 128 bit value using 2^64 = 2^32 - 1 and 2^96 = -1 (mod p)
+static inline uint64_t Fr_reduce128(__uint128_t x) {
+    uint64_t lo = (uint64_t)x;
+    uint64_t hi = (uint64_t)(x >> 64);
+    uint64_t hi_hi = hi >> 32;
+    uint64_t hi_lo = hi & EPSILON;
+
+    uint64_t t0;
+    if (__builtin_sub_overflow(lo, hi_hi, &t0)) {
+        t0 -= EPSILON;
+    }
+    uint64_t t1 = hi_lo * EPSILON;
+    uint64_t t2;
+    if (__builtin_add_overflow(t0, t1, &t2)) {
+        t2 += EPSILON;
+    }
+    return Fr_canonical(t2);
+}
+
+static inline uint64_t Fr_rawAdd(uint64_t a, uint64_t b) {
+    uint64_t r;
+    if (__builtin_add_overflow(a, b, &r)) {
+        r += EPSILON;
+    }
+    return Fr_canonical(r);
+}
+
+static inline uint64_t Fr_rawSub(uint64_t a, uint64_t b) {
+    uint64_t r;
+    if (__builtin_sub_overflow(a, b, &r)) {
+        r -= EPSILON;
+    }
+    return r;
+}
+
+static inline uint64_t Fr_rawMul(uint64_t a, uint64_t b) {
+    return Fr_reduce128((__uint128_t)a * (__uint128_t)b);
+}
+
+static uint64_t Fr_rawPow(uint64_t base, uint64_t exp) {
+    uint64_t r = 1;
+    while (exp > 0) {
+        if (exp & 1) {
+            r = Fr_rawMul(r, base);
+        }
+        base = Fr_rawMul(base, base);
+        exp >>= 1;
+    }
+    return r;
+}
+
+// Comparisons interpret the elements in (-p/2, p/2], as the constraint generator does
+static inline __int128 Fr_signed(uint64_t a) {
+    return a > HALF ? (__int128)a - (__int128)MOD : (__int128)a;
+}
+
+void Fr_copy(PFrElement r, PFrElement a) {
+    r->longVal[0] = a->longVal[0];
+}
+
+void Fr_copyn(PFrElement r, PFrElement a, int n) {
+    for(int i  = 0; i < n; i++) {
+        (r++)->longVal[0] = (a++)->longVal[0];
+    }
+}
+
+void Fr_add(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawAdd(a->longVal[0], b->longVal[0]);
+}
+
+void Fr_sub(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawSub(a->longVal[0], b->longVal[0]);
+}
+
+void Fr_neg(PFrElement r, PFrElement a) {
+    r->longVal[0] = Fr_rawSub(0, a->longVal[0]);
+}
+
+void Fr_mul(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawMul(a->longVal[0], b->longVal[0]);
+}
+
+void Fr_band(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = a->longVal[0] & b->longVal[0];
+}
+
+void Fr_bor(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_canonical(a->longVal[0] | b->longVal[0]);
+}
+
+void Fr_bxor(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_canonical(a->longVal[0] ^ b->longVal[0]);
+}
+
+void Fr_bnot(PFrElement r, PFrElement a) {
+    r->longVal[0] = Fr_canonical(~a->longVal[0]);
+}
+
+static uint64_t Fr_shiftLeft(uint64_t a, uint64_t n) {
+    return n >= 64 ? 0 : Fr_canonical(a << n);
+}
+
+static uint64_t Fr_shiftRight(uint64_t a, uint64_t n) {
+    return n >= 64 ? 0 : a >> n;
+}
+
+// Shifting by a "negative" amount (greater than p/2) shifts in the opposite direction
+void Fr_shl(PFrElement r, PFrElement a, PFrElement b) {
+    uint64_t n = b->longVal[0];
+    if (n <= HALF) {
+        r->longVal[0] = Fr_shiftLeft(a->longVal[0], n);
+    } else {
+        r->longVal[0] = Fr_shiftRight(a->longVal[0], MOD - n);
+    }
+}
+
+void Fr_shr(PFrElement r, PFrElement a, PFrElement b) {
+    uint64_t n = b->longVal[0];
+    if (n <= HALF) {
+        r->longVal[0] = Fr_shiftRight(a->longVal[0], n);
+    } else {
+        r->longVal[0] = Fr_shiftLeft(a->longVal[0], MOD - n);
+    }
+}
+
+void Fr_eq(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = a->longVal[0] == b->longVal[0] ? 1 : 0;
+}
+
+void Fr_neq(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = a->longVal[0] != b->longVal[0] ? 1 : 0;
+}
+
+void Fr_lt(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_signed(a->longVal[0]) < Fr_signed(b->longVal[0]) ? 1 : 0;
+}
+
+void Fr_gt(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_signed(a->longVal[0]) > Fr_signed(b->longVal[0]) ? 1 : 0;
+}
+
+void Fr_leq(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_signed(a->longVal[0]) <= Fr_signed(b->longVal[0]) ? 1 : 0;
+}
+
+void Fr_geq(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_signed(a->longVal[0]) >= Fr_signed(b->longVal[0]) ? 1 : 0;
+}
+
+void Fr_land(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = (a->longVal[0] != 0) && (b->longVal[0] != 0) ? 1 : 0;
+}
+
+void Fr_lor(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = (a->longVal[0] != 0) || (b->longVal[0] != 0) ? 1 : 0;
+}
+
+void Fr_lnot(PFrElement r, PFrElement a) {
+    r->longVal[0] = a->longVal[0] == 0 ? 1 : 0;
+}
+
+void Fr_toLongNormal(PFrElement r, PFrElement a) {
+    r->longVal[0] = a->longVal[0];
+}
+
+// Any 64 bit number is below 2 * MOD
+void Fr_fromLongNormal(PFrElement r, PFrElement a) {
+    r->longVal[0] = Fr_canonical(a->longVal[0]);
+}
+
+int Fr_isTrue(PFrElement pE) {
+    return pE->longVal[0] != 0 ? 1 : 0;
+}
+
+int Fr_toInt(PFrElement pE) {
+    __int128 v = Fr_signed(pE->longVal[0]);
+    if (v > 0x7FFFFFFF || v < -0x7FFFFFFF) {
+        fprintf(stderr, "Fr_toInt: value does not fit in an int\n");
+        assert(false);
+    }
+    return (int)v;
+}
+
+void Fr_str2element(PFrElement pE, char const *s, unsigned int base) {
+    mpz_t mr;
+    mpz_t mq;
+    mpz_init_set_str(mr, s, base);
+    mpz_init(mq);
+    mpz_set_ui(mq, 1);
+    mpz_mul_2exp(mq, mq, 64);
+    mpz_sub_ui(mq, mq, EPSILON);
+    mpz_fdiv_r(mr, mr, mq);
+    uint64_t v = 0;
+    mpz_export(&v, NULL, -1, sizeof(uint64_t), 0, 0, mr);
+    pE->longVal[0] = v;
+    mpz_clear(mr);
+    mpz_clear(mq);
+}
+
+char *Fr_element2str(PFrElement pE) {
+    char *r = new char[32];
+    snprintf(r, 32, "%llu", (unsigned long long) pE->longVal[0]);
+    return r;
+}
+
+void Fr_idiv(PFrElement r, PFrElement a, PFrElement b) {
+    if (b->longVal[0] == 0) {
+        fprintf(stderr, "Division by zero\n");
+        assert(false);
+    }
+    r->longVal[0] = a->longVal[0] / b->longVal[0];
+}
+
+void Fr_mod(PFrElement r, PFrElement a, PFrElement b) {
+    if (b->longVal[0] == 0) {
+        fprintf(stderr, "Division by zero\n");
+        assert(false);
+    }
+    r->longVal[0] = a->longVal[0] % b->longVal[0];
+}
+
+void Fr_inv(PFrElement r, PFrElement a) {
+    if (a->longVal[0] == 0) {
+        fprintf(stderr, "Division by zero\n");
+        assert(false);
+    }
+    r->longVal[0] = Fr_rawPow(a->longVal[0], MOD - 2);
+}
+
+void Fr_div(PFrElement r, PFrElement a, PFrElement b) {
+    FrElement inv;
+    Fr_inv(&inv, b);
+    Fr_mul(r, a, &inv);
+}
+
+void Fr_pow(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawPow(a->longVal[0], b->longVal[0]);
+}
@@ -137,6 +137,10 @@ void Fr_toLongNormal(PFrElement r, PFrElement a) {
     r->longVal[0] = a->longVal[0];
 }
 
+void Fr_fromLongNormal(PFrElement r, PFrElement a) {
+    r->longVal[0] = (uint32_t)(a->longVal[0] % MOD);
+}
+
 int Fr_isTrue(PFrElement pE) {
     if(pE->longVal[0] != 0) {
         return 1;
@@ -225,4 +229,55 @@ void Fr_pow(PFrElement r, PFrElement a, PFrElement b) {
     mpz_clear(mb);
     mpz_clear(mq);
     mpz_clear(mr);
-}
\ No newline at end of file
+}
+
+void Fr_qm31_add(PFrElement r, PFrElement a, PFrElement b) {
+    for(int i = 0; i < 4; i++) {
+        Fr_add(&r[i], &a[i], &b[i]);
+    }
+}
+
+void Fr_qm31_sub(PFrElement r, PFrElement a, PFrElement b) {
+    for(int i = 0; i < 4; i++) {
+        Fr_sub(&r[i], &a[i], &b[i]);
+    }
+}
+
+static void cm31_mul(uint64_t r[2], uint64_t a0, uint64_t a1, uint64_t b0, uint64_t b1) {
+    r[0] = (a0 * b0 % MOD + MOD - a1 * b1 % MOD) % MOD;
+    r[1] = (a0 * b1 % MOD + a1 * b0 % MOD) % MOD;
+}
+
+void Fr_qm31_mul(PFrElement r, PFrElement a, PFrElement b) {
+    uint64_t xx[2], yy[2], xy[2], yx[2];
+    cm31_mul(xx, a[0].longVal[0], a[1].longVal[0], b[0].longVal[0], b[1].longVal[0]);
+    cm31_mul(yy, a[2].longVal[0], a[3].longVal[0], b[2].longVal[0], b[3].longVal[0]);
+    cm31_mul(xy, a[0].longVal[0], a[1].longVal[0], b[2].longVal[0], b[3].longVal[0]);
+    cm31_mul(yx, a[2].longVal[0], a[3].longVal[0], b[0].longVal[0], b[1].longVal[0]);
+    // (2 + i) * (c + d * i) = (2c - d) + (2d + c) * i
+    r[0].longVal[0] = (uint32_t)((xx[0] + 2 * yy[0] + MOD - yy[1]) % MOD);
+    r[1].longVal[0] = (uint32_t)((xx[1] + 2 * yy[1] + yy[0]) % MOD);
+    r[2].longVal[0] = (uint32_t)((xy[0] + yx[0]) % MOD);
+    r[3].longVal[0] = (uint32_t)((xy[1] + yx[1]) % MOD);
+}
+
+void Fr_qm31_inv(PFrElement r, PFrElement a) {
+    // (x + y * u)^-1 = (x - y * u) / (x^2 - (2 + i) * y^2)
+    uint64_t xx[2], yy[2], x[2], y[2];
+    cm31_mul(xx, a[0].longVal[0], a[1].longVal[0], a[0].longVal[0], a[1].longVal[0]);
+    cm31_mul(yy, a[2].longVal[0], a[3].longVal[0], a[2].longVal[0], a[3].longVal[0]);
+    uint64_t d0 = (xx[0] + 2 * (MOD - yy[0]) + yy[1]) % MOD;
+    uint64_t d1 = (xx[1] + 2 * (MOD - yy[1]) + MOD - yy[0]) % MOD;
+    // (d0 + d1 * i)^-1 = (d0 - d1 * i) / (d0^2 + d1^2)
+    FrElement norm = {{ (uint32_t)((d0 * d0 % MOD + d1 * d1 % MOD) % MOD) }};
+    Fr_inv(&norm, &norm);
+    uint64_t n = norm.longVal[0];
+    uint64_t e0 = d0 * n % MOD;
+    uint64_t e1 = (MOD - d1) % MOD * n % MOD;
+    cm31_mul(x, a[0].longVal[0], a[1].longVal[0], e0, e1);
+    cm31_mul(y, a[2].longVal[0], a[3].longVal[0], e0, e1);
+    r[0].longVal[0] = (uint32_t)x[0];
+    r[1].longVal[0] = (uint32_t)x[1];
+    r[2].longVal[0] = (uint32_t)((MOD - y[0]) % MOD);
+    r[3].longVal[0] = (uint32_t)((MOD - y[1]) % MOD);
+}
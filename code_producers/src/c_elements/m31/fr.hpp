@@ -37,6 +37,9 @@ void Fr_land(PFrElement r, PFrElement a, PFrElement b);
 void Fr_lor(PFrElement r, PFrElement a, PFrElement b);
 void Fr_lnot(PFrElement r, PFrElement a);
 void Fr_toLongNormal(PFrElement r, PFrElement a);
+// Takes the Fr_N32 words of longVal as a number in normal form, which can be
+// larger than the prime, to the element of the field
+void Fr_fromLongNormal(PFrElement r, PFrElement a);
 
 int Fr_isTrue(PFrElement pE);
 int Fr_toInt(PFrElement pE);
@@ -48,6 +51,14 @@ void Fr_mod(PFrElement r, PFrElement a, PFrElement b);
 void Fr_div(PFrElement r, PFrElement a, PFrElement b);
 void Fr_pow(PFrElement r, PFrElement a, PFrElement b);
 
+// QM31 = CM31[u] / (u^2 - 2 - i), CM31 = M31[i] / (i^2 + 1).
+// An element is stored in 4 consecutive elements [a0, a1, a2, a3],
+// (a0 + a1 * i) + (a2 + a3 * i) * u
+void Fr_qm31_add(PFrElement r, PFrElement a, PFrElement b);
+void Fr_qm31_sub(PFrElement r, PFrElement a, PFrElement b);
+void Fr_qm31_mul(PFrElement r, PFrElement a, PFrElement b);
+void Fr_qm31_inv(PFrElement r, PFrElement a);
+
 #endif // __FR_H
 
 
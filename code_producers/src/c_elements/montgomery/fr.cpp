@@ -0,0 +1,461 @@
+#include "fr.hpp"
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <assert.h>
+#include <gmp.h>
+
+// Elements are kept in Montgomery form with R = 2^(32*Fr_N32).
+// Fr_q and the values handed to Fr_toLongNormal callers are in normal form.
+static const uint32_t Fr_rawq[Fr_N32] = { {{q}} };
+static const uint32_t Fr_rawR2[Fr_N32] = { {{r2}} };
+static const uint32_t Fr_rawOne[Fr_N32] = { {{one}} };
+static const uint32_t Fr_rawHalf[Fr_N32] = { {{half}} };
+static const uint32_t Fr_rawMask[Fr_N32] = { {{mask}} };
+static const uint32_t Fr_np = {{np}};
+static const uint32_t Fr_nBits = {{bits}};
+
+static int Fr_rawCmp(const uint32_t *a, const uint32_t *b) {
+    for (int i = Fr_N32 - 1; i >= 0; i--) {
+        if (a[i] > b[i]) return 1;
+        if (a[i] < b[i]) return -1;
+    }
+    return 0;
+}
+
+static int Fr_rawIsZero(const uint32_t *a) {
+    for (int i = 0; i < Fr_N32; i++) {
+        if (a[i] != 0) return 0;
+    }
+    return 1;
+}
+
+static uint32_t Fr_rawAdd(uint32_t *r, const uint32_t *a, const uint32_t *b) {
+    uint64_t carry = 0;
+    for (int i = 0; i < Fr_N32; i++) {
+        uint64_t s = (uint64_t)a[i] + (uint64_t)b[i] + carry;
+        r[i] = (uint32_t)s;
+        carry = s >> 32;
+    }
+    return (uint32_t)carry;
+}
+
+static uint32_t Fr_rawSub(uint32_t *r, const uint32_t *a, const uint32_t *b) {
+    uint64_t borrow = 0;
+    for (int i = 0; i < Fr_N32; i++) {
+        uint64_t d = (uint64_t)a[i] - (uint64_t)b[i] - borrow;
+        r[i] = (uint32_t)d;
+        borrow = (d >> 32) & 1;
+    }
+    return (uint32_t)borrow;
+}
+
+// CIOS Montgomery multiplication: r = a * b * R^-1 mod q
+static void Fr_rawMMul(uint32_t *r, const uint32_t *a, const uint32_t *b) {
+    uint32_t t[Fr_N32 + 2];
+    memset(t, 0, sizeof(t));
+    for (int i = 0; i < Fr_N32; i++) {
+        uint64_t c = 0;
+        for (int j = 0; j < Fr_N32; j++) {
+            uint64_t s = (uint64_t)t[j] + (uint64_t)a[j] * (uint64_t)b[i] + c;
+            t[j] = (uint32_t)s;
+            c = s >> 32;
+        }
+        uint64_t s = (uint64_t)t[Fr_N32] + c;
+        t[Fr_N32] = (uint32_t)s;
+        t[Fr_N32 + 1] = (uint32_t)(s >> 32);
+
+        uint32_t m = t[0] * Fr_np;
+        s = (uint64_t)t[0] + (uint64_t)m * (uint64_t)Fr_rawq[0];
+        c = s >> 32;
+        for (int j = 1; j < Fr_N32; j++) {
+            s = (uint64_t)t[j] + (uint64_t)m * (uint64_t)Fr_rawq[j] + c;
+            t[j - 1] = (uint32_t)s;
+            c = s >> 32;
+        }
+        s = (uint64_t)t[Fr_N32] + c;
+        t[Fr_N32 - 1] = (uint32_t)s;
+        t[Fr_N32] = t[Fr_N32 + 1] + (uint32_t)(s >> 32);
+    }
+    if (t[Fr_N32] != 0 || Fr_rawCmp(t, Fr_rawq) >= 0) {
+        Fr_rawSub(r, t, Fr_rawq);
+    } else {
+        memcpy(r, t, Fr_N32 * sizeof(uint32_t));
+    }
+}
+
+static void Fr_rawToNormal(uint32_t *r, const uint32_t *a) {
+    uint32_t one[Fr_N32];
+    memset(one, 0, sizeof(one));
+    one[0] = 1;
+    Fr_rawMMul(r, a, one);
+}
+
+static void Fr_rawToMontgomery(uint32_t *r, const uint32_t *a) {
+    Fr_rawMMul(r, a, Fr_rawR2);
+}
+
+static void Fr_setBool(PFrElement r, int v) {
+    if (v) {
+        memcpy(r->longVal, Fr_rawOne, Fr_N32 * sizeof(uint32_t));
+    } else {
+        memset(r->longVal, 0, Fr_N32 * sizeof(uint32_t));
+    }
+}
+
+static void Fr_qMpz(mpz_t q) {
+    mpz_init(q);
+    mpz_import(q, Fr_N32, -1, sizeof(uint32_t), 0, 0, Fr_rawq);
+}
+
+void Fr_toMpz(mpz_t r, PFrElement pE) {
+    uint32_t n[Fr_N32];
+    Fr_rawToNormal(n, pE->longVal);
+    mpz_import(r, Fr_N32, -1, sizeof(uint32_t), 0, 0, n);
+}
+
+void Fr_fromMpz(PFrElement pE, mpz_t v) {
+    mpz_t mq;
+    mpz_t mr;
+    Fr_qMpz(mq);
+    mpz_init(mr);
+    mpz_fdiv_r(mr, v, mq);
+    uint32_t n[Fr_N32];
+    memset(n, 0, sizeof(n));
+    mpz_export(n, NULL, -1, sizeof(uint32_t), 0, 0, mr);
+    Fr_rawToMontgomery(pE->longVal, n);
+    mpz_clear(mr);
+    mpz_clear(mq);
+}
+
+void Fr_copy(PFrElement r, PFrElement a) {
+    memcpy(r->longVal, a->longVal, Fr_N32 * sizeof(uint32_t));
+}
+
+void Fr_copyn(PFrElement r, PFrElement a, int n) {
+    for(int i  = 0; i < n; i++) {
+        Fr_copy(r++, a++);
+    }
+}
+
+void Fr_add(PFrElement r, PFrElement a, PFrElement b) {
+    uint32_t carry = Fr_rawAdd(r->longVal, a->longVal, b->longVal);
+    if (carry || Fr_rawCmp(r->longVal, Fr_rawq) >= 0) {
+        Fr_rawSub(r->longVal, r->longVal, Fr_rawq);
+    }
+}
+
+void Fr_sub(PFrElement r, PFrElement a, PFrElement b) {
+    if (Fr_rawSub(r->longVal, a->longVal, b->longVal)) {
+        Fr_rawAdd(r->longVal, r->longVal, Fr_rawq);
+    }
+}
+
+void Fr_neg(PFrElement r, PFrElement a) {
+    if (Fr_rawIsZero(a->longVal)) {
+        memset(r->longVal, 0, Fr_N32 * sizeof(uint32_t));
+    } else {
+        Fr_rawSub(r->longVal, Fr_rawq, a->longVal);
+    }
+}
+
+void Fr_mul(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_rawMMul(r->longVal, a->longVal, b->longVal);
+}
+
+// Bitwise operators work over the normal form and are reduced back into the field
+static void Fr_reduceOnce(uint32_t *r) {
+    if (Fr_rawCmp(r, Fr_rawq) >= 0) {
+        Fr_rawSub(r, r, Fr_rawq);
+    }
+}
+
+void Fr_band(PFrElement r, PFrElement a, PFrElement b) {
+    uint32_t na[Fr_N32], nb[Fr_N32];
+    Fr_rawToNormal(na, a->longVal);
+    Fr_rawToNormal(nb, b->longVal);
+    for (int i = 0; i < Fr_N32; i++) {
+        na[i] = na[i] & nb[i];
+    }
+    Fr_reduceOnce(na);
+    Fr_rawToMontgomery(r->longVal, na);
+}
+
+void Fr_bor(PFrElement r, PFrElement a, PFrElement b) {
+    uint32_t na[Fr_N32], nb[Fr_N32];
+    Fr_rawToNormal(na, a->longVal);
+    Fr_rawToNormal(nb, b->longVal);
+    for (int i = 0; i < Fr_N32; i++) {
+        na[i] = na[i] | nb[i];
+    }
+    Fr_reduceOnce(na);
+    Fr_rawToMontgomery(r->longVal, na);
+}
+
+void Fr_bxor(PFrElement r, PFrElement a, PFrElement b) {
+    uint32_t na[Fr_N32], nb[Fr_N32];
+    Fr_rawToNormal(na, a->longVal);
+    Fr_rawToNormal(nb, b->longVal);
+    for (int i = 0; i < Fr_N32; i++) {
+        na[i] = na[i] ^ nb[i];
+    }
+    Fr_reduceOnce(na);
+    Fr_rawToMontgomery(r->longVal, na);
+}
+
+void Fr_bnot(PFrElement r, PFrElement a) {
+    uint32_t na[Fr_N32];
+    Fr_rawToNormal(na, a->longVal);
+    for (int i = 0; i < Fr_N32; i++) {
+        na[i] = (~na[i]) & Fr_rawMask[i];
+    }
+    Fr_reduceOnce(na);
+    Fr_rawToMontgomery(r->longVal, na);
+}
+
+// Returns the shift amount if it is smaller than the number of bits of q, -1 otherwise
+static int Fr_shiftAmount(const uint32_t *n) {
+    for (int i = 1; i < Fr_N32; i++) {
+        if (n[i] != 0) return -1;
+    }
+    return n[0] < Fr_nBits ? (int)n[0] : -1;
+}
+
+static void Fr_shiftLeft(PFrElement r, PFrElement a, const uint32_t *n) {
+    int amount = Fr_shiftAmount(n);
+    if (amount < 0) {
+        memset(r->longVal, 0, Fr_N32 * sizeof(uint32_t));
+        return;
+    }
+    mpz_t ma;
+    mpz_t mmask;
+    mpz_init(ma);
+    mpz_init(mmask);
+    Fr_toMpz(ma, a);
+    mpz_import(mmask, Fr_N32, -1, sizeof(uint32_t), 0, 0, Fr_rawMask);
+    mpz_mul_2exp(ma, ma, amount);
+    mpz_and(ma, ma, mmask);
+    Fr_fromMpz(r, ma);
+    mpz_clear(ma);
+    mpz_clear(mmask);
+}
+
+static void Fr_shiftRight(PFrElement r, PFrElement a, const uint32_t *n) {
+    int amount = Fr_shiftAmount(n);
+    if (amount < 0) {
+        memset(r->longVal, 0, Fr_N32 * sizeof(uint32_t));
+        return;
+    }
+    mpz_t ma;
+    mpz_init(ma);
+    Fr_toMpz(ma, a);
+    mpz_fdiv_q_2exp(ma, ma, amount);
+    Fr_fromMpz(r, ma);
+    mpz_clear(ma);
+}
+
+// Shifting by a "negative" amount (greater than q/2) shifts in the opposite direction
+void Fr_shl(PFrElement r, PFrElement a, PFrElement b) {
+    uint32_t nb[Fr_N32];
+    Fr_rawToNormal(nb, b->longVal);
+    if (Fr_rawCmp(nb, Fr_rawHalf) <= 0) {
+        Fr_shiftLeft(r, a, nb);
+    } else {
+        Fr_rawSub(nb, Fr_rawq, nb);
+        Fr_shiftRight(r, a, nb);
+    }
+}
+
+void Fr_shr(PFrElement r, PFrElement a, PFrElement b) {
+    uint32_t nb[Fr_N32];
+    Fr_rawToNormal(nb, b->longVal);
+    if (Fr_rawCmp(nb, Fr_rawHalf) <= 0) {
+        Fr_shiftRight(r, a, nb);
+    } else {
+        Fr_rawSub(nb, Fr_rawq, nb);
+        Fr_shiftLeft(r, a, nb);
+    }
+}
+
+void Fr_eq(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_setBool(r, Fr_rawCmp(a->longVal, b->longVal) == 0);
+}
+
+void Fr_neq(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_setBool(r, Fr_rawCmp(a->longVal, b->longVal) != 0);
+}
+
+// Comparisons interpret the elements in (-q/2, q/2], as the constraint generator does
+static int Fr_signedCmp(PFrElement a, PFrElement b) {
+    uint32_t na[Fr_N32], nb[Fr_N32];
+    Fr_rawToNormal(na, a->longVal);
+    Fr_rawToNormal(nb, b->longVal);
+    int negA = Fr_rawCmp(na, Fr_rawHalf) > 0;
+    int negB = Fr_rawCmp(nb, Fr_rawHalf) > 0;
+    if (negA != negB) {
+        return negA ? -1 : 1;
+    }
+    return Fr_rawCmp(na, nb);
+}
+
+void Fr_lt(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_setBool(r, Fr_signedCmp(a, b) < 0);
+}
+
+void Fr_gt(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_setBool(r, Fr_signedCmp(a, b) > 0);
+}
+
+void Fr_leq(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_setBool(r, Fr_signedCmp(a, b) <= 0);
+}
+
+void Fr_geq(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_setBool(r, Fr_signedCmp(a, b) >= 0);
+}
+
+void Fr_land(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_setBool(r, Fr_isTrue(a) && Fr_isTrue(b));
+}
+
+void Fr_lor(PFrElement r, PFrElement a, PFrElement b) {
+    Fr_setBool(r, Fr_isTrue(a) || Fr_isTrue(b));
+}
+
+void Fr_lnot(PFrElement r, PFrElement a) {
+    Fr_setBool(r, !Fr_isTrue(a));
+}
+
+void Fr_toLongNormal(PFrElement r, PFrElement a) {
+    Fr_rawToNormal(r->longVal, a->longVal);
+}
+
+// The multiplication by R2 also reduces any number below R
+void Fr_fromLongNormal(PFrElement r, PFrElement a) {
+    Fr_rawToMontgomery(r->longVal, a->longVal);
+}
+
+int Fr_isTrue(PFrElement pE) {
+    return !Fr_rawIsZero(pE->longVal);
+}
+
+int Fr_toInt(PFrElement pE) {
+    uint32_t n[Fr_N32];
+    Fr_rawToNormal(n, pE->longVal);
+    uint32_t shifted[Fr_N32];
+    memcpy(shifted, n, sizeof(n));
+    int negative = Fr_rawCmp(n, Fr_rawHalf) > 0;
+    if (negative) {
+        Fr_rawSub(shifted, Fr_rawq, n);
+    }
+    for (int i = 1; i < Fr_N32; i++) {
+        if (shifted[i] != 0) {
+            fprintf(stderr, "Fr_toInt: value does not fit in an int\n");
+            assert(false);
+        }
+    }
+    if (shifted[0] > 0x7FFFFFFF) {
+        fprintf(stderr, "Fr_toInt: value does not fit in an int\n");
+        assert(false);
+    }
+    return negative ? -(int)shifted[0] : (int)shifted[0];
+}
+
+void Fr_str2element(PFrElement pE, char const *s, unsigned int base) {
+    mpz_t mr;
+    mpz_init_set_str(mr, s, base);
+    Fr_fromMpz(pE, mr);
+    mpz_clear(mr);
+}
+
+char *Fr_element2str(PFrElement pE) {
+    mpz_t r;
+    mpz_init(r);
+    Fr_toMpz(r, pE);
+    size_t size = mpz_sizeinbase(r, 10) + 2;
+    char *res = new char[size];
+    mpz_get_str(res, 10, r);
+    mpz_clear(r);
+    return res;
+}
+
+void Fr_idiv(PFrElement r, PFrElement a, PFrElement b) {
+    if (Fr_rawIsZero(b->longVal)) {
+        fprintf(stderr, "Division by zero\n");
+        assert(false);
+    }
+    mpz_t ma;
+    mpz_t mb;
+    mpz_init(ma);
+    mpz_init(mb);
+    Fr_toMpz(ma, a);
+    Fr_toMpz(mb, b);
+    mpz_fdiv_q(ma, ma, mb);
+    Fr_fromMpz(r, ma);
+    mpz_clear(ma);
+    mpz_clear(mb);
+}
+
+void Fr_mod(PFrElement r, PFrElement a, PFrElement b) {
+    if (Fr_rawIsZero(b->longVal)) {
+        fprintf(stderr, "Division by zero\n");
+        assert(false);
+    }
+    mpz_t ma;
+    mpz_t mb;
+    mpz_init(ma);
+    mpz_init(mb);
+    Fr_toMpz(ma, a);
+    Fr_toMpz(mb, b);
+    mpz_fdiv_r(ma, ma, mb);
+    Fr_fromMpz(r, ma);
+    mpz_clear(ma);
+    mpz_clear(mb);
+}
+
+void Fr_inv(PFrElement r, PFrElement a) {
+    if (Fr_rawIsZero(a->longVal)) {
+        fprintf(stderr, "Division by zero\n");
+        assert(false);
+    }
+    mpz_t ma;
+    mpz_t mr;
+    mpz_t mq;
+    mpz_init(ma);
+    mpz_init(mr);
+    Fr_qMpz(mq);
+
+    Fr_toMpz(ma, a);
+    mpz_invert(mr, ma, mq);
+    Fr_fromMpz(r, mr);
+    mpz_clear(ma);
+    mpz_clear(mr);
+    mpz_clear(mq);
+}
+
+void Fr_div(PFrElement r, PFrElement a, PFrElement b) {
+    FrElement inv;
+    Fr_inv(&inv, b);
+    Fr_mul(r, a, &inv);
+}
+
+void Fr_pow(PFrElement r, PFrElement a, PFrElement b) {
+    mpz_t ma;
+    mpz_t mb;
+    mpz_t mq;
+    mpz_t mr;
+    mpz_init(ma);
+    mpz_init(mb);
+    Fr_qMpz(mq);
+    mpz_init(mr);
+
+    Fr_toMpz(ma, a);
+    Fr_toMpz(mb, b);
+    mpz_powm(mr, ma, mb, mq);
+    Fr_fromMpz(r, mr);
+
+    mpz_clear(ma);
+    mpz_clear(mb);
+    mpz_clear(mq);
+    mpz_clear(mr);
+}
@@ -2,3 +2,9 @@
 pub mod c_elements;
 
 pub mod components;
+
+#[allow(dead_code)]
+pub mod rust_elements;
+
+#[allow(dead_code)]
+pub mod wasm_elements;
@@ -0,0 +1,241 @@
+// Port of calcwit.cpp: the memory of the witness calculation and the loading of the
+// .dat file generated by circom
+use crate::circuit::*;
+use crate::fr::*;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Default)]
+pub struct HashSignalInfo {
+    pub hash: u64,
+    pub signal_id: u64,
+    pub signal_size: u64,
+}
+
+pub struct IODef {
+    pub offset: usize,
+    pub lengths: Vec<usize>,
+}
+
+pub struct CircomCircuit {
+    pub input_hash_map: Vec<HashSignalInfo>,
+    pub witness_to_signal_list: Vec<usize>,
+    pub circuit_constants: Vec<FrElement>,
+    pub template_ins_id_2_io_signal_info: HashMap<usize, Vec<IODef>>,
+}
+
+struct DatReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> DatReader<'a> {
+    fn read(&mut self, size: usize) -> Result<&'a [u8], String> {
+        let end = self.position + size;
+        if end > self.data.len() {
+            return Err("The .dat file is truncated".to_string());
+        }
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_u64(&mut self) -> Result<u64, String> {
+        let bytes = self.read(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<usize, String> {
+        let bytes = self.read(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    }
+}
+
+impl CircomCircuit {
+    // Same layout as loadCircuit in the C++ main.cpp
+    pub fn load(dat: &[u8]) -> Result<CircomCircuit, String> {
+        let mut reader = DatReader {
+            data: dat,
+            position: 0,
+        };
+        let mut input_hash_map = Vec::with_capacity(get_size_of_input_hashmap());
+        for _ in 0..get_size_of_input_hashmap() {
+            input_hash_map.push(HashSignalInfo {
+                hash: reader.read_u64()?,
+                signal_id: reader.read_u64()?,
+                signal_size: reader.read_u64()?,
+            });
+        }
+        let mut witness_to_signal_list = Vec::with_capacity(get_size_of_witness());
+        for _ in 0..get_size_of_witness() {
+            witness_to_signal_list.push(reader.read_u64()? as usize);
+        }
+        let mut circuit_constants = Vec::with_capacity(get_size_of_constants());
+        for _ in 0..get_size_of_constants() {
+            circuit_constants.push(fr_from_bytes_le(reader.read(FR_N8)?));
+        }
+        let mut template_ids = Vec::with_capacity(get_size_of_io_map());
+        for _ in 0..get_size_of_io_map() {
+            template_ids.push(reader.read_u32()?);
+        }
+        let mut template_ins_id_2_io_signal_info = HashMap::new();
+        for template_id in template_ids {
+            let number_of_defs = reader.read_u32()?;
+            let mut defs = Vec::with_capacity(number_of_defs);
+            for _ in 0..number_of_defs {
+                let offset = reader.read_u32()?;
+                let number_of_lengths = reader.read_u32()?;
+                let mut lengths = Vec::with_capacity(number_of_lengths);
+                for _ in 0..number_of_lengths {
+                    lengths.push(reader.read_u32()?);
+                }
+                defs.push(IODef { offset, lengths });
+            }
+            template_ins_id_2_io_signal_info.insert(template_id, defs);
+        }
+        Ok(CircomCircuit {
+            input_hash_map,
+            witness_to_signal_list,
+            circuit_constants,
+            template_ins_id_2_io_signal_info,
+        })
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ComponentMemory {
+    pub template_id: usize,
+    pub template_name: &'static str,
+    pub signal_start: usize,
+    pub input_counter: usize,
+    pub component_name: String,
+    pub id_father: usize,
+    pub subcomponents: Vec<usize>,
+}
+
+pub struct CircomCalcWit<'a> {
+    pub circuit: &'a CircomCircuit,
+    pub signal_values: Vec<FrElement>,
+    pub component_memory: Vec<ComponentMemory>,
+    input_signal_assigned: Vec<bool>,
+    input_counter: usize,
+}
+
+pub fn fnv1a(s: &str) -> u64 {
+    let mut hash: u64 = 0xCBF29CE484222325;
+    for byte in s.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001B3);
+    }
+    hash
+}
+
+impl<'a> CircomCalcWit<'a> {
+    pub fn new(circuit: &'a CircomCircuit) -> CircomCalcWit<'a> {
+        let mut signal_values = vec![FrElement::default(); get_total_signal_no()];
+        signal_values[0] = FrElement::from(1);
+        CircomCalcWit {
+            circuit,
+            signal_values,
+            component_memory: vec![ComponentMemory::default(); get_number_of_components()],
+            input_signal_assigned: vec![false; get_main_input_signal_no()],
+            input_counter: get_main_input_signal_no(),
+        }
+    }
+
+    pub fn get_remaining_inputs_to_be_set(&self) -> usize {
+        self.input_counter
+    }
+
+    fn get_input_signal_hash_position(&self, h: u64) -> Result<usize, String> {
+        let map = &self.circuit.input_hash_map;
+        let size = map.len();
+        let mut position = (h as usize) % size;
+        for _ in 0..size {
+            if map[position].hash == h {
+                return Ok(position);
+            }
+            if map[position].signal_id == 0 {
+                break;
+            }
+            position = (position + 1) % size;
+        }
+        Err("Signal not found".to_string())
+    }
+
+    pub fn get_input_signal_size(&self, h: u64) -> Result<usize, String> {
+        let position = self.get_input_signal_hash_position(h)?;
+        Ok(self.circuit.input_hash_map[position].signal_size as usize)
+    }
+
+    // Sets the input and runs the circuit once every input has been set
+    pub fn set_input_signal(&mut self, h: u64, i: usize, value: FrElement) -> Result<(), String> {
+        self.assign_input_signal(h, i, value)?;
+        self.try_run_circuit()
+    }
+
+    pub fn assign_input_signal(
+        &mut self,
+        h: u64,
+        i: usize,
+        value: FrElement,
+    ) -> Result<(), String> {
+        if self.input_counter == 0 {
+            return Err("No more signals to be assigned".to_string());
+        }
+        let position = self.get_input_signal_hash_position(h)?;
+        let info = self.circuit.input_hash_map[position];
+        if i >= info.signal_size as usize {
+            return Err("Input signal array access exceeds the size".to_string());
+        }
+        let si = info.signal_id as usize + i;
+        let assigned = &mut self.input_signal_assigned[si - get_main_input_signal_start()];
+        if *assigned {
+            return Err(format!("Signal assigned twice: {}", si));
+        }
+        *assigned = true;
+        self.signal_values[si] = value;
+        self.input_counter -= 1;
+        Ok(())
+    }
+
+    pub fn try_run_circuit(&mut self) -> Result<(), String> {
+        if self.input_counter == 0 {
+            run(self)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_trace(&self, id_cmp: usize) -> String {
+        let component = &self.component_memory[id_cmp];
+        if id_cmp == 0 {
+            component.component_name.clone()
+        } else {
+            format!(
+                "{}.{}",
+                self.get_trace(component.id_father),
+                component.component_name
+            )
+        }
+    }
+
+    pub fn generate_position_array(&self, dimensions: &[usize], mut index: usize) -> String {
+        let mut positions = String::new();
+        for dimension in dimensions.iter().rev() {
+            positions = format!("[{}]{}", index % dimension, positions);
+            index /= dimension;
+        }
+        positions
+    }
+
+    pub fn release_memory_component(&mut self, id_cmp: usize) {
+        self.component_memory[id_cmp].subcomponents = Vec::new();
+    }
+
+    pub fn get_witness(&self) -> Vec<FrElement> {
+        self.circuit
+            .witness_to_signal_list
+            .iter()
+            .map(|signal| self.signal_values[*signal].clone())
+            .collect()
+    }
+}
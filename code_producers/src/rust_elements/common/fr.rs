@@ -0,0 +1,213 @@
+// Arithmetic of the prime field used by the circuit, with the same semantics as the
+// circom compiler: elements are canonical values in [0, p) and comparisons are done
+// on the signed representative in (-p/2, p/2]
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, ToPrimitive, Zero};
+use std::sync::OnceLock;
+
+pub type FrElement = BigInt;
+
+const PRIME: &str = "{{prime}}";
+
+// Number of bytes used to store an element in the .dat and .wtns files
+pub const FR_N8: usize = {{n8}};
+
+pub fn fr_prime() -> &'static BigInt {
+    static PRIME_VALUE: OnceLock<BigInt> = OnceLock::new();
+    PRIME_VALUE.get_or_init(|| BigInt::parse_bytes(PRIME.as_bytes(), 10).unwrap())
+}
+
+fn fr_half() -> &'static BigInt {
+    static HALF_VALUE: OnceLock<BigInt> = OnceLock::new();
+    HALF_VALUE.get_or_init(|| fr_prime() >> 1)
+}
+
+fn fr_mask() -> &'static BigInt {
+    static MASK_VALUE: OnceLock<BigInt> = OnceLock::new();
+    MASK_VALUE.get_or_init(|| (BigInt::one() << fr_prime().bits()) - 1)
+}
+
+fn from_bool(value: bool) -> FrElement {
+    if value {
+        BigInt::one()
+    } else {
+        BigInt::zero()
+    }
+}
+
+fn modulus(a: &BigInt) -> FrElement {
+    let p = fr_prime();
+    ((a % p) + p) % p
+}
+
+fn signed_value(a: &FrElement) -> BigInt {
+    let a = modulus(a);
+    if &a > fr_half() {
+        a - fr_prime()
+    } else {
+        a
+    }
+}
+
+pub fn fr_add(a: &FrElement, b: &FrElement) -> FrElement {
+    modulus(&(a + b))
+}
+
+pub fn fr_sub(a: &FrElement, b: &FrElement) -> FrElement {
+    modulus(&(a - b))
+}
+
+pub fn fr_mul(a: &FrElement, b: &FrElement) -> FrElement {
+    modulus(&(a * b))
+}
+
+pub fn fr_neg(a: &FrElement) -> FrElement {
+    modulus(&-a)
+}
+
+pub fn fr_div(a: &FrElement, b: &FrElement) -> Result<FrElement, String> {
+    let p = fr_prime();
+    let b = modulus(b);
+    if b.is_zero() {
+        return Err("Division by zero".to_string());
+    }
+    // Fermat's little theorem, p is prime
+    let inverse = b.modpow(&(p - 2), p);
+    Ok(fr_mul(a, &inverse))
+}
+
+pub fn fr_pow(a: &FrElement, b: &FrElement) -> FrElement {
+    modulus(a).modpow(&modulus(b), fr_prime())
+}
+
+pub fn fr_idiv(a: &FrElement, b: &FrElement) -> Result<FrElement, String> {
+    let b = modulus(b);
+    if b.is_zero() {
+        return Err("Division by zero".to_string());
+    }
+    Ok(modulus(a) / b)
+}
+
+pub fn fr_mod(a: &FrElement, b: &FrElement) -> Result<FrElement, String> {
+    let b = modulus(b);
+    if b.is_zero() {
+        return Err("Division by zero".to_string());
+    }
+    Ok(modulus(a) % b)
+}
+
+fn shift_amount(b: &FrElement) -> Option<usize> {
+    // Any shift that does not fit in a usize clears every bit of the element
+    b.to_usize().filter(|shift| (*shift as u64) < fr_prime().bits())
+}
+
+pub fn fr_shl(a: &FrElement, b: &FrElement) -> FrElement {
+    let b = modulus(b);
+    if &b > fr_half() {
+        return fr_shr(a, &(fr_prime() - b));
+    }
+    match shift_amount(&b) {
+        Some(shift) => modulus(&((modulus(a) << shift) & fr_mask())),
+        None => BigInt::zero(),
+    }
+}
+
+pub fn fr_shr(a: &FrElement, b: &FrElement) -> FrElement {
+    let b = modulus(b);
+    if &b > fr_half() {
+        return fr_shl(a, &(fr_prime() - b));
+    }
+    match shift_amount(&b) {
+        Some(shift) => modulus(a) >> shift,
+        None => BigInt::zero(),
+    }
+}
+
+pub fn fr_lt(a: &FrElement, b: &FrElement) -> FrElement {
+    from_bool(signed_value(a) < signed_value(b))
+}
+
+pub fn fr_leq(a: &FrElement, b: &FrElement) -> FrElement {
+    from_bool(signed_value(a) <= signed_value(b))
+}
+
+pub fn fr_gt(a: &FrElement, b: &FrElement) -> FrElement {
+    from_bool(signed_value(a) > signed_value(b))
+}
+
+pub fn fr_geq(a: &FrElement, b: &FrElement) -> FrElement {
+    from_bool(signed_value(a) >= signed_value(b))
+}
+
+pub fn fr_eq(a: &FrElement, b: &FrElement) -> FrElement {
+    from_bool(modulus(a) == modulus(b))
+}
+
+// Equality of two arrays of n elements
+pub fn fr_eqn(a: &[FrElement], b: &[FrElement]) -> FrElement {
+    from_bool(a.iter().zip(b).all(|(x, y)| modulus(x) == modulus(y)))
+}
+
+pub fn fr_neq(a: &FrElement, b: &FrElement) -> FrElement {
+    from_bool(modulus(a) != modulus(b))
+}
+
+pub fn fr_land(a: &FrElement, b: &FrElement) -> FrElement {
+    from_bool(fr_is_true(a) && fr_is_true(b))
+}
+
+pub fn fr_lor(a: &FrElement, b: &FrElement) -> FrElement {
+    from_bool(fr_is_true(a) || fr_is_true(b))
+}
+
+pub fn fr_lnot(a: &FrElement) -> FrElement {
+    from_bool(!fr_is_true(a))
+}
+
+pub fn fr_band(a: &FrElement, b: &FrElement) -> FrElement {
+    modulus(&(modulus(a) & modulus(b)))
+}
+
+pub fn fr_bor(a: &FrElement, b: &FrElement) -> FrElement {
+    modulus(&(modulus(a) | modulus(b)))
+}
+
+pub fn fr_bxor(a: &FrElement, b: &FrElement) -> FrElement {
+    modulus(&(modulus(a) ^ modulus(b)))
+}
+
+// Complement of the 254 least significant bits, as done by the compiler
+pub fn fr_bnot(a: &FrElement) -> FrElement {
+    let mask = (BigInt::one() << 254) - 1;
+    modulus(&((modulus(a) & &mask) ^ mask))
+}
+
+pub fn fr_is_true(a: &FrElement) -> bool {
+    !modulus(a).is_zero()
+}
+
+pub fn fr_to_int(a: &FrElement) -> Result<usize, String> {
+    modulus(a)
+        .to_usize()
+        .ok_or_else(|| "Address out of range".to_string())
+}
+
+pub fn fr_element2str(a: &FrElement) -> String {
+    modulus(a).to_str_radix(10)
+}
+
+pub fn fr_str2element(value: &str, base: u32) -> Option<FrElement> {
+    BigInt::parse_bytes(value.as_bytes(), base).map(|n| modulus(&n))
+}
+
+// Little endian representation in n8 bytes
+pub fn fr_to_bytes_le(a: &FrElement, n8: usize) -> Vec<u8> {
+    let (sign, mut bytes) = modulus(a).to_bytes_le();
+    debug_assert!(sign != Sign::Minus);
+    bytes.resize(n8, 0);
+    bytes
+}
+
+pub fn fr_from_bytes_le(bytes: &[u8]) -> FrElement {
+    BigInt::from_bytes_le(Sign::Plus, bytes)
+}
@@ -0,0 +1,124 @@
+// Witness calculator for the {{run_name}} circuit, generated by circom {{version}}
+mod calcwit;
+mod circuit;
+pub mod fr;
+
+pub use calcwit::{CircomCalcWit, CircomCircuit};
+pub use fr::{fr_prime, FrElement};
+
+use calcwit::fnv1a;
+use std::io::Write;
+
+const CIRCUIT_DAT: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/{{run_name}}.dat"));
+
+pub fn load_circuit() -> Result<CircomCircuit, String> {
+    CircomCircuit::load(CIRCUIT_DAT)
+}
+
+// Mirrors json2FrElements in the C++ main.cpp: arrays are flattened and every leaf
+// must be a number or a string with an optional 0b, 0o or 0x prefix
+fn json_to_elements(value: &json::JsonValue, elements: &mut Vec<FrElement>) -> Result<(), String> {
+    use json::JsonValue;
+    match value {
+        JsonValue::Array(values) => {
+            for value in values {
+                json_to_elements(value, elements)?;
+            }
+            Ok(())
+        }
+        JsonValue::String(_) | JsonValue::Short(_) => {
+            let string = value.as_str().unwrap();
+            let (digits, base) = match string.get(0..2).unwrap_or("") {
+                "0b" | "0B" => (&string[2..], 2),
+                "0o" | "0O" => (&string[2..], 8),
+                "0x" | "0X" => (&string[2..], 16),
+                _ => (string, 10),
+            };
+            let is_valid = !digits.is_empty() && digits.chars().all(|c| c.is_digit(base));
+            match fr::fr_str2element(digits, base) {
+                Some(element) if is_valid => {
+                    elements.push(element);
+                    Ok(())
+                }
+                _ => Err(format!("Invalid number in JSON input: {}", string)),
+            }
+        }
+        JsonValue::Number(_) => {
+            let number = format!("{:.0}", value.as_f64().unwrap());
+            elements.push(fr::fr_str2element(&number, 10).unwrap());
+            Ok(())
+        }
+        _ => Err("Invalid JSON type".to_string()),
+    }
+}
+
+// Same steps as loadJson in the C++ main.cpp
+pub fn load_json(ctx: &mut CircomCalcWit, input: &str) -> Result<(), String> {
+    let parsed = json::parse(input).map_err(|err| format!("Invalid JSON input: {}", err))?;
+    if !parsed.is_object() {
+        return Err("The JSON input must be an object".to_string());
+    }
+    if parsed.is_empty() {
+        ctx.try_run_circuit()?;
+    }
+    for (name, value) in parsed.entries() {
+        let mut elements = Vec::new();
+        json_to_elements(value, &mut elements)
+            .map_err(|err| format!("Error loading signal {}: {}", name, err))?;
+        let h = fnv1a(name);
+        let signal_size = ctx
+            .get_input_signal_size(h)
+            .map_err(|err| format!("Error loading signal {}: {}", name, err))?;
+        if elements.len() < signal_size {
+            return Err(format!("Error loading signal {}: Not enough values", name));
+        }
+        if elements.len() > signal_size {
+            return Err(format!("Error loading signal {}: Too many values", name));
+        }
+        for (i, element) in elements.into_iter().enumerate() {
+            ctx.assign_input_signal(h, i, element)
+                .map_err(|err| format!("Error loading signal {}: {}", name, err))?;
+            ctx.try_run_circuit()?;
+        }
+    }
+    Ok(())
+}
+
+// Computes the witness given the content of an input.json file
+pub fn calculate_witness(circuit: &CircomCircuit, input: &str) -> Result<Vec<FrElement>, String> {
+    let mut ctx = CircomCalcWit::new(circuit);
+    load_json(&mut ctx, input)?;
+    let remaining = ctx.get_remaining_inputs_to_be_set();
+    if remaining != 0 {
+        let total = circuit::get_main_input_signal_no();
+        return Err(format!(
+            "Not all inputs have been set. Only {} out of {}",
+            total - remaining,
+            total
+        ));
+    }
+    Ok(ctx.get_witness())
+}
+
+// Same layout as writeBinWitness in the C++ main.cpp
+pub fn write_bin_witness<W: Write>(writer: &mut W, witness: &[FrElement]) -> std::io::Result<()> {
+    let n8 = fr::FR_N8;
+    writer.write_all(b"wtns")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&2u32.to_le_bytes())?;
+
+    writer.write_all(&1u32.to_le_bytes())?;
+    writer.write_all(&(8 + n8 as u64).to_le_bytes())?;
+    writer.write_all(&(n8 as u32).to_le_bytes())?;
+    let (_, mut prime) = fr_prime().to_bytes_le();
+    prime.resize(n8, 0);
+    writer.write_all(&prime)?;
+    writer.write_all(&(witness.len() as u32).to_le_bytes())?;
+
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&((n8 * witness.len()) as u64).to_le_bytes())?;
+    for element in witness {
+        writer.write_all(&fr::fr_to_bytes_le(element, n8))?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,27 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::process::exit;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        eprintln!("Usage: {} <input.json> <output.wtns>", args[0]);
+        exit(1);
+    }
+    let result = std::fs::read_to_string(&args[1])
+        .map_err(|err| format!("Could not read {}: {}", args[1], err))
+        .and_then(|input| {
+            let circuit = {{lib_name}}::load_circuit()?;
+            {{lib_name}}::calculate_witness(&circuit, &input)
+        })
+        .and_then(|witness| {
+            let file = File::create(&args[2])
+                .map_err(|err| format!("Could not create {}: {}", args[2], err))?;
+            let mut writer = BufWriter::new(file);
+            {{lib_name}}::write_bin_witness(&mut writer, &witness).map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        exit(1);
+    }
+}
@@ -0,0 +1,113 @@
+pub mod rust_code_generator;
+
+pub use crate::components::*;
+
+pub type RustInstruction = String;
+pub struct RustProducer {
+    pub main_header: String,
+    pub number_of_main_outputs: usize,
+    pub number_of_main_inputs: usize,
+    pub total_number_of_signals: usize,
+    pub number_of_components: usize,
+    pub size_32_bit: usize,
+    pub prime: String,
+    pub prime_str: String,
+    pub main_input_list: InputList,
+    pub witness_to_signal_list: SignalList,
+    pub io_map: TemplateInstanceIOMap,
+    pub template_instance_list: TemplateList,
+    pub field_tracking: Vec<String>,
+    pub major_version: usize,
+    pub minor_version: usize,
+    pub patch_version: usize,
+    string_table: Vec<String>,
+}
+
+impl Default for RustProducer {
+    fn default() -> Self {
+        RustProducer {
+            main_header: "Main_0".to_string(),
+            number_of_main_outputs: 0,
+            number_of_main_inputs: 0,
+            total_number_of_signals: 1,
+            number_of_components: 1,
+            size_32_bit: 8,
+            prime: "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+                .to_string(),
+            prime_str: "bn128".to_string(),
+            main_input_list: InputList::new(),
+            witness_to_signal_list: SignalList::new(),
+            io_map: TemplateInstanceIOMap::new(),
+            template_instance_list: TemplateList::new(),
+            field_tracking: Vec::new(),
+            major_version: 0,
+            minor_version: 0,
+            patch_version: 0,
+            string_table: Vec::new(),
+        }
+    }
+}
+
+impl RustProducer {
+    pub fn get_version(&self) -> usize {
+        self.major_version
+    }
+    pub fn get_minor_version(&self) -> usize {
+        self.minor_version
+    }
+    pub fn get_patch_version(&self) -> usize {
+        self.patch_version
+    }
+    pub fn get_main_header(&self) -> &str {
+        &self.main_header
+    }
+    pub fn get_prime(&self) -> &str {
+        &self.prime
+    }
+    pub fn get_number_of_main_outputs(&self) -> usize {
+        self.number_of_main_outputs
+    }
+    pub fn get_number_of_main_inputs(&self) -> usize {
+        self.number_of_main_inputs
+    }
+    pub fn get_main_input_list(&self) -> &InputList {
+        &self.main_input_list
+    }
+    // Same size as in the C producer so both .dat files share the layout
+    pub fn get_input_hash_map_entry_size(&self) -> usize {
+        std::cmp::max(
+            usize::pow(2, (self.main_input_list.len() as f32).log2().ceil() as u32),
+            256,
+        )
+    }
+    pub fn get_witness_to_signal_list(&self) -> &SignalList {
+        &self.witness_to_signal_list
+    }
+    pub fn get_total_number_of_signals(&self) -> usize {
+        self.total_number_of_signals
+    }
+    pub fn get_number_of_components(&self) -> usize {
+        self.number_of_components
+    }
+    pub fn get_io_map(&self) -> &TemplateInstanceIOMap {
+        &self.io_map
+    }
+    pub fn get_template_instance_list(&self) -> &TemplateList {
+        &self.template_instance_list
+    }
+    pub fn get_number_of_template_instances(&self) -> usize {
+        self.template_instance_list.len()
+    }
+    pub fn get_field_constant_list(&self) -> &Vec<String> {
+        &self.field_tracking
+    }
+    pub fn get_size_32_bit(&self) -> usize {
+        self.size_32_bit
+    }
+    pub fn get_string_table(&self) -> &Vec<String> {
+        &self.string_table
+    }
+    pub fn set_string_table(&mut self, string_table: Vec<String>) {
+        self.string_table = string_table;
+    }
+}
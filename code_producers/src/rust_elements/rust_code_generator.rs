@@ -0,0 +1,288 @@
+use super::*;
+use crate::c_elements::c_code_generator::{
+    generate_dat_from_hash_map, generate_dat_witness_to_signal_list, generate_hash_map,
+};
+use num_bigint_dig::{BigInt, Sign};
+use serde_json::json;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+// Names used in the generated code
+pub const CIRCOM_CALC_WIT: &str = "ctx";
+pub const CTX_INDEX: &str = "ctx_index";
+pub const MY_SIGNAL_START: &str = "my_signal_start";
+pub const MY_TEMPLATE_NAME: &str = "my_template_name";
+pub const MY_ID: &str = "my_id";
+pub const COMPONENT_FATHER: &str = "component_father";
+pub const L_INTERMEDIATE_COMPUTATIONS_STACK: &str = "expaux";
+pub const L_VAR_STACK: &str = "lvar";
+pub const L_VAR_FUNC_CALL_STACK: &str = "lvarcall";
+pub const FUNCTION_TABLE: &str = "FUNCTION_TABLE";
+pub const T_FR_ELEMENT: &str = "FrElement";
+pub const SUB_COMPONENT: &str = "sub_component";
+pub const AUX_DEST: &str = "aux_dest";
+
+pub fn declare_expaux(size: usize) -> RustInstruction {
+    format!(
+        "let mut {} = vec![{}::default(); {}];",
+        L_INTERMEDIATE_COMPUTATIONS_STACK, T_FR_ELEMENT, size
+    )
+}
+pub fn expaux(at: RustInstruction) -> RustInstruction {
+    format!("{}[{}]", L_INTERMEDIATE_COMPUTATIONS_STACK, at)
+}
+pub fn expaux_value(at: RustInstruction) -> RustInstruction {
+    format!("{}[{}..]", L_INTERMEDIATE_COMPUTATIONS_STACK, at)
+}
+pub fn declare_lvar(size: usize) -> RustInstruction {
+    format!(
+        "let mut {} = vec![{}::default(); {}];",
+        L_VAR_STACK, T_FR_ELEMENT, size
+    )
+}
+pub fn declare_lvar_func_call(size: usize) -> RustInstruction {
+    format!(
+        "let mut {} = vec![{}::default(); {}];",
+        L_VAR_FUNC_CALL_STACK, T_FR_ELEMENT, size
+    )
+}
+pub fn lvar(at: RustInstruction) -> RustInstruction {
+    format!("{}[{}..]", L_VAR_STACK, at)
+}
+pub fn signal_values(at: RustInstruction) -> RustInstruction {
+    format!(
+        "{}.signal_values[{} + {}..]",
+        CIRCOM_CALC_WIT, MY_SIGNAL_START, at
+    )
+}
+pub fn subcmp_signal_values(
+    sub_component: RustInstruction,
+    at: RustInstruction,
+) -> RustInstruction {
+    format!(
+        "{}.signal_values[{}.signal_start + {}..]",
+        CIRCOM_CALC_WIT,
+        component_memory(sub_component),
+        at
+    )
+}
+pub fn circuit_constants(at: RustInstruction) -> RustInstruction {
+    format!("{}.circuit.circuit_constants[{}..]", CIRCOM_CALC_WIT, at)
+}
+pub fn component_memory(at: RustInstruction) -> RustInstruction {
+    format!("{}.component_memory[{}]", CIRCOM_CALC_WIT, at)
+}
+pub fn my_subcomponents(at: RustInstruction) -> RustInstruction {
+    format!(
+        "{}.subcomponents[{}]",
+        component_memory(CTX_INDEX.to_string()),
+        at
+    )
+}
+pub fn io_signal_info(component: RustInstruction, code: usize) -> RustInstruction {
+    format!(
+        "{}.circuit.template_ins_id_2_io_signal_info[&{}.template_id][{}]",
+        CIRCOM_CALC_WIT,
+        component_memory(component),
+        code
+    )
+}
+
+// Field values are slices that start at the accessed position: the first element is
+// used by scalar operations and the first n elements when copying n values
+pub fn first_element(value: &str) -> RustInstruction {
+    format!("&{}[0]", value)
+}
+pub fn first_elements(value: &str, size: usize) -> RustInstruction {
+    format!("{}[..{}]", value, size)
+}
+
+pub fn build_callable(
+    header: String,
+    params: Vec<String>,
+    result: &str,
+    body: Vec<String>,
+) -> String {
+    format!(
+        "pub fn {}({}) -> {} {{\n{}}}\n",
+        header,
+        argument_list(params),
+        result,
+        merge_code(body)
+    )
+}
+
+pub fn argument_list(args: Vec<String>) -> String {
+    args.join(", ")
+}
+
+pub fn build_call(header: String, arguments: Vec<String>) -> String {
+    format!("{}({})", header, argument_list(arguments))
+}
+
+pub fn set_list(elems: &[usize]) -> String {
+    let elems: Vec<String> = elems.iter().map(|e| e.to_string()).collect();
+    format!("[{}]", elems.join(", "))
+}
+
+pub fn generate_my_trace() -> String {
+    format!("{}.get_trace({})", CIRCOM_CALC_WIT, MY_ID)
+}
+
+pub fn build_failed_assert_message(line: usize) -> String {
+    format!(
+        "format!(\"Failed assert in template/function {{}} line {}. Followed trace of components: {{}}\", {}, {})",
+        line,
+        MY_TEMPLATE_NAME,
+        generate_my_trace()
+    )
+}
+
+// Propagates the error of a fallible call adding where it happened
+pub fn build_located_error(call: String, line: usize) -> String {
+    format!(
+        "{}.map_err(|err| format!(\"{{}} in template/function {{}} line {}\", err, {}))?",
+        call, line, MY_TEMPLATE_NAME
+    )
+}
+
+pub fn build_conditional(cond: String, if_body: Vec<String>, else_body: Vec<String>) -> String {
+    let mut conditional = format!("if {} {{\n{}}}", cond, merge_code(if_body));
+    if !else_body.is_empty() {
+        conditional.push_str(&format!(" else {{\n{}}}", merge_code(else_body)));
+    }
+    conditional
+}
+
+pub fn merge_code(instructions: Vec<String>) -> String {
+    format!("{}\n", instructions.join("\n"))
+}
+
+//--------------- generate the .dat file ---------------
+
+// The constants are stored in canonical form, the Rust runtime does not use the
+// Montgomery representation of the C++ one
+pub fn generate_dat_constant_list(producer: &RustProducer, constant_list: &[String]) -> Vec<u8> {
+    let mut constant_list_data = vec![];
+    let p = producer.get_prime().parse::<BigInt>().unwrap();
+    let n8 = producer.get_size_32_bit() * 4;
+    for s in constant_list {
+        let mut n = s.parse::<BigInt>().unwrap();
+        if n < BigInt::from(0) {
+            n += &p;
+        }
+        let (snn, mut bnn) = n.to_bytes_le();
+        assert_ne!(snn, Sign::Minus);
+        assert!(bnn.len() <= n8);
+        bnn.resize(n8, 0);
+        constant_list_data.append(&mut bnn);
+    }
+    constant_list_data
+}
+
+pub fn generate_dat_io_signals_info(io_map: &TemplateInstanceIOMap) -> Vec<u8> {
+    let mut io_signals_info = vec![];
+    for t_ins in io_map.keys() {
+        io_signals_info.extend_from_slice(&(*t_ins as u32).to_le_bytes());
+    }
+    for l_io_def in io_map.values() {
+        io_signals_info.extend_from_slice(&(l_io_def.len() as u32).to_le_bytes());
+        for s in l_io_def {
+            let dims = if s.lengths.is_empty() {
+                0
+            } else {
+                s.lengths.len() - 1
+            };
+            io_signals_info.extend_from_slice(&(s.offset as u32).to_le_bytes());
+            io_signals_info.extend_from_slice(&(dims as u32).to_le_bytes());
+            for length in s.lengths.iter().skip(1) {
+                io_signals_info.extend_from_slice(&(*length as u32).to_le_bytes());
+            }
+        }
+    }
+    io_signals_info
+}
+
+// Same layout as the .dat file of the C++ witness generator
+pub fn generate_dat_file(dat_file: &mut dyn Write, producer: &RustProducer) -> std::io::Result<()> {
+    let map = generate_hash_map(
+        producer.get_main_input_list(),
+        producer.get_input_hash_map_entry_size(),
+    );
+    dat_file.write_all(&generate_dat_from_hash_map(&map))?;
+    let s = generate_dat_witness_to_signal_list(producer.get_witness_to_signal_list());
+    dat_file.write_all(&s)?;
+    let s = generate_dat_constant_list(producer, producer.get_field_constant_list());
+    dat_file.write_all(&s)?;
+    let s = generate_dat_io_signals_info(producer.get_io_map());
+    dat_file.write_all(&s)?;
+    dat_file.flush()?;
+    Ok(())
+}
+
+//--------------- generate the files of the crate ---------------
+
+// Name of the library target, Cargo replaces hyphens the same way
+pub fn lib_name(run_name: &str) -> String {
+    run_name.replace('-', "_")
+}
+
+fn write_rendered_file(
+    path: PathBuf,
+    template: &str,
+    data: &serde_json::Value,
+) -> std::io::Result<()> {
+    use std::io::BufWriter;
+    let code = handlebars::Handlebars::new()
+        .render_template(template, data)
+        .expect("must render");
+    let mut rust_file = BufWriter::new(File::create(path)?);
+    rust_file.write_all(code.as_bytes())?;
+    rust_file.flush()
+}
+
+fn template_data(producer: &RustProducer, run_name: &str) -> serde_json::Value {
+    json!({
+        "run_name": run_name,
+        "lib_name": lib_name(run_name),
+        "prime": producer.get_prime(),
+        "n8": producer.get_size_32_bit() * 4,
+        "version": format!(
+            "{}.{}.{}",
+            producer.get_version(),
+            producer.get_minor_version(),
+            producer.get_patch_version()
+        ),
+    })
+}
+
+pub fn generate_cargo_file(
+    rust_folder: &Path,
+    run_name: &str,
+    producer: &RustProducer,
+) -> std::io::Result<()> {
+    let file_path = rust_folder.join("Cargo.toml");
+    let data = template_data(producer, run_name);
+    write_rendered_file(file_path, include_str!("common/Cargo.toml"), &data)
+}
+
+// Every file of the src folder except circuit.rs
+pub fn generate_src_files(
+    rust_folder: &Path,
+    run_name: &str,
+    producer: &RustProducer,
+) -> std::io::Result<()> {
+    let src_folder = rust_folder.join("src");
+    let data = template_data(producer, run_name);
+    let files = [
+        ("lib.rs", include_str!("common/lib.rs")),
+        ("main.rs", include_str!("common/main.rs")),
+        ("fr.rs", include_str!("common/fr.rs")),
+        ("calcwit.rs", include_str!("common/calcwit.rs")),
+    ];
+    for (name, template) in files {
+        write_rendered_file(src_folder.join(name), template, &data)?;
+    }
+    Ok(())
+}
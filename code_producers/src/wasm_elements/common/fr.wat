@@ -0,0 +1,490 @@
+  ;; Arithmetic of the prime field {{prime_str}} with the same semantics as the circom
+  ;; compiler. Elements are stored in canonical form, in [0, p), as {{n32}} little endian
+  ;; limbs of 32 bits. The Montgomery representation is only used internally by the
+  ;; multiplication and the exponentiation.
+
+  ;; ---------- integers of {{n32}} limbs ----------
+
+  (func $int_copy (param $dst i32) (param $src i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (i32.store (i32.add (local.get $dst) (local.get $i))
+          (i32.load (i32.add (local.get $src) (local.get $i))))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next))))
+
+  (func $int_zero (param $dst i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (i32.store (i32.add (local.get $dst) (local.get $i)) (i32.const 0))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next))))
+
+  (func $int_is_zero (param $a i32) (result i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (if (i32.load (i32.add (local.get $a) (local.get $i)))
+          (then (return (i32.const 0))))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next)))
+    (i32.const 1))
+
+  ;; returns -1, 0 or 1 if a is lower, equal or greater than b
+  (func $int_cmp (param $a i32) (param $b i32) (result i32)
+    (local $i i32) (local $x i32) (local $y i32)
+    (local.set $i (i32.const {{n8}}))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eqz (local.get $i)))
+        (local.set $i (i32.sub (local.get $i) (i32.const 4)))
+        (local.set $x (i32.load (i32.add (local.get $a) (local.get $i))))
+        (local.set $y (i32.load (i32.add (local.get $b) (local.get $i))))
+        (if (i32.lt_u (local.get $x) (local.get $y))
+          (then (return (i32.const -1))))
+        (if (i32.gt_u (local.get $x) (local.get $y))
+          (then (return (i32.const 1))))
+        (br $next)))
+    (i32.const 0))
+
+  ;; returns the carry
+  (func $int_add (param $dst i32) (param $a i32) (param $b i32) (result i32)
+    (local $i i32) (local $c i64)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (local.set $c (i64.add (local.get $c)
+          (i64.add (i64.load32_u (i32.add (local.get $a) (local.get $i)))
+                   (i64.load32_u (i32.add (local.get $b) (local.get $i))))))
+        (i64.store32 (i32.add (local.get $dst) (local.get $i)) (local.get $c))
+        (local.set $c (i64.shr_u (local.get $c) (i64.const 32)))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next)))
+    (i32.wrap_i64 (local.get $c)))
+
+  ;; returns the borrow
+  (func $int_sub (param $dst i32) (param $a i32) (param $b i32) (result i32)
+    (local $i i32) (local $c i64) (local $borrow i64)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (local.set $c (i64.sub
+          (i64.sub (i64.load32_u (i32.add (local.get $a) (local.get $i)))
+                   (i64.load32_u (i32.add (local.get $b) (local.get $i))))
+          (local.get $borrow)))
+        (i64.store32 (i32.add (local.get $dst) (local.get $i)) (local.get $c))
+        (local.set $borrow (i64.shr_u (local.get $c) (i64.const 63)))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next)))
+    (i32.wrap_i64 (local.get $borrow)))
+
+  (func $int_and (param $dst i32) (param $a i32) (param $b i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (i32.store (i32.add (local.get $dst) (local.get $i))
+          (i32.and (i32.load (i32.add (local.get $a) (local.get $i)))
+                   (i32.load (i32.add (local.get $b) (local.get $i)))))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next))))
+
+  (func $int_or (param $dst i32) (param $a i32) (param $b i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (i32.store (i32.add (local.get $dst) (local.get $i))
+          (i32.or (i32.load (i32.add (local.get $a) (local.get $i)))
+                  (i32.load (i32.add (local.get $b) (local.get $i)))))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next))))
+
+  (func $int_xor (param $dst i32) (param $a i32) (param $b i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (i32.store (i32.add (local.get $dst) (local.get $i))
+          (i32.xor (i32.load (i32.add (local.get $a) (local.get $i)))
+                   (i32.load (i32.add (local.get $b) (local.get $i)))))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next))))
+
+  ;; limb j of a, zero when j is out of range
+  (func $int_limb (param $a i32) (param $j i32) (result i64)
+    (if (i32.ge_u (local.get $j) (i32.const {{n32}}))
+      (then (return (i64.const 0))))
+    (i64.load32_u (i32.add (local.get $a) (i32.shl (local.get $j) (i32.const 2)))))
+
+  ;; n must be lower than {{n32_bits}}, dst can be a
+  (func $int_shl (param $dst i32) (param $a i32) (param $n i32)
+    (local $i i32) (local $k i32) (local $s i64)
+    (local.set $k (i32.shr_u (local.get $n) (i32.const 5)))
+    (local.set $s (i64.extend_i32_u (i32.and (local.get $n) (i32.const 31))))
+    (local.set $i (i32.const {{n32}}))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eqz (local.get $i)))
+        (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+        (i64.store32 (i32.add (local.get $dst) (i32.shl (local.get $i) (i32.const 2)))
+          (i64.shr_u
+            (i64.or
+              (i64.shl (call $int_limb (local.get $a) (i32.sub (local.get $i) (local.get $k)))
+                       (i64.const 32))
+              (call $int_limb (local.get $a)
+                (i32.sub (i32.sub (local.get $i) (local.get $k)) (i32.const 1))))
+            (i64.sub (i64.const 32) (local.get $s))))
+        (br $next))))
+
+  ;; n must be lower than {{n32_bits}}, dst can be a
+  (func $int_shr (param $dst i32) (param $a i32) (param $n i32)
+    (local $i i32) (local $k i32) (local $s i64)
+    (local.set $k (i32.shr_u (local.get $n) (i32.const 5)))
+    (local.set $s (i64.extend_i32_u (i32.and (local.get $n) (i32.const 31))))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n32}})))
+        (i64.store32 (i32.add (local.get $dst) (i32.shl (local.get $i) (i32.const 2)))
+          (i64.shr_u
+            (i64.or
+              (i64.shl (call $int_limb (local.get $a)
+                         (i32.add (i32.add (local.get $i) (local.get $k)) (i32.const 1)))
+                       (i64.const 32))
+              (call $int_limb (local.get $a) (i32.add (local.get $i) (local.get $k))))
+            (local.get $s)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next))))
+
+  (func $int_bit (param $a i32) (param $n i32) (result i32)
+    (i32.and
+      (i32.shr_u
+        (i32.load (i32.add (local.get $a) (i32.shl (i32.shr_u (local.get $n) (i32.const 5)) (i32.const 2))))
+        (i32.and (local.get $n) (i32.const 31)))
+      (i32.const 1)))
+
+  ;; binary long division, leaves the quotient and the remainder of a / b in
+  ;; div_q and div_r, b must not be zero
+  (func $int_divmod (param $a i32) (param $b i32)
+    (local $n i32) (local $top i32) (local $q i32)
+    (call $int_zero (i32.const {{div_q}}))
+    (call $int_zero (i32.const {{div_r}}))
+    (local.set $n (i32.const {{n32_bits}}))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (local.set $top (i32.shr_u (i32.load (i32.const {{div_r_top}})) (i32.const 31)))
+        (call $int_shl (i32.const {{div_r}}) (i32.const {{div_r}}) (i32.const 1))
+        (i32.store (i32.const {{div_r}})
+          (i32.or (i32.load (i32.const {{div_r}})) (call $int_bit (local.get $a) (local.get $n))))
+        (if (i32.or (local.get $top)
+                    (i32.ge_s (call $int_cmp (i32.const {{div_r}}) (local.get $b)) (i32.const 0)))
+          (then
+            (drop (call $int_sub (i32.const {{div_r}}) (i32.const {{div_r}}) (local.get $b)))
+            (local.set $q (i32.add (i32.const {{div_q}})
+              (i32.shl (i32.shr_u (local.get $n) (i32.const 5)) (i32.const 2))))
+            (i32.store (local.get $q)
+              (i32.or (i32.load (local.get $q))
+                      (i32.shl (i32.const 1) (i32.and (local.get $n) (i32.const 31)))))))
+        (br $next))))
+
+  ;; ---------- montgomery multiplication ----------
+
+  ;; dst = a * b / R mod p, with R = 2^{{n32_bits}}
+  (func $Fr_montmul (param $dst i32) (param $a i32) (param $b i32)
+    (local $i i32) (local $j i32) (local $c i64) (local $m i64) (local $bi i64) (local $t i32)
+    (call $int_zero (i32.const {{mont_t}}))
+    (i64.store (i32.const {{mont_t_top}}) (i64.const 0))
+    (block $done_i
+      (loop $next_i
+        (br_if $done_i (i32.eq (local.get $i) (i32.const {{n8}})))
+        (local.set $bi (i64.load32_u (i32.add (local.get $b) (local.get $i))))
+        ;; t += a * b[i]
+        (local.set $c (i64.const 0))
+        (local.set $j (i32.const 0))
+        (block $done_j
+          (loop $next_j
+            (br_if $done_j (i32.eq (local.get $j) (i32.const {{n8}})))
+            (local.set $t (i32.add (i32.const {{mont_t}}) (local.get $j)))
+            (local.set $c (i64.add
+              (i64.add (i64.load32_u (local.get $t)) (local.get $c))
+              (i64.mul (i64.load32_u (i32.add (local.get $a) (local.get $j))) (local.get $bi))))
+            (i64.store32 (local.get $t) (local.get $c))
+            (local.set $c (i64.shr_u (local.get $c) (i64.const 32)))
+            (local.set $j (i32.add (local.get $j) (i32.const 4)))
+            (br $next_j)))
+        (local.set $c (i64.add (i64.load32_u (i32.const {{mont_t_top}})) (local.get $c)))
+        (i64.store32 (i32.const {{mont_t_top}}) (local.get $c))
+        (i64.store32 offset=4 (i32.const {{mont_t_top}}) (i64.shr_u (local.get $c) (i64.const 32)))
+        ;; t = (t + m * p) / 2^32
+        (local.set $m (i64.and
+          (i64.mul (i64.load32_u (i32.const {{mont_t}})) (i64.const {{np}}))
+          (i64.const 0xffffffff)))
+        (local.set $c (i64.shr_u
+          (i64.add (i64.load32_u (i32.const {{mont_t}}))
+                   (i64.mul (local.get $m) (i64.load32_u (i32.const {{p}}))))
+          (i64.const 32)))
+        (local.set $j (i32.const 4))
+        (block $done_j
+          (loop $next_j
+            (br_if $done_j (i32.eq (local.get $j) (i32.const {{n8}})))
+            (local.set $t (i32.add (i32.const {{mont_t}}) (local.get $j)))
+            (local.set $c (i64.add
+              (i64.add (i64.load32_u (local.get $t)) (local.get $c))
+              (i64.mul (local.get $m) (i64.load32_u (i32.add (i32.const {{p}}) (local.get $j))))))
+            (i64.store32 (i32.sub (local.get $t) (i32.const 4)) (local.get $c))
+            (local.set $c (i64.shr_u (local.get $c) (i64.const 32)))
+            (local.set $j (i32.add (local.get $j) (i32.const 4)))
+            (br $next_j)))
+        (local.set $c (i64.add (i64.load32_u (i32.const {{mont_t_top}})) (local.get $c)))
+        (i64.store32 (i32.const {{mont_t_last}}) (local.get $c))
+        (i64.store32 (i32.const {{mont_t_top}})
+          (i64.add (i64.load32_u offset=4 (i32.const {{mont_t_top}}))
+                   (i64.shr_u (local.get $c) (i64.const 32))))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next_i)))
+    ;; the result is lower than 2p
+    (if (i32.or (i32.load (i32.const {{mont_t_top}}))
+                (i32.ge_s (call $int_cmp (i32.const {{mont_t}}) (i32.const {{p}})) (i32.const 0)))
+      (then (drop (call $int_sub (i32.const {{mont_t}}) (i32.const {{mont_t}}) (i32.const {{p}})))))
+    (call $int_copy (local.get $dst) (i32.const {{mont_t}})))
+
+  ;; ---------- field operations ----------
+
+  (func $Fr_error (param $message i32)
+    (global.set $message_ptr (local.get $message))
+    (call $printErrorMessage)
+    (call $exceptionHandler (i32.const 7)))
+
+  (func $Fr_copy (param $dst i32) (param $src i32)
+    (call $int_copy (local.get $dst) (local.get $src)))
+
+  ;; copies n elements, the source and the destination can overlap
+  (func $Fr_copyn (param $dst i32) (param $src i32) (param $n i32)
+    (local $i i32) (local $size i32)
+    (local.set $size (i32.mul (local.get $n) (i32.const {{n8}})))
+    (if (i32.le_u (local.get $dst) (local.get $src))
+      (then
+        (block $done
+          (loop $next
+            (br_if $done (i32.eq (local.get $i) (local.get $size)))
+            (i32.store (i32.add (local.get $dst) (local.get $i))
+              (i32.load (i32.add (local.get $src) (local.get $i))))
+            (local.set $i (i32.add (local.get $i) (i32.const 4)))
+            (br $next))))
+      (else
+        (local.set $i (local.get $size))
+        (block $done
+          (loop $next
+            (br_if $done (i32.eqz (local.get $i)))
+            (local.set $i (i32.sub (local.get $i) (i32.const 4)))
+            (i32.store (i32.add (local.get $dst) (local.get $i))
+              (i32.load (i32.add (local.get $src) (local.get $i))))
+            (br $next))))))
+
+  ;; reduces a value lower than 2^{{n32_bits}}
+  (func $Fr_reduce (param $a i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.lt_s (call $int_cmp (local.get $a) (i32.const {{p}})) (i32.const 0)))
+        (drop (call $int_sub (local.get $a) (local.get $a) (i32.const {{p}})))
+        (br $next))))
+
+  (func $Fr_isTrue (param $a i32) (result i32)
+    (i32.eqz (call $int_is_zero (local.get $a))))
+
+  (func $Fr_toInt (param $a i32) (result i32)
+    (local $i i32)
+    (local.set $i (i32.const 4))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (if (i32.load (i32.add (local.get $a) (local.get $i)))
+          (then (call $Fr_error (i32.const {{address_out_of_range_str}}))))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next)))
+    (i32.load (local.get $a)))
+
+  (func $Fr_setBool (param $dst i32) (param $value i32)
+    (call $int_zero (local.get $dst))
+    (i32.store (local.get $dst) (local.get $value)))
+
+  (func $Fr_add (param $dst i32) (param $a i32) (param $b i32)
+    (if (i32.or (call $int_add (local.get $dst) (local.get $a) (local.get $b))
+                (i32.ge_s (call $int_cmp (local.get $dst) (i32.const {{p}})) (i32.const 0)))
+      (then (drop (call $int_sub (local.get $dst) (local.get $dst) (i32.const {{p}}))))))
+
+  (func $Fr_sub (param $dst i32) (param $a i32) (param $b i32)
+    (if (call $int_sub (local.get $dst) (local.get $a) (local.get $b))
+      (then (drop (call $int_add (local.get $dst) (local.get $dst) (i32.const {{p}}))))))
+
+  (func $Fr_neg (param $dst i32) (param $a i32)
+    (if (call $int_is_zero (local.get $a))
+      (then (call $int_zero (local.get $dst)))
+      (else (drop (call $int_sub (local.get $dst) (i32.const {{p}}) (local.get $a))))))
+
+  (func $Fr_mul (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_montmul (i32.const {{mul_tmp}}) (local.get $a) (local.get $b))
+    (call $Fr_montmul (local.get $dst) (i32.const {{mul_tmp}}) (i32.const {{r2}})))
+
+  (func $Fr_pow (param $dst i32) (param $a i32) (param $b i32)
+    (local $n i32)
+    (call $int_copy (i32.const {{pow_exp}}) (local.get $b))
+    (call $Fr_montmul (i32.const {{pow_base}}) (local.get $a) (i32.const {{r2}}))
+    (call $int_copy (i32.const {{pow_acc}}) (i32.const {{mont_one}}))
+    (local.set $n (i32.const {{n32_bits}}))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (call $Fr_montmul (i32.const {{pow_acc}}) (i32.const {{pow_acc}}) (i32.const {{pow_acc}}))
+        (if (call $int_bit (i32.const {{pow_exp}}) (local.get $n))
+          (then
+            (call $Fr_montmul (i32.const {{pow_acc}}) (i32.const {{pow_acc}}) (i32.const {{pow_base}}))))
+        (br $next)))
+    (call $Fr_montmul (local.get $dst) (i32.const {{pow_acc}}) (i32.const {{one}})))
+
+  ;; Fermat's little theorem, p is prime
+  (func $Fr_div (param $dst i32) (param $a i32) (param $b i32)
+    (if (call $int_is_zero (local.get $b))
+      (then (call $Fr_error (i32.const {{division_by_zero_str}}))))
+    (call $Fr_pow (i32.const {{inv_tmp}}) (local.get $b) (i32.const {{p_minus_2}}))
+    (call $Fr_mul (local.get $dst) (local.get $a) (i32.const {{inv_tmp}})))
+
+  (func $Fr_idiv (param $dst i32) (param $a i32) (param $b i32)
+    (if (call $int_is_zero (local.get $b))
+      (then (call $Fr_error (i32.const {{division_by_zero_str}}))))
+    (call $int_divmod (local.get $a) (local.get $b))
+    (call $int_copy (local.get $dst) (i32.const {{div_q}})))
+
+  (func $Fr_mod (param $dst i32) (param $a i32) (param $b i32)
+    (if (call $int_is_zero (local.get $b))
+      (then (call $Fr_error (i32.const {{division_by_zero_str}}))))
+    (call $int_divmod (local.get $a) (local.get $b))
+    (call $int_copy (local.get $dst) (i32.const {{div_r}})))
+
+  ;; comparisons use the signed representative in (-p/2, p/2]
+  (func $Fr_isNegative (param $a i32) (result i32)
+    (i32.gt_s (call $int_cmp (local.get $a) (i32.const {{half}})) (i32.const 0)))
+
+  (func $Fr_signedLt (param $a i32) (param $b i32) (result i32)
+    (local $a_neg i32)
+    (local.set $a_neg (call $Fr_isNegative (local.get $a)))
+    (if (i32.ne (local.get $a_neg) (call $Fr_isNegative (local.get $b)))
+      (then (return (local.get $a_neg))))
+    (i32.lt_s (call $int_cmp (local.get $a) (local.get $b)) (i32.const 0)))
+
+  (func $Fr_lt (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_setBool (local.get $dst) (call $Fr_signedLt (local.get $a) (local.get $b))))
+
+  (func $Fr_gt (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_setBool (local.get $dst) (call $Fr_signedLt (local.get $b) (local.get $a))))
+
+  (func $Fr_leq (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_setBool (local.get $dst)
+      (i32.eqz (call $Fr_signedLt (local.get $b) (local.get $a)))))
+
+  (func $Fr_geq (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_setBool (local.get $dst)
+      (i32.eqz (call $Fr_signedLt (local.get $a) (local.get $b)))))
+
+  (func $Fr_eq (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_setBool (local.get $dst)
+      (i32.eqz (call $int_cmp (local.get $a) (local.get $b)))))
+
+  (func $Fr_neq (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_setBool (local.get $dst)
+      (i32.ne (call $int_cmp (local.get $a) (local.get $b)) (i32.const 0))))
+
+  ;; compares n consecutive elements
+  (func $Fr_eqn (param $dst i32) (param $a i32) (param $b i32) (param $n i32)
+    (local $i i32) (local $result i32)
+    (local.set $result (i32.const 1))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (local.get $n)))
+        (if (call $int_cmp
+              (i32.add (local.get $a) (i32.mul (local.get $i) (i32.const {{n8}})))
+              (i32.add (local.get $b) (i32.mul (local.get $i) (i32.const {{n8}}))))
+          (then (local.set $result (i32.const 0))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (call $Fr_setBool (local.get $dst) (local.get $result)))
+
+  (func $Fr_land (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_setBool (local.get $dst)
+      (i32.and (call $Fr_isTrue (local.get $a)) (call $Fr_isTrue (local.get $b)))))
+
+  (func $Fr_lor (param $dst i32) (param $a i32) (param $b i32)
+    (call $Fr_setBool (local.get $dst)
+      (i32.or (call $Fr_isTrue (local.get $a)) (call $Fr_isTrue (local.get $b)))))
+
+  (func $Fr_lnot (param $dst i32) (param $a i32)
+    (call $Fr_setBool (local.get $dst) (call $int_is_zero (local.get $a))))
+
+  (func $Fr_band (param $dst i32) (param $a i32) (param $b i32)
+    (call $int_and (local.get $dst) (local.get $a) (local.get $b)))
+
+  (func $Fr_bor (param $dst i32) (param $a i32) (param $b i32)
+    (call $int_or (local.get $dst) (local.get $a) (local.get $b))
+    (call $Fr_reduce (local.get $dst)))
+
+  (func $Fr_bxor (param $dst i32) (param $a i32) (param $b i32)
+    (call $int_xor (local.get $dst) (local.get $a) (local.get $b))
+    (call $Fr_reduce (local.get $dst)))
+
+  ;; complement of the 254 least significant bits, as done by the compiler
+  (func $Fr_bnot (param $dst i32) (param $a i32)
+    (call $int_and (i32.const {{bnot_tmp}}) (local.get $a) (i32.const {{mask254}}))
+    (call $Fr_sub (local.get $dst) (i32.const {{c254}}) (i32.const {{bnot_tmp}})))
+
+  ;; returns the shift or {{bits}} if b is at least the number of bits of the prime
+  (func $Fr_shiftAmount (param $b i32) (result i32)
+    (local $i i32)
+    (local.set $i (i32.const 4))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $i) (i32.const {{n8}})))
+        (if (i32.load (i32.add (local.get $b) (local.get $i)))
+          (then (return (i32.const {{bits}}))))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next)))
+    (if (i32.ge_u (i32.load (local.get $b)) (i32.const {{bits}}))
+      (then (return (i32.const {{bits}}))))
+    (i32.load (local.get $b)))
+
+  (func $Fr_shl (param $dst i32) (param $a i32) (param $b i32)
+    (local $n i32)
+    (if (call $Fr_isNegative (local.get $b))
+      (then
+        (drop (call $int_sub (i32.const {{shift_amount}}) (i32.const {{p}}) (local.get $b)))
+        (call $Fr_shr (local.get $dst) (local.get $a) (i32.const {{shift_amount}}))
+        (return)))
+    (local.set $n (call $Fr_shiftAmount (local.get $b)))
+    (if (i32.eq (local.get $n) (i32.const {{bits}}))
+      (then
+        (call $int_zero (local.get $dst))
+        (return)))
+    (call $int_shl (local.get $dst) (local.get $a) (local.get $n))
+    (call $int_and (local.get $dst) (local.get $dst) (i32.const {{mask}}))
+    (call $Fr_reduce (local.get $dst)))
+
+  (func $Fr_shr (param $dst i32) (param $a i32) (param $b i32)
+    (local $n i32)
+    (if (call $Fr_isNegative (local.get $b))
+      (then
+        (drop (call $int_sub (i32.const {{shift_amount}}) (i32.const {{p}}) (local.get $b)))
+        (call $Fr_shl (local.get $dst) (local.get $a) (i32.const {{shift_amount}}))
+        (return)))
+    (local.set $n (call $Fr_shiftAmount (local.get $b)))
+    (if (i32.eq (local.get $n) (i32.const {{bits}}))
+      (then
+        (call $int_zero (local.get $dst))
+        (return)))
+    (call $int_shr (local.get $dst) (local.get $a) (local.get $n)))
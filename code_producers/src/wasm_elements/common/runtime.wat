@@ -0,0 +1,221 @@
+  ;; ---------- memory management ----------
+
+  (func $zeroMemory (param $address i32) (param $size i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $size)))
+        (i32.store (i32.add (local.get $address) (local.get $i)) (i32.const 0))
+        (local.set $i (i32.add (local.get $i) (i32.const 4)))
+        (br $next))))
+
+  ;; reserves size bytes on top of the stack, growing the memory if needed, and
+  ;; returns the address of the reserved block
+  (func $reserveStack (param $size i32) (result i32)
+    (local $address i32) (local $end i32)
+    (local.set $address (global.get $sp))
+    (local.set $end (i32.add (local.get $address) (local.get $size)))
+    (if (i32.gt_u (local.get $end) (i32.shl (memory.size) (i32.const 16)))
+      (then
+        (if (i32.eq
+              (memory.grow (i32.add
+                (i32.shr_u (i32.sub (local.get $end) (i32.shl (memory.size) (i32.const 16)))
+                           (i32.const 16))
+                (i32.const 1)))
+              (i32.const -1))
+          (then (call $exceptionHandler (i32.const 5))))))
+    (global.set $sp (local.get $end))
+    (local.get $address))
+
+  ;; reserves the list of n subcomponents of a component
+  (func $reserveSubcomponents (param $n i32) (result i32)
+    (local $address i32)
+    (local.set $address (global.get $subcomponents_free))
+    (global.set $subcomponents_free (i32.add (local.get $address) (i32.shl (local.get $n) (i32.const 2))))
+    (if (i32.gt_u (global.get $subcomponents_free) (i32.const {{stack}}))
+      (then (call $exceptionHandler (i32.const 5))))
+    (local.get $address))
+
+  (func $componentAddress (param $cmp i32) (result i32)
+    (i32.add (i32.const {{component_memory}}) (i32.mul (local.get $cmp) (i32.const {{component_size}}))))
+
+  ;; address of the definitions of the signals of the template in the io map
+  (func $ioDefinitions (param $template_id i32) (result i32)
+    (i32.load (i32.add (i32.const {{io_map}}) (i32.shl (local.get $template_id) (i32.const 2)))))
+
+  ;; ---------- messages ----------
+
+  (func $appendString (param $string i32)
+    (local $c i32)
+    (block $done
+      (loop $next
+        (local.set $c (i32.load8_u (local.get $string)))
+        (br_if $done (i32.eqz (local.get $c)))
+        (br_if $done (i32.ge_u (global.get $message_end) (i32.const {{message_buffer_last}})))
+        (i32.store8 (global.get $message_end) (local.get $c))
+        (global.set $message_end (i32.add (global.get $message_end) (i32.const 1)))
+        (local.set $string (i32.add (local.get $string) (i32.const 1)))
+        (br $next)))
+    (i32.store8 (global.get $message_end) (i32.const 0)))
+
+  (func $appendNumber (param $value i32)
+    (local $start i32) (local $end i32) (local $c i32)
+    (local.set $start (global.get $message_end))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (global.get $message_end) (i32.const {{message_buffer_last}})))
+        (i32.store8 (global.get $message_end)
+          (i32.add (i32.const 48) (i32.rem_u (local.get $value) (i32.const 10))))
+        (global.set $message_end (i32.add (global.get $message_end) (i32.const 1)))
+        (local.set $value (i32.div_u (local.get $value) (i32.const 10)))
+        (br_if $next (local.get $value))))
+    (i32.store8 (global.get $message_end) (i32.const 0))
+    ;; the digits were written from the least significant one
+    (local.set $end (i32.sub (global.get $message_end) (i32.const 1)))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $start) (local.get $end)))
+        (local.set $c (i32.load8_u (local.get $start)))
+        (i32.store8 (local.get $start) (i32.load8_u (local.get $end)))
+        (i32.store8 (local.get $end) (local.get $c))
+        (local.set $start (i32.add (local.get $start) (i32.const 1)))
+        (local.set $end (i32.sub (local.get $end) (i32.const 1)))
+        (br $next))))
+
+  ;; writes "<message> line: <line>" in the message buffer
+  (func $buildBufferMessage (param $message_id i32) (param $line i32)
+    (global.set $message_end (i32.const {{message_buffer}}))
+    (call $appendString
+      (i32.load (i32.add (i32.const {{message_list}}) (i32.shl (local.get $message_id) (i32.const 2)))))
+    (call $appendString (i32.const {{line_str}}))
+    (call $appendNumber (local.get $line))
+    (global.set $message_ptr (i32.const {{message_buffer}})))
+
+  (func $assertFailed (param $message_id i32) (param $line i32)
+    (call $buildBufferMessage (local.get $message_id) (local.get $line))
+    (call $printErrorMessage)
+    (call $exceptionHandler (i32.const 4)))
+
+  (func $logString (param $string_id i32)
+    (global.set $message_ptr
+      (i32.load (i32.add (i32.const {{string_list}}) (i32.shl (local.get $string_id) (i32.const 2)))))
+    (call $writeBufferMessage))
+
+  (func $logValue (param $value i32)
+    (call $int_copy (i32.const {{shared_rw_memory}}) (local.get $value))
+    (call $showSharedRWMemory))
+
+  (func $logEnd
+    (global.set $message_ptr (i32.const {{end_str}}))
+    (call $writeBufferMessage))
+
+  ;; ---------- interface of the witness calculator ----------
+
+  (func (export "getVersion") (result i32)
+    (i32.const {{major_version}}))
+
+  (func (export "getMinorVersion") (result i32)
+    (i32.const {{minor_version}}))
+
+  (func (export "getPatchVersion") (result i32)
+    (i32.const {{patch_version}}))
+
+  (func (export "getSharedRWMemoryStart") (result i32)
+    (i32.const {{shared_rw_memory}}))
+
+  (func (export "readSharedRWMemory") (param $i i32) (result i32)
+    (i32.load (i32.add (i32.const {{shared_rw_memory}}) (i32.shl (local.get $i) (i32.const 2)))))
+
+  (func (export "writeSharedRWMemory") (param $i i32) (param $value i32)
+    (i32.store (i32.add (i32.const {{shared_rw_memory}}) (i32.shl (local.get $i) (i32.const 2)))
+      (local.get $value)))
+
+  (func (export "getFieldNumLen32") (result i32)
+    (i32.const {{n32}}))
+
+  (func (export "getRawPrime")
+    (call $int_copy (i32.const {{shared_rw_memory}}) (i32.const {{p}})))
+
+  (func (export "getInputSize") (result i32)
+    (i32.const {{number_of_main_inputs}}))
+
+  (func (export "getWitnessSize") (result i32)
+    (i32.const {{witness_size}}))
+
+  (func (export "getWitness") (param $i i32)
+    (call $int_copy (i32.const {{shared_rw_memory}})
+      (i32.add (i32.const {{signal_memory}})
+        (i32.mul
+          (i32.load (i32.add (i32.const {{witness_signal_id_list}}) (i32.shl (local.get $i) (i32.const 2))))
+          (i32.const {{n8}})))))
+
+  (func (export "getMessageChar") (result i32)
+    (local $c i32)
+    (local.set $c (i32.load8_u (global.get $message_ptr)))
+    (if (local.get $c)
+      (then (global.set $message_ptr (i32.add (global.get $message_ptr) (i32.const 1)))))
+    (local.get $c))
+
+  (func (export "init") (param $sanity_check i32)
+    (global.set $sanity_check (local.get $sanity_check))
+    (global.set $sp (i32.const {{stack}}))
+    (global.set $subcomponents_free (i32.const {{subcomponents_memory}}))
+    (global.set $input_counter (i32.const {{number_of_main_inputs}}))
+    (global.set $message_end (i32.const {{message_buffer}}))
+    (i32.store8 (i32.const {{message_buffer}}) (i32.const 0))
+    (global.set $message_ptr (i32.const {{message_buffer}}))
+    (call $zeroMemory (i32.const {{input_assigned}}) (i32.sub (i32.const {{signal_memory}}) (i32.const {{input_assigned}})))
+    (call $zeroMemory (i32.const {{signal_memory}}) (i32.const {{signal_memory_size}}))
+    ;; the first signal is the constant one
+    (call $int_copy (i32.const {{signal_memory}}) (i32.const {{one}}))
+    ;; a main component without inputs is run when it is created
+    (call ${{main_header}}_create (i32.const 1) (i32.const 0) (i32.const 0)))
+
+  ;; returns the address of the entry of the input hash map of the signal, 0 if it
+  ;; is not an input of the main component
+  (func $getInputSignalEntry (param $hmsb i32) (param $hlsb i32) (result i32)
+    (local $pos i32) (local $entry i32) (local $tries i32)
+    (local.set $pos (i32.and (local.get $hlsb) (i32.const {{input_hash_map_mask}})))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $tries) (i32.const {{input_hash_map_size}})))
+        (local.set $entry (i32.add (i32.const {{input_hash_map}}) (i32.shl (local.get $pos) (i32.const 4))))
+        ;; empty entries have signal id 0
+        (br_if $done (i32.eqz (i32.load offset=8 (local.get $entry))))
+        (if (i32.and (i32.eq (i32.load (local.get $entry)) (local.get $hlsb))
+                     (i32.eq (i32.load offset=4 (local.get $entry)) (local.get $hmsb)))
+          (then (return (local.get $entry))))
+        (local.set $pos (i32.and (i32.add (local.get $pos) (i32.const 1)) (i32.const {{input_hash_map_mask}})))
+        (local.set $tries (i32.add (local.get $tries) (i32.const 1)))
+        (br $next)))
+    (i32.const 0))
+
+  (func (export "getInputSignalSize") (param $hmsb i32) (param $hlsb i32) (result i32)
+    (local $entry i32)
+    (local.set $entry (call $getInputSignalEntry (local.get $hmsb) (local.get $hlsb)))
+    (if (i32.eqz (local.get $entry))
+      (then (return (i32.const -1))))
+    (i32.load offset=12 (local.get $entry)))
+
+  ;; sets the position pos of the input signal to the value in the shared memory
+  (func (export "setInputSignal") (param $hmsb i32) (param $hlsb i32) (param $pos i32)
+    (local $entry i32) (local $signal i32) (local $assigned i32)
+    (local.set $entry (call $getInputSignalEntry (local.get $hmsb) (local.get $hlsb)))
+    (if (i32.eqz (local.get $entry))
+      (then (call $exceptionHandler (i32.const 1))))
+    (if (i32.ge_u (local.get $pos) (i32.load offset=12 (local.get $entry)))
+      (then (call $exceptionHandler (i32.const 6))))
+    (if (i32.eqz (global.get $input_counter))
+      (then (call $exceptionHandler (i32.const 2))))
+    (local.set $signal (i32.add (i32.load offset=8 (local.get $entry)) (local.get $pos)))
+    (local.set $assigned (i32.add (i32.const {{input_assigned}})
+      (i32.sub (local.get $signal) (i32.const {{main_input_signal_start}}))))
+    (if (i32.load8_u (local.get $assigned))
+      (then (call $exceptionHandler (i32.const 3))))
+    (i32.store8 (local.get $assigned) (i32.const 1))
+    (local.set $signal (i32.add (i32.const {{signal_memory}}) (i32.mul (local.get $signal) (i32.const {{n8}}))))
+    (call $int_copy (local.get $signal) (i32.const {{shared_rw_memory}}))
+    (call $Fr_reduce (local.get $signal))
+    (global.set $input_counter (i32.sub (global.get $input_counter) (i32.const 1)))
+    (if (i32.eqz (global.get $input_counter))
+      (then (call ${{main_header}}_run (i32.const 0)))))
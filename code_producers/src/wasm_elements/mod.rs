@@ -0,0 +1,266 @@
+pub mod wasm_code_generator;
+
+pub use crate::components::*;
+
+pub type WasmInstruction = String;
+
+// Layout of a component in memory: template id, signal start, input counter,
+// father and address of the list of its subcomponents (4 bytes each)
+pub const COMPONENT_SIZE: usize = 20;
+pub const COMPONENT_TEMPLATE_ID: usize = 0;
+pub const COMPONENT_SIGNAL_START: usize = 4;
+pub const COMPONENT_INPUT_COUNTER: usize = 8;
+pub const COMPONENT_FATHER: usize = 12;
+pub const COMPONENT_SUBCOMPONENTS: usize = 16;
+
+// Entries of the input hash map: hash (lsb and msb), signal id and size
+pub const INPUT_HASH_MAP_ENTRY_SIZE: usize = 16;
+
+pub const MESSAGE_BUFFER_SIZE: usize = 2048;
+
+// Field constants used by the runtime, each one takes n8 bytes
+pub const FR_CONSTANTS: [&str; 9] = [
+    "p",
+    "p_minus_2",
+    "half",
+    "r2",
+    "one",
+    "mont_one",
+    "mask",
+    "mask254",
+    "c254",
+];
+
+// Scratch elements used by the field functions, each one takes n8 + 8 bytes
+// (the montgomery multiplication uses two extra limbs)
+pub const FR_SCRATCH: [&str; 10] = [
+    "mont_t",
+    "mul_tmp",
+    "pow_base",
+    "pow_acc",
+    "pow_exp",
+    "inv_tmp",
+    "div_q",
+    "div_r",
+    "shift_amount",
+    "bnot_tmp",
+];
+
+// Strings used by the runtime to build its messages
+pub const RUNTIME_STRINGS: [(&str, &str); 4] = [
+    ("line_str", " line: "),
+    ("end_str", "\n"),
+    ("division_by_zero_str", "Division by zero"),
+    ("address_out_of_range_str", "Address out of range"),
+];
+
+pub struct WASMProducer {
+    pub main_header: String,
+    pub number_of_main_outputs: usize,
+    pub number_of_main_inputs: usize,
+    pub total_number_of_signals: usize,
+    pub number_of_components: usize,
+    pub number_of_subcomponent_indexes: usize,
+    pub size_32_bit: usize,
+    pub prime: String,
+    pub prime_str: String,
+    pub main_input_list: InputList,
+    pub witness_to_signal_list: SignalList,
+    pub io_map: TemplateInstanceIOMap,
+    pub template_instance_list: TemplateList,
+    pub message_list: MessageList,
+    pub field_tracking: Vec<String>,
+    pub major_version: usize,
+    pub minor_version: usize,
+    pub patch_version: usize,
+    string_table: Vec<String>,
+}
+
+impl Default for WASMProducer {
+    fn default() -> Self {
+        WASMProducer {
+            main_header: "Main_0".to_string(),
+            number_of_main_outputs: 0,
+            number_of_main_inputs: 0,
+            total_number_of_signals: 1,
+            number_of_components: 1,
+            number_of_subcomponent_indexes: 0,
+            size_32_bit: 8,
+            prime: "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+                .to_string(),
+            prime_str: "bn128".to_string(),
+            main_input_list: InputList::new(),
+            witness_to_signal_list: SignalList::new(),
+            io_map: TemplateInstanceIOMap::new(),
+            template_instance_list: TemplateList::new(),
+            message_list: MessageList::new(),
+            field_tracking: Vec::new(),
+            major_version: 0,
+            minor_version: 0,
+            patch_version: 0,
+            string_table: Vec::new(),
+        }
+    }
+}
+
+fn align(address: usize) -> usize {
+    address.div_ceil(8) * 8
+}
+
+impl WASMProducer {
+    pub fn get_version(&self) -> usize {
+        self.major_version
+    }
+    pub fn get_minor_version(&self) -> usize {
+        self.minor_version
+    }
+    pub fn get_patch_version(&self) -> usize {
+        self.patch_version
+    }
+    pub fn get_main_header(&self) -> &str {
+        &self.main_header
+    }
+    pub fn get_prime(&self) -> &str {
+        &self.prime
+    }
+    pub fn get_number_of_main_outputs(&self) -> usize {
+        self.number_of_main_outputs
+    }
+    pub fn get_number_of_main_inputs(&self) -> usize {
+        self.number_of_main_inputs
+    }
+    pub fn get_main_input_list(&self) -> &InputList {
+        &self.main_input_list
+    }
+    // Same size as in the C producer so both hash maps share the layout
+    pub fn get_input_hash_map_entry_size(&self) -> usize {
+        std::cmp::max(
+            usize::pow(2, (self.main_input_list.len() as f32).log2().ceil() as u32),
+            256,
+        )
+    }
+    pub fn get_witness_to_signal_list(&self) -> &SignalList {
+        &self.witness_to_signal_list
+    }
+    pub fn get_total_number_of_signals(&self) -> usize {
+        self.total_number_of_signals
+    }
+    pub fn get_number_of_components(&self) -> usize {
+        self.number_of_components
+    }
+    pub fn get_io_map(&self) -> &TemplateInstanceIOMap {
+        &self.io_map
+    }
+    pub fn get_template_instance_list(&self) -> &TemplateList {
+        &self.template_instance_list
+    }
+    pub fn get_number_of_template_instances(&self) -> usize {
+        self.template_instance_list.len()
+    }
+    pub fn get_message_list(&self) -> &MessageList {
+        &self.message_list
+    }
+    pub fn get_field_constant_list(&self) -> &Vec<String> {
+        &self.field_tracking
+    }
+    pub fn get_size_32_bit(&self) -> usize {
+        self.size_32_bit
+    }
+    pub fn get_size_of_field_element(&self) -> usize {
+        self.size_32_bit * 4
+    }
+    pub fn get_string_table(&self) -> &Vec<String> {
+        &self.string_table
+    }
+    pub fn set_string_table(&mut self, string_table: Vec<String>) {
+        self.string_table = string_table;
+    }
+
+    // Memory layout, every region starts at an address aligned to 8 bytes
+    pub fn get_shared_rw_memory_start(&self) -> usize {
+        8
+    }
+    pub fn get_message_buffer_start(&self) -> usize {
+        align(self.get_shared_rw_memory_start() + self.get_size_of_field_element())
+    }
+    pub fn get_runtime_strings_start(&self) -> usize {
+        align(self.get_message_buffer_start() + MESSAGE_BUFFER_SIZE)
+    }
+    pub fn get_runtime_strings_size(&self) -> usize {
+        RUNTIME_STRINGS.iter().map(|(_, s)| s.len() + 1).sum()
+    }
+    pub fn get_fr_constants_start(&self) -> usize {
+        align(self.get_runtime_strings_start() + self.get_runtime_strings_size())
+    }
+    pub fn get_fr_scratch_start(&self) -> usize {
+        self.get_fr_constants_start() + FR_CONSTANTS.len() * self.get_size_of_field_element()
+    }
+    pub fn get_fr_scratch_size(&self) -> usize {
+        self.get_size_of_field_element() + 8
+    }
+    pub fn get_constants_start(&self) -> usize {
+        self.get_fr_scratch_start() + FR_SCRATCH.len() * self.get_fr_scratch_size()
+    }
+    pub fn get_constant_address(&self, index: usize) -> usize {
+        self.get_constants_start() + index * self.get_size_of_field_element()
+    }
+    pub fn get_input_hash_map_start(&self) -> usize {
+        align(
+            self.get_constants_start()
+                + self.field_tracking.len() * self.get_size_of_field_element(),
+        )
+    }
+    pub fn get_witness_signal_id_list_start(&self) -> usize {
+        self.get_input_hash_map_start()
+            + self.get_input_hash_map_entry_size() * INPUT_HASH_MAP_ENTRY_SIZE
+    }
+    pub fn get_io_map_start(&self) -> usize {
+        align(self.get_witness_signal_id_list_start() + self.witness_to_signal_list.len() * 4)
+    }
+    // A pointer per template followed by the definitions of the signals of the
+    // templates in the io map and their lengths
+    pub fn get_io_map_size(&self) -> usize {
+        let mut size = self.get_number_of_template_instances() * 4;
+        for io_list in self.io_map.values() {
+            size += io_list.len() * 8;
+            for io_def in io_list {
+                size += io_def.lengths.len().saturating_sub(1) * 4;
+            }
+        }
+        size
+    }
+    pub fn get_string_list_start(&self) -> usize {
+        align(self.get_io_map_start() + self.get_io_map_size())
+    }
+    pub fn get_message_list_start(&self) -> usize {
+        self.get_string_list_start() + self.string_table.len() * 4
+    }
+    pub fn get_strings_start(&self) -> usize {
+        self.get_message_list_start() + self.message_list.len() * 4
+    }
+    pub fn get_strings_size(&self) -> usize {
+        let strings: usize = self.string_table.iter().map(|s| s.len() + 1).sum();
+        let messages: usize = self.message_list.iter().map(|s| s.len() + 1).sum();
+        strings + messages
+    }
+    pub fn get_input_assigned_start(&self) -> usize {
+        align(self.get_strings_start() + self.get_strings_size())
+    }
+    pub fn get_signal_memory_start(&self) -> usize {
+        align(self.get_input_assigned_start() + self.number_of_main_inputs)
+    }
+    pub fn get_component_memory_start(&self) -> usize {
+        self.get_signal_memory_start()
+            + self.total_number_of_signals * self.get_size_of_field_element()
+    }
+    pub fn get_subcomponents_memory_start(&self) -> usize {
+        self.get_component_memory_start() + self.number_of_components * COMPONENT_SIZE
+    }
+    pub fn get_stack_start(&self) -> usize {
+        align(self.get_subcomponents_memory_start() + self.number_of_subcomponent_indexes * 4)
+    }
+    // The stack grows on demand, we start with one free page for it
+    pub fn get_number_of_initial_pages(&self) -> usize {
+        self.get_stack_start() / 65536 + 2
+    }
+}
@@ -0,0 +1,533 @@
+use super::*;
+use crate::c_elements::c_code_generator::generate_hash_map;
+use num_bigint_dig::{BigInt, Sign};
+use serde_json::json;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Locals of the generated functions
+pub const CMP: &str = "$cmp";
+pub const SIGNAL_START: &str = "$signalstart";
+pub const EXPAUX: &str = "$expaux";
+pub const LVAR: &str = "$lvar";
+pub const SUBCMPS: &str = "$subcmps";
+pub const SUB_COMPONENT: &str = "$sub";
+pub const AUX_DEST: &str = "$aux_dest";
+pub const AUX_CREATE: &str = "$aux_create";
+pub const AUX_CMP_NUM: &str = "$aux_cmp_num";
+pub const CSOFFSET: &str = "$csoffset";
+pub const CREATE_INDEX: &str = "$i";
+pub const DESTINATION: &str = "$destination";
+pub const DESTINATION_SIZE: &str = "$destination_size";
+// Globals of the module
+pub const STACK_POINTER: &str = "$sp";
+pub const SUBCOMPONENTS_FREE: &str = "$subcomponents_free";
+// Type of the run functions of the templates, called through the table
+pub const RUN_TYPE: &str = "$runType";
+
+pub fn set_constant(value: &str) -> WasmInstruction {
+    format!("i32.const {}", value)
+}
+pub fn get_local(name: &str) -> WasmInstruction {
+    format!("local.get {}", name)
+}
+pub fn set_local(name: &str) -> WasmInstruction {
+    format!("local.set {}", name)
+}
+pub fn tee_local(name: &str) -> WasmInstruction {
+    format!("local.tee {}", name)
+}
+pub fn get_global(name: &str) -> WasmInstruction {
+    format!("global.get {}", name)
+}
+pub fn set_global(name: &str) -> WasmInstruction {
+    format!("global.set {}", name)
+}
+pub fn add32() -> WasmInstruction {
+    "i32.add".to_string()
+}
+pub fn sub32() -> WasmInstruction {
+    "i32.sub".to_string()
+}
+pub fn mul32() -> WasmInstruction {
+    "i32.mul".to_string()
+}
+pub fn ge32_u() -> WasmInstruction {
+    "i32.ge_u".to_string()
+}
+pub fn eqz32() -> WasmInstruction {
+    "i32.eqz".to_string()
+}
+pub fn load32(offset: usize) -> WasmInstruction {
+    if offset == 0 {
+        "i32.load".to_string()
+    } else {
+        format!("i32.load offset={}", offset)
+    }
+}
+pub fn store32(offset: usize) -> WasmInstruction {
+    if offset == 0 {
+        "i32.store".to_string()
+    } else {
+        format!("i32.store offset={}", offset)
+    }
+}
+pub fn call(name: &str) -> WasmInstruction {
+    format!("call ${}", name)
+}
+pub fn call_indirect() -> WasmInstruction {
+    format!("call_indirect (type {})", RUN_TYPE)
+}
+pub fn drop() -> WasmInstruction {
+    "drop".to_string()
+}
+pub fn add_block() -> WasmInstruction {
+    "block".to_string()
+}
+pub fn add_loop() -> WasmInstruction {
+    "loop".to_string()
+}
+pub fn add_if() -> WasmInstruction {
+    "if".to_string()
+}
+pub fn add_else() -> WasmInstruction {
+    "else".to_string()
+}
+pub fn add_end() -> WasmInstruction {
+    "end".to_string()
+}
+pub fn br(depth: usize) -> WasmInstruction {
+    format!("br {}", depth)
+}
+pub fn br_if(depth: usize) -> WasmInstruction {
+    format!("br_if {}", depth)
+}
+pub fn add_return() -> WasmInstruction {
+    "return".to_string()
+}
+pub fn add_comment(comment: &str) -> WasmInstruction {
+    format!(";; {}", comment)
+}
+
+// Address of the memory of the component whose id is on the stack
+pub fn component_address() -> Vec<WasmInstruction> {
+    vec![call("componentAddress")]
+}
+
+// Address of the signal whose absolute position is on the stack
+pub fn signal_address(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    vec![
+        set_constant(&producer.get_size_of_field_element().to_string()),
+        mul32(),
+        set_constant(&producer.get_signal_memory_start().to_string()),
+        add32(),
+    ]
+}
+
+// Address of the element whose position is on the stack in the storage starting at base
+pub fn element_address(producer: &WASMProducer, base: &str) -> Vec<WasmInstruction> {
+    vec![
+        set_constant(&producer.get_size_of_field_element().to_string()),
+        mul32(),
+        get_local(base),
+        add32(),
+    ]
+}
+
+// Address of the auxiliar element used to store the result of an operation
+pub fn expaux_address(producer: &WASMProducer, op_aux_no: usize) -> Vec<WasmInstruction> {
+    vec![
+        get_local(EXPAUX),
+        set_constant(&(op_aux_no * producer.get_size_of_field_element()).to_string()),
+        add32(),
+    ]
+}
+
+// Id of the subcomponent whose position is computed by cmp_index
+pub fn subcomponent_id(cmp_index: Vec<WasmInstruction>) -> Vec<WasmInstruction> {
+    let mut instructions = vec![get_local(SUBCMPS)];
+    instructions.extend(cmp_index);
+    instructions.push(set_constant("4"));
+    instructions.push(mul32());
+    instructions.push(add32());
+    instructions.push(load32(0));
+    instructions
+}
+
+// Offset of a signal of a subcomponent accessed through the io map, sub_id
+// computes the id of the subcomponent and indexes the positions of each dimension
+pub fn io_signal_offset(
+    sub_id: &[WasmInstruction],
+    signal_code: usize,
+    indexes: Vec<Vec<WasmInstruction>>,
+) -> Vec<WasmInstruction> {
+    let mut io_def = sub_id.to_vec();
+    io_def.append(&mut component_address());
+    io_def.push(load32(COMPONENT_TEMPLATE_ID));
+    io_def.push(call("ioDefinitions"));
+    io_def.push(set_constant(&(signal_code * 8).to_string()));
+    io_def.push(add32());
+    let mut instructions = io_def.clone();
+    instructions.push(load32(0));
+    let mut indexes = indexes.into_iter();
+    if let Some(first) = indexes.next() {
+        instructions.extend(first);
+        for (i, index) in indexes.enumerate() {
+            instructions.extend(io_def.clone());
+            instructions.push(load32(4));
+            instructions.push(load32(i * 4));
+            instructions.push(mul32());
+            instructions.extend(index);
+            instructions.push(add32());
+        }
+        instructions.push(add32());
+    }
+    instructions
+}
+
+pub fn merge_code(instructions: Vec<WasmInstruction>) -> String {
+    let mut code = String::new();
+    for instruction in instructions {
+        code.push_str(&instruction);
+        code.push('\n');
+    }
+    code
+}
+
+//--------------- module definitions ---------------
+
+pub fn generate_imports_list() -> Vec<WasmInstruction> {
+    vec![
+        "(import \"runtime\" \"exceptionHandler\" (func $exceptionHandler (param i32)))"
+            .to_string(),
+        "(import \"runtime\" \"printErrorMessage\" (func $printErrorMessage))".to_string(),
+        "(import \"runtime\" \"writeBufferMessage\" (func $writeBufferMessage))".to_string(),
+        "(import \"runtime\" \"showSharedRWMemory\" (func $showSharedRWMemory))".to_string(),
+    ]
+}
+
+pub fn generate_types_list() -> Vec<WasmInstruction> {
+    vec![format!("(type {} (func (param i32)))", RUN_TYPE)]
+}
+
+pub fn generate_memory_def_list(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    vec![format!(
+        "(memory (export \"memory\") {})",
+        producer.get_number_of_initial_pages()
+    )]
+}
+
+pub fn generate_globals_list(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let message_buffer = producer.get_message_buffer_start();
+    vec![
+        format!(
+            "(global {} (mut i32) (i32.const {}))",
+            STACK_POINTER,
+            producer.get_stack_start()
+        ),
+        format!(
+            "(global {} (mut i32) (i32.const {}))",
+            SUBCOMPONENTS_FREE,
+            producer.get_subcomponents_memory_start()
+        ),
+        format!(
+            "(global $input_counter (mut i32) (i32.const {}))",
+            producer.get_number_of_main_inputs()
+        ),
+        format!(
+            "(global $message_ptr (mut i32) (i32.const {}))",
+            message_buffer
+        ),
+        format!(
+            "(global $message_end (mut i32) (i32.const {}))",
+            message_buffer
+        ),
+        "(global $sanity_check (mut i32) (i32.const 0))".to_string(),
+    ]
+}
+
+// The run functions of the templates indexed by their template id
+pub fn generate_table_list(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let run_functions: Vec<String> = producer
+        .get_template_instance_list()
+        .iter()
+        .map(|header| format!("${}_run", header))
+        .collect();
+    vec![
+        format!(
+            "(table {} funcref)",
+            producer.get_number_of_template_instances()
+        ),
+        format!("(elem (i32.const 0) {})", run_functions.join(" ")),
+    ]
+}
+
+fn data_segment(address: usize, bytes: &[u8]) -> WasmInstruction {
+    let mut data = String::with_capacity(bytes.len() * 3);
+    for b in bytes {
+        data.push_str(&format!("\\{:02x}", b));
+    }
+    format!("(data (i32.const {}) \"{}\")", address, data)
+}
+
+fn element_bytes(n: &BigInt, n8: usize) -> Vec<u8> {
+    let (sign, mut bytes) = n.to_bytes_le();
+    assert_ne!(sign, Sign::Minus);
+    assert!(bytes.len() <= n8);
+    bytes.resize(n8, 0);
+    bytes
+}
+
+// p, p - 2, p / 2, R^2 mod p, 1, R mod p, the mask of the bits of p, the mask of
+// the 254 least significant bits and (2^254 - 1) mod p, R = 2^(32 * n32)
+fn generate_fr_constant_values(producer: &WASMProducer) -> Vec<BigInt> {
+    let p = producer.get_prime().parse::<BigInt>().unwrap();
+    let one = BigInt::from(1);
+    let r = &one << (producer.get_size_32_bit() * 32);
+    let mask = (&one << p.bits()) - &one;
+    let mask254 = (&one << std::cmp::min(254, producer.get_size_32_bit() * 32)) - &one;
+    let c254 = ((&one << 254) - &one) % &p;
+    vec![
+        p.clone(),
+        &p - BigInt::from(2),
+        &p >> 1,
+        (&r * &r) % &p,
+        one.clone(),
+        &r % &p,
+        mask,
+        mask254,
+        c254,
+    ]
+}
+
+// -p^-1 mod 2^32, used by the montgomery multiplication
+fn generate_np(producer: &WASMProducer) -> u64 {
+    let p = producer.get_prime().parse::<BigInt>().unwrap();
+    let p0 = (p % BigInt::from(1u64 << 32))
+        .to_str_radix(10)
+        .parse::<u64>()
+        .unwrap();
+    let mut inverse: u64 = 1;
+    // Newton iteration, each step doubles the number of correct bits
+    for _ in 0..5 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(p0.wrapping_mul(inverse))) & 0xffffffff;
+    }
+    (1u64 << 32) - inverse
+}
+
+fn generate_constant_list(producer: &WASMProducer) -> Vec<u8> {
+    let p = producer.get_prime().parse::<BigInt>().unwrap();
+    let n8 = producer.get_size_of_field_element();
+    let mut constant_list = vec![];
+    for s in producer.get_field_constant_list() {
+        let mut n = s.parse::<BigInt>().unwrap();
+        if n < BigInt::from(0) {
+            n += &p;
+        }
+        constant_list.append(&mut element_bytes(&n, n8));
+    }
+    constant_list
+}
+
+fn generate_input_hash_map(producer: &WASMProducer) -> Vec<u8> {
+    let map = generate_hash_map(
+        producer.get_main_input_list(),
+        producer.get_input_hash_map_entry_size(),
+    );
+    let mut hash_map = vec![];
+    for (h, signal_id, size) in map {
+        hash_map.extend_from_slice(&h.to_le_bytes());
+        hash_map.extend_from_slice(&(signal_id as u32).to_le_bytes());
+        hash_map.extend_from_slice(&(size as u32).to_le_bytes());
+    }
+    hash_map
+}
+
+// For each template a pointer to the definitions of its signals, each definition
+// is its offset and a pointer to the lengths of its dimensions but the first one
+fn generate_io_map(producer: &WASMProducer) -> Vec<u8> {
+    let start = producer.get_io_map_start();
+    let mut pointers = vec![0u32; producer.get_number_of_template_instances()];
+    let mut definitions = vec![];
+    let mut next = start + pointers.len() * 4;
+    for (template_id, io_list) in producer.get_io_map() {
+        pointers[*template_id] = next as u32;
+        let mut lengths = vec![];
+        let mut lengths_address = next + io_list.len() * 8;
+        for io_def in io_list {
+            definitions.extend_from_slice(&(io_def.offset as u32).to_le_bytes());
+            definitions.extend_from_slice(&(lengths_address as u32).to_le_bytes());
+            for length in io_def.lengths.iter().skip(1) {
+                lengths.extend_from_slice(&(*length as u32).to_le_bytes());
+                lengths_address += 4;
+            }
+        }
+        definitions.append(&mut lengths);
+        next = lengths_address;
+    }
+    let mut io_map: Vec<u8> = pointers.iter().flat_map(|p| p.to_le_bytes()).collect();
+    io_map.append(&mut definitions);
+    io_map
+}
+
+// The pointers to the strings of the logs and to the messages followed by the
+// strings themselves, ended by 0
+fn generate_string_list(producer: &WASMProducer) -> Vec<u8> {
+    let strings: Vec<&String> = producer
+        .get_string_table()
+        .iter()
+        .chain(producer.get_message_list().iter())
+        .collect();
+    let mut pointers = vec![];
+    let mut data = vec![];
+    let mut next = producer.get_strings_start();
+    for s in strings {
+        pointers.extend_from_slice(&(next as u32).to_le_bytes());
+        data.extend_from_slice(s.as_bytes());
+        data.push(0);
+        next += s.len() + 1;
+    }
+    pointers.append(&mut data);
+    pointers
+}
+
+fn generate_runtime_strings() -> Vec<u8> {
+    let mut data = vec![];
+    for (_, s) in RUNTIME_STRINGS {
+        data.extend_from_slice(s.as_bytes());
+        data.push(0);
+    }
+    data
+}
+
+pub fn generate_data_list(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let n8 = producer.get_size_of_field_element();
+    let fr_constants: Vec<u8> = generate_fr_constant_values(producer)
+        .iter()
+        .flat_map(|n| element_bytes(n, n8))
+        .collect();
+    let witness_list: Vec<u8> = producer
+        .get_witness_to_signal_list()
+        .iter()
+        .flat_map(|s| (*s as u32).to_le_bytes())
+        .collect();
+    let segments = [
+        (
+            producer.get_runtime_strings_start(),
+            generate_runtime_strings(),
+        ),
+        (producer.get_fr_constants_start(), fr_constants),
+        (
+            producer.get_constants_start(),
+            generate_constant_list(producer),
+        ),
+        (
+            producer.get_input_hash_map_start(),
+            generate_input_hash_map(producer),
+        ),
+        (producer.get_witness_signal_id_list_start(), witness_list),
+        (producer.get_io_map_start(), generate_io_map(producer)),
+        (
+            producer.get_string_list_start(),
+            generate_string_list(producer),
+        ),
+    ];
+    segments
+        .iter()
+        .filter(|(_, bytes)| !bytes.is_empty())
+        .map(|(address, bytes)| data_segment(*address, bytes))
+        .collect()
+}
+
+//--------------- field and runtime code ---------------
+
+fn template_data(producer: &WASMProducer) -> serde_json::Value {
+    let n8 = producer.get_size_of_field_element();
+    let prime = producer.get_prime().parse::<BigInt>().unwrap();
+    let mut data = json!({
+        "prime_str": producer.prime_str,
+        "main_header": producer.get_main_header(),
+        "n32": producer.get_size_32_bit(),
+        "n8": n8,
+        "n32_bits": producer.get_size_32_bit() * 32,
+        "bits": prime.bits(),
+        "np": generate_np(producer),
+        "major_version": producer.get_version(),
+        "minor_version": producer.get_minor_version(),
+        "patch_version": producer.get_patch_version(),
+        "number_of_main_inputs": producer.get_number_of_main_inputs(),
+        "main_input_signal_start": producer.get_number_of_main_outputs() + 1,
+        "witness_size": producer.get_witness_to_signal_list().len(),
+        "component_size": COMPONENT_SIZE,
+        "input_hash_map_size": producer.get_input_hash_map_entry_size(),
+        "input_hash_map_mask": producer.get_input_hash_map_entry_size() - 1,
+        "shared_rw_memory": producer.get_shared_rw_memory_start(),
+        "message_buffer": producer.get_message_buffer_start(),
+        "message_buffer_last": producer.get_message_buffer_start() + MESSAGE_BUFFER_SIZE - 1,
+        "witness_signal_id_list": producer.get_witness_signal_id_list_start(),
+        "input_hash_map": producer.get_input_hash_map_start(),
+        "io_map": producer.get_io_map_start(),
+        "string_list": producer.get_string_list_start(),
+        "message_list": producer.get_message_list_start(),
+        "input_assigned": producer.get_input_assigned_start(),
+        "signal_memory": producer.get_signal_memory_start(),
+        "signal_memory_size": producer.get_total_number_of_signals() * n8,
+        "component_memory": producer.get_component_memory_start(),
+        "subcomponents_memory": producer.get_subcomponents_memory_start(),
+        "stack": producer.get_stack_start(),
+    });
+    let map = data.as_object_mut().unwrap();
+    let mut address = producer.get_runtime_strings_start();
+    for (name, s) in RUNTIME_STRINGS {
+        map.insert(name.to_string(), json!(address));
+        address += s.len() + 1;
+    }
+    for (i, name) in FR_CONSTANTS.iter().enumerate() {
+        map.insert(
+            name.to_string(),
+            json!(producer.get_fr_constants_start() + i * n8),
+        );
+    }
+    for (i, name) in FR_SCRATCH.iter().enumerate() {
+        map.insert(
+            name.to_string(),
+            json!(producer.get_fr_scratch_start() + i * producer.get_fr_scratch_size()),
+        );
+    }
+    let mont_t = producer.get_fr_scratch_start();
+    map.insert("mont_t_top".to_string(), json!(mont_t + n8));
+    map.insert("mont_t_last".to_string(), json!(mont_t + n8 - 4));
+    let div_r = producer.get_fr_scratch_start() + 7 * producer.get_fr_scratch_size();
+    map.insert("div_r_top".to_string(), json!(div_r + n8 - 4));
+    data
+}
+
+fn render(template: &str, producer: &WASMProducer) -> String {
+    handlebars::Handlebars::new()
+        .render_template(template, &template_data(producer))
+        .expect("must render")
+}
+
+pub fn generate_fr_code(producer: &WASMProducer) -> String {
+    render(include_str!("common/fr.wat"), producer)
+}
+
+// Memory management, messages and the functions exported to the witness calculator
+pub fn generate_runtime_code(producer: &WASMProducer) -> String {
+    render(include_str!("common/runtime.wat"), producer)
+}
+
+//--------------- generate the javascript files ---------------
+
+pub fn generate_witness_calculator_js_file(js_folder: &Path) -> std::io::Result<()> {
+    let mut file = File::create(js_folder.join("witness_calculator.js"))?;
+    file.write_all(include_str!("common/witness_calculator.js").as_bytes())?;
+    file.flush()
+}
+
+pub fn generate_generate_witness_js_file(js_folder: &Path) -> std::io::Result<()> {
+    let mut file = File::create(js_folder.join("generate_witness.js"))?;
+    file.write_all(include_str!("common/generate_witness.js").as_bytes())?;
+    file.flush()
+}
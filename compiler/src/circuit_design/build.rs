@@ -7,6 +7,9 @@ use crate::intermediate_representation::translate::{
     CodeInfo, FieldTracker, ParallelClusters, TemplateDB,
 };
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
+use program_structure::builtin_library::is_qm31_function;
 use program_structure::file_definition::FileLibrary;
 use std::collections::{BTreeMap, HashMap};
 
@@ -54,6 +57,7 @@ fn build_template_instances(
             let xtype = cluster.xtype.clone();
             cmp_to_type.insert(name, xtype);
         }
+        circuit.wasm_producer.message_list.push(msg.clone());
         circuit.c_producer.message_list.push(msg);
         circuit.c_producer.has_parallelism |=
             template.is_parallel || template.is_parallel_component;
@@ -146,6 +150,7 @@ fn build_function_instances(
         let params = instance.params_types;
         let returns = instance.return_type;
         let id = circuit.c_producer.message_list.len();
+        circuit.wasm_producer.message_list.push(msg.clone());
         circuit.c_producer.message_list.push(msg);
         let code_info = CodeInfo {
             field_tracker,
@@ -174,6 +179,9 @@ fn build_function_instances(
             ..FunctionCodeInfo::default()
         };
         let code = instance.body;
+        function_info.is_qm31 = code.get_meta().file_id.is_some_and(|file_id| {
+            is_qm31_function(&function_info.name, &c_info.file_library, file_id)
+        });
         let out = translate::translate_code(code, code_info);
         string_table = out.string_table;
         field_tracker = out.constant_tracker;
@@ -224,6 +232,61 @@ fn initialize_c_producer(vcp: &VCP, database: &TemplateDB, version: &str) -> CPr
     producer
 }
 
+fn initialize_rust_producer(vcp: &VCP, database: &TemplateDB, version: &str) -> RustProducer {
+    use program_structure::utils::constants::UsefulConstants;
+    let initial_node = vcp.get_main_id();
+    let prime = UsefulConstants::new(&vcp.prime).get_p().clone();
+    let stats = vcp.get_stats();
+    let mut producer = RustProducer::default();
+    producer.main_header = vcp.get_main_instance().unwrap().template_header.clone();
+    producer.prime = prime.to_str_radix(10);
+    producer.prime_str = vcp.prime.clone();
+    producer.size_32_bit = prime.bits().div_ceil(32);
+    producer.total_number_of_signals = stats.all_signals + 1;
+    producer.number_of_components = stats.all_created_components;
+    producer.witness_to_signal_list = vcp.get_witness_list().clone();
+    producer.number_of_main_inputs = vcp.templates[initial_node].number_of_inputs;
+    producer.number_of_main_outputs = vcp.templates[initial_node].number_of_outputs;
+    producer.main_input_list = main_input_list(&vcp.templates[initial_node]);
+    producer.io_map = build_io_map(vcp, database);
+    producer.template_instance_list = build_template_list(vcp);
+    producer.field_tracking.clear();
+    (
+        producer.major_version,
+        producer.minor_version,
+        producer.patch_version,
+    ) = get_number_version(version);
+    producer
+}
+
+fn initialize_wasm_producer(vcp: &VCP, database: &TemplateDB, version: &str) -> WASMProducer {
+    use program_structure::utils::constants::UsefulConstants;
+    let initial_node = vcp.get_main_id();
+    let prime = UsefulConstants::new(&vcp.prime).get_p().clone();
+    let stats = vcp.get_stats();
+    let mut producer = WASMProducer::default();
+    producer.main_header = vcp.get_main_instance().unwrap().template_header.clone();
+    producer.prime = prime.to_str_radix(10);
+    producer.prime_str = vcp.prime.clone();
+    producer.size_32_bit = prime.bits().div_ceil(32);
+    producer.total_number_of_signals = stats.all_signals + 1;
+    producer.number_of_components = stats.all_created_components;
+    producer.number_of_subcomponent_indexes = stats.all_needed_subcomponents_indexes;
+    producer.witness_to_signal_list = vcp.get_witness_list().clone();
+    producer.number_of_main_inputs = vcp.templates[initial_node].number_of_inputs;
+    producer.number_of_main_outputs = vcp.templates[initial_node].number_of_outputs;
+    producer.main_input_list = main_input_list(&vcp.templates[initial_node]);
+    producer.io_map = build_io_map(vcp, database);
+    producer.template_instance_list = build_template_list(vcp);
+    producer.field_tracking.clear();
+    (
+        producer.major_version,
+        producer.minor_version,
+        producer.patch_version,
+    ) = get_number_version(version);
+    producer
+}
+
 fn main_input_list(main: &TemplateInstance) -> InputList {
     use program_structure::ast::SignalType::*;
     let mut input_list = vec![];
@@ -328,6 +391,8 @@ pub fn build_circuit(vcp: VCP, flag: CompilationFlags, version: &str) -> Circuit
     let template_database = TemplateDB::build(&vcp.templates);
     let mut circuit = Circuit::default();
     circuit.c_producer = initialize_c_producer(&vcp, &template_database, version);
+    circuit.rust_producer = initialize_rust_producer(&vcp, &template_database, version);
+    circuit.wasm_producer = initialize_wasm_producer(&vcp, &template_database, version);
 
     let field_tracker = FieldTracker::new();
     let circuit_info = CircuitInfo {
@@ -347,9 +412,17 @@ pub fn build_circuit(vcp: VCP, flag: CompilationFlags, version: &str) -> Circuit
     );
 
     let table_usize_to_string = create_table_usize_to_string(table_string_to_usize);
+    circuit
+        .rust_producer
+        .set_string_table(table_usize_to_string.clone());
+    circuit
+        .wasm_producer
+        .set_string_table(table_usize_to_string.clone());
     circuit.c_producer.set_string_table(table_usize_to_string);
     for i in 0..field_tracker.next_id() {
         let constant = field_tracker.get_constant(i).unwrap().clone();
+        circuit.rust_producer.field_tracking.push(constant.clone());
+        circuit.wasm_producer.field_tracking.push(constant.clone());
         circuit.c_producer.field_tracking.push(constant);
     }
     for fun in &mut circuit.functions {
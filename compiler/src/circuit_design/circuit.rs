@@ -4,6 +4,8 @@ use super::types::*;
 use crate::hir::very_concrete_program::VCP;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 use std::io::Write;
 
 pub struct CompilationFlags {
@@ -12,6 +14,8 @@ pub struct CompilationFlags {
 
 pub struct Circuit {
     pub c_producer: CProducer,
+    pub rust_producer: RustProducer,
+    pub wasm_producer: WASMProducer,
     pub templates: Vec<TemplateCode>,
     pub functions: Vec<FunctionCode>,
 }
@@ -20,6 +24,8 @@ impl Default for Circuit {
     fn default() -> Self {
         Circuit {
             c_producer: CProducer::default(),
+            rust_producer: RustProducer::default(),
+            wasm_producer: WASMProducer::default(),
             templates: Vec::new(),
             functions: Vec::new(),
         }
@@ -293,6 +299,139 @@ impl WriteC for Circuit {
     }
 }
 
+impl WriteRust for Circuit {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        // Prologue
+        let mut code = vec![
+            "#![allow(unused, non_snake_case, clippy::all)]".to_string(),
+            "use crate::calcwit::*;".to_string(),
+            "use crate::fr::*;".to_string(),
+            String::new(),
+            "pub type CircomTemplateFunction = fn(usize, &mut CircomCalcWit) -> Result<(), String>;"
+                .to_string(),
+        ];
+        let run_functions: Vec<String> = producer
+            .get_template_instance_list()
+            .iter()
+            .map(|header| format!("{}_run", header))
+            .collect();
+        code.push(format!(
+            "pub const {}: [CircomTemplateFunction; {}] = [{}];",
+            FUNCTION_TABLE,
+            producer.get_number_of_template_instances(),
+            argument_list(run_functions)
+        ));
+        let getters = [
+            // main signals are the constant one, the outputs and then the inputs
+            (
+                "get_main_input_signal_start",
+                producer.get_number_of_main_outputs() + 1,
+            ),
+            (
+                "get_main_input_signal_no",
+                producer.get_number_of_main_inputs(),
+            ),
+            (
+                "get_total_signal_no",
+                producer.get_total_number_of_signals(),
+            ),
+            (
+                "get_number_of_components",
+                producer.get_number_of_components(),
+            ),
+            (
+                "get_size_of_input_hashmap",
+                producer.get_input_hash_map_entry_size(),
+            ),
+            (
+                "get_size_of_witness",
+                producer.get_witness_to_signal_list().len(),
+            ),
+            (
+                "get_size_of_constants",
+                producer.get_field_constant_list().len(),
+            ),
+            ("get_size_of_io_map", producer.get_io_map().len()),
+        ];
+        for (getter, value) in getters {
+            code.push(format!("pub fn {}() -> usize {{\n{}\n}}", getter, value));
+        }
+
+        // Actual code of the circuit
+        code.push("// function declarations".to_string());
+        for f in &self.functions {
+            let (mut f_code, _) = f.produce_rust(producer);
+            code.append(&mut f_code);
+        }
+        code.push("// template declarations".to_string());
+        for t in &self.templates {
+            let (mut t_code, _) = t.produce_rust(producer);
+            code.append(&mut t_code);
+        }
+
+        // Epilogue
+        // We use 0 to indicate that the main component has no father
+        let create_args = vec![
+            "1".to_string(),
+            "0".to_string(),
+            CIRCOM_CALC_WIT.to_string(),
+            "\"main\".to_string()".to_string(),
+            "0".to_string(),
+        ];
+        let mut main_run_body = vec![format!(
+            "{}?;",
+            build_call(
+                format!("{}_create", producer.get_main_header()),
+                create_args
+            )
+        )];
+        // a main component without inputs is run when it is created
+        if producer.get_number_of_main_inputs() > 0 {
+            main_run_body.push(format!(
+                "{}_run(0, {})?;",
+                producer.get_main_header(),
+                CIRCOM_CALC_WIT
+            ));
+        }
+        main_run_body.push("Ok(())".to_string());
+        code.push(build_callable(
+            "run".to_string(),
+            vec![format!("{}: &mut CircomCalcWit", CIRCOM_CALC_WIT)],
+            "Result<(), String>",
+            main_run_body,
+        ));
+        (code, "".to_string())
+    }
+}
+
+impl WriteWasm for Circuit {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let mut code = vec!["(module".to_string()];
+        code.append(&mut generate_types_list());
+        code.append(&mut generate_imports_list());
+        code.append(&mut generate_memory_def_list(producer));
+        code.append(&mut generate_table_list(producer));
+        code.append(&mut generate_globals_list(producer));
+        code.append(&mut generate_data_list(producer));
+        code.push(generate_fr_code(producer));
+        code.push(generate_runtime_code(producer));
+
+        // Actual code of the circuit
+        code.push(";; function declarations".to_string());
+        for f in &self.functions {
+            code.append(&mut f.produce_wasm(producer));
+        }
+        code.push(";; template declarations".to_string());
+        for t in &self.templates {
+            code.append(&mut t.produce_wasm(producer));
+        }
+        code.push(")".to_string());
+        code
+    }
+}
+
 impl Circuit {
     pub fn build(vcp: VCP, flags: CompilationFlags, version: &str) -> Self {
         use super::build::build_circuit;
@@ -328,20 +467,51 @@ impl Circuit {
         run_name: &str,
         c_circuit: &mut W,
         c_dat: &mut W,
+        library: bool,
     ) -> Result<(), ()> {
         use std::path::Path;
         let c_folder_path = Path::new(c_folder).to_path_buf();
-        c_code_generator::generate_main_cpp_file(&c_folder_path).map_err(|_err| {})?;
+        if library {
+            c_code_generator::generate_witness_library_files(&c_folder_path).map_err(|_err| {})?;
+        } else {
+            c_code_generator::generate_main_cpp_file(&c_folder_path).map_err(|_err| {})?;
+        }
         c_code_generator::generate_circom_hpp_file(&c_folder_path).map_err(|_err| {})?;
-        c_code_generator::generate_fr_hpp_file(&c_folder_path, &self.c_producer.prime_str)
+        c_code_generator::generate_fr_hpp_file(&c_folder_path, &self.c_producer)
             .map_err(|_err| {})?;
         c_code_generator::generate_calcwit_hpp_file(&c_folder_path).map_err(|_err| {})?;
-        c_code_generator::generate_fr_cpp_file(&c_folder_path, &self.c_producer.prime_str)
+        c_code_generator::generate_fr_cpp_file(&c_folder_path, &self.c_producer)
             .map_err(|_err| {})?;
         c_code_generator::generate_calcwit_cpp_file(&c_folder_path).map_err(|_err| {})?;
-        c_code_generator::generate_cmake_file(&c_folder_path, run_name).map_err(|_err| {})?;
+        c_code_generator::generate_cmake_file(&c_folder_path, run_name, library)
+            .map_err(|_err| {})?;
         c_code_generator::generate_findgmp_file(&c_folder_path).map_err(|_err| {})?;
         c_code_generator::generate_dat_file(c_dat, &self.c_producer).map_err(|_err| {})?;
         self.write_c(c_circuit, &self.c_producer)
     }
+    pub fn produce_rust<W: Write>(
+        &self,
+        rust_folder: &str,
+        run_name: &str,
+        rust_circuit: &mut W,
+        rust_dat: &mut W,
+    ) -> Result<(), ()> {
+        use std::path::Path;
+        let rust_folder_path = Path::new(rust_folder).to_path_buf();
+        rust_code_generator::generate_cargo_file(&rust_folder_path, run_name, &self.rust_producer)
+            .map_err(|_err| {})?;
+        rust_code_generator::generate_src_files(&rust_folder_path, run_name, &self.rust_producer)
+            .map_err(|_err| {})?;
+        rust_code_generator::generate_dat_file(rust_dat, &self.rust_producer).map_err(|_err| {})?;
+        self.write_rust(rust_circuit, &self.rust_producer)
+    }
+    pub fn produce_wasm<W: Write>(&self, js_folder: &str, writer: &mut W) -> Result<(), ()> {
+        use std::path::Path;
+        let js_folder_path = Path::new(js_folder).to_path_buf();
+        wasm_code_generator::generate_generate_witness_js_file(&js_folder_path)
+            .map_err(|_err| {})?;
+        wasm_code_generator::generate_witness_calculator_js_file(&js_folder_path)
+            .map_err(|_err| {})?;
+        self.write_wasm(writer, &self.wasm_producer)
+    }
 }
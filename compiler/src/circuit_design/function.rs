@@ -3,6 +3,8 @@ use crate::hir::very_concrete_program::Param;
 use crate::intermediate_representation::InstructionList;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 pub type FunctionCode = Box<FunctionCodeInfo>;
 #[derive(Default)]
@@ -14,6 +16,8 @@ pub struct FunctionCodeInfo {
     pub body: InstructionList,
     pub max_number_of_vars: usize,
     pub max_number_of_ops_in_expression: usize,
+    // QM31 function of the builtin library, the m31 runtime computes it natively
+    pub is_qm31: bool,
 }
 
 impl ToString for FunctionCodeInfo {
@@ -48,15 +52,73 @@ impl WriteC for FunctionCodeInfo {
             declare_my_template_name_function(&self.name)
         ));
         body.push(format!("u64 {} = {};", my_id(), component_father()));
+        if let Some(mut native) = self.produce_native_qm31_c(producer) {
+            body.append(&mut native);
+        } else {
+            for t in &self.body {
+                let (mut instructions_body, _) = t.produce_c(producer, Some(false));
+                body.append(&mut instructions_body);
+            }
+        }
+        let callable = build_callable(header, params, body);
+        (vec![callable], "".to_string())
+    }
+}
+
+impl WriteRust for FunctionCodeInfo {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let params = vec![
+            format!("{}: &mut CircomCalcWit", CIRCOM_CALC_WIT),
+            format!("{}: &mut [{}]", L_VAR_STACK, T_FR_ELEMENT),
+            format!("{}: usize", COMPONENT_FATHER),
+        ];
+        let mut body = vec![
+            declare_expaux(self.max_number_of_ops_in_expression),
+            format!("let {} = \"{}\";", MY_TEMPLATE_NAME, self.name),
+            format!("let {} = {};", MY_ID, COMPONENT_FATHER),
+        ];
         for t in &self.body {
-            let (mut instructions_body, _) = t.produce_c(producer, Some(false));
+            let (mut instructions_body, _) = t.produce_rust(producer);
             body.append(&mut instructions_body);
         }
-        let callable = build_callable(header, params, body);
+        // only reached by functions whose last statement is not a return
+        body.push("Ok(Vec::new())".to_string());
+        let result = format!("Result<Vec<{}>, String>", T_FR_ELEMENT);
+        let callable = build_callable(self.header.clone(), params, &result, body);
         (vec![callable], "".to_string())
     }
 }
 
+impl WriteWasm for FunctionCodeInfo {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let mut instructions = vec![
+            format!(
+                "(func ${} (param {} i32) (param {} i32) (param {} i32)",
+                self.header, LVAR, DESTINATION, DESTINATION_SIZE
+            ),
+            format!("(local {} i32)", EXPAUX),
+            format!("(local {} i32)", SUB_COMPONENT),
+            format!("(local {} i32)", AUX_DEST),
+            set_constant(
+                &(self.max_number_of_ops_in_expression * producer.get_size_of_field_element())
+                    .to_string(),
+            ),
+            call("reserveStack"),
+            set_local(EXPAUX),
+        ];
+        for t in &self.body {
+            instructions.append(&mut t.produce_wasm(producer));
+        }
+        // only reached by functions whose last statement is not a return
+        instructions.push(get_local(EXPAUX));
+        instructions.push(set_global(STACK_POINTER));
+        instructions.push(")".to_string());
+        vec![merge_code(instructions)]
+    }
+}
+
 impl FunctionCodeInfo {
     pub fn wrap(self) -> FunctionCode {
         FunctionCode::new(self)
@@ -64,4 +126,34 @@ impl FunctionCodeInfo {
     pub fn is_linked(&self, name: &str, params: &Vec<Param>) -> bool {
         self.name.eq(name) && self.params.eq(params)
     }
+
+    // the arguments of the QM31 functions are consecutive in lvar, the result
+    // is computed apart in case the destination is smaller
+    fn produce_native_qm31_c(&self, producer: &CProducer) -> Option<Vec<String>> {
+        use c_code_generator::*;
+        let is_m31 = matches!(get_fr_implementation(producer), Ok(FrImplementation::M31));
+        let all_elements = self.params.iter().all(|param| param.length == vec![4]);
+        if !self.is_qm31 || !is_m31 || !all_elements {
+            return None;
+        }
+        let mut arguments = vec!["result".to_string()];
+        for i in 0..self.params.len() {
+            arguments.push(format!("&{}", lvar((4 * i).to_string())));
+        }
+        Some(vec![
+            "FrElement result[4];".to_string(),
+            format!("{};", build_call(format!("Fr_{}", self.name), arguments)),
+            format!(
+                "{};",
+                build_call(
+                    "Fr_copyn".to_string(),
+                    vec![
+                        FUNCTION_DESTINATION.to_string(),
+                        "result".to_string(),
+                        FUNCTION_DESTINATION_SIZE.to_string()
+                    ]
+                )
+            ),
+        ])
+    }
 }
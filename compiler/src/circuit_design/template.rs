@@ -1,6 +1,8 @@
 use crate::intermediate_representation::InstructionList;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 type TemplateID = usize;
 pub type TemplateCode = Box<TemplateCodeInfo>;
@@ -46,6 +48,170 @@ impl WriteC for TemplateCodeInfo {
     }
 }
 
+impl WriteRust for TemplateCodeInfo {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let create_header = format!("{}_create", self.header);
+        let create_params = vec![
+            "soffset: usize".to_string(),
+            "coffset: usize".to_string(),
+            format!("{}: &mut CircomCalcWit", CIRCOM_CALC_WIT),
+            "component_name: String".to_string(),
+            format!("{}: usize", COMPONENT_FATHER),
+        ];
+        let component = component_memory("coffset".to_string());
+        let mut create_body = vec![
+            format!("{}.template_id = {};", component, self.id),
+            format!("{}.template_name = \"{}\";", component, self.name),
+            format!("{}.signal_start = soffset;", component),
+            format!("{}.input_counter = {};", component, self.number_of_inputs),
+            format!("{}.component_name = component_name;", component),
+            format!("{}.id_father = {};", component, COMPONENT_FATHER),
+            format!(
+                "{}.subcomponents = vec![0; {}];",
+                component, self.number_of_components
+            ),
+        ];
+        // if has no inputs should be runned
+        if self.number_of_inputs == 0 {
+            create_body.push(format!(
+                "{}_run(coffset, {})?;",
+                self.header, CIRCOM_CALC_WIT
+            ));
+        }
+        create_body.push("Ok(())".to_string());
+        let create_fun = build_callable(
+            create_header,
+            create_params,
+            "Result<(), String>",
+            create_body,
+        );
+
+        let run_header = format!("{}_run", self.header);
+        let run_params = vec![
+            format!("{}: usize", CTX_INDEX),
+            format!("{}: &mut CircomCalcWit", CIRCOM_CALC_WIT),
+        ];
+        let mut run_body = vec![
+            format!(
+                "let {} = {}.signal_start;",
+                MY_SIGNAL_START,
+                component_memory(CTX_INDEX.to_string())
+            ),
+            format!("let {} = \"{}\";", MY_TEMPLATE_NAME, self.name),
+            format!("let {} = {};", MY_ID, CTX_INDEX),
+            declare_expaux(self.expression_stack_depth),
+            declare_lvar(self.var_stack_depth),
+        ];
+        for t in &self.body {
+            let (mut instructions_body, _) = t.produce_rust(producer);
+            run_body.append(&mut instructions_body);
+        }
+        // to release the memory of its subcomponents
+        run_body.push(format!("for i in 0..{} {{", self.number_of_components));
+        run_body.push(format!(
+            "let index_subc = {};",
+            my_subcomponents("i".to_string())
+        ));
+        run_body.push("if index_subc != 0 {".to_string());
+        run_body.push(format!(
+            "{}.release_memory_component(index_subc);",
+            CIRCOM_CALC_WIT
+        ));
+        run_body.push("}".to_string());
+        run_body.push("}".to_string());
+        run_body.push("Ok(())".to_string());
+        let run_fun = build_callable(run_header, run_params, "Result<(), String>", run_body);
+        (vec![create_fun, run_fun], "".to_string())
+    }
+}
+
+impl WriteWasm for TemplateCodeInfo {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let n8 = producer.get_size_of_field_element();
+        let component = "$component";
+        let mut create_fun = vec![
+            format!(
+                "(func ${}_create (param $soffset i32) (param {} i32) (param $father i32)",
+                self.header, CMP
+            ),
+            format!("(local {} i32)", component),
+            get_local(CMP),
+        ];
+        create_fun.append(&mut component_address());
+        create_fun.push(set_local(component));
+        let fields = [
+            (COMPONENT_TEMPLATE_ID, set_constant(&self.id.to_string())),
+            (COMPONENT_SIGNAL_START, get_local("$soffset")),
+            (
+                COMPONENT_INPUT_COUNTER,
+                set_constant(&self.number_of_inputs.to_string()),
+            ),
+            (COMPONENT_FATHER, get_local("$father")),
+        ];
+        for (offset, value) in fields {
+            create_fun.push(get_local(component));
+            create_fun.push(value);
+            create_fun.push(store32(offset));
+        }
+        create_fun.push(get_local(component));
+        create_fun.push(set_constant(&self.number_of_components.to_string()));
+        create_fun.push(call("reserveSubcomponents"));
+        create_fun.push(store32(COMPONENT_SUBCOMPONENTS));
+        // if has no inputs should be runned
+        if self.number_of_inputs == 0 {
+            create_fun.push(get_local(CMP));
+            create_fun.push(call(&format!("{}_run", self.header)));
+        }
+        create_fun.push(")".to_string());
+
+        let mut run_fun = vec![
+            format!(
+                "(func ${}_run (type {}) (param {} i32)",
+                self.header, RUN_TYPE, CMP
+            ),
+            format!("(local {} i32)", SIGNAL_START),
+            format!("(local {} i32)", SUBCMPS),
+            format!("(local {} i32)", EXPAUX),
+            format!("(local {} i32)", LVAR),
+            format!("(local {} i32)", SUB_COMPONENT),
+            format!("(local {} i32)", AUX_DEST),
+            format!("(local {} i32)", AUX_CREATE),
+            format!("(local {} i32)", AUX_CMP_NUM),
+            format!("(local {} i32)", CSOFFSET),
+            format!("(local {} i32)", CREATE_INDEX),
+            get_local(CMP),
+        ];
+        run_fun.append(&mut component_address());
+        run_fun.push(load32(COMPONENT_SIGNAL_START));
+        run_fun.push(set_local(SIGNAL_START));
+        run_fun.push(get_local(CMP));
+        run_fun.append(&mut component_address());
+        run_fun.push(load32(COMPONENT_SUBCOMPONENTS));
+        run_fun.push(set_local(SUBCMPS));
+        // the frame keeps the auxiliar values of the expressions and then the variables
+        run_fun.push(set_constant(
+            &((self.expression_stack_depth + self.var_stack_depth) * n8).to_string(),
+        ));
+        run_fun.push(call("reserveStack"));
+        run_fun.push(tee_local(EXPAUX));
+        run_fun.push(set_constant(
+            &(self.expression_stack_depth * n8).to_string(),
+        ));
+        run_fun.push(add32());
+        run_fun.push(set_local(LVAR));
+        for t in &self.body {
+            run_fun.append(&mut t.produce_wasm(producer));
+        }
+        // to release the frame of the template
+        run_fun.push(get_local(EXPAUX));
+        run_fun.push(set_global(STACK_POINTER));
+        run_fun.push(")".to_string());
+        vec![merge_code(create_fun), merge_code(run_fun)]
+    }
+}
+
 impl TemplateCodeInfo {
     fn produce_c_parallel_case(&self, producer: &CProducer, parallel: bool) -> Vec<String> {
         use c_code_generator::*;
@@ -184,10 +350,23 @@ impl TemplateCodeInfo {
         run_body.push(format!("{};", declare_sub_component_aux()));
         run_body.push(format!("{};", declare_index_multiple_eq()));
 
+        // the threads of the subcomponents are joined even when the body
+        // throws, and a parallel component cannot throw out of its thread
+        let catches_failures =
+            parallel || (self.number_of_components > 0 && self.has_parallel_sub_cmp);
+        if catches_failures {
+            run_body.push("std::exception_ptr failure;".to_string());
+            run_body.push("try {".to_string());
+        }
         for t in &self.body {
             let (mut instructions_body, _) = t.produce_c(producer, Some(parallel));
             run_body.append(&mut instructions_body);
         }
+        if catches_failures {
+            run_body.push("} catch (...) {".to_string());
+            run_body.push("failure = std::current_exception();".to_string());
+            run_body.push("}".to_string());
+        }
         // parallelism (join at the end of the function)
         if self.number_of_components > 0 && self.has_parallel_sub_cmp {
             run_body.push(format!("{{"));
@@ -204,6 +383,10 @@ impl TemplateCodeInfo {
             run_body.push(format!("}}"));
         }
         if parallel {
+            run_body.push(format!(
+                "if (failure) {}->recordFailure(failure);",
+                CIRCOM_CALC_WIT
+            ));
             // parallelism
             // set to true all outputs
             run_body.push(format!(
@@ -254,6 +437,9 @@ impl TemplateCodeInfo {
         ));
 
         run_body.push(format!("}}"));
+        if catches_failures && !parallel {
+            run_body.push("if (failure) std::rethrow_exception(failure);".to_string());
+        }
         let run_fun = build_callable(run_header, run_params, run_body);
         vec![create_fun, run_fun]
     }
@@ -3,20 +3,9 @@ pub use crate::hir::very_concrete_program::VCP;
 use std::fs::File;
 use std::io::BufWriter;
 
-pub struct Config {
-    pub debug_output: bool,
-    pub produce_input_log: bool,
-}
-
-pub fn run_compiler(vcp: VCP, config: Config, version: &str) -> Result<Circuit, ()> {
-    let flags = CompilationFlags {
-        main_inputs_log: config.produce_input_log,
-    };
-    let circuit = Circuit::build(vcp, flags, version);
-    if config.debug_output {
-        produce_debug_output(&circuit)?;
-    }
-    Ok(circuit)
+pub fn check_c_support(circuit: &Circuit) -> Result<(), String> {
+    use code_producers::c_elements::c_code_generator::get_fr_implementation;
+    get_fr_implementation(&circuit.c_producer).map(|_| ())
 }
 
 pub fn write_c(
@@ -25,6 +14,7 @@ pub fn write_c(
     c_run_name: &str,
     c_file: &str,
     dat_file: &str,
+    library: bool,
 ) -> Result<(), ()> {
     use std::path::Path;
     if Path::new(c_folder).is_dir() {
@@ -35,10 +25,53 @@ pub fn write_c(
     let c_file = File::create(c_file).map_err(|_err| {})?;
     let mut c_file = BufWriter::new(c_file);
     let mut dat_file = BufWriter::new(dat_file);
-    circuit.produce_c(c_folder, c_run_name, &mut c_file, &mut dat_file)
+    circuit.produce_c(c_folder, c_run_name, &mut c_file, &mut dat_file, library)
+}
+
+pub fn write_rust(
+    circuit: &Circuit,
+    rust_folder: &str,
+    rust_run_name: &str,
+    rust_file: &str,
+    dat_file: &str,
+) -> Result<(), ()> {
+    use std::path::Path;
+    if Path::new(rust_folder).is_dir() {
+        std::fs::remove_dir_all(rust_folder).map_err(|_err| {})?;
+    }
+    std::fs::create_dir(rust_folder).map_err(|_err| {})?;
+    std::fs::create_dir(Path::new(rust_folder).join("src")).map_err(|_err| {})?;
+    let dat_file = File::create(dat_file).map_err(|_err| {})?;
+    let rust_file = File::create(rust_file).map_err(|_err| {})?;
+    let mut rust_file = BufWriter::new(rust_file);
+    let mut dat_file = BufWriter::new(dat_file);
+    circuit.produce_rust(rust_folder, rust_run_name, &mut rust_file, &mut dat_file)
+}
+
+pub fn write_wasm(circuit: &Circuit, js_folder: &str, wat_file: &str) -> Result<(), ()> {
+    use std::path::Path;
+    if Path::new(js_folder).is_dir() {
+        std::fs::remove_dir_all(js_folder).map_err(|_err| {})?;
+    }
+    std::fs::create_dir(js_folder).map_err(|_err| {})?;
+    let wat_file = File::create(wat_file).map_err(|_err| {})?;
+    let mut wat_file = BufWriter::new(wat_file);
+    circuit.produce_wasm(js_folder, &mut wat_file)
+}
+
+pub fn write_wtns(circuit: &Circuit, input_file: &str, wtns_file: &str) -> Result<(), String> {
+    use crate::witness::{calculate_witness, write_wtns};
+    let input = std::fs::read_to_string(input_file)
+        .map_err(|err| format!("Could not read {}: {}", input_file, err))?;
+    let witness = calculate_witness(circuit, &input)?;
+    let wtns_file = File::create(wtns_file)
+        .map_err(|err| format!("Could not create {}: {}", wtns_file, err))?;
+    let mut wtns_file = BufWriter::new(wtns_file);
+    write_wtns(&mut wtns_file, &circuit.c_producer, &witness).map_err(|err| err.to_string())
 }
 
-fn produce_debug_output(circuit: &Circuit) -> Result<(), ()> {
+// Writes the IR of every template and function to the ir_log folder
+pub fn write_debug_output(circuit: &Circuit) -> Result<(), ()> {
     use std::io::Write;
     use std::path::Path;
     let path = format!("ir_log");
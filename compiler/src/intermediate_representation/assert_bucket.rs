@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct AssertBucket {
@@ -47,15 +49,46 @@ impl WriteC for AssertBucket {
         use c_code_generator::*;
         let (prologue, value) = self.evaluate.produce_c(producer, parallel);
         let is_true = build_call("Fr_isTrue".to_string(), vec![value]);
+        // thrown instead of aborting, so that the caller can go on with other
+        // inputs
         let if_condition = format!(
-            "if (!{}) {};",
+            "if (!{}) throw CircuitAssertFailed({});",
             is_true,
             build_failed_assert_message(self.line)
         );
-        let assertion = format!("{};", build_call("assert".to_string(), vec![is_true]));
         let mut assert_c = prologue;
         assert_c.push(if_condition);
-        assert_c.push(assertion);
         (assert_c, "".to_string())
     }
 }
+
+impl WriteRust for AssertBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let (prologue, value) = self.evaluate.produce_rust(producer);
+        let is_true = format!("fr_is_true({})", first_element(&value));
+        let failed_assert = format!("return Err({});", build_failed_assert_message(self.line));
+        let mut assert_rust = prologue;
+        assert_rust.push(build_conditional(
+            format!("!{}", is_true),
+            vec![failed_assert],
+            vec![],
+        ));
+        (assert_rust, "".to_string())
+    }
+}
+
+impl WriteWasm for AssertBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let mut instructions = self.evaluate.produce_wasm(producer);
+        instructions.push(call("Fr_isTrue"));
+        instructions.push(eqz32());
+        instructions.push(add_if());
+        instructions.push(set_constant(&self.message_id.to_string()));
+        instructions.push(set_constant(&self.line.to_string()));
+        instructions.push(call("assertFailed"));
+        instructions.push(add_end());
+        instructions
+    }
+}
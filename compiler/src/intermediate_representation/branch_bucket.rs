@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct BranchBucket {
@@ -75,3 +77,44 @@ impl WriteC for BranchBucket {
         (c_branch, "".to_string())
     }
 }
+
+impl WriteRust for BranchBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let (condition_code, condition_result) = self.cond.produce_rust(producer);
+        let condition_result = format!("fr_is_true({})", first_element(&condition_result));
+        let mut if_body = Vec::new();
+        for instr in &self.if_branch {
+            let (mut instr_code, _) = instr.produce_rust(producer);
+            if_body.append(&mut instr_code);
+        }
+        let mut else_body = Vec::new();
+        for instr in &self.else_branch {
+            let (mut instr_code, _) = instr.produce_rust(producer);
+            else_body.append(&mut instr_code);
+        }
+        let mut rust_branch = condition_code;
+        rust_branch.push(build_conditional(condition_result, if_body, else_body));
+        (rust_branch, "".to_string())
+    }
+}
+
+impl WriteWasm for BranchBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let mut instructions = self.cond.produce_wasm(producer);
+        instructions.push(call("Fr_isTrue"));
+        instructions.push(add_if());
+        for instr in &self.if_branch {
+            instructions.append(&mut instr.produce_wasm(producer));
+        }
+        if !self.else_branch.is_empty() {
+            instructions.push(add_else());
+            for instr in &self.else_branch {
+                instructions.append(&mut instr.produce_wasm(producer));
+            }
+        }
+        instructions.push(add_end());
+        instructions
+    }
+}
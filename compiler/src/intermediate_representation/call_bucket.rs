@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct FinalData {
@@ -499,3 +501,142 @@ impl WriteC for CallBucket {
         (prologue, result)
     }
 }
+
+impl WriteRust for CallBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use super::store_bucket::{rust_store_copy, rust_store_epilogue, rust_store_prologue};
+        use rust_code_generator::*;
+        let mut prologue = vec![];
+        //create block
+        prologue.push("{".to_string());
+        prologue.push("// start of call bucket".to_string());
+        prologue.push(declare_lvar_func_call(self.arena_size));
+        // copying parameters
+        let mut count = 0;
+        for (i, p) in self.arguments.iter().enumerate() {
+            prologue.push(format!("// copying argument {}", i));
+            let (mut prologue_value, src) = p.produce_rust(producer);
+            prologue.append(&mut prologue_value);
+            let size = self.argument_types[i].size;
+            if size > 1 {
+                prologue.push(format!(
+                    "{}[{}..{}].clone_from_slice(&{});",
+                    L_VAR_FUNC_CALL_STACK,
+                    count,
+                    count + size,
+                    first_elements(&src, size)
+                ));
+            } else {
+                prologue.push(format!(
+                    "{}[{}] = {}[0].clone();",
+                    L_VAR_FUNC_CALL_STACK, count, src
+                ));
+            }
+            prologue.push(format!("// end copying argument {}", i));
+            count += size;
+        }
+        let call = build_call(
+            self.symbol.clone(),
+            vec![
+                CIRCOM_CALC_WIT.to_string(),
+                format!("&mut {}", L_VAR_FUNC_CALL_STACK),
+                MY_ID.to_string(),
+            ],
+        );
+        let result = match &self.return_info {
+            ReturnType::Intermediate { op_aux_no } => {
+                prologue.push(format!(
+                    "{} = {}?[0].clone();",
+                    expaux(op_aux_no.to_string()),
+                    call
+                ));
+                expaux_value(op_aux_no.to_string())
+            }
+            ReturnType::Final(data) => {
+                let mut dest_prologue =
+                    rust_store_prologue(producer, &data.dest_address_type, &data.dest);
+                prologue.append(&mut dest_prologue);
+                prologue.push(format!("let aux_result = {}?;", call));
+                prologue.push(rust_store_copy(
+                    &data.dest_address_type,
+                    "aux_result",
+                    data.context.size,
+                ));
+                let mut epilogue =
+                    rust_store_epilogue(&data.dest_address_type, &data.dest, data.context.size);
+                prologue.append(&mut epilogue);
+                "".to_string()
+            }
+        };
+        prologue.push("// end call bucket".to_string());
+        prologue.push("}".to_string());
+        (prologue, result)
+    }
+}
+
+impl WriteWasm for CallBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use super::store_bucket::{wasm_store_copy, wasm_store_epilogue, wasm_store_prologue};
+        use wasm_code_generator::*;
+        let arena_size = self.arena_size * producer.get_size_of_field_element();
+        // the arena is on top of the stack while the arguments are copied
+        let arena_address = vec![
+            get_global(STACK_POINTER),
+            set_constant(&arena_size.to_string()),
+            sub32(),
+        ];
+        let mut instructions = vec![add_comment("start of call bucket")];
+        instructions.push(set_constant(&arena_size.to_string()));
+        instructions.push(call("reserveStack"));
+        instructions.push(drop());
+        let mut count = 0;
+        for (i, p) in self.arguments.iter().enumerate() {
+            instructions.push(add_comment(&format!("copying argument {}", i)));
+            instructions.extend(arena_address.clone());
+            instructions.push(set_constant(
+                &(count * producer.get_size_of_field_element()).to_string(),
+            ));
+            instructions.push(add32());
+            instructions.append(&mut p.produce_wasm(producer));
+            let size = self.argument_types[i].size;
+            instructions.append(&mut wasm_store_copy(size));
+            count += size;
+        }
+        let release_arena = vec![
+            get_global(STACK_POINTER),
+            set_constant(&arena_size.to_string()),
+            sub32(),
+            set_global(STACK_POINTER),
+        ];
+        match &self.return_info {
+            ReturnType::Intermediate { op_aux_no } => {
+                instructions.extend(arena_address);
+                instructions.append(&mut expaux_address(producer, *op_aux_no));
+                instructions.push(set_constant("1"));
+                instructions.push(call(&self.symbol));
+                instructions.extend(release_arena);
+                instructions.push(add_comment("end call bucket"));
+                instructions.append(&mut expaux_address(producer, *op_aux_no));
+            }
+            ReturnType::Final(data) => {
+                instructions.append(&mut wasm_store_prologue(
+                    producer,
+                    &data.dest_address_type,
+                    &data.dest,
+                ));
+                instructions.extend(arena_address);
+                instructions.push(get_local(AUX_DEST));
+                instructions.push(set_constant(&data.context.size.to_string()));
+                instructions.push(call(&self.symbol));
+                instructions.extend(release_arena);
+                instructions.append(&mut wasm_store_epilogue(
+                    &data.dest_address_type,
+                    &data.dest,
+                    data.context.size,
+                ));
+                instructions.push(add_comment("end call bucket"));
+            }
+        }
+        instructions
+    }
+}
@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum OperatorType {
@@ -243,3 +245,146 @@ impl WriteC for ComputeBucket {
         (compute_c, result)
     }
 }
+
+impl WriteRust for ComputeBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        fn get_fr_op(op_type: OperatorType) -> String {
+            match op_type {
+                OperatorType::Add => "fr_add".to_string(),
+                OperatorType::Div => "fr_div".to_string(),
+                OperatorType::Mul => "fr_mul".to_string(),
+                OperatorType::Sub => "fr_sub".to_string(),
+                OperatorType::Pow => "fr_pow".to_string(),
+                OperatorType::IntDiv => "fr_idiv".to_string(),
+                OperatorType::Mod => "fr_mod".to_string(),
+                OperatorType::ShiftL => "fr_shl".to_string(),
+                OperatorType::ShiftR => "fr_shr".to_string(),
+                OperatorType::LesserEq => "fr_leq".to_string(),
+                OperatorType::GreaterEq => "fr_geq".to_string(),
+                OperatorType::Lesser => "fr_lt".to_string(),
+                OperatorType::Greater => "fr_gt".to_string(),
+                OperatorType::Eq(1) => "fr_eq".to_string(),
+                OperatorType::Eq(_) => "fr_eqn".to_string(),
+                OperatorType::NotEq => "fr_neq".to_string(),
+                OperatorType::BoolOr => "fr_lor".to_string(),
+                OperatorType::BoolAnd => "fr_land".to_string(),
+                OperatorType::BitOr => "fr_bor".to_string(),
+                OperatorType::BitAnd => "fr_band".to_string(),
+                OperatorType::BitXor => "fr_bxor".to_string(),
+                OperatorType::PrefixSub => "fr_neg".to_string(),
+                OperatorType::BoolNot => "fr_lnot".to_string(),
+                OperatorType::Complement => "fr_bnot".to_string(),
+                _ => unreachable!(),
+            }
+        }
+
+        let mut compute_rust = vec![];
+        let mut operands = vec![];
+        for instr in &self.stack {
+            let (mut instr_rust, operand) = instr.produce_rust(producer);
+            operands.push(operand);
+            compute_rust.append(&mut instr_rust);
+        }
+        let result = match &self.op {
+            OperatorType::AddAddress => format!("({} + {})", operands[0], operands[1]),
+            OperatorType::MulAddress => format!("({} * {})", operands[0], operands[1]),
+            OperatorType::ToAddress => {
+                let call = format!("fr_to_int({})", first_element(&operands[0]));
+                build_located_error(call, self.line)
+            }
+            _ => {
+                let arguments: Vec<String> = match &self.op {
+                    OperatorType::Eq(n) if *n > 1 => operands
+                        .iter()
+                        .map(|operand| format!("&{}", first_elements(operand, *n)))
+                        .collect(),
+                    _ => operands
+                        .iter()
+                        .map(|operand| first_element(operand))
+                        .collect(),
+                };
+                let mut call = build_call(get_fr_op(self.op), arguments);
+                if let OperatorType::Div | OperatorType::IntDiv | OperatorType::Mod = &self.op {
+                    call = build_located_error(call, self.line);
+                }
+                compute_rust.push(format!(
+                    "{} = {}; // line circom {}",
+                    expaux(self.op_aux_no.to_string()),
+                    call,
+                    self.line
+                ));
+                expaux_value(self.op_aux_no.to_string())
+            }
+        };
+        (compute_rust, result)
+    }
+}
+
+impl WriteWasm for ComputeBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        fn get_fr_op(op_type: OperatorType) -> String {
+            match op_type {
+                OperatorType::Add => "Fr_add".to_string(),
+                OperatorType::Div => "Fr_div".to_string(),
+                OperatorType::Mul => "Fr_mul".to_string(),
+                OperatorType::Sub => "Fr_sub".to_string(),
+                OperatorType::Pow => "Fr_pow".to_string(),
+                OperatorType::IntDiv => "Fr_idiv".to_string(),
+                OperatorType::Mod => "Fr_mod".to_string(),
+                OperatorType::ShiftL => "Fr_shl".to_string(),
+                OperatorType::ShiftR => "Fr_shr".to_string(),
+                OperatorType::LesserEq => "Fr_leq".to_string(),
+                OperatorType::GreaterEq => "Fr_geq".to_string(),
+                OperatorType::Lesser => "Fr_lt".to_string(),
+                OperatorType::Greater => "Fr_gt".to_string(),
+                OperatorType::Eq(1) => "Fr_eq".to_string(),
+                OperatorType::Eq(_) => "Fr_eqn".to_string(),
+                OperatorType::NotEq => "Fr_neq".to_string(),
+                OperatorType::BoolOr => "Fr_lor".to_string(),
+                OperatorType::BoolAnd => "Fr_land".to_string(),
+                OperatorType::BitOr => "Fr_bor".to_string(),
+                OperatorType::BitAnd => "Fr_band".to_string(),
+                OperatorType::BitXor => "Fr_bxor".to_string(),
+                OperatorType::PrefixSub => "Fr_neg".to_string(),
+                OperatorType::BoolNot => "Fr_lnot".to_string(),
+                OperatorType::Complement => "Fr_bnot".to_string(),
+                _ => unreachable!(),
+            }
+        }
+
+        let mut instructions = vec![];
+        match &self.op {
+            OperatorType::AddAddress | OperatorType::MulAddress => {
+                for instr in &self.stack {
+                    instructions.append(&mut instr.produce_wasm(producer));
+                }
+                if let OperatorType::AddAddress = &self.op {
+                    instructions.push(add32());
+                } else {
+                    instructions.push(mul32());
+                }
+            }
+            OperatorType::ToAddress => {
+                instructions.append(&mut self.stack[0].produce_wasm(producer));
+                instructions.push(call("Fr_toInt"));
+            }
+            _ => {
+                instructions.push(add_comment(&format!("line circom {}", self.line)));
+                instructions.append(&mut expaux_address(producer, self.op_aux_no));
+                for instr in &self.stack {
+                    instructions.append(&mut instr.produce_wasm(producer));
+                }
+                if let OperatorType::Eq(n) = &self.op {
+                    if *n > 1 {
+                        instructions.push(set_constant(&n.to_string()));
+                    }
+                }
+                instructions.push(call(&get_fr_op(self.op)));
+                instructions.append(&mut expaux_address(producer, self.op_aux_no));
+            }
+        }
+        instructions
+    }
+}
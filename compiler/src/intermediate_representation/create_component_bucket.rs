@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct CreateCmpBucket {
@@ -195,3 +197,142 @@ impl WriteC for CreateCmpBucket {
         (instructions, "".to_string())
     }
 }
+
+impl WriteRust for CreateCmpBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let complete_array: bool = self.defined_positions.len() == self.number_of_cmp;
+        let mut instructions = vec![];
+        let (mut scmp_idx_instructions, scmp_idx) = self.sub_cmp_id.produce_rust(producer);
+        instructions.append(&mut scmp_idx_instructions);
+        instructions.push("{".to_string());
+        instructions.push(format!("let aux_create = {};", scmp_idx));
+        instructions.push(format!(
+            "let mut aux_cmp_num = {} + {} + 1;",
+            self.component_offset, CTX_INDEX
+        ));
+        instructions.push(format!(
+            "let mut csoffset = {} + {};",
+            MY_SIGNAL_START, self.signal_offset
+        ));
+        if self.number_of_cmp > 1 {
+            instructions.push(format!(
+                "let aux_dimensions: [usize; {}] = {};",
+                self.dimensions.len(),
+                set_list(&self.dimensions)
+            ));
+        }
+        // if the array is complete traverse all its positions
+        if complete_array {
+            instructions.push(format!("for i in 0..{} {{", self.number_of_cmp));
+        }
+        // if not only traverse the defined positions, but i gets the value of the indexed accessed position
+        else {
+            let positions: Vec<usize> = self.defined_positions.iter().map(|(x, _y)| *x).collect();
+            instructions.push(format!(
+                "let aux_positions: [usize; {}] = {};",
+                positions.len(),
+                set_list(&positions)
+            ));
+            instructions.push("for i in aux_positions {".to_string());
+        }
+        if self.number_of_cmp > 1 {
+            instructions.push(format!(
+                "let new_cmp_name = \"{}\".to_string() + &{}.generate_position_array(&aux_dimensions, i);",
+                self.name_subcomponent, CIRCOM_CALC_WIT
+            ));
+        } else {
+            instructions.push(format!(
+                "let new_cmp_name = \"{}\".to_string();",
+                self.name_subcomponent
+            ));
+        }
+        let create_args = vec![
+            "csoffset".to_string(),
+            "aux_cmp_num".to_string(),
+            CIRCOM_CALC_WIT.to_string(),
+            "new_cmp_name".to_string(),
+            MY_ID.to_string(),
+        ];
+        let create_call = build_call(format!("{}_create", self.symbol), create_args);
+        instructions.push(format!("{}?;", create_call));
+        instructions.push(format!(
+            "{} = aux_cmp_num;",
+            my_subcomponents("aux_create + i".to_string())
+        ));
+        instructions.push(format!("csoffset += {};", self.signal_offset_jump));
+        instructions.push(format!("aux_cmp_num += {};", self.component_offset_jump));
+        instructions.push("}".to_string());
+        instructions.push("}".to_string());
+        (instructions, "".to_string())
+    }
+}
+
+impl WriteWasm for CreateCmpBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let complete_array: bool = self.defined_positions.len() == self.number_of_cmp;
+        let mut instructions = self.sub_cmp_id.produce_wasm(producer);
+        instructions.push(set_local(AUX_CREATE));
+        instructions.push(set_constant(&(self.component_offset + 1).to_string()));
+        instructions.push(get_local(CMP));
+        instructions.push(add32());
+        instructions.push(set_local(AUX_CMP_NUM));
+        instructions.push(get_local(SIGNAL_START));
+        instructions.push(set_constant(&self.signal_offset.to_string()));
+        instructions.push(add32());
+        instructions.push(set_local(CSOFFSET));
+        let create = |position: Vec<String>| {
+            let mut create = vec![
+                get_local(CSOFFSET),
+                get_local(AUX_CMP_NUM),
+                get_local(CMP),
+                call(&format!("{}_create", self.symbol)),
+                get_local(SUBCMPS),
+                get_local(AUX_CREATE),
+            ];
+            create.extend(position);
+            create.push(add32());
+            create.push(set_constant("4"));
+            create.push(mul32());
+            create.push(add32());
+            create.push(get_local(AUX_CMP_NUM));
+            create.push(store32(0));
+            create.push(get_local(CSOFFSET));
+            create.push(set_constant(&self.signal_offset_jump.to_string()));
+            create.push(add32());
+            create.push(set_local(CSOFFSET));
+            create.push(get_local(AUX_CMP_NUM));
+            create.push(set_constant(&self.component_offset_jump.to_string()));
+            create.push(add32());
+            create.push(set_local(AUX_CMP_NUM));
+            create
+        };
+        // if the array is complete traverse all its positions
+        if complete_array && self.number_of_cmp > 1 {
+            instructions.push(set_constant("0"));
+            instructions.push(set_local(CREATE_INDEX));
+            instructions.push(add_block());
+            instructions.push(add_loop());
+            instructions.push(get_local(CREATE_INDEX));
+            instructions.push(set_constant(&self.number_of_cmp.to_string()));
+            instructions.push(ge32_u());
+            instructions.push(br_if(1));
+            instructions.append(&mut create(vec![get_local(CREATE_INDEX)]));
+            instructions.push(get_local(CREATE_INDEX));
+            instructions.push(set_constant("1"));
+            instructions.push(add32());
+            instructions.push(set_local(CREATE_INDEX));
+            instructions.push(br(0));
+            instructions.push(add_end());
+            instructions.push(add_end());
+        }
+        // if not only traverse the defined positions
+        else {
+            for (position, _) in &self.defined_positions {
+                instructions.append(&mut create(vec![set_constant(&position.to_string())]));
+            }
+        }
+        instructions
+    }
+}
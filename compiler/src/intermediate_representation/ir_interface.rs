@@ -16,6 +16,8 @@ pub use super::value_bucket::ValueBucket;
 
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 pub trait IntoInstruction {
     fn into_instruction(self) -> Instruction;
@@ -127,3 +129,41 @@ impl ToString for Instruction {
         }
     }
 }
+
+impl WriteRust for Instruction {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use Instruction::*;
+        match self {
+            Value(v) => v.produce_rust(producer),
+            Load(v) => v.produce_rust(producer),
+            Store(v) => v.produce_rust(producer),
+            Compute(v) => v.produce_rust(producer),
+            Call(v) => v.produce_rust(producer),
+            Branch(v) => v.produce_rust(producer),
+            Return(v) => v.produce_rust(producer),
+            Loop(v) => v.produce_rust(producer),
+            Assert(v) => v.produce_rust(producer),
+            CreateCmp(v) => v.produce_rust(producer),
+            Log(v) => v.produce_rust(producer),
+        }
+    }
+}
+
+impl WriteWasm for Instruction {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use Instruction::*;
+        match self {
+            Value(v) => v.produce_wasm(producer),
+            Load(v) => v.produce_wasm(producer),
+            Store(v) => v.produce_wasm(producer),
+            Compute(v) => v.produce_wasm(producer),
+            Call(v) => v.produce_wasm(producer),
+            Branch(v) => v.produce_wasm(producer),
+            Return(v) => v.produce_wasm(producer),
+            Loop(v) => v.produce_wasm(producer),
+            Assert(v) => v.produce_wasm(producer),
+            CreateCmp(v) => v.produce_wasm(producer),
+            Log(v) => v.produce_wasm(producer),
+        }
+    }
+}
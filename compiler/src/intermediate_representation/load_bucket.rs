@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct LoadBucket {
@@ -211,3 +213,100 @@ impl WriteC for LoadBucket {
         (prologue, access)
     }
 }
+
+impl WriteRust for LoadBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let mut prologue = vec![];
+        let cmp_index_ref;
+        if let AddressType::SubcmpSignal { cmp_address, .. } = &self.address_type {
+            let (mut cmp_prologue, cmp_index) = cmp_address.produce_rust(producer);
+            prologue.append(&mut cmp_prologue);
+            cmp_index_ref = cmp_index;
+        } else {
+            cmp_index_ref = "".to_string();
+        }
+        let sub_component_pos_in_memory = my_subcomponents(cmp_index_ref);
+
+        let (mut src_prologue, src_index) = match &self.src {
+            LocationRule::Indexed { location, .. } => location.produce_rust(producer),
+            LocationRule::Mapped {
+                signal_code,
+                indexes,
+            } => {
+                let mut map_prologue = vec![];
+                let io_def = io_signal_info(sub_component_pos_in_memory.clone(), *signal_code);
+                let mut map_access = format!("{}.offset", io_def);
+                if !indexes.is_empty() {
+                    let (mut index_code_0, mut map_index) = indexes[0].produce_rust(producer);
+                    map_prologue.append(&mut index_code_0);
+                    for (i, index) in indexes.iter().enumerate().skip(1) {
+                        let (mut index_code, index_exp) = index.produce_rust(producer);
+                        map_prologue.append(&mut index_code);
+                        map_index = format!(
+                            "({}) * {}.lengths[{}] + {}",
+                            map_index,
+                            io_def,
+                            i - 1,
+                            index_exp
+                        );
+                    }
+                    map_access = format!("{} + {}", map_access, map_index);
+                }
+                (map_prologue, map_access)
+            }
+        };
+        prologue.append(&mut src_prologue);
+        let access = match &self.address_type {
+            AddressType::Variable => lvar(src_index),
+            AddressType::Signal => signal_values(src_index),
+            AddressType::SubcmpSignal { .. } => {
+                subcmp_signal_values(sub_component_pos_in_memory, src_index)
+            }
+        };
+        (prologue, access)
+    }
+}
+
+impl WriteWasm for LoadBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let sub_id = if let AddressType::SubcmpSignal { cmp_address, .. } = &self.address_type {
+            subcomponent_id(cmp_address.produce_wasm(producer))
+        } else {
+            vec![]
+        };
+        let src_index = match &self.src {
+            LocationRule::Indexed { location, .. } => location.produce_wasm(producer),
+            LocationRule::Mapped {
+                signal_code,
+                indexes,
+            } => {
+                let indexes = indexes.iter().map(|i| i.produce_wasm(producer)).collect();
+                io_signal_offset(&sub_id, *signal_code, indexes)
+            }
+        };
+        let mut instructions = vec![];
+        match &self.address_type {
+            AddressType::Variable => {
+                instructions.extend(src_index);
+                instructions.append(&mut element_address(producer, LVAR));
+            }
+            AddressType::Signal => {
+                instructions.extend(src_index);
+                instructions.push(get_local(SIGNAL_START));
+                instructions.push(add32());
+                instructions.append(&mut signal_address(producer));
+            }
+            AddressType::SubcmpSignal { .. } => {
+                instructions.extend(sub_id);
+                instructions.append(&mut component_address());
+                instructions.push(load32(COMPONENT_SIGNAL_START));
+                instructions.extend(src_index);
+                instructions.push(add32());
+                instructions.append(&mut signal_address(producer));
+            }
+        }
+        instructions
+    }
+}
@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub enum LogBucketArg {
@@ -112,3 +114,52 @@ impl WriteC for LogBucket {
         (log_c, "".to_string())
     }
 }
+
+impl WriteRust for LogBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let mut log_rust = Vec::new();
+        for (index, logarg) in self.argsprint.iter().enumerate() {
+            match logarg {
+                LogBucketArg::LogExp(exp) => {
+                    let (mut argument_code, argument_result) = exp.produce_rust(producer);
+                    log_rust.append(&mut argument_code);
+                    log_rust.push(format!(
+                        "print!(\"{{}}\", fr_element2str({}));",
+                        first_element(&argument_result)
+                    ));
+                }
+                LogBucketArg::LogStr(string_id) => {
+                    let string_value = &producer.get_string_table()[*string_id];
+                    log_rust.push(format!("print!(\"{{}}\", \"{}\");", string_value));
+                }
+            }
+            if index != self.argsprint.len() - 1 {
+                log_rust.push("print!(\" \");".to_string());
+            }
+        }
+        log_rust.push("println!();".to_string());
+        (log_rust, "".to_string())
+    }
+}
+
+impl WriteWasm for LogBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let mut instructions = vec![];
+        for logarg in &self.argsprint {
+            match logarg {
+                LogBucketArg::LogExp(exp) => {
+                    instructions.append(&mut exp.produce_wasm(producer));
+                    instructions.push(call("logValue"));
+                }
+                LogBucketArg::LogStr(string_id) => {
+                    instructions.push(set_constant(&string_id.to_string()));
+                    instructions.push(call("logString"));
+                }
+            }
+        }
+        instructions.push(call("logEnd"));
+        instructions
+    }
+}
@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct LoopBucket {
@@ -65,3 +67,39 @@ impl WriteC for LoopBucket {
         (loop_c, "".to_string())
     }
 }
+
+impl WriteRust for LoopBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let (continue_code, continue_result) = self.continue_condition.produce_rust(producer);
+        let continue_result = format!("fr_is_true({})", first_element(&continue_result));
+        let mut body = vec![];
+        for instr in &self.body {
+            let (mut instr_code, _) = instr.produce_rust(producer);
+            body.append(&mut instr_code);
+        }
+        body.append(&mut continue_code.clone());
+        let while_loop = format!("while {} {{\n{}}}", continue_result, merge_code(body));
+        let mut loop_rust = continue_code;
+        loop_rust.push(while_loop);
+        (loop_rust, "".to_string())
+    }
+}
+
+impl WriteWasm for LoopBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let mut instructions = vec![add_block(), add_loop()];
+        instructions.append(&mut self.continue_condition.produce_wasm(producer));
+        instructions.push(call("Fr_isTrue"));
+        instructions.push(eqz32());
+        instructions.push(br_if(1));
+        for instr in &self.body {
+            instructions.append(&mut instr.produce_wasm(producer));
+        }
+        instructions.push(br(0));
+        instructions.push(add_end());
+        instructions.push(add_end());
+        instructions
+    }
+}
@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct ReturnBucket {
@@ -71,3 +73,37 @@ impl WriteC for ReturnBucket {
         (instructions, "".to_string())
     }
 }
+
+impl WriteRust for ReturnBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let mut instructions = vec![];
+        instructions.push("// return bucket".to_string());
+        let (mut instructions_value, src) = self.value.produce_rust(producer);
+        instructions.append(&mut instructions_value);
+        instructions.push(format!(
+            "return Ok({}.to_vec());",
+            first_elements(&src, self.with_size)
+        ));
+        (instructions, "".to_string())
+    }
+}
+
+impl WriteWasm for ReturnBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let mut instructions = vec![add_comment("return bucket"), get_local(DESTINATION)];
+        instructions.append(&mut self.value.produce_wasm(producer));
+        if self.with_size > 1 {
+            instructions.push(get_local(DESTINATION_SIZE));
+            instructions.push(call("Fr_copyn"));
+        } else {
+            instructions.push(call("Fr_copy"));
+        }
+        // release the frame of the function
+        instructions.push(get_local(EXPAUX));
+        instructions.push(set_global(STACK_POINTER));
+        instructions.push(add_return());
+        instructions
+    }
+}
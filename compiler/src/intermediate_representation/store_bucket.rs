@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct StoreBucket {
@@ -436,3 +438,310 @@ impl WriteC for StoreBucket {
         (prologue, "".to_string())
     }
 }
+
+// Opens a block and binds aux_dest to the first position written by a store, the
+// block is closed after rust_store_epilogue. Shared with the final call buckets.
+pub fn rust_store_prologue(
+    producer: &RustProducer,
+    dest_address_type: &AddressType,
+    dest: &LocationRule,
+) -> Vec<String> {
+    use rust_code_generator::*;
+    let mut prologue = vec![];
+    if let AddressType::SubcmpSignal { cmp_address, .. } = dest_address_type {
+        let (mut cmp_prologue, cmp_index) = cmp_address.produce_rust(producer);
+        prologue.append(&mut cmp_prologue);
+        prologue.push("{".to_string());
+        prologue.push(format!(
+            "let {} = {};",
+            SUB_COMPONENT,
+            my_subcomponents(cmp_index)
+        ));
+    } else {
+        prologue.push("{".to_string());
+    }
+    let (mut dest_prologue, dest_index) = match dest {
+        LocationRule::Indexed { location, .. } => location.produce_rust(producer),
+        LocationRule::Mapped {
+            signal_code,
+            indexes,
+        } => {
+            let mut map_prologue = vec![];
+            let io_def = io_signal_info(SUB_COMPONENT.to_string(), *signal_code);
+            let mut map_access = format!("{}.offset", io_def);
+            if !indexes.is_empty() {
+                let (mut index_code_0, mut map_index) = indexes[0].produce_rust(producer);
+                map_prologue.append(&mut index_code_0);
+                for (i, index) in indexes.iter().enumerate().skip(1) {
+                    let (mut index_code, index_exp) = index.produce_rust(producer);
+                    map_prologue.append(&mut index_code);
+                    map_index = format!(
+                        "({}) * {}.lengths[{}] + {}",
+                        map_index,
+                        io_def,
+                        i - 1,
+                        index_exp
+                    );
+                }
+                map_access = format!("{} + {}", map_access, map_index);
+            }
+            (map_prologue, map_access)
+        }
+    };
+    prologue.append(&mut dest_prologue);
+    let dest_position = match dest_address_type {
+        AddressType::Variable => dest_index,
+        AddressType::Signal => format!("{} + {}", MY_SIGNAL_START, dest_index),
+        AddressType::SubcmpSignal { .. } => format!(
+            "{}.signal_start + {}",
+            component_memory(SUB_COMPONENT.to_string()),
+            dest_index
+        ),
+    };
+    prologue.push(format!("let {} = {};", AUX_DEST, dest_position));
+    prologue
+}
+
+// Copies size elements of src to aux_dest, src must not borrow the destination storage
+pub fn rust_store_copy(dest_address_type: &AddressType, src: &str, size: usize) -> String {
+    use rust_code_generator::*;
+    let storage = match dest_address_type {
+        AddressType::Variable => L_VAR_STACK.to_string(),
+        _ => format!("{}.signal_values", CIRCOM_CALC_WIT),
+    };
+    if size > 1 {
+        format!(
+            "{}[{}..{} + {}].clone_from_slice(&{});",
+            storage,
+            AUX_DEST,
+            AUX_DEST,
+            size,
+            first_elements(src, size)
+        )
+    } else {
+        format!("{}[{}] = {}[0].clone();", storage, AUX_DEST, src)
+    }
+}
+
+// Updates the input counter of the subcomponent, running it when it gets all its
+// inputs, and closes the block opened by rust_store_prologue
+pub fn rust_store_epilogue(
+    dest_address_type: &AddressType,
+    dest: &LocationRule,
+    size: usize,
+) -> Vec<String> {
+    use rust_code_generator::*;
+    let mut epilogue = vec![];
+    if let AddressType::SubcmpSignal {
+        input_information, ..
+    } = dest_address_type
+    {
+        let sub_cmp_counter = format!(
+            "{}.input_counter",
+            component_memory(SUB_COMPONENT.to_string())
+        );
+        let InputInformation::Input { status } = input_information else {
+            unreachable!("stores in subcomponents are always inputs")
+        };
+        epilogue.push(format!("{} -= {};", sub_cmp_counter, size));
+        let sub_cmp_call_name = match dest {
+            LocationRule::Indexed {
+                template_header, ..
+            } => {
+                format!("{}_run", template_header.as_ref().unwrap())
+            }
+            LocationRule::Mapped { .. } => format!(
+                "{}[{}.template_id]",
+                FUNCTION_TABLE,
+                component_memory(SUB_COMPONENT.to_string())
+            ),
+        };
+        let sub_cmp_call = format!(
+            "{}?;",
+            build_call(
+                sub_cmp_call_name,
+                vec![SUB_COMPONENT.to_string(), CIRCOM_CALC_WIT.to_string()]
+            )
+        );
+        match status {
+            StatusInput::NoLast => {
+                epilogue.push("// no need to run sub component".to_string());
+                epilogue.push(format!("assert!({} > 0);", sub_cmp_counter));
+            }
+            StatusInput::Last => {
+                epilogue.push("// need to run sub component".to_string());
+                epilogue.push(format!("assert_eq!({}, 0);", sub_cmp_counter));
+                epilogue.push(sub_cmp_call);
+            }
+            StatusInput::Unknown => {
+                epilogue.push("// run sub component if needed".to_string());
+                epilogue.push(build_conditional(
+                    format!("{} == 0", sub_cmp_counter),
+                    vec![sub_cmp_call],
+                    vec![],
+                ));
+            }
+        }
+    }
+    epilogue.push("}".to_string());
+    epilogue
+}
+
+impl WriteRust for StoreBucket {
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String) {
+        let mut prologue = rust_store_prologue(producer, &self.dest_address_type, &self.dest);
+        prologue.push("// load src".to_string());
+        let (mut src_prologue, src) = self.src.produce_rust(producer);
+        prologue.append(&mut src_prologue);
+        prologue.push("// end load src".to_string());
+        if self.context.size > 1 {
+            // the source may overlap with the destination
+            prologue.push(format!(
+                "let aux_src = {}.to_vec();",
+                rust_code_generator::first_elements(&src, self.context.size)
+            ));
+            prologue.push(rust_store_copy(
+                &self.dest_address_type,
+                "aux_src",
+                self.context.size,
+            ));
+        } else {
+            prologue.push(rust_store_copy(&self.dest_address_type, &src, 1));
+        }
+        let mut epilogue =
+            rust_store_epilogue(&self.dest_address_type, &self.dest, self.context.size);
+        prologue.append(&mut epilogue);
+        (prologue, "".to_string())
+    }
+}
+
+// Stores the id of the destination subcomponent, if any, in SUB_COMPONENT and the
+// address of the destination in AUX_DEST
+pub fn wasm_store_prologue(
+    producer: &WASMProducer,
+    dest_address_type: &AddressType,
+    dest: &LocationRule,
+) -> Vec<String> {
+    use wasm_code_generator::*;
+    let mut prologue = vec![];
+    if let AddressType::SubcmpSignal { cmp_address, .. } = dest_address_type {
+        prologue.append(&mut subcomponent_id(cmp_address.produce_wasm(producer)));
+        prologue.push(set_local(SUB_COMPONENT));
+    }
+    let dest_index = match dest {
+        LocationRule::Indexed { location, .. } => location.produce_wasm(producer),
+        LocationRule::Mapped {
+            signal_code,
+            indexes,
+        } => {
+            let indexes = indexes.iter().map(|i| i.produce_wasm(producer)).collect();
+            io_signal_offset(&[get_local(SUB_COMPONENT)], *signal_code, indexes)
+        }
+    };
+    match dest_address_type {
+        AddressType::Variable => {
+            prologue.extend(dest_index);
+            prologue.append(&mut element_address(producer, LVAR));
+        }
+        AddressType::Signal => {
+            prologue.extend(dest_index);
+            prologue.push(get_local(SIGNAL_START));
+            prologue.push(add32());
+            prologue.append(&mut signal_address(producer));
+        }
+        AddressType::SubcmpSignal { .. } => {
+            prologue.push(get_local(SUB_COMPONENT));
+            prologue.append(&mut component_address());
+            prologue.push(load32(COMPONENT_SIGNAL_START));
+            prologue.extend(dest_index);
+            prologue.push(add32());
+            prologue.append(&mut signal_address(producer));
+        }
+    }
+    prologue.push(set_local(AUX_DEST));
+    prologue
+}
+
+// Copies size elements, the destination and the source addresses must be on the stack
+pub fn wasm_store_copy(size: usize) -> Vec<String> {
+    use wasm_code_generator::*;
+    if size > 1 {
+        vec![set_constant(&size.to_string()), call("Fr_copyn")]
+    } else {
+        vec![call("Fr_copy")]
+    }
+}
+
+// Updates the input counter of the subcomponent, running it when it gets all its inputs
+pub fn wasm_store_epilogue(
+    dest_address_type: &AddressType,
+    dest: &LocationRule,
+    size: usize,
+) -> Vec<String> {
+    use wasm_code_generator::*;
+    let mut epilogue = vec![];
+    if let AddressType::SubcmpSignal {
+        input_information, ..
+    } = dest_address_type
+    {
+        let InputInformation::Input { status } = input_information else {
+            unreachable!("stores in subcomponents are always inputs")
+        };
+        let mut sub_cmp_address = vec![get_local(SUB_COMPONENT)];
+        sub_cmp_address.append(&mut component_address());
+        epilogue.extend(sub_cmp_address.clone());
+        epilogue.extend(sub_cmp_address.clone());
+        epilogue.push(load32(COMPONENT_INPUT_COUNTER));
+        epilogue.push(set_constant(&size.to_string()));
+        epilogue.push(sub32());
+        epilogue.push(store32(COMPONENT_INPUT_COUNTER));
+        let mut sub_cmp_call = vec![get_local(SUB_COMPONENT)];
+        match dest {
+            LocationRule::Indexed {
+                template_header, ..
+            } => {
+                sub_cmp_call.push(call(&format!("{}_run", template_header.as_ref().unwrap())));
+            }
+            LocationRule::Mapped { .. } => {
+                sub_cmp_call.extend(sub_cmp_address.clone());
+                sub_cmp_call.push(load32(COMPONENT_TEMPLATE_ID));
+                sub_cmp_call.push(call_indirect());
+            }
+        }
+        match status {
+            StatusInput::NoLast => {
+                epilogue.push(add_comment("no need to run sub component"));
+            }
+            StatusInput::Last => {
+                epilogue.push(add_comment("need to run sub component"));
+                epilogue.append(&mut sub_cmp_call);
+            }
+            StatusInput::Unknown => {
+                epilogue.push(add_comment("run sub component if needed"));
+                epilogue.append(&mut sub_cmp_address);
+                epilogue.push(load32(COMPONENT_INPUT_COUNTER));
+                epilogue.push(eqz32());
+                epilogue.push(add_if());
+                epilogue.append(&mut sub_cmp_call);
+                epilogue.push(add_end());
+            }
+        }
+    }
+    epilogue
+}
+
+impl WriteWasm for StoreBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        let mut instructions = wasm_store_prologue(producer, &self.dest_address_type, &self.dest);
+        instructions.push(get_local(AUX_DEST));
+        instructions.append(&mut self.src.produce_wasm(producer));
+        instructions.append(&mut wasm_store_copy(self.context.size));
+        instructions.append(&mut wasm_store_epilogue(
+            &self.dest_address_type,
+            &self.dest,
+            self.context.size,
+        ));
+        instructions
+    }
+}
@@ -1,6 +1,8 @@
 use super::ir_interface::*;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
+use code_producers::rust_elements::*;
+use code_producers::wasm_elements::*;
 
 #[derive(Clone)]
 pub struct ValueBucket {
@@ -59,3 +61,26 @@ impl WriteC for ValueBucket {
         }
     }
 }
+
+impl WriteRust for ValueBucket {
+    fn produce_rust(&self, _producer: &RustProducer) -> (Vec<String>, String) {
+        use rust_code_generator::*;
+        let index = self.value.to_string();
+        match self.parse_as {
+            ValueType::U32 => (vec![], index),
+            ValueType::BigInt => (vec![], circuit_constants(index)),
+        }
+    }
+}
+
+impl WriteWasm for ValueBucket {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+        use wasm_code_generator::*;
+        match self.parse_as {
+            ValueType::U32 => vec![set_constant(&self.value.to_string())],
+            ValueType::BigInt => vec![set_constant(
+                &producer.get_constant_address(self.value).to_string(),
+            )],
+        }
+    }
+}
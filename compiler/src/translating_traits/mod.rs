@@ -1,4 +1,6 @@
 use code_producers::c_elements::*;
+use code_producers::rust_elements::{self, RustProducer};
+use code_producers::wasm_elements::{self, WASMProducer};
 use std::io::Write;
 
 pub trait WriteC {
@@ -15,3 +17,34 @@ pub trait WriteC {
         writer.flush().map_err(|_| {})
     }
 }
+
+pub trait WriteRust {
+    /*
+        returns (x, y) where:
+            x: rust instructions produced.
+            y: if the instructions in x compute some value, that value is stored in y.
+               Field values are slices starting at the value, addresses are usize expressions.
+    */
+    fn produce_rust(&self, producer: &RustProducer) -> (Vec<String>, String);
+    fn write_rust<T: Write>(&self, writer: &mut T, producer: &RustProducer) -> Result<(), ()> {
+        let (rust_instructions, _) = self.produce_rust(producer);
+        let code = rust_elements::rust_code_generator::merge_code(rust_instructions);
+        writer.write_all(code.as_bytes()).map_err(|_| {})?;
+        writer.flush().map_err(|_| {})
+    }
+}
+
+pub trait WriteWasm {
+    /*
+        returns the wasm instructions produced.
+        If the instructions compute some value it is left on the stack: the address
+        of the first element for field values and the value itself for addresses.
+    */
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String>;
+    fn write_wasm<T: Write>(&self, writer: &mut T, producer: &WASMProducer) -> Result<(), ()> {
+        let wasm_instructions = self.produce_wasm(producer);
+        let code = wasm_elements::wasm_code_generator::merge_code(wasm_instructions);
+        writer.write_all(code.as_bytes()).map_err(|_| {})?;
+        writer.flush().map_err(|_| {})
+    }
+}
@@ -0,0 +1,664 @@
+use crate::circuit_design::circuit::Circuit;
+use crate::circuit_design::function::FunctionCodeInfo;
+use crate::circuit_design::template::TemplateCodeInfo;
+use crate::intermediate_representation::ir_interface::*;
+use circom_algebra::modular_arithmetic::{self, ArithmeticError};
+use num_bigint_dig::BigInt;
+use num_traits::{ToPrimitive, Zero};
+use program_structure::diagnostics::print_progress;
+use std::collections::HashMap;
+
+// Result of evaluating an instruction: addresses are plain integers, while field
+// values are the elements placed in consecutive positions starting at the accessed one
+enum Value {
+    Address(usize),
+    Field(Vec<BigInt>),
+}
+
+enum Location {
+    Variable(usize),
+    Signal(usize),
+}
+
+#[derive(Default)]
+struct ComponentMemory {
+    template: usize,
+    signal_start: usize,
+    input_counter: usize,
+    name: String,
+    father: usize,
+    subcomponents: Vec<usize>,
+}
+
+// Equivalent to the local state of the <template>_run and function bodies in C
+struct Frame<'a> {
+    component: usize,
+    signal_start: usize,
+    name: &'a str,
+    vars: Vec<BigInt>,
+}
+
+pub struct Execution<'a> {
+    circuit: &'a Circuit,
+    field: BigInt,
+    constants: Vec<BigInt>,
+    template_headers: HashMap<&'a str, usize>,
+    function_headers: HashMap<&'a str, usize>,
+    signals: Vec<BigInt>,
+    components: Vec<ComponentMemory>,
+}
+
+fn arithmetic_error(error: ArithmeticError) -> String {
+    match error {
+        ArithmeticError::DivisionByZero => "Division by zero".to_string(),
+        ArithmeticError::BitOverFlowInShift => "Overflow in shift".to_string(),
+    }
+}
+
+fn error_at(frame: &Frame, line: usize, message: &str) -> String {
+    format!(
+        "{} in template/function {} line {}",
+        message, frame.name, line
+    )
+}
+
+impl<'a> Execution<'a> {
+    pub fn new(circuit: &'a Circuit) -> Execution<'a> {
+        let producer = &circuit.c_producer;
+        let field = BigInt::parse_bytes(producer.prime.as_bytes(), 10).unwrap();
+        let constants = producer
+            .get_field_constant_list()
+            .iter()
+            .map(|c| BigInt::parse_bytes(c.as_bytes(), 10).unwrap())
+            .collect();
+        let template_headers = circuit
+            .templates
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.header.as_str(), i))
+            .collect();
+        let function_headers = circuit
+            .functions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.header.as_str(), i))
+            .collect();
+        let mut signals = vec![BigInt::zero(); producer.get_total_number_of_signals()];
+        if !signals.is_empty() {
+            signals[0] = BigInt::from(1);
+        }
+        let mut components = Vec::new();
+        components.resize_with(
+            producer.get_number_of_components(),
+            ComponentMemory::default,
+        );
+        Execution {
+            circuit,
+            field,
+            constants,
+            template_headers,
+            function_headers,
+            signals,
+            components,
+        }
+    }
+
+    pub fn set_input(&mut self, signal: usize, value: &BigInt) {
+        let reduced = ((value % &self.field) + &self.field) % &self.field;
+        self.signals[signal] = reduced;
+    }
+
+    pub fn run(&mut self) -> Result<(), String> {
+        let producer = &self.circuit.c_producer;
+        let main = *self
+            .template_headers
+            .get(producer.get_main_header())
+            .ok_or_else(|| "The main template was not found".to_string())?;
+        // The main component has no father and starts after the constant signal
+        self.create_component(main, 0, 1, "main".to_string(), 0)?;
+        if self.circuit.templates[main].number_of_inputs > 0 {
+            self.run_component(0)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_witness(&self) -> Vec<BigInt> {
+        self.circuit
+            .c_producer
+            .get_witness_to_signal_list()
+            .iter()
+            .map(|s| self.signals[*s].clone())
+            .collect()
+    }
+
+    fn create_component(
+        &mut self,
+        template: usize,
+        id: usize,
+        signal_start: usize,
+        name: String,
+        father: usize,
+    ) -> Result<(), String> {
+        let code = &self.circuit.templates[template];
+        if id >= self.components.len() {
+            return Err(format!("Component {} is out of the component memory", name));
+        }
+        self.components[id] = ComponentMemory {
+            template,
+            signal_start,
+            input_counter: code.number_of_inputs,
+            name,
+            father,
+            subcomponents: vec![0; code.number_of_components],
+        };
+        if code.number_of_inputs == 0 {
+            self.run_component(id)?;
+        }
+        Ok(())
+    }
+
+    fn run_component(&mut self, id: usize) -> Result<(), String> {
+        let circuit = self.circuit;
+        let code: &TemplateCodeInfo = &circuit.templates[self.components[id].template];
+        let mut frame = Frame {
+            component: id,
+            signal_start: self.components[id].signal_start,
+            name: &code.name,
+            vars: vec![BigInt::zero(); code.var_stack_depth],
+        };
+        self.execute_list(&code.body, &mut frame)?;
+        Ok(())
+    }
+
+    fn trace(&self, id: usize) -> String {
+        if id == 0 {
+            self.components[id].name.clone()
+        } else {
+            let father = self.components[id].father;
+            format!("{}.{}", self.trace(father), self.components[id].name)
+        }
+    }
+
+    fn execute_list(
+        &mut self,
+        list: &'a InstructionList,
+        frame: &mut Frame<'a>,
+    ) -> Result<Option<Vec<BigInt>>, String> {
+        for instruction in list {
+            if let Some(returned) = self.execute(instruction, frame)? {
+                return Ok(Some(returned));
+            }
+        }
+        Ok(None)
+    }
+
+    // Runs a statement, returns the returned values if it was a return
+    fn execute(
+        &mut self,
+        instruction: &'a Instruction,
+        frame: &mut Frame<'a>,
+    ) -> Result<Option<Vec<BigInt>>, String> {
+        use Instruction::*;
+        match instruction {
+            Store(bucket) => {
+                self.execute_store(bucket, frame)?;
+                Ok(None)
+            }
+            Call(bucket) => {
+                self.evaluate_call(bucket, frame)?;
+                Ok(None)
+            }
+            Branch(bucket) => {
+                let condition = self.evaluate_condition(&bucket.cond, frame)?;
+                if condition {
+                    self.execute_list(&bucket.if_branch, frame)
+                } else {
+                    self.execute_list(&bucket.else_branch, frame)
+                }
+            }
+            Loop(bucket) => {
+                while self.evaluate_condition(&bucket.continue_condition, frame)? {
+                    if let Some(returned) = self.execute_list(&bucket.body, frame)? {
+                        return Ok(Some(returned));
+                    }
+                }
+                Ok(None)
+            }
+            Return(bucket) => {
+                let values = self.evaluate_field(&bucket.value, frame)?;
+                Ok(Some(values))
+            }
+            Assert(bucket) => {
+                if !self.evaluate_condition(&bucket.evaluate, frame)? {
+                    return Err(format!(
+                        "Failed assert in template/function {} line {}. Followed trace of components: {}",
+                        frame.name,
+                        bucket.line,
+                        self.trace(frame.component)
+                    ));
+                }
+                Ok(None)
+            }
+            Log(bucket) => {
+                self.execute_log(bucket, frame)?;
+                Ok(None)
+            }
+            CreateCmp(bucket) => {
+                self.execute_create_component(bucket, frame)?;
+                Ok(None)
+            }
+            Value(_) | Load(_) | Compute(_) => {
+                self.evaluate(instruction, frame)?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn evaluate(
+        &mut self,
+        instruction: &'a Instruction,
+        frame: &mut Frame<'a>,
+    ) -> Result<Value, String> {
+        use Instruction::*;
+        match instruction {
+            Value(bucket) => match bucket.parse_as {
+                ValueType::U32 => Ok(self::Value::Address(bucket.value)),
+                ValueType::BigInt => {
+                    let constant = self.constants[bucket.value].clone();
+                    Ok(self::Value::Field(vec![constant]))
+                }
+            },
+            Load(bucket) => {
+                let (location, _) =
+                    self.resolve_location(&bucket.address_type, &bucket.src, bucket.line, frame)?;
+                let values = self.read(location, bucket.context.size, bucket.line, frame)?;
+                Ok(self::Value::Field(values))
+            }
+            Compute(bucket) => self.evaluate_compute(bucket, frame),
+            Call(bucket) => {
+                let values = self.evaluate_call(bucket, frame)?;
+                Ok(self::Value::Field(values))
+            }
+            _ => Err(error_at(
+                frame,
+                instruction.get_line(),
+                "Instruction without value",
+            )),
+        }
+    }
+
+    fn evaluate_field(
+        &mut self,
+        instruction: &'a Instruction,
+        frame: &mut Frame<'a>,
+    ) -> Result<Vec<BigInt>, String> {
+        match self.evaluate(instruction, frame)? {
+            Value::Field(values) => Ok(values),
+            Value::Address(_) => Err(error_at(
+                frame,
+                instruction.get_line(),
+                "Expected a field element",
+            )),
+        }
+    }
+
+    fn evaluate_address(
+        &mut self,
+        instruction: &'a Instruction,
+        frame: &mut Frame<'a>,
+    ) -> Result<usize, String> {
+        match self.evaluate(instruction, frame)? {
+            Value::Address(address) => Ok(address),
+            Value::Field(_) => Err(error_at(
+                frame,
+                instruction.get_line(),
+                "Expected an address",
+            )),
+        }
+    }
+
+    // Same as Fr_isTrue
+    fn evaluate_condition(
+        &mut self,
+        instruction: &'a Instruction,
+        frame: &mut Frame<'a>,
+    ) -> Result<bool, String> {
+        let values = self.evaluate_field(instruction, frame)?;
+        Ok(!values[0].is_zero())
+    }
+
+    fn evaluate_compute(
+        &mut self,
+        bucket: &'a ComputeBucket,
+        frame: &mut Frame<'a>,
+    ) -> Result<Value, String> {
+        use OperatorType::*;
+        if bucket.op.is_address_op() {
+            return match bucket.op {
+                ToAddress => {
+                    let values = self.evaluate_field(&bucket.stack[0], frame)?;
+                    let address = values[0]
+                        .to_usize()
+                        .ok_or_else(|| error_at(frame, bucket.line, "Address out of range"))?;
+                    Ok(Value::Address(address))
+                }
+                AddAddress => {
+                    let left = self.evaluate_address(&bucket.stack[0], frame)?;
+                    let right = self.evaluate_address(&bucket.stack[1], frame)?;
+                    Ok(Value::Address(left + right))
+                }
+                _ => {
+                    let left = self.evaluate_address(&bucket.stack[0], frame)?;
+                    let right = self.evaluate_address(&bucket.stack[1], frame)?;
+                    Ok(Value::Address(left * right))
+                }
+            };
+        }
+        let mut operands = Vec::with_capacity(bucket.stack.len());
+        for operand in &bucket.stack {
+            operands.push(self.evaluate_field(operand, frame)?);
+        }
+        let field = &self.field;
+        let result = match bucket.op {
+            Eq(n) => {
+                let equal = operands[0][..n] == operands[1][..n];
+                BigInt::from(equal as u8)
+            }
+            PrefixSub => modular_arithmetic::prefix_sub(&operands[0][0], field),
+            BoolNot => modular_arithmetic::not(&operands[0][0], field),
+            Complement => modular_arithmetic::complement_254(&operands[0][0], field),
+            _ => {
+                let left = &operands[0][0];
+                let right = &operands[1][0];
+                let result = match bucket.op {
+                    Mul => Ok(modular_arithmetic::mul(left, right, field)),
+                    Div => modular_arithmetic::div(left, right, field),
+                    Add => Ok(modular_arithmetic::add(left, right, field)),
+                    Sub => Ok(modular_arithmetic::sub(left, right, field)),
+                    Pow => Ok(modular_arithmetic::pow(left, right, field)),
+                    IntDiv => modular_arithmetic::idiv(left, right, field),
+                    Mod => modular_arithmetic::mod_op(left, right, field),
+                    ShiftL => modular_arithmetic::shift_l(left, right, field),
+                    ShiftR => modular_arithmetic::shift_r(left, right, field),
+                    LesserEq => Ok(modular_arithmetic::lesser_eq(left, right, field)),
+                    GreaterEq => Ok(modular_arithmetic::greater_eq(left, right, field)),
+                    Lesser => Ok(modular_arithmetic::lesser(left, right, field)),
+                    Greater => Ok(modular_arithmetic::greater(left, right, field)),
+                    NotEq => Ok(modular_arithmetic::not_eq(left, right, field)),
+                    BoolOr => Ok(modular_arithmetic::bool_or(left, right, field)),
+                    BoolAnd => Ok(modular_arithmetic::bool_and(left, right, field)),
+                    BitOr => Ok(modular_arithmetic::bit_or(left, right, field)),
+                    BitAnd => Ok(modular_arithmetic::bit_and(left, right, field)),
+                    BitXor => Ok(modular_arithmetic::bit_xor(left, right, field)),
+                    _ => unreachable!(),
+                };
+                result.map_err(|err| error_at(frame, bucket.line, &arithmetic_error(err)))?
+            }
+        };
+        Ok(Value::Field(vec![result]))
+    }
+
+    fn subcomponent(
+        &mut self,
+        cmp_address: &'a Instruction,
+        frame: &mut Frame<'a>,
+    ) -> Result<usize, String> {
+        let index = self.evaluate_address(cmp_address, frame)?;
+        let subcomponents = &self.components[frame.component].subcomponents;
+        match subcomponents.get(index) {
+            Some(id) if *id != 0 => Ok(*id),
+            _ => Err(error_at(
+                frame,
+                cmp_address.get_line(),
+                "Access to a subcomponent that was not created",
+            )),
+        }
+    }
+
+    fn resolve_location(
+        &mut self,
+        address_type: &'a AddressType,
+        rule: &'a LocationRule,
+        line: usize,
+        frame: &mut Frame<'a>,
+    ) -> Result<(Location, Option<usize>), String> {
+        let subcomponent = if let AddressType::SubcmpSignal { cmp_address, .. } = address_type {
+            Some(self.subcomponent(cmp_address, frame)?)
+        } else {
+            None
+        };
+        let offset = match rule {
+            LocationRule::Indexed { location, .. } => self.evaluate_address(location, frame)?,
+            LocationRule::Mapped {
+                signal_code,
+                indexes,
+            } => {
+                let component = subcomponent
+                    .ok_or_else(|| error_at(frame, line, "Mapped access out of a subcomponent"))?;
+                let template_id = self.circuit.templates[self.components[component].template].id;
+                let io_map = self.circuit.c_producer.get_io_map();
+                let definition = io_map
+                    .get(&template_id)
+                    .and_then(|defs| defs.get(*signal_code))
+                    .ok_or_else(|| error_at(frame, line, "Unknown subcomponent signal"))?;
+                let mut index = 0;
+                for (i, instruction) in indexes.iter().enumerate() {
+                    let value = self.evaluate_address(instruction, frame)?;
+                    index = if i == 0 {
+                        value
+                    } else {
+                        index * definition.lengths[i] + value
+                    };
+                }
+                definition.offset + index
+            }
+        };
+        let location = match subcomponent {
+            Some(component) => Location::Signal(self.components[component].signal_start + offset),
+            None if matches!(address_type, AddressType::Variable) => Location::Variable(offset),
+            None => Location::Signal(frame.signal_start + offset),
+        };
+        Ok((location, subcomponent))
+    }
+
+    fn read(
+        &self,
+        location: Location,
+        size: usize,
+        line: usize,
+        frame: &Frame,
+    ) -> Result<Vec<BigInt>, String> {
+        let (memory, start) = match location {
+            Location::Variable(start) => (&frame.vars, start),
+            Location::Signal(start) => (&self.signals, start),
+        };
+        memory
+            .get(start..start + size)
+            .map(|values| values.to_vec())
+            .ok_or_else(|| error_at(frame, line, "Memory access out of bounds"))
+    }
+
+    fn write(
+        &mut self,
+        location: Location,
+        values: &[BigInt],
+        line: usize,
+        frame: &mut Frame,
+    ) -> Result<(), String> {
+        let (memory, start) = match location {
+            Location::Variable(start) => (&mut frame.vars, start),
+            Location::Signal(start) => (&mut self.signals, start),
+        };
+        if start + values.len() > memory.len() {
+            return Err(error_at(frame, line, "Memory access out of bounds"));
+        }
+        memory[start..start + values.len()].clone_from_slice(values);
+        Ok(())
+    }
+
+    // Writes the values in the destination and runs the subcomponent once all its
+    // inputs have been assigned
+    fn store_values(
+        &mut self,
+        address_type: &'a AddressType,
+        dest: &'a LocationRule,
+        values: &[BigInt],
+        line: usize,
+        frame: &mut Frame<'a>,
+    ) -> Result<(), String> {
+        let (location, subcomponent) = self.resolve_location(address_type, dest, line, frame)?;
+        self.write(location, values, line, frame)?;
+        if let AddressType::SubcmpSignal {
+            input_information: InputInformation::Input { .. },
+            ..
+        } = address_type
+        {
+            let component = subcomponent.unwrap();
+            let counter = &mut self.components[component].input_counter;
+            *counter = counter.checked_sub(values.len()).ok_or_else(|| {
+                error_at(frame, line, "Subcomponent input assigned more than once")
+            })?;
+            if *counter == 0 {
+                self.run_component(component)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_store(
+        &mut self,
+        bucket: &'a StoreBucket,
+        frame: &mut Frame<'a>,
+    ) -> Result<(), String> {
+        let values = self.evaluate_field(&bucket.src, frame)?;
+        if values.len() < bucket.context.size {
+            return Err(error_at(
+                frame,
+                bucket.line,
+                "Not enough values in assignment",
+            ));
+        }
+        self.store_values(
+            &bucket.dest_address_type,
+            &bucket.dest,
+            &values[..bucket.context.size],
+            bucket.line,
+            frame,
+        )
+    }
+
+    fn evaluate_call(
+        &mut self,
+        bucket: &'a CallBucket,
+        frame: &mut Frame<'a>,
+    ) -> Result<Vec<BigInt>, String> {
+        let circuit = self.circuit;
+        let function: &FunctionCodeInfo = self
+            .function_headers
+            .get(bucket.symbol.as_str())
+            .map(|id| circuit.functions[*id].as_ref())
+            .ok_or_else(|| error_at(frame, bucket.line, "Unknown function"))?;
+        let mut arena = vec![BigInt::zero(); bucket.arena_size];
+        let mut count = 0;
+        for (argument, context) in bucket.arguments.iter().zip(&bucket.argument_types) {
+            let values = self.evaluate_field(argument, frame)?;
+            if values.len() < context.size || count + context.size > arena.len() {
+                return Err(error_at(frame, bucket.line, "Invalid function argument"));
+            }
+            arena[count..count + context.size].clone_from_slice(&values[..context.size]);
+            count += context.size;
+        }
+        let mut function_frame = Frame {
+            component: frame.component,
+            signal_start: 0,
+            name: &function.name,
+            vars: arena,
+        };
+        let returned = self
+            .execute_list(&function.body, &mut function_frame)?
+            .ok_or_else(|| error_at(frame, bucket.line, "Function finished without return"))?;
+        match &bucket.return_info {
+            ReturnType::Intermediate { .. } => Ok(vec![returned[0].clone()]),
+            ReturnType::Final(data) => {
+                let size = data.context.size;
+                if returned.len() < size {
+                    return Err(error_at(frame, bucket.line, "Not enough values returned"));
+                }
+                self.store_values(
+                    &data.dest_address_type,
+                    &data.dest,
+                    &returned[..size],
+                    bucket.line,
+                    frame,
+                )?;
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    fn execute_log(&mut self, bucket: &'a LogBucket, frame: &mut Frame<'a>) -> Result<(), String> {
+        let mut printed = Vec::with_capacity(bucket.argsprint.len());
+        for argument in &bucket.argsprint {
+            match argument {
+                LogBucketArg::LogExp(expression) => {
+                    let values = self.evaluate_field(expression, frame)?;
+                    printed.push(values[0].to_string());
+                }
+                LogBucketArg::LogStr(id) => {
+                    printed.push(self.circuit.c_producer.get_string_table()[*id].clone());
+                }
+            }
+        }
+        print_progress(&printed.join(" "));
+        Ok(())
+    }
+
+    fn execute_create_component(
+        &mut self,
+        bucket: &'a CreateCmpBucket,
+        frame: &mut Frame<'a>,
+    ) -> Result<(), String> {
+        let template = *self
+            .template_headers
+            .get(bucket.symbol.as_str())
+            .ok_or_else(|| error_at(frame, bucket.line, "Unknown template"))?;
+        let first = self.evaluate_address(&bucket.sub_cmp_id, frame)?;
+        let mut component = bucket.component_offset + frame.component + 1;
+        let mut signal_start = frame.signal_start + bucket.signal_offset;
+        let positions: Vec<usize> = if bucket.defined_positions.len() == bucket.number_of_cmp {
+            (0..bucket.number_of_cmp).collect()
+        } else {
+            bucket.defined_positions.iter().map(|(p, _)| *p).collect()
+        };
+        for position in positions {
+            let name = if bucket.number_of_cmp > 1 {
+                format!(
+                    "{}{}",
+                    bucket.name_subcomponent,
+                    position_in_array(&bucket.dimensions, position)
+                )
+            } else {
+                bucket.name_subcomponent.clone()
+            };
+            let subcomponents = &mut self.components[frame.component].subcomponents;
+            if first + position >= subcomponents.len() {
+                return Err(error_at(frame, bucket.line, "Subcomponent out of bounds"));
+            }
+            subcomponents[first + position] = component;
+            self.create_component(template, component, signal_start, name, frame.component)?;
+            signal_start += bucket.signal_offset_jump;
+            component += bucket.component_offset_jump;
+        }
+        Ok(())
+    }
+}
+
+// Same as Circom_CalcWit::generate_position_array
+fn position_in_array(dimensions: &[usize], mut index: usize) -> String {
+    let mut positions = String::new();
+    for dimension in dimensions.iter().rev() {
+        positions = format!("[{}]{}", index % dimension, positions);
+        index /= dimension;
+    }
+    positions
+}
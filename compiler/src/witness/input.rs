@@ -0,0 +1,81 @@
+use num_bigint_dig::BigInt;
+
+// Mirrors json2FrElements in main.cpp: arrays are flattened and every leaf must be
+// a number or a string with an optional 0b, 0o or 0x prefix
+fn flatten_value(value: &json::JsonValue, values: &mut Vec<BigInt>) -> Result<(), String> {
+    use json::JsonValue;
+    match value {
+        JsonValue::Array(elements) => {
+            for element in elements {
+                flatten_value(element, values)?;
+            }
+            Ok(())
+        }
+        JsonValue::String(_) | JsonValue::Short(_) => {
+            let string = value.as_str().unwrap();
+            values.push(parse_string(string)?);
+            Ok(())
+        }
+        JsonValue::Number(_) => {
+            let number = format!("{:.0}", value.as_f64().unwrap());
+            values.push(BigInt::parse_bytes(number.as_bytes(), 10).unwrap());
+            Ok(())
+        }
+        _ => Err("Invalid JSON type".to_string()),
+    }
+}
+
+fn parse_string(string: &str) -> Result<BigInt, String> {
+    let prefix = string.get(0..2).unwrap_or("");
+    let (digits, base) = match prefix {
+        "0b" | "0B" => (&string[2..], 2),
+        "0o" | "0O" => (&string[2..], 8),
+        "0x" | "0X" => (&string[2..], 16),
+        _ => (string, 10),
+    };
+    let is_valid = !digits.is_empty() && digits.chars().all(|c| c.is_digit(base));
+    if is_valid {
+        Ok(BigInt::parse_bytes(digits.as_bytes(), base).unwrap())
+    } else {
+        Err(format!("Invalid number in JSON input: {}", string))
+    }
+}
+
+// Returns the values of each input signal in the order they appear in the file
+pub fn parse_input(input: &str) -> Result<Vec<(String, Vec<BigInt>)>, String> {
+    let parsed = json::parse(input).map_err(|err| format!("Invalid JSON input: {}", err))?;
+    if !parsed.is_object() {
+        return Err("The JSON input must be an object".to_string());
+    }
+    let mut signals = Vec::new();
+    for (name, value) in parsed.entries() {
+        let mut values = Vec::new();
+        flatten_value(value, &mut values)
+            .map_err(|err| format!("Error loading signal {}: {}", name, err))?;
+        signals.push((name.to_string(), values));
+    }
+    Ok(signals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrays_are_flattened_in_order() {
+        let input = r#"{"b": [[1, "0x10"], ["0b11", "0o7"]], "a": "12"}"#;
+        let signals = parse_input(input).unwrap();
+        let expected: Vec<BigInt> = [1, 16, 3, 7].iter().map(|v| BigInt::from(*v)).collect();
+        assert_eq!(signals[0].0, "b");
+        assert_eq!(signals[0].1, expected);
+        assert_eq!(signals[1], ("a".to_string(), vec![BigInt::from(12)]));
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(parse_input(r#"{"a": "0x1g"}"#).is_err());
+        assert!(parse_input(r#"{"a": "-1"}"#).is_err());
+        assert!(parse_input(r#"{"a": true}"#).is_err());
+        assert!(parse_input("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,82 @@
+mod execution;
+mod input;
+
+use crate::circuit_design::circuit::Circuit;
+use code_producers::c_elements::CProducer;
+use execution::Execution;
+use num_bigint_dig::{BigInt, Sign};
+use std::io::Write;
+
+// Computes the witness of the circuit given the content of an input.json file,
+// following the same steps as the generated C++ witness calculator
+pub fn calculate_witness(circuit: &Circuit, input: &str) -> Result<Vec<BigInt>, String> {
+    let producer = &circuit.c_producer;
+    let mut execution = Execution::new(circuit);
+    let mut assigned = vec![false; producer.get_total_number_of_signals()];
+    let mut remaining = producer.get_number_of_main_inputs();
+    for (name, values) in input::parse_input(input)? {
+        let (_, start, size) = producer
+            .get_main_input_list()
+            .iter()
+            .find(|(input, _, _)| *input == name)
+            .ok_or_else(|| format!("Error loading signal {}: Signal not found", name))?;
+        if values.len() < *size {
+            return Err(format!("Error loading signal {}: Not enough values", name));
+        }
+        if values.len() > *size {
+            return Err(format!("Error loading signal {}: Too many values", name));
+        }
+        for (i, value) in values.iter().enumerate() {
+            let signal = start + i;
+            if assigned[signal] {
+                return Err(format!("Signal assigned twice: {}", signal));
+            }
+            assigned[signal] = true;
+            remaining -= 1;
+            execution.set_input(signal, value);
+        }
+    }
+    if remaining != 0 {
+        let total = producer.get_number_of_main_inputs();
+        return Err(format!(
+            "Not all inputs have been set. Only {} out of {}",
+            total - remaining,
+            total
+        ));
+    }
+    execution.run()?;
+    Ok(execution.get_witness())
+}
+
+fn write_element<W: Write>(writer: &mut W, value: &BigInt, n8: usize) -> std::io::Result<()> {
+    let (_, mut bytes) = value.to_bytes_le();
+    bytes.resize(n8, 0);
+    writer.write_all(&bytes)
+}
+
+// Same layout as writeBinWitness in the generated main.cpp
+pub fn write_wtns<W: Write>(
+    writer: &mut W,
+    producer: &CProducer,
+    witness: &[BigInt],
+) -> std::io::Result<()> {
+    let n8 = producer.get_size_32_bit() * 4;
+    let prime = BigInt::parse_bytes(producer.get_prime().as_bytes(), 10).unwrap();
+    writer.write_all(b"wtns")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&2u32.to_le_bytes())?;
+
+    writer.write_all(&1u32.to_le_bytes())?;
+    writer.write_all(&(8 + n8 as u64).to_le_bytes())?;
+    writer.write_all(&(n8 as u32).to_le_bytes())?;
+    write_element(writer, &prime, n8)?;
+    writer.write_all(&(witness.len() as u32).to_le_bytes())?;
+
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&((n8 * witness.len()) as u64).to_le_bytes())?;
+    for value in witness {
+        debug_assert!(value.sign() != Sign::Minus);
+        write_element(writer, value, n8)?;
+    }
+    writer.flush()
+}
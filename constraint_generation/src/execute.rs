@@ -12,9 +12,12 @@ use super::environment_utils::{
     },
 };
 
+use program_structure::builtin_library::is_qm31_function;
 use program_structure::constants::UsefulConstants;
+use program_structure::diagnostics::print_progress;
 
 use super::execution_data::analysis::Analysis;
+use super::execution_data::template_cache::TemplateCache;
 use super::execution_data::{ExecutedProgram, ExecutedTemplate, NodePointer, PreExecutedTemplate};
 use super::{
     ast::*, ArithmeticError, FileID, ProgramArchive, Report, ReportCode, ReportCollection,
@@ -43,6 +46,7 @@ struct RuntimeInformation {
     pub environment: ExecutionEnvironment,
     pub exec_program: ExecutedProgram,
     pub anonymous_components: AnonymousComponentsInfo,
+    pub cache: Option<TemplateCache>,
 }
 impl RuntimeInformation {
     pub fn new(current_file: FileID, id_max: usize, prime: &String) -> RuntimeInformation {
@@ -57,6 +61,7 @@ impl RuntimeInformation {
             environment: ExecutionEnvironment::new(),
             exec_program: ExecutedProgram::new(prime),
             anonymous_components: AnonymousComponentsInfo::new(),
+            cache: Option::None,
         }
     }
 }
@@ -100,6 +105,7 @@ enum ExecutionError {
     NonValidTagAssignment,
     FalseAssert,
     ArraySizeTooBig,
+    QM31OutsideM31(String),
 }
 
 enum ExecutionWarning {
@@ -111,10 +117,12 @@ pub fn constraint_execution(
     program_archive: &ProgramArchive,
     flags: FlagsExecution,
     prime: &String,
+    cache: Option<TemplateCache>,
 ) -> Result<(ExecutedProgram, ReportCollection), ReportCollection> {
     let main_file_id = program_archive.get_file_id_main();
     let mut runtime_information =
         RuntimeInformation::new(*main_file_id, program_archive.id_max, prime);
+    runtime_information.cache = cache;
     use Expression::Call;
 
     runtime_information.public_inputs = program_archive.get_public_inputs_main_component().clone();
@@ -404,6 +412,13 @@ fn execute_statement(
                         } else if let AssignOp::AssignSignal = op {
                             // needs fix, check case arrays
                             //debug_assert!(possible_constraint.is_some());
+                            if flags.inspect_deep && !node.is_custom_gate {
+                                node.add_unsafe_assignment(
+                                    &full_symbol,
+                                    meta.get_file_id(),
+                                    meta.location.clone(),
+                                );
+                            }
                             if !value_right.is_nonquadratic() && !node.is_custom_gate {
                                 needs_double_arrow.push(full_symbol);
                             }
@@ -579,24 +594,25 @@ fn execute_statement(
             can_be_simplified = false;
             if flags.verbose {
                 let mut index = 0;
+                let mut line = String::new();
                 for arglog in args {
                     if let LogArgument::LogExp(arg) = arglog {
                         let f_result = execute_expression(arg, program_archive, runtime, flags)?;
                         let arith = safe_unwrap_to_single_arithmetic_expression(f_result, line!());
                         if AExpr::is_number(&arith) {
-                            print!("{}", arith);
+                            line.push_str(&arith.to_string());
                         } else {
-                            print!("Unknown")
+                            line.push_str("Unknown")
                         }
                     } else if let LogArgument::LogStr(s) = arglog {
-                        print!("{}", s);
+                        line.push_str(s);
                     }
                     if index != args.len() - 1 {
-                        print!(" ");
+                        line.push(' ');
                     }
                     index += 1;
                 }
-                println!("");
+                print_progress(&line);
             } else {
                 for arglog in args {
                     if let LogArgument::LogExp(arg) = arglog {
@@ -880,6 +896,22 @@ fn execute_call(
         arg_values.push(safe_f_arg);
     }
     if program_archive.contains_function(id) {
+        let file_id = program_archive.get_function_data(id).get_file_id();
+        if is_qm31_function(id, program_archive.get_file_library(), file_id) {
+            // QM31 is an extension of M31, over other primes the result is meaningless
+            if runtime.constants.get_p() != &BigInt::from(2147483647) {
+                treat_result_with_execution_error(
+                    Result::Err(ExecutionError::QM31OutsideM31(id.clone())),
+                    meta,
+                    &mut runtime.runtime_errors,
+                    &runtime.call_trace,
+                )?;
+            }
+            if let Option::Some(folded_result) = execute_qm31_call(id, meta, &arg_values, runtime)?
+            {
+                return Ok((folded_result, true));
+            }
+        }
         // in this case we execute
         let new_environment = prepare_environment_for_call(id, &arg_values, program_archive);
         let previous_environment = std::mem::replace(&mut runtime.environment, new_environment);
@@ -908,6 +940,54 @@ fn execute_call(
     }
 }
 
+// Evaluates natively the QM31 functions of the builtin library, the call is
+// executed as any other function if some of the arguments is not known
+fn execute_qm31_call(
+    id: &str,
+    meta: &Meta,
+    arg_values: &[AExpressionSlice],
+    runtime: &mut RuntimeInformation,
+) -> Result<Option<FoldedValue>, ()> {
+    use circom_algebra::modular_arithmetic::{qm31_add, qm31_inv, qm31_mul, qm31_sub};
+    let mut elements = Vec::new();
+    for arg_value in arg_values {
+        let values = MemorySlice::get_reference_values(arg_value);
+        if values.len() != 4 {
+            return Result::Ok(Option::None);
+        }
+        let mut element = Vec::new();
+        for value in values {
+            if let AExpr::Number { value } = value {
+                element.push(value.clone());
+            } else {
+                return Result::Ok(Option::None);
+            }
+        }
+        elements.push(element);
+    }
+    let field = runtime.constants.get_p().clone();
+    let result = match (id, elements.as_slice()) {
+        ("qm31_add", [left, right]) => qm31_add(left, right, &field),
+        ("qm31_sub", [left, right]) => qm31_sub(left, right, &field),
+        ("qm31_mul", [left, right]) => qm31_mul(left, right, &field),
+        ("qm31_inv", [elem]) => treat_result_with_arithmetic_error(
+            qm31_inv(elem, &field),
+            meta,
+            &mut runtime.runtime_errors,
+            &runtime.call_trace,
+        )?,
+        _ => return Result::Ok(Option::None),
+    };
+    let values = result
+        .into_iter()
+        .map(|value| AExpr::Number { value })
+        .collect();
+    Result::Ok(Option::Some(FoldedValue {
+        arithmetic_slice: Option::Some(AExpressionSlice::new_array(vec![4], values)),
+        ..FoldedValue::default()
+    }))
+}
+
 fn execute_template_call_complete(
     id: &String,
     arg_values: Vec<AExpressionSlice>,
@@ -2118,12 +2198,26 @@ fn execute_template_call(
         instantiation_name.pop();
     }
     instantiation_name.push(')');
+    let cache_key = runtime
+        .cache
+        .as_mut()
+        .and_then(|cache| cache.key(id, &args_to_values, &tag_values, &is_main, program_archive));
     let existent_node = runtime
         .exec_program
         .identify_node(id, &args_to_values, &tag_values);
-    let node_pointer = if let Option::Some(pointer) = existent_node {
+    let cached_node = match (&runtime.cache, &cache_key) {
+        (Some(cache), Some(key)) if existent_node.is_none() => {
+            cache.load(key, program_archive, &mut runtime.exec_program)
+        }
+        _ => Option::None,
+    };
+    let node_pointer = if let Option::Some(pointer) = existent_node.or(cached_node) {
         pointer
     } else {
+        let reports_before = runtime.runtime_errors.len();
+        if let Some(cache) = &mut runtime.cache {
+            cache.enter();
+        }
         let analysis =
             std::mem::replace(&mut runtime.analysis, Analysis::new(program_archive.id_max));
         let code = program_archive.get_template_data(id).get_body().clone();
@@ -2163,9 +2257,21 @@ fn execute_template_call(
 
         let new_node = node_wrap.unwrap();
         let analysis = std::mem::replace(&mut runtime.analysis, analysis);
+        if let Some(cache) = &mut runtime.cache {
+            let calls = cache.exit();
+            // instances that report something are executed again so the
+            // reports are not lost
+            if let (Some(key), true) = (&cache_key, runtime.runtime_errors.len() == reports_before)
+            {
+                cache.store(key, &new_node, &analysis, &calls, program_archive);
+            }
+        }
         let node_pointer = runtime.exec_program.add_node_to_scheme(new_node, analysis);
         node_pointer
     };
+    if let Some(cache) = &mut runtime.cache {
+        cache.add_call(cache_key, node_pointer);
+    }
     Result::Ok(FoldedValue {
         node_pointer: Option::Some(node_pointer),
         is_parallel: Option::Some(false),
@@ -2753,6 +2859,10 @@ fn treat_result_with_execution_error<C>(
                     "The size of the array is expected to be a usize".to_string(),
                     ReportCode::RuntimeError,
                 ),
+                QM31OutsideM31(id) => Report::error(
+                    format!("{} is a QM31 function, which can only be used with --prime m31", id),
+                    ReportCode::RuntimeError,
+                ),
                 ConstraintInUnknown => Report::error(
                     "There are constraints depending on the value of a condition that can be unknown during the constraint generation phase".to_string(),
                     ReportCode::RuntimeError,
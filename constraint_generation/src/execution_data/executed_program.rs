@@ -145,6 +145,10 @@ impl ExecutedProgram {
             let mut w = dag.constraint_analysis()?;
             warnings.append(&mut w);
         }
+        if flags.inspect_deep {
+            let mut w = dag.determinism_analysis();
+            warnings.append(&mut w);
+        }
 
         let dag_stats = produce_dags_stats(&dag);
         crate::compute_constants::manage_functions(&mut program, flags, &self.prime)?;
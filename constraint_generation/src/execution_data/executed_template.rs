@@ -6,6 +6,7 @@ use compiler::hir::very_concrete_program::*;
 use dag::DAG;
 use num_bigint::BigInt;
 use program_structure::ast::{SignalType, Statement};
+use program_structure::file_definition::{FileID, FileLocation};
 use std::collections::{HashMap, HashSet};
 
 struct Connexion {
@@ -76,6 +77,8 @@ pub struct ExecutedTemplate {
     pub has_parallel_sub_cmp: bool,
     pub is_custom_gate: bool,
     pub underscored_signals: Vec<String>,
+    // signals assigned with <-- and where they are assigned
+    pub unsafe_assignments: Vec<(String, FileID, FileLocation)>,
     connexions: Vec<Connexion>,
 }
 
@@ -111,6 +114,7 @@ impl ExecutedTemplate {
             number_of_components: 0,
             connexions: Vec::new(),
             underscored_signals: Vec::new(),
+            unsafe_assignments: Vec::new(),
         }
     }
 
@@ -137,6 +141,10 @@ impl ExecutedTemplate {
         self.connexions.push(cnn);
     }
 
+    pub fn arrows(&self) -> impl Iterator<Item = (&String, &SubComponentData)> {
+        self.connexions.iter().map(|c| (&c.full_name, &c.inspect))
+    }
+
     pub fn add_input(&mut self, input_name: &str, dimensions: &[usize]) {
         self.inputs
             .push((input_name.to_string(), dimensions.to_vec()));
@@ -203,6 +211,11 @@ impl ExecutedTemplate {
         self.underscored_signals.push(signal.to_string());
     }
 
+    pub fn add_unsafe_assignment(&mut self, signal: &str, file_id: FileID, location: FileLocation) {
+        self.unsafe_assignments
+            .push((signal.to_string(), file_id, location));
+    }
+
     pub fn template_name(&self) -> &String {
         &self.template_name
     }
@@ -341,6 +354,12 @@ impl ExecutedTemplate {
             let new_s = correspondence.get(s).unwrap().clone();
             dag.add_underscored_signal(new_s);
         }
+        for (s, file_id, location) in &self.unsafe_assignments {
+            let correspondence = dag.get_main().unwrap().correspondence();
+            if let Some(new_s) = correspondence.get(s).cloned() {
+                dag.add_unsafe_assignment(new_s, *file_id, location.clone());
+            }
+        }
     }
 
     pub fn export_to_circuit(self, instances: &mut [TemplateInstance]) -> TemplateInstance {
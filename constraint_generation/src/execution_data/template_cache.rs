@@ -0,0 +1,579 @@
+use super::analysis::Analysis;
+use super::executed_program::ExecutedProgram;
+use super::executed_template::ExecutedTemplate;
+use super::type_definitions::*;
+use crate::environment_utils::slice_types::{AExpressionSlice, ArithmeticExpression};
+use crate::FlagsExecution;
+use num_bigint::BigInt;
+use program_structure::ast::{Access, Expression, LogArgument, Statement};
+use program_structure::file_definition::{FileID, FileLocation};
+use program_structure::program_archive::ProgramArchive;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+// changes whenever the content of the records changes
+const FORMAT_VERSION: &str = "1";
+
+type CachedTags = Vec<(String, Vec<(String, Option<String>)>)>;
+type CachedLinear = Vec<(String, String)>;
+type CachedParameters = Vec<(String, Vec<usize>, Vec<String>)>;
+
+// The result of executing an instance of a template. The children are
+// referenced by their keys, in the order the template called them, so
+// loading an instance adds the same nodes in the same order as executing it.
+// The cleaned code is not stored, it is rebuilt from the body of the
+// template and the facts of the analysis of each of its nodes.
+#[derive(Serialize, Deserialize)]
+struct CachedTemplate {
+    template_name: String,
+    report_name: String,
+    public_inputs: Vec<String>,
+    parameters: CachedParameters,
+    tags: CachedTags,
+    inputs: SignalCollector,
+    outputs: SignalCollector,
+    intermediates: SignalCollector,
+    ordered_signals: Vec<String>,
+    constraints: Vec<(CachedLinear, CachedLinear, CachedLinear)>,
+    components: ComponentCollector,
+    signal_to_tags: CachedTags,
+    underscored_signals: Vec<String>,
+    unsafe_assignments: Vec<(String, FileID, FileLocation)>,
+    calls: Vec<String>,
+    // full name, component, is parallel, indexes and position of the call
+    arrows: Vec<(String, String, bool, Vec<usize>, usize)>,
+    // reached and computed value of each node of the body
+    facts: Vec<(bool, Option<String>)>,
+}
+
+// On disk cache of executed template instances. An instance is identified
+// by a hash of its arguments and of the source of the template and of all the
+// templates and functions it may call, so a change in a file only invalidates
+// the instances that depend on it. Only the instances whose execution does
+// not produce any report are stored, the warnings are not lost.
+pub struct TemplateCache {
+    folder: PathBuf,
+    flags: FlagsExecution,
+    prime: String,
+    // hash of the sources each template depends on
+    sources: HashMap<String, Vec<u8>>,
+    // calls made by each template that is being executed
+    calls: Vec<Vec<(Option<String>, NodePointer)>>,
+}
+
+impl TemplateCache {
+    pub fn new(folder: PathBuf, flags: FlagsExecution, prime: &str) -> Result<TemplateCache, ()> {
+        fs::create_dir_all(&folder).map_err(|_| ())?;
+        Result::Ok(TemplateCache {
+            folder,
+            flags,
+            prime: prime.to_string(),
+            sources: HashMap::new(),
+            calls: Vec::new(),
+        })
+    }
+
+    // None when the instance cannot be restored from the cache
+    pub fn key(
+        &mut self,
+        template: &str,
+        parameters: &ParameterContext,
+        tags: &TagContext,
+        public_inputs: &[String],
+        program: &ProgramArchive,
+    ) -> Option<String> {
+        let parameters = export_parameters(parameters)?;
+        if !self.sources.contains_key(template) {
+            let digest = hash_sources(template, program);
+            self.sources.insert(template.to_string(), digest);
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(FORMAT_VERSION);
+        hasher.update(env!("CARGO_PKG_VERSION"));
+        hasher.update(&self.prime);
+        hasher.update(format!("{:?}", self.flags));
+        hasher.update(&self.sources[template]);
+        hasher.update(format!("{:?}", public_inputs));
+        hasher.update(format!("{:?}", parameters));
+        hasher.update(format!("{:?}", export_tags(tags)));
+        Option::Some(to_hex(&hasher.finalize()))
+    }
+
+    // the template starts its execution
+    pub fn enter(&mut self) {
+        self.calls.push(Vec::new());
+    }
+
+    // the template finishes its execution, returns the calls it made
+    pub fn exit(&mut self) -> Vec<(Option<String>, NodePointer)> {
+        self.calls.pop().unwrap_or_default()
+    }
+
+    pub fn add_call(&mut self, key: Option<String>, node_pointer: NodePointer) {
+        if let Some(calls) = self.calls.last_mut() {
+            calls.push((key, node_pointer));
+        }
+    }
+
+    pub fn store(
+        &self,
+        key: &str,
+        node: &ExecutedTemplate,
+        analysis: &Analysis,
+        calls: &[(Option<String>, NodePointer)],
+        program: &ProgramArchive,
+    ) {
+        if let Some(record) = export_template(node, analysis, calls, program) {
+            if let Ok(bytes) = bincode::serialize(&record) {
+                // written under another name first so a record is never
+                // read half written
+                let temporary = self.folder.join(format!("{}.tmp", key));
+                if fs::write(&temporary, bytes).is_ok() {
+                    let _ = fs::rename(&temporary, self.record_path(key));
+                }
+            }
+        }
+    }
+
+    // Adds the instance and the ones it calls to the program, nothing is
+    // added if the record of any of them is missing
+    pub fn load(
+        &self,
+        key: &str,
+        program: &ProgramArchive,
+        exec_program: &mut ExecutedProgram,
+    ) -> Option<NodePointer> {
+        let mut records = HashMap::new();
+        if !self.read_all(key, &mut records) {
+            return Option::None;
+        }
+        insert_template(key, &records, program, exec_program)
+    }
+
+    fn record_path(&self, key: &str) -> PathBuf {
+        self.folder.join(format!("{}.bin", key))
+    }
+
+    fn read_all(&self, key: &str, records: &mut HashMap<String, CachedTemplate>) -> bool {
+        if records.contains_key(key) {
+            return true;
+        }
+        let record = fs::read(self.record_path(key))
+            .ok()
+            .and_then(|bytes| bincode::deserialize::<CachedTemplate>(&bytes).ok());
+        match record {
+            Some(record) => {
+                let calls = record.calls.clone();
+                records.insert(key.to_string(), record);
+                calls.iter().all(|call| self.read_all(call, records))
+            }
+            None => false,
+        }
+    }
+}
+
+fn insert_template(
+    key: &str,
+    records: &HashMap<String, CachedTemplate>,
+    program: &ProgramArchive,
+    exec_program: &mut ExecutedProgram,
+) -> Option<NodePointer> {
+    let record = &records[key];
+    if !program.contains_template(&record.template_name) {
+        return Option::None;
+    }
+    let parameters = import_parameters(&record.parameters)?;
+    let tags = import_tags(&record.tags)?;
+    let existent_node = exec_program.identify_node(&record.template_name, &parameters, &tags);
+    if existent_node.is_some() {
+        return existent_node;
+    }
+    let mut children = Vec::new();
+    for call in &record.calls {
+        children.push(insert_template(call, records, program, exec_program)?);
+    }
+
+    let template = program.get_template_data(&record.template_name);
+    let mut node = ExecutedTemplate::new(
+        record.public_inputs.clone(),
+        record.template_name.clone(),
+        record.report_name.clone(),
+        parameters,
+        tags,
+        template.get_body().clone(),
+        template.is_parallel(),
+        template.is_custom_gate(),
+    );
+    node.inputs = record.inputs.clone();
+    node.outputs = record.outputs.clone();
+    node.intermediates = record.intermediates.clone();
+    node.ordered_signals = record.ordered_signals.clone();
+    for (a, b, c) in &record.constraints {
+        node.add_constraint(Constraint::new(
+            import_linear(a)?,
+            import_linear(b)?,
+            import_linear(c)?,
+        ));
+    }
+    for (component, dimensions) in &record.components {
+        node.add_component(component, dimensions);
+    }
+    node.signal_to_tags = import_tags(&record.signal_to_tags)?;
+    node.underscored_signals = record.underscored_signals.clone();
+    node.unsafe_assignments = record.unsafe_assignments.clone();
+    for (full_name, name, is_parallel, indexed_with, call) in &record.arrows {
+        let data = SubComponentData {
+            name: name.clone(),
+            is_parallel: *is_parallel,
+            indexed_with: indexed_with.clone(),
+            goes_to: *children.get(*call)?,
+        };
+        node.add_arrow(full_name.clone(), data);
+    }
+
+    let mut ids = Vec::new();
+    walk_statement(template.get_body(), &mut ids, &mut BTreeSet::new());
+    if ids.len() != record.facts.len() {
+        return Option::None;
+    }
+    let mut analysis = Analysis::new(program.id_max);
+    for (id, (reached, value)) in ids.into_iter().zip(&record.facts) {
+        if *reached {
+            Analysis::reached(&mut analysis, id);
+        }
+        if let Some(value) = value {
+            let value = BigInt::from_str(value).ok()?;
+            Analysis::computed(&mut analysis, id, ArithmeticExpression::Number { value });
+        }
+    }
+    Option::Some(exec_program.add_node_to_scheme(node, analysis))
+}
+
+fn export_template(
+    node: &ExecutedTemplate,
+    analysis: &Analysis,
+    calls: &[(Option<String>, NodePointer)],
+    program: &ProgramArchive,
+) -> Option<CachedTemplate> {
+    let mut call_keys = Vec::new();
+    for (key, _) in calls {
+        call_keys.push(key.clone()?);
+    }
+    let mut arrows = Vec::new();
+    for (full_name, data) in node.arrows() {
+        let call = calls
+            .iter()
+            .position(|(_, pointer)| *pointer == data.goes_to)?;
+        arrows.push((
+            full_name.clone(),
+            data.name.clone(),
+            data.is_parallel,
+            data.indexed_with.clone(),
+            call,
+        ));
+    }
+    let mut ids = Vec::new();
+    let body = program.get_template_data(node.template_name()).get_body();
+    walk_statement(body, &mut ids, &mut BTreeSet::new());
+    let facts = ids
+        .into_iter()
+        .map(|id| {
+            let value = Analysis::read_computed(analysis, id).map(|v| v.to_string());
+            (Analysis::is_reached(analysis, id), value)
+        })
+        .collect();
+    let constraints = node
+        .constraints
+        .iter()
+        .map(|c| {
+            (
+                export_linear(c.a()),
+                export_linear(c.b()),
+                export_linear(c.c()),
+            )
+        })
+        .collect();
+    Option::Some(CachedTemplate {
+        template_name: node.template_name.clone(),
+        report_name: node.report_name.clone(),
+        public_inputs: node.public_inputs.iter().cloned().collect(),
+        parameters: export_parameters(node.parameter_instances())?,
+        tags: export_tags(node.tag_instances()),
+        inputs: node.inputs.clone(),
+        outputs: node.outputs.clone(),
+        intermediates: node.intermediates.clone(),
+        ordered_signals: node.ordered_signals.clone(),
+        constraints,
+        components: node.components.clone(),
+        signal_to_tags: export_tags(&node.signal_to_tags),
+        underscored_signals: node.underscored_signals.clone(),
+        unsafe_assignments: node.unsafe_assignments.clone(),
+        calls: call_keys,
+        arrows,
+        facts,
+    })
+}
+
+// The parameters of a template are always known numbers
+fn export_parameters(parameters: &ParameterContext) -> Option<CachedParameters> {
+    let mut exported = Vec::new();
+    for (name, slice) in parameters {
+        let mut values = Vec::new();
+        for value in AExpressionSlice::get_reference_values(slice) {
+            match value {
+                ArithmeticExpression::Number { value } => values.push(value.to_string()),
+                _ => return Option::None,
+            }
+        }
+        exported.push((name.clone(), slice.route().to_vec(), values));
+    }
+    Option::Some(exported)
+}
+
+fn import_parameters(parameters: &[(String, Vec<usize>, Vec<String>)]) -> Option<ParameterContext> {
+    let mut imported = ParameterContext::new();
+    for (name, route, values) in parameters {
+        let mut numbers = Vec::new();
+        for value in values {
+            let value = BigInt::from_str(value).ok()?;
+            numbers.push(ArithmeticExpression::Number { value });
+        }
+        imported.insert(
+            name.clone(),
+            AExpressionSlice::new_array(route.clone(), numbers),
+        );
+    }
+    Option::Some(imported)
+}
+
+fn export_tags(tags: &TagContext) -> CachedTags {
+    tags.iter()
+        .map(|(signal, info)| {
+            let info = info
+                .iter()
+                .map(|(tag, value)| (tag.clone(), value.as_ref().map(|v| v.to_string())))
+                .collect();
+            (signal.clone(), info)
+        })
+        .collect()
+}
+
+fn import_tags(tags: &CachedTags) -> Option<TagContext> {
+    let mut imported = TagContext::new();
+    for (signal, info) in tags {
+        let mut tag_info = TagInfo::new();
+        for (tag, value) in info {
+            let value = match value {
+                Some(value) => Some(BigInt::from_str(value).ok()?),
+                None => None,
+            };
+            tag_info.insert(tag.clone(), value);
+        }
+        imported.insert(signal.clone(), tag_info);
+    }
+    Option::Some(imported)
+}
+
+fn export_linear(linear: &HashMap<String, BigInt>) -> CachedLinear {
+    linear
+        .iter()
+        .map(|(signal, coefficient)| (signal.clone(), coefficient.to_string()))
+        .collect()
+}
+
+fn import_linear(linear: &CachedLinear) -> Option<HashMap<String, BigInt>> {
+    let mut imported = HashMap::new();
+    for (signal, coefficient) in linear {
+        imported.insert(signal.clone(), BigInt::from_str(coefficient).ok()?);
+    }
+    Option::Some(imported)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Hash of the source and location of the template and of every template and
+// function it may call, directly or not
+fn hash_sources(template: &str, program: &ProgramArchive) -> Vec<u8> {
+    let mut pending = vec![template.to_string()];
+    let mut visited = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        if !visited.insert(name.clone()) {
+            continue;
+        }
+        let body = if program.contains_template(&name) {
+            program.get_template_data(&name).get_body()
+        } else if program.contains_function(&name) {
+            program.get_function_data(&name).get_body()
+        } else {
+            continue;
+        };
+        let mut calls = BTreeSet::new();
+        walk_statement(body, &mut Vec::new(), &mut calls);
+        pending.extend(calls);
+    }
+    let mut hasher = Sha256::new();
+    for name in &visited {
+        let (file_id, body, params, modifiers) = if program.contains_template(name) {
+            let data = program.get_template_data(name);
+            let modifiers = (data.is_parallel(), data.is_custom_gate());
+            (
+                data.get_file_id(),
+                data.get_body(),
+                data.get_name_of_params(),
+                Some(modifiers),
+            )
+        } else if program.contains_function(name) {
+            let data = program.get_function_data(name);
+            (
+                data.get_file_id(),
+                data.get_body(),
+                data.get_name_of_params(),
+                None,
+            )
+        } else {
+            continue;
+        };
+        let location = body.get_meta().location.clone();
+        let file_name = program
+            .file_library
+            .get_file_name(file_id)
+            .unwrap_or_default();
+        let source = program
+            .file_library
+            .get_source(file_id)
+            .and_then(|source| source.get(location.clone()))
+            .unwrap_or_default();
+        hasher.update(format!(
+            "{}:{:?}:{:?}:{}:{}:{:?}:",
+            name, params, modifiers, file_id, file_name, location
+        ));
+        hasher.update(source);
+    }
+    hasher.finalize().to_vec()
+}
+
+// Collects the ids of the nodes in a fixed order and the names of the
+// templates and functions that are called
+fn walk_statement(stmt: &Statement, ids: &mut Vec<usize>, calls: &mut BTreeSet<String>) {
+    use Statement::*;
+    ids.push(stmt.get_meta().elem_id);
+    match stmt {
+        IfThenElse {
+            cond,
+            if_case,
+            else_case,
+            ..
+        } => {
+            walk_expression(cond, ids, calls);
+            walk_statement(if_case, ids, calls);
+            if let Some(else_case) = else_case {
+                walk_statement(else_case, ids, calls);
+            }
+        }
+        While { cond, stmt, .. } => {
+            walk_expression(cond, ids, calls);
+            walk_statement(stmt, ids, calls);
+        }
+        Return { value, .. } => walk_expression(value, ids, calls),
+        InitializationBlock {
+            initializations, ..
+        } => {
+            for stmt in initializations {
+                walk_statement(stmt, ids, calls);
+            }
+        }
+        Declaration { dimensions, .. } => {
+            for dimension in dimensions {
+                walk_expression(dimension, ids, calls);
+            }
+        }
+        Substitution { access, rhe, .. } => {
+            walk_access(access, ids, calls);
+            walk_expression(rhe, ids, calls);
+        }
+        MultSubstitution { lhe, rhe, .. } | ConstraintEquality { lhe, rhe, .. } => {
+            walk_expression(lhe, ids, calls);
+            walk_expression(rhe, ids, calls);
+        }
+        UnderscoreSubstitution { rhe, .. } => walk_expression(rhe, ids, calls),
+        LogCall { args, .. } => {
+            for arg in args {
+                if let LogArgument::LogExp(expr) = arg {
+                    walk_expression(expr, ids, calls);
+                }
+            }
+        }
+        Block { stmts, .. } => {
+            for stmt in stmts {
+                walk_statement(stmt, ids, calls);
+            }
+        }
+        Assert { arg, .. } => walk_expression(arg, ids, calls),
+    }
+}
+
+fn walk_expression(expr: &Expression, ids: &mut Vec<usize>, calls: &mut BTreeSet<String>) {
+    use Expression::*;
+    ids.push(expr.get_meta().elem_id);
+    match expr {
+        InfixOp { lhe, rhe, .. } => {
+            walk_expression(lhe, ids, calls);
+            walk_expression(rhe, ids, calls);
+        }
+        PrefixOp { rhe, .. } | ParallelOp { rhe, .. } => walk_expression(rhe, ids, calls),
+        InlineSwitchOp {
+            cond,
+            if_true,
+            if_false,
+            ..
+        } => {
+            walk_expression(cond, ids, calls);
+            walk_expression(if_true, ids, calls);
+            walk_expression(if_false, ids, calls);
+        }
+        Variable { access, .. } => walk_access(access, ids, calls),
+        Number(..) => {}
+        Call { id, args, .. } => {
+            calls.insert(id.clone());
+            for arg in args {
+                walk_expression(arg, ids, calls);
+            }
+        }
+        AnonymousComp {
+            id,
+            params,
+            signals,
+            ..
+        } => {
+            calls.insert(id.clone());
+            for expr in params.iter().chain(signals) {
+                walk_expression(expr, ids, calls);
+            }
+        }
+        ArrayInLine { values, .. } | Tuple { values, .. } => {
+            for value in values {
+                walk_expression(value, ids, calls);
+            }
+        }
+        UniformArray {
+            value, dimension, ..
+        } => {
+            walk_expression(value, ids, calls);
+            walk_expression(dimension, ids, calls);
+        }
+    }
+}
+
+fn walk_access(access: &[Access], ids: &mut Vec<usize>, calls: &mut BTreeSet<String>) {
+    for a in access {
+        if let Access::ArrayAccess(index) = a {
+            walk_expression(index, ids, calls);
+        }
+    }
+}
@@ -6,102 +6,106 @@ mod environment_utils;
 mod execute;
 mod execution_data;
 
-use ansi_term::Colour;
 use circom_algebra::algebra::{ArithmeticError, ArithmeticExpression};
 use compiler::hir::very_concrete_program::VCP;
 use constraint_list::ConstraintList;
 use constraint_writers::ConstraintExporter;
+pub use dag::Statistics;
 use dag::DAG;
 use execution_data::executed_program::ExportResult;
+use execution_data::template_cache::TemplateCache;
 use execution_data::ExecutedProgram;
 use program_structure::ast::{self};
 use program_structure::error_code::ReportCode;
 use program_structure::error_definition::{Report, ReportCollection};
 use program_structure::file_definition::FileID;
 use program_structure::program_archive::ProgramArchive;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 pub struct BuildConfig {
     pub no_rounds: usize,
     pub flag_json_sub: bool,
     pub json_substitutions: String,
+    pub flag_stats: bool,
     pub flag_s: bool,
     pub flag_f: bool,
     pub flag_p: bool,
     pub flag_verbose: bool,
     pub flag_old_heuristics: bool,
     pub inspect_constraints: bool,
+    pub inspect_deep: bool,
     pub prime: String,
+    // folder of the cache of template instances, no cache when None
+    pub cache_folder: Option<String>,
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct FlagsExecution {
     pub verbose: bool,
     pub inspect: bool,
+    pub inspect_deep: bool,
 }
 
 pub type ConstraintWriter = Box<dyn ConstraintExporter>;
-type BuildResponse = Result<(ConstraintWriter, VCP), ()>;
-pub fn build_circuit(program: ProgramArchive, config: BuildConfig) -> BuildResponse {
-    let files = program.file_library.clone();
+
+pub struct BuildOutput {
+    pub exporter: ConstraintWriter,
+    pub vcp: VCP,
+    pub template_instances: usize,
+    pub warnings: ReportCollection,
+    // per template statistics, only when they are asked for
+    pub statistics: Option<Statistics>,
+}
+
+// Executes and simplifies the program without printing anything. On failure
+// the collection holds the warnings found before the errors.
+pub fn generate_circuit(
+    program: ProgramArchive,
+    config: &BuildConfig,
+) -> Result<BuildOutput, ReportCollection> {
     let flags = FlagsExecution {
         verbose: config.flag_verbose,
         inspect: config.inspect_constraints,
+        inspect_deep: config.inspect_deep,
     };
-    let (exe, warnings) = instantiation(&program, flags, &config.prime).map_err(|r| {
-        Report::print_reports(&r, &files);
-    })?;
-    Report::print_reports(&warnings, &files);
-    let (mut dag, mut vcp, warnings) = export(exe, program, flags).map_err(|r| {
-        Report::print_reports(&r, &files);
-    })?;
+    // the build goes on without the cache if its folder cannot be created
+    let cache = config
+        .cache_folder
+        .as_ref()
+        .and_then(|folder| TemplateCache::new(PathBuf::from(folder), flags, &config.prime).ok());
+    let (exe, mut warnings) = execute::constraint_execution(&program, flags, &config.prime, cache)?;
+    let template_instances = exe.number_of_nodes();
+    let (mut dag, mut vcp, mut export_warnings) =
+        export(exe, program, flags).map_err(|mut r| {
+            warnings.append(&mut r);
+            warnings.clone()
+        })?;
     if config.inspect_constraints {
-        Report::print_reports(&warnings, &files);
+        warnings.append(&mut export_warnings);
     }
-    if config.flag_f {
+    let mut statistics = if config.flag_stats {
+        Some(Statistics::new(&dag))
+    } else {
+        None
+    };
+    let exporter: ConstraintWriter = if config.flag_f {
         sync_dag_and_vcp(&mut vcp, &mut dag);
-        if config.flag_json_sub {
-            use constraint_writers::json_writer::SubstitutionJSON;
-            let substitution_log = SubstitutionJSON::new(&config.json_substitutions).unwrap();
-            let _ = substitution_log.end();
-            println!(
-                "{} {}",
-                Colour::Green.paint("Written successfully:"),
-                config.json_substitutions
-            );
-        };
-
-        Result::Ok((Box::new(dag), vcp))
+        Box::new(dag)
     } else {
-        let list = simplification_process(&mut vcp, dag, &config);
-        if config.flag_json_sub {
-            println!(
-                "{} {}",
-                Colour::Green.paint("Written successfully:"),
-                config.json_substitutions
-            );
-        };
-        Result::Ok((Box::new(list), vcp))
-    }
-}
-
-type InstantiationResponse = Result<(ExecutedProgram, ReportCollection), ReportCollection>;
-fn instantiation(
-    program: &ProgramArchive,
-    flags: FlagsExecution,
-    prime: &String,
-) -> InstantiationResponse {
-    let execution_result = execute::constraint_execution(&program, flags, prime);
-    match execution_result {
-        Ok((program_exe, warnings)) => {
-            let no_nodes = program_exe.number_of_nodes();
-            let success = Colour::Green.paint("template instances");
-            let nodes_created = format!("{}: {}", success, no_nodes);
-            println!("{}", &nodes_created);
-            InstantiationResponse::Ok((program_exe, warnings))
+        let list = simplification_process(&mut vcp, dag, config);
+        if let Some(statistics) = &mut statistics {
+            statistics.add_simplification(&list);
         }
-        Err(reports) => InstantiationResponse::Err(reports),
-    }
+        Box::new(list)
+    };
+    Result::Ok(BuildOutput {
+        exporter,
+        vcp,
+        template_instances,
+        warnings,
+        statistics,
+    })
 }
 
 fn export(exe: ExecutedProgram, program: ProgramArchive, flags: FlagsExecution) -> ExportResult {
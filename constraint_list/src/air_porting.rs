@@ -0,0 +1,13 @@
+use super::{ConstraintList, C};
+use constraint_writers::air_writer::AIRWriter;
+
+pub fn port_air(list: &ConstraintList, output: &str, columns: &str) -> Result<(), ()> {
+    let wires = ConstraintList::no_wires(list);
+    let mut writer = AIRWriter::new(output, columns, &list.field, wires)?;
+    for c_id in list.constraints.get_ids() {
+        let c = list.constraints.read_constraint(c_id).unwrap();
+        let c = C::apply_correspondence(&c, &list.signal_map);
+        writer.write_constraint(c.a(), c.b(), c.c())?;
+    }
+    writer.end()
+}
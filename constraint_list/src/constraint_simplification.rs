@@ -698,15 +698,23 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
         }
     }
 
-    let erased =
-        crate::non_linear_simplification::simplify(&mut constraint_storage, &forbidden, &field);
-
-    for signal in erased {
-        deleted.insert(signal);
+    if apply_linear {
+        let substitutions =
+            crate::non_linear_simplification::simplify(&mut constraint_storage, &forbidden, &field);
+        log_substitutions(&substitutions, &mut substitution_log);
+        for substitution in &substitutions {
+            deleted.insert(*substitution.from());
+        }
     }
 
     let _trash = constraint_storage.extract_with(&|c| C::is_empty(c));
 
+    if apply_linear && remove_unused {
+        // the non-linear substitutions rewrite the constraints in place, so the
+        // map no longer tells which signals are used
+        non_linear_map = build_non_linear_signal_map(&constraint_storage);
+    }
+
     let signal_map = {
         // println!("Rebuild witness");
         let now = SystemTime::now();
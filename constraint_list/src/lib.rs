@@ -3,15 +3,21 @@ use std::collections::{HashMap, HashSet, LinkedList};
 use circom_algebra::constraint_storage::ConstraintStorage;
 use circom_algebra::num_bigint::BigInt;
 use constraint_writers::debug_writer::DebugWriter;
+use constraint_writers::log_writer::Log;
+use constraint_writers::r1cs_reader::R1CSConstraint;
+use constraint_writers::sym_writer::SymElem;
 use constraint_writers::ConstraintExporter;
 
+mod air_porting;
 mod constraint_simplification;
 mod json_porting;
 mod non_linear_simplification;
 mod non_linear_utils;
 mod r1cs_porting;
+mod smt2_porting;
 mod state_utils;
 mod sym_porting;
+pub mod witness_checker;
 
 type C = circom_algebra::algebra::Constraint<usize>;
 type S = circom_algebra::algebra::Substitution<usize>;
@@ -174,7 +180,7 @@ pub struct ConstraintList {
 }
 
 impl ConstraintExporter for ConstraintList {
-    fn r1cs(&self, out: &str, custom_gates: bool) -> Result<(), ()> {
+    fn r1cs(&self, out: &str, custom_gates: bool) -> Result<Log, ()> {
         r1cs_porting::port_r1cs(self, out, custom_gates)
     }
 
@@ -185,6 +191,22 @@ impl ConstraintExporter for ConstraintList {
     fn sym(&self, out: &str) -> Result<(), ()> {
         sym_porting::port_sym(self, out)
     }
+
+    fn r1cs_constraints(&self) -> Vec<R1CSConstraint> {
+        r1cs_porting::port_constraints(self)
+    }
+
+    fn sym_elems(&self) -> Vec<SymElem> {
+        sym_porting::sym_elems(self)
+    }
+
+    fn air(&self, out: &str, columns: &str) -> Result<(), ()> {
+        air_porting::port_air(self, out, columns)
+    }
+
+    fn smt2(&self, out: &str) -> Result<(), ()> {
+        smt2_porting::port_smt2(self, out)
+    }
 }
 
 impl ConstraintList {
@@ -1,11 +1,340 @@
-use circom_algebra::constraint_storage::ConstraintStorage;
+use super::{A, C, S};
+use circom_algebra::constraint_storage::{ConstraintID, ConstraintStorage};
+use circom_algebra::modular_arithmetic;
 use circom_algebra::num_bigint::BigInt;
-use std::collections::{HashSet, LinkedList};
+use std::collections::{BTreeMap, HashMap, HashSet, LinkedList};
 
+// A constraint A*B - C = 0 is expanded into a polynomial over the monomials x_i*x_j,
+// where x_0 is the constant signal. Monomials with a 0 index are the linear part.
+type Monomial = (usize, usize);
+type Polynomial = BTreeMap<Monomial, BigInt>;
+type QuadraticKey = Vec<(Monomial, BigInt)>;
+
+// Constraints whose expansion exceeds this number of products are left untouched
+const MAX_EXPANSION: usize = 1 << 16;
+
+fn monomial(i: usize, j: usize) -> Monomial {
+    if i <= j {
+        (i, j)
+    } else {
+        (j, i)
+    }
+}
+
+fn is_quadratic_monomial(m: &Monomial) -> bool {
+    let constant = C::constant_coefficient();
+    m.0 != constant && m.1 != constant
+}
+
+fn add_to_polynomial(p: &mut Polynomial, m: Monomial, value: &BigInt, field: &BigInt) {
+    let entry = p.entry(m).or_insert_with(|| BigInt::from(0));
+    *entry = modular_arithmetic::add(entry, value, field);
+}
+
+fn expand(constraint: &C, field: &BigInt) -> Option<Polynomial> {
+    if constraint.a().len() * constraint.b().len() > MAX_EXPANSION {
+        return None;
+    }
+    let constant = C::constant_coefficient();
+    let mut polynomial = Polynomial::new();
+    for (i, a_i) in constraint.a() {
+        for (j, b_j) in constraint.b() {
+            let value = modular_arithmetic::mul(a_i, b_j, field);
+            add_to_polynomial(&mut polynomial, monomial(*i, *j), &value, field);
+        }
+    }
+    for (k, c_k) in constraint.c() {
+        let value = modular_arithmetic::prefix_sub(c_k, field);
+        add_to_polynomial(&mut polynomial, monomial(constant, *k), &value, field);
+    }
+    polynomial.retain(|_, v| *v != BigInt::from(0));
+    Some(polynomial)
+}
+
+// Returns the quadratic part of the polynomial scaled so that its first coefficient is 1,
+// together with the inverse of the factor that was used.
+fn quadratic_key(polynomial: &Polynomial, field: &BigInt) -> (QuadraticKey, BigInt) {
+    let mut quadratic = polynomial.iter().filter(|(m, _)| is_quadratic_monomial(m));
+    let inverse = match quadratic.next() {
+        Some((_, first)) => modular_arithmetic::div(&BigInt::from(1), first, field)
+            .unwrap_or_else(|_| unreachable!("zero coefficients are removed")),
+        None => BigInt::from(1),
+    };
+    let key = polynomial
+        .iter()
+        .filter(|(m, _)| is_quadratic_monomial(m))
+        .map(|(m, v)| (*m, modular_arithmetic::mul(v, &inverse, field)))
+        .collect();
+    (key, inverse)
+}
+
+// Linear constraint equivalent to inverse_0 * p_0 - inverse_1 * p_1 = 0,
+// assuming that the quadratic parts of both sides cancel out
+fn linear_difference(
+    p_0: &Polynomial,
+    inverse_0: &BigInt,
+    p_1: &Polynomial,
+    inverse_1: &BigInt,
+    field: &BigInt,
+) -> C {
+    let mut difference = Polynomial::new();
+    for (m, v) in p_0.iter().filter(|(m, _)| !is_quadratic_monomial(m)) {
+        let value = modular_arithmetic::mul(v, inverse_0, field);
+        add_to_polynomial(&mut difference, *m, &value, field);
+    }
+    for (m, v) in p_1.iter().filter(|(m, _)| !is_quadratic_monomial(m)) {
+        let value = modular_arithmetic::mul(v, inverse_1, field);
+        let value = modular_arithmetic::prefix_sub(&value, field);
+        add_to_polynomial(&mut difference, *m, &value, field);
+    }
+    into_linear_constraint(difference, field)
+}
+
+fn into_linear_constraint(polynomial: Polynomial, field: &BigInt) -> C {
+    let mut coefficients = HashMap::new();
+    for ((_, signal), value) in polynomial {
+        coefficients.insert(signal, value);
+    }
+    let expression = A::hashmap_into_arith(coefficients);
+    let mut constraint = A::transform_expression_to_constraint_form(expression, field).unwrap();
+    C::fix_constraint(&mut constraint, field);
+    constraint
+}
+
+// Replaces every non-linear constraint whose quadratic part is a multiple of the quadratic
+// part of a previous constraint by the linear constraint obtained subtracting both.
+// Redundant constraints become empty. Returns the ids of the new linear constraints.
+fn collapse_quadratic_classes(
+    storage: &mut ConstraintStorage,
+    field: &BigInt,
+) -> Vec<ConstraintID> {
+    let mut classes: HashMap<QuadraticKey, (Polynomial, BigInt)> = HashMap::new();
+    let mut new_linear = Vec::new();
+    for id in storage.get_ids() {
+        let mut constraint = storage.read_constraint(id).unwrap();
+        if C::is_linear(&constraint) {
+            continue;
+        }
+        C::fix_constraint(&mut constraint, field);
+        if C::is_linear(&constraint) {
+            storage.replace(id, constraint);
+            new_linear.push(id);
+            continue;
+        }
+        let polynomial = if let Some(polynomial) = expand(&constraint, field) {
+            polynomial
+        } else {
+            continue;
+        };
+        let (key, inverse) = quadratic_key(&polynomial, field);
+        if key.is_empty() {
+            storage.replace(id, into_linear_constraint(polynomial, field));
+            new_linear.push(id);
+        } else if let Some((pivot, pivot_inverse)) = classes.get(&key) {
+            let linear = linear_difference(&polynomial, &inverse, pivot, pivot_inverse, field);
+            if !linear.is_empty() {
+                new_linear.push(id);
+            }
+            storage.replace(id, linear);
+        } else {
+            classes.insert(key, (polynomial, inverse));
+        }
+    }
+    new_linear
+}
+
+fn build_occurrences(storage: &ConstraintStorage) -> HashMap<usize, HashSet<ConstraintID>> {
+    let mut occurrences: HashMap<usize, HashSet<ConstraintID>> = HashMap::new();
+    for id in storage.get_ids() {
+        let constraint = storage.read_constraint(id).unwrap();
+        for signal in constraint.take_cloned_signals() {
+            occurrences.entry(signal).or_default().insert(id);
+        }
+    }
+    occurrences
+}
+
+// Erases one signal of each new linear constraint that is not forbidden, substituting it
+// in the rest of the storage. Returns true if some substitution was applied.
+fn apply_linear_constraints(
+    storage: &mut ConstraintStorage,
+    new_linear: Vec<ConstraintID>,
+    forbidden: &HashSet<usize>,
+    field: &BigInt,
+    substitutions: &mut LinkedList<S>,
+) -> bool {
+    let mut occurrences = build_occurrences(storage);
+    let mut applied = false;
+    for id in new_linear {
+        let constraint = storage.read_constraint(id).unwrap();
+        if constraint.is_empty() || !C::is_linear(&constraint) {
+            continue;
+        }
+        let signal = constraint
+            .take_cloned_signals()
+            .into_iter()
+            .filter(|s| !forbidden.contains(s))
+            .max();
+        let signal = if let Some(signal) = signal {
+            signal
+        } else {
+            continue;
+        };
+        let substitution = C::clear_signal_from_linear(constraint, &signal, field);
+        storage.replace(id, C::empty());
+        let affected = occurrences.remove(&signal).unwrap_or_default();
+        for c_id in affected {
+            if c_id == id {
+                continue;
+            }
+            let mut constraint = storage.read_constraint(c_id).unwrap();
+            C::apply_substitution(&mut constraint, &substitution, field);
+            for new_signal in constraint.take_cloned_signals() {
+                occurrences.entry(new_signal).or_default().insert(c_id);
+            }
+            storage.replace(c_id, constraint);
+        }
+        for previous in substitutions.iter_mut() {
+            S::apply_substitution(previous, &substitution, field);
+            S::rmv_zero_coefficients(previous);
+        }
+        substitutions.push_back(substitution);
+        applied = true;
+    }
+    applied
+}
+
+// Looks for non-linear constraints that are redundant or that collapse to linear ones
+// when combined with the rest of the storage. The linear constraints found are used to
+// remove signals that are not forbidden; the substitutions of those signals are returned.
 pub fn simplify(
-    _storage: &mut ConstraintStorage,
-    _forbidden: &HashSet<usize>,
-    _field: &BigInt,
-) -> LinkedList<usize> {
-    LinkedList::new()
+    storage: &mut ConstraintStorage,
+    forbidden: &HashSet<usize>,
+    field: &BigInt,
+) -> LinkedList<S> {
+    let mut substitutions = LinkedList::new();
+    loop {
+        let new_linear = collapse_quadratic_classes(storage, field);
+        if new_linear.is_empty()
+            || !apply_linear_constraints(storage, new_linear, forbidden, field, &mut substitutions)
+        {
+            break;
+        }
+    }
+    substitutions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const FIELD: &str = "257";
+
+    fn field() -> BigInt {
+        BigInt::parse_bytes(FIELD.as_bytes(), 10).unwrap()
+    }
+
+    fn linear(terms: &[(usize, i64)]) -> HashMap<usize, BigInt> {
+        let mut coefficients = HashMap::new();
+        coefficients.insert(C::constant_coefficient(), BigInt::from(0));
+        for (signal, value) in terms {
+            coefficients.insert(*signal, BigInt::from(*value));
+        }
+        coefficients
+    }
+
+    // a * b = c
+    fn quadratic(a: &[(usize, i64)], b: &[(usize, i64)], c: &[(usize, i64)]) -> C {
+        let mut c = linear(c);
+        for value in c.values_mut() {
+            *value = modular_arithmetic::prefix_sub(value, &field());
+        }
+        let expression = A::Quadratic {
+            a: linear(a),
+            b: linear(b),
+            c,
+        };
+        A::transform_expression_to_constraint_form(expression, &field()).unwrap()
+    }
+
+    fn run(constraints: Vec<C>, forbidden: &[usize]) -> (Vec<C>, Vec<usize>) {
+        let mut storage = ConstraintStorage::new();
+        for constraint in constraints {
+            storage.add_constraint(constraint);
+        }
+        let forbidden: HashSet<usize> = forbidden.iter().cloned().collect();
+        let erased = simplify(&mut storage, &forbidden, &field());
+        let _ = storage.extract_with(&|c| C::is_empty(c));
+        let remaining = storage
+            .get_ids()
+            .into_iter()
+            .map(|id| storage.read_constraint(id).unwrap())
+            .collect();
+        (remaining, erased.iter().map(|s| *s.from()).collect())
+    }
+
+    #[test]
+    fn duplicated_quadratic_constraints_are_removed() {
+        let constraints = vec![
+            quadratic(&[(1, 1)], &[(2, 1)], &[(3, 1)]),
+            quadratic(&[(1, 1)], &[(2, 1)], &[(3, 1)]),
+            quadratic(&[(2, 2)], &[(1, 3)], &[(3, 6)]),
+        ];
+        assert_eq!(constraints.len(), 3);
+        let (remaining, erased) = run(constraints, &[1, 2, 3]);
+        assert_eq!(remaining.len(), 1);
+        assert!(!C::is_linear(&remaining[0]));
+        assert!(erased.is_empty());
+    }
+
+    #[test]
+    fn equal_products_collapse_to_linear() {
+        let constraints = vec![
+            quadratic(&[(1, 1)], &[(2, 1)], &[(3, 1)]),
+            quadratic(&[(1, 1)], &[(2, 1)], &[(4, 1)]),
+        ];
+        let (remaining, erased) = run(constraints, &[1, 2, 3]);
+        assert_eq!(remaining.len(), 1);
+        assert!(!C::is_linear(&remaining[0]));
+        assert_eq!(erased, vec![4]);
+    }
+
+    #[test]
+    fn forbidden_signals_are_kept() {
+        let constraints = vec![
+            quadratic(&[(1, 1)], &[(2, 1)], &[(3, 1)]),
+            quadratic(&[(1, 1)], &[(2, 1)], &[(4, 1)]),
+        ];
+        let (remaining, erased) = run(constraints, &[1, 2, 3, 4]);
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining.iter().filter(|c| C::is_linear(c)).count(), 1);
+        assert!(erased.is_empty());
+    }
+
+    #[test]
+    fn constant_products_collapse_to_linear() {
+        let constraints = vec![
+            quadratic(&[(0, 2)], &[(1, 1)], &[(2, 1)]),
+            quadratic(&[(1, 1)], &[(2, 1)], &[(3, 1)]),
+        ];
+        let (remaining, erased) = run(constraints, &[1, 3]);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(erased, vec![2]);
+        let signals = remaining[0].take_cloned_signals();
+        assert!(!signals.contains(&2));
+    }
+
+    #[test]
+    fn substitutions_expose_new_redundancies() {
+        let constraints = vec![
+            quadratic(&[(1, 1)], &[(2, 1)], &[(3, 1)]),
+            quadratic(&[(1, 1)], &[(2, 1)], &[(4, 1)]),
+            quadratic(&[(4, 1)], &[(5, 1)], &[(6, 1)]),
+            quadratic(&[(3, 1)], &[(5, 1)], &[(7, 1)]),
+        ];
+        assert_eq!(constraints.len(), 4);
+        let (remaining, erased) = run(constraints, &[1, 2, 5, 6]);
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.iter().all(|c| !C::is_linear(c)));
+        assert_eq!(erased, vec![4, 7]);
+    }
 }
@@ -19,7 +19,7 @@ pub fn obtain_and_simplify_non_linear(
         if C::is_linear(&constraint) {
             linear.push_back(constraint);
         } else {
-            storage.add_constraint(constraint);
+            storage.add_constraint_from(constraint, iter.node_id);
         }
     }
     for edge in EncodingIterator::edges(&iter) {
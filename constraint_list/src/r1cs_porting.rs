@@ -1,10 +1,26 @@
 use super::{ConstraintList, EncodingIterator, SignalMap, C};
+use constraint_writers::log_writer::Log;
+use constraint_writers::r1cs_reader::R1CSConstraint;
 use constraint_writers::r1cs_writer::{
     ConstraintSection, CustomGatesAppliedData, HeaderData, R1CSWriter, SignalSection,
 };
 
-pub fn port_r1cs(list: &ConstraintList, output: &str, custom_gates: bool) -> Result<(), ()> {
-    use constraint_writers::log_writer::Log;
+// the constraints in the order of the r1cs file, over the witness
+pub fn port_constraints(list: &ConstraintList) -> Vec<R1CSConstraint> {
+    let mut constraints = Vec::new();
+    for c_id in list.constraints.get_ids() {
+        let c = list.constraints.read_constraint(c_id).unwrap();
+        let c = C::apply_correspondence(&c, &list.signal_map);
+        constraints.push(R1CSConstraint {
+            a: c.a().clone(),
+            b: c.b().clone(),
+            c: c.c().clone(),
+        });
+    }
+    constraints
+}
+
+pub fn port_r1cs(list: &ConstraintList, output: &str, custom_gates: bool) -> Result<Log, ()> {
     let field_size = if list.field.bits() % 32 == 0 {
         list.field.bits() / 4
     } else {
@@ -122,6 +138,5 @@ pub fn port_r1cs(list: &ConstraintList, output: &str, custom_gates: bool) -> Res
         let r1cs = custom_gates_applied_section.end_section()?;
         R1CSWriter::finish_writing(r1cs)?;
     }
-    Log::print(&log);
-    Ok(())
+    Ok(log)
 }
@@ -0,0 +1,45 @@
+use super::{ConstraintList, EncodingIterator, IteratorSignal, SignalMap, C};
+use constraint_writers::smt2_writer::SMTWriter;
+
+pub fn port_smt2(list: &ConstraintList, output: &str) -> Result<(), ()> {
+    let mut writer = SMTWriter::new(output, &list.field)?;
+    let iter = EncodingIterator::new(&list.dag_encoding);
+    let mut declared = vec![false; list.signal_map.len()];
+    declare_signals(iter, &list.signal_map, &mut declared, &mut writer)?;
+    // the signals of the main component are the first ones after the signal one
+    let outputs = 1 + list.no_public_outputs;
+    let public_inputs = outputs + list.no_public_inputs;
+    let private_inputs = public_inputs + list.no_private_inputs_witness;
+    writer.tag_signals("outputs", 1..outputs)?;
+    writer.tag_signals("public-inputs", outputs..public_inputs)?;
+    writer.tag_signals("private-inputs", public_inputs..private_inputs)?;
+    for c_id in list.constraints.get_ids() {
+        let c = list.constraints.read_constraint(c_id).unwrap();
+        let c = C::apply_correspondence(&c, &list.signal_map);
+        writer.write_constraint(c.a(), c.b(), c.c())?;
+    }
+    writer.end()
+}
+
+// the witness is named after the first signal that is mapped to it,
+// the removed signals are not declared
+fn declare_signals(
+    mut iter: EncodingIterator,
+    map: &SignalMap,
+    declared: &mut Vec<bool>,
+    writer: &mut SMTWriter,
+) -> Result<(), ()> {
+    let (signals, _) = EncodingIterator::take(&mut iter);
+    for signal in signals {
+        let signal = IteratorSignal::new(signal, map);
+        if signal.witness != map.len() && !declared[signal.witness] {
+            declared[signal.witness] = true;
+            writer.declare_signal(signal.witness, &signal.name)?;
+        }
+    }
+    for edge in EncodingIterator::edges(&iter) {
+        let next = EncodingIterator::next(&iter, edge);
+        declare_signals(next, map, declared, writer)?;
+    }
+    Result::Ok(())
+}
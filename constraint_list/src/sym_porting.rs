@@ -3,19 +3,23 @@ use circom_algebra::num_traits::AsPrimitive;
 use constraint_writers::sym_writer::*;
 
 pub fn port_sym(list: &ConstraintList, file_name: &str) -> Result<(), ()> {
-    let iter = EncodingIterator::new(&list.dag_encoding);
     let mut dot_sym = SymFile::new(file_name)?;
-    signal_iteration(iter, &list.signal_map, &mut dot_sym)?;
+    for sym_elem in sym_elems(list) {
+        SymFile::write_sym_elem(&mut dot_sym, sym_elem)?;
+    }
     SymFile::finish_writing(dot_sym)?;
     //SymFile::close(dot_sym);
     Ok(())
 }
 
-pub fn signal_iteration(
-    mut iter: EncodingIterator,
-    map: &SignalMap,
-    dot_sym: &mut SymFile,
-) -> Result<(), ()> {
+pub fn sym_elems(list: &ConstraintList) -> Vec<SymElem> {
+    let iter = EncodingIterator::new(&list.dag_encoding);
+    let mut elems = Vec::new();
+    signal_iteration(iter, &list.signal_map, &mut elems);
+    elems
+}
+
+pub fn signal_iteration(mut iter: EncodingIterator, map: &SignalMap, elems: &mut Vec<SymElem>) {
     let (signals, _) = EncodingIterator::take(&mut iter);
 
     for signal in signals {
@@ -30,12 +34,11 @@ pub fn signal_iteration(
             node_id: iter.node_id.as_(),
             symbol: signal.name.clone(),
         };
-        SymFile::write_sym_elem(dot_sym, sym_elem)?;
+        elems.push(sym_elem);
     }
 
     for edge in EncodingIterator::edges(&iter) {
         let next = EncodingIterator::next(&iter, edge);
-        signal_iteration(next, map, dot_sym)?;
+        signal_iteration(next, map, elems);
     }
-    Ok(())
 }
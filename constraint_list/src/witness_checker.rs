@@ -0,0 +1,95 @@
+use circom_algebra::modular_arithmetic;
+use circom_algebra::num_bigint::BigInt;
+use constraint_writers::r1cs_reader::{LinearCombination, R1CSConstraint};
+
+pub struct UnsatisfiedConstraint {
+    // position of the constraint in the r1cs file
+    pub index: usize,
+    pub a: BigInt,
+    pub b: BigInt,
+    pub c: BigInt,
+    // witness positions used by the constraint, in increasing order
+    pub signals: Vec<usize>,
+}
+
+fn evaluate(
+    linear_combination: &LinearCombination,
+    witness: &[BigInt],
+    field: &BigInt,
+) -> Result<BigInt, ()> {
+    let mut value = BigInt::from(0);
+    for (signal, coefficient) in linear_combination {
+        let signal_value = witness.get(*signal).ok_or(())?;
+        let term = modular_arithmetic::mul(coefficient, signal_value, field);
+        value = modular_arithmetic::add(&value, &term, field);
+    }
+    Result::Ok(value)
+}
+
+// Evaluates A * B - C for every constraint of the r1cs, the witness must contain
+// a value for every wire, starting by the signal one
+pub fn check_witness(
+    field: &BigInt,
+    constraints: &[R1CSConstraint],
+    witness: &[BigInt],
+) -> Result<Vec<UnsatisfiedConstraint>, ()> {
+    let mut unsatisfied = Vec::new();
+    for (index, constraint) in constraints.iter().enumerate() {
+        let a = evaluate(&constraint.a, witness, field)?;
+        let b = evaluate(&constraint.b, witness, field)?;
+        let c = evaluate(&constraint.c, witness, field)?;
+        let product = modular_arithmetic::mul(&a, &b, field);
+        if product != c {
+            let mut signals: Vec<usize> = constraint
+                .a
+                .keys()
+                .chain(constraint.b.keys())
+                .chain(constraint.c.keys())
+                .filter(|signal| **signal != 0)
+                .cloned()
+                .collect();
+            signals.sort();
+            signals.dedup();
+            unsatisfied.push(UnsatisfiedConstraint {
+                index,
+                a,
+                b,
+                c,
+                signals,
+            });
+        }
+    }
+    Result::Ok(unsatisfied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constraint_writers::r1cs_reader::R1CSFile;
+    use constraint_writers::wtns_reader::WtnsFile;
+
+    const EXAMPLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../examples/multiplier2/");
+
+    #[test]
+    fn checks_the_multiplier_example() {
+        let r1cs = R1CSFile::read(&format!("{}multiplier2.r1cs", EXAMPLE)).unwrap();
+        let wtns = WtnsFile::read(&format!("{}output.wtns", EXAMPLE)).unwrap();
+        assert_eq!(wtns.field, r1cs.header.field);
+        assert_eq!(wtns.witness.len(), r1cs.header.total_wires);
+        let (field, constraints) = (&r1cs.header.field, &r1cs.constraints);
+        assert!(check_witness(field, constraints, &wtns.witness)
+            .unwrap()
+            .is_empty());
+
+        // c = a * b with a = 3 and b = 11
+        let mut witness = wtns.witness.clone();
+        witness[1] = BigInt::from(32);
+        let unsatisfied = check_witness(field, constraints, &witness).unwrap();
+        assert_eq!(unsatisfied.len(), 1);
+        assert_eq!(unsatisfied[0].index, 0);
+        assert_eq!(unsatisfied[0].signals, vec![1, 2, 3]);
+
+        witness.truncate(2);
+        assert!(check_witness(field, constraints, &witness).is_err());
+    }
+}
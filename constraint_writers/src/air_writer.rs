@@ -0,0 +1,340 @@
+use circom_algebra::num_bigint::BigInt;
+use json::JsonValue;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+// Circle-STARK provers work with columns packed in lanes of 16 elements,
+// shorter traces are padded up to this size
+const LOG_MIN_ROWS: usize = 4;
+
+// Every row of the trace satisfies the same identity over the trace columns
+// x, y, z and the preprocessed columns q_*. The padding rows have every
+// preprocessed value set to zero.
+pub const IDENTITY: &str = "q_m * x * y + q_x * x + q_y * y + q_z * z + q_c";
+const TRACE_COLUMNS: [&str; 3] = ["x", "y", "z"];
+const PREPROCESSED_COLUMNS: [&str; 5] = ["q_m", "q_x", "q_y", "q_z", "q_c"];
+
+// A linear combination reduced to coefficient * variable + constant, the
+// variable is None when the combination is a constant
+struct Wire {
+    variable: Option<usize>,
+    coefficient: BigInt,
+    constant: BigInt,
+}
+
+// Writes the constraints A * B - C = 0 as rows of the identity and, in the
+// columns file, the variable that each cell of the trace holds. The variables
+// below the number of wires are the witness, the others are auxiliary values
+// that accumulate the linear combinations with more than one signal. An
+// auxiliary value first appears in the z cell of a row with q_m = 0 and
+// q_z = -1, which defines it as q_x * x + q_y * y + q_c. Cells holding the same
+// variable must be equal and empty cells hold zero. The columns file also lists,
+// for every variable, the [column, row] cells that hold it.
+pub struct AIRWriter {
+    air: BufWriter<File>,
+    columns: BufWriter<File>,
+    cells: Vec<Vec<(usize, usize)>>,
+    field: BigInt,
+    no_wires: usize,
+    no_auxiliary: usize,
+    rows_written: usize,
+    constraints_written: usize,
+}
+
+impl AIRWriter {
+    pub fn new(
+        file: &str,
+        columns_file: &str,
+        field: &BigInt,
+        no_wires: usize,
+    ) -> Result<AIRWriter, ()> {
+        let mut air = BufWriter::new(File::create(file).map_err(|_err| {})?);
+        let mut columns = BufWriter::new(File::create(columns_file).map_err(|_err| {})?);
+        let header = format!(
+            "{{\n\"field\": \"{}\",\n\"identity\": \"{}\",\n\"trace_columns\": {},\n\"preprocessed_columns\": {},\n\"rows\": [",
+            field.to_str_radix(10),
+            IDENTITY,
+            JsonValue::from(TRACE_COLUMNS.to_vec()).dump(),
+            JsonValue::from(PREPROCESSED_COLUMNS.to_vec()).dump()
+        );
+        air.write_all(header.as_bytes()).map_err(|_err| {})?;
+        let header = format!(
+            "{{\n\"n_wires\": {},\n\"trace_columns\": {},\n\"rows\": [",
+            no_wires,
+            JsonValue::from(TRACE_COLUMNS.to_vec()).dump()
+        );
+        columns.write_all(header.as_bytes()).map_err(|_err| {})?;
+        Result::Ok(AIRWriter {
+            air,
+            columns,
+            cells: vec![Vec::new(); no_wires],
+            field: field.clone(),
+            no_wires,
+            no_auxiliary: 0,
+            rows_written: 0,
+            constraints_written: 0,
+        })
+    }
+
+    fn normalize(&self, value: &BigInt) -> BigInt {
+        ((value % &self.field) + &self.field) % &self.field
+    }
+
+    fn write_row(&mut self, selectors: [BigInt; 5], cells: [Option<usize>; 3]) -> Result<(), ()> {
+        let separator: &[u8] = if self.rows_written == 0 {
+            b"\n"
+        } else {
+            b",\n"
+        };
+        let mut row = JsonValue::new_array();
+        for selector in &selectors {
+            row.push(self.normalize(selector).to_str_radix(10)).unwrap();
+        }
+        self.air.write_all(separator).map_err(|_err| {})?;
+        self.air
+            .write_all(row.dump().as_bytes())
+            .map_err(|_err| {})?;
+        let mut row = JsonValue::new_array();
+        for (column, cell) in cells.iter().enumerate() {
+            if let Some(variable) = cell {
+                self.cells[*variable].push((column, self.rows_written));
+            }
+            row.push(cell.map_or(JsonValue::Null, JsonValue::from))
+                .unwrap();
+        }
+        self.columns.write_all(separator).map_err(|_err| {})?;
+        self.columns
+            .write_all(row.dump().as_bytes())
+            .map_err(|_err| {})?;
+        self.rows_written += 1;
+        Result::Ok(())
+    }
+
+    fn new_auxiliary(&mut self) -> usize {
+        self.no_auxiliary += 1;
+        self.cells.push(Vec::new());
+        self.no_wires + self.no_auxiliary - 1
+    }
+
+    // Adds one row for every signal of the combination after the first one
+    fn reduce(&mut self, values: &HashMap<usize, BigInt>) -> Result<Wire, ()> {
+        let zero = BigInt::from(0);
+        let mut terms: Vec<(usize, BigInt)> = values
+            .iter()
+            .map(|(signal, value)| (*signal, self.normalize(value)))
+            .filter(|(signal, value)| *signal != 0 && *value != zero)
+            .collect();
+        terms.sort();
+        let constant = values.get(&0).map_or(zero.clone(), |v| self.normalize(v));
+        if terms.is_empty() {
+            return Result::Ok(Wire {
+                variable: None,
+                coefficient: zero,
+                constant,
+            });
+        }
+        let (first, coefficient) = terms[0].clone();
+        if terms.len() == 1 {
+            return Result::Ok(Wire {
+                variable: Some(first),
+                coefficient,
+                constant,
+            });
+        }
+        let minus_one = BigInt::from(-1);
+        let mut accumulated = self.new_auxiliary();
+        let (second, second_coefficient) = terms[1].clone();
+        self.write_row(
+            [
+                zero.clone(),
+                coefficient,
+                second_coefficient,
+                minus_one.clone(),
+                zero.clone(),
+            ],
+            [Some(first), Some(second), Some(accumulated)],
+        )?;
+        for (signal, coefficient) in terms.into_iter().skip(2) {
+            let next = self.new_auxiliary();
+            self.write_row(
+                [
+                    zero.clone(),
+                    BigInt::from(1),
+                    coefficient,
+                    minus_one.clone(),
+                    zero.clone(),
+                ],
+                [Some(accumulated), Some(signal), Some(next)],
+            )?;
+            accumulated = next;
+        }
+        Result::Ok(Wire {
+            variable: Some(accumulated),
+            coefficient: BigInt::from(1),
+            constant,
+        })
+    }
+
+    pub fn write_constraint(
+        &mut self,
+        a: &HashMap<usize, BigInt>,
+        b: &HashMap<usize, BigInt>,
+        c: &HashMap<usize, BigInt>,
+    ) -> Result<(), ()> {
+        let a = self.reduce(a)?;
+        let b = self.reduce(b)?;
+        let c = self.reduce(c)?;
+        // (ka * x + ca) * (kb * y + cb) - (kc * z + cc) = 0
+        let selectors = [
+            &a.coefficient * &b.coefficient,
+            &a.coefficient * &b.constant,
+            &a.constant * &b.coefficient,
+            -&c.coefficient,
+            &a.constant * &b.constant - &c.constant,
+        ];
+        let zero = BigInt::from(0);
+        if selectors.iter().any(|s| self.normalize(s) != zero) {
+            self.write_row(selectors, [a.variable, b.variable, c.variable])?;
+        }
+        self.constraints_written += 1;
+        Result::Ok(())
+    }
+
+    pub fn end(mut self) -> Result<(), ()> {
+        let mut log_n_rows = LOG_MIN_ROWS;
+        while (1 << log_n_rows) < self.rows_written {
+            log_n_rows += 1;
+        }
+        let footer = format!(
+            "\n],\n\"n_rows\": {},\n\"log_n_rows\": {},\n\"n_constraints\": {}\n}}",
+            self.rows_written, log_n_rows, self.constraints_written
+        );
+        self.air.write_all(footer.as_bytes()).map_err(|_err| {})?;
+        let footer = format!(
+            "\n],\n\"n_auxiliary\": {},\n\"cells\": [",
+            self.no_auxiliary
+        );
+        self.columns
+            .write_all(footer.as_bytes())
+            .map_err(|_err| {})?;
+        for (variable, cells) in self.cells.iter().enumerate() {
+            let separator = if variable == 0 { "\n" } else { ",\n" };
+            let mut list = JsonValue::new_array();
+            for (column, row) in cells {
+                list.push(JsonValue::from(vec![*column, *row])).unwrap();
+            }
+            self.columns
+                .write_all(format!("{}{}", separator, list.dump()).as_bytes())
+                .map_err(|_err| {})?;
+        }
+        self.columns.write_all(b"\n]\n}").map_err(|_err| {})?;
+        self.air.flush().map_err(|_err| {})?;
+        self.columns.flush().map_err(|_err| {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combination(terms: &[(usize, i64)]) -> HashMap<usize, BigInt> {
+        terms.iter().map(|(s, v)| (*s, BigInt::from(*v))).collect()
+    }
+
+    fn parse(value: &JsonValue) -> BigInt {
+        BigInt::parse_bytes(value.as_str().unwrap().as_bytes(), 10).unwrap()
+    }
+
+    // Fills the trace from the witness and returns the rows that do not
+    // satisfy the identity, the padding rows included
+    fn unsatisfied_rows(air: &JsonValue, columns: &JsonValue, witness: &[i64]) -> Vec<usize> {
+        let field = parse(&air["field"]);
+        let n_wires = columns["n_wires"].as_usize().unwrap();
+        let mut values: Vec<BigInt> = witness.iter().map(|v| BigInt::from(*v)).collect();
+        values.resize(
+            n_wires + columns["n_auxiliary"].as_usize().unwrap(),
+            BigInt::from(0),
+        );
+        let mut unsatisfied = Vec::new();
+        for row in 0..(1 << air["log_n_rows"].as_usize().unwrap()) {
+            let (q, cells) = if row < air["n_rows"].as_usize().unwrap() {
+                let q: Vec<BigInt> = air["rows"][row].members().map(parse).collect();
+                let cells: Vec<Option<usize>> = columns["rows"][row]
+                    .members()
+                    .map(|c| c.as_usize())
+                    .collect();
+                (q, cells)
+            } else {
+                (vec![BigInt::from(0); 5], vec![None; 3])
+            };
+            let value = |cell: Option<usize>, values: &Vec<BigInt>| {
+                cell.map_or(BigInt::from(0), |v| values[v].clone())
+            };
+            let x = value(cells[0], &values);
+            let y = value(cells[1], &values);
+            if let Some(z) = cells[2].filter(|z| *z >= n_wires) {
+                values[z] = (&q[1] * &x + &q[2] * &y + &q[4]) % &field;
+            }
+            let z = value(cells[2], &values);
+            let identity = &q[0] * &x * &y + &q[1] * &x + &q[2] * &y + &q[3] * &z + &q[4];
+            if identity % &field != BigInt::from(0) {
+                unsatisfied.push(row);
+            }
+        }
+        unsatisfied
+    }
+
+    #[test]
+    fn trace_satisfies_the_identity_for_valid_witnesses() {
+        let folder = std::env::temp_dir().join(format!("circom_air_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let air_file = folder.join("air.json").display().to_string();
+        let columns_file = folder.join("columns.json").display().to_string();
+        let field = BigInt::from(2147483647);
+        let mut writer = AIRWriter::new(&air_file, &columns_file, &field, 5).unwrap();
+        // (w1 + 2 * w2 + 3) * w3 = w4 + 5
+        writer
+            .write_constraint(
+                &combination(&[(1, 1), (2, 2), (0, 3)]),
+                &combination(&[(3, 1)]),
+                &combination(&[(4, 1), (0, 5)]),
+            )
+            .unwrap();
+        // 0 = w1 + w2 - w3
+        writer
+            .write_constraint(
+                &combination(&[]),
+                &combination(&[]),
+                &combination(&[(1, 1), (2, 1), (3, 2147483646)]),
+            )
+            .unwrap();
+        // w1 * w1 = w2
+        writer
+            .write_constraint(
+                &combination(&[(1, 1)]),
+                &combination(&[(1, 1)]),
+                &combination(&[(2, 1)]),
+            )
+            .unwrap();
+        writer.end().unwrap();
+        let air = json::parse(&std::fs::read_to_string(&air_file).unwrap()).unwrap();
+        let columns = json::parse(&std::fs::read_to_string(&columns_file).unwrap()).unwrap();
+        let _ = std::fs::remove_dir_all(&folder);
+
+        assert_eq!(air["n_constraints"], 3);
+        assert_eq!(air["n_rows"], 6);
+        assert_eq!(air["log_n_rows"], LOG_MIN_ROWS);
+        assert_eq!(columns["n_auxiliary"], 3);
+        assert_eq!(columns["rows"][0].dump(), "[1,2,5]");
+        // the witness 1 is in the first row of both sums and in the square
+        assert_eq!(columns["cells"].len(), 8);
+        assert_eq!(columns["cells"][1].dump(), "[[0,0],[0,2],[0,5],[1,5]]");
+        assert_eq!(columns["cells"][5].dump(), "[[2,0],[0,1]]");
+        assert_eq!(columns["cells"][0].dump(), "[]");
+
+        assert!(unsatisfied_rows(&air, &columns, &[1, 3, 9, 12, 283]).is_empty());
+        assert_eq!(unsatisfied_rows(&air, &columns, &[1, 3, 9, 12, 284]), [1]);
+        assert_eq!(unsatisfied_rows(&air, &columns, &[1, 3, 10, 13, 333]), [5]);
+    }
+}
@@ -1,11 +1,27 @@
+pub mod air_writer;
 pub mod debug_writer;
 pub mod json_writer;
 pub mod log_writer;
+pub mod r1cs_reader;
 pub mod r1cs_writer;
+pub mod smt2_writer;
+pub mod sym_reader;
 pub mod sym_writer;
+pub mod wtns_reader;
+
+use r1cs_reader::R1CSConstraint;
+use sym_writer::SymElem;
 
 pub trait ConstraintExporter {
-    fn r1cs(&self, out: &str, custom_gates: bool) -> Result<(), ()>;
+    // returns the summary of the constraints that were written, the caller
+    // decides whether to print it
+    fn r1cs(&self, out: &str, custom_gates: bool) -> Result<log_writer::Log, ()>;
     fn json_constraints(&self, writer: &debug_writer::DebugWriter) -> Result<(), ()>;
     fn sym(&self, out: &str) -> Result<(), ()>;
+    // what the r1cs and the sym files contain, for the checks that do not
+    // need the files
+    fn r1cs_constraints(&self) -> Vec<R1CSConstraint>;
+    fn sym_elems(&self) -> Vec<SymElem>;
+    fn air(&self, out: &str, columns: &str) -> Result<(), ()>;
+    fn smt2(&self, out: &str) -> Result<(), ()>;
 }
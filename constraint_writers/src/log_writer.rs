@@ -1,3 +1,5 @@
+use program_structure::diagnostics::print_progress;
+
 pub struct Log {
     pub no_linear: usize,
     pub no_non_linear: usize,
@@ -24,24 +26,24 @@ impl Log {
     }
 
     pub fn print(log: &Log) {
-        println!("non-linear constraints: {}", log.no_non_linear);
-        println!("linear constraints: {}", log.no_linear);
-        println!("public inputs: {}", log.no_public_inputs);
+        print_progress(&format!("non-linear constraints: {}", log.no_non_linear));
+        print_progress(&format!("linear constraints: {}", log.no_linear));
+        print_progress(&format!("public inputs: {}", log.no_public_inputs));
         if log.no_private_inputs == log.no_private_inputs_witness {
-            println!("private inputs: {}", log.no_private_inputs);
+            print_progress(&format!("private inputs: {}", log.no_private_inputs));
         } else if log.no_private_inputs_witness == 0 {
-            println!(
+            print_progress(&format!(
                 "private inputs: {} (none belong to witness)",
                 log.no_private_inputs
-            );
+            ));
         } else {
-            println!(
+            print_progress(&format!(
                 "private inputs: {} ({} belong to witness)",
                 log.no_private_inputs, log.no_private_inputs_witness,
-            );
+            ));
         }
-        println!("public outputs: {}", log.no_public_outputs);
-        println!("wires: {}", log.no_wires);
-        println!("labels: {}", log.no_labels);
+        print_progress(&format!("public outputs: {}", log.no_public_outputs));
+        print_progress(&format!("wires: {}", log.no_wires));
+        print_progress(&format!("labels: {}", log.no_labels));
     }
 }
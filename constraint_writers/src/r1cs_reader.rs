@@ -0,0 +1,349 @@
+use super::r1cs_writer::{CustomGatesAppliedData, CustomGatesUsedData, HeaderData};
+use circom_algebra::num_bigint::{BigInt, Sign};
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+
+const MAGIC: &[u8] = b"r1cs";
+const VERSION: usize = 1;
+const HEADER_TYPE: usize = 1;
+const CONSTRAINT_TYPE: usize = 2;
+const WIRE2LABEL_TYPE: usize = 3;
+const CUSTOM_GATES_USED_TYPE: usize = 4;
+const CUSTOM_GATES_APPLIED_TYPE: usize = 5;
+
+pub type LinearCombination = HashMap<usize, BigInt>;
+
+// A * B - C = 0
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1CSConstraint {
+    pub a: LinearCombination,
+    pub b: LinearCombination,
+    pub c: LinearCombination,
+}
+
+// Contents of a file in the iden3 r1cs format. The sections of custom gates
+// are only present in circuits that use them.
+pub struct R1CSFile {
+    pub field_size: usize,
+    pub header: HeaderData,
+    pub constraints: Vec<R1CSConstraint>,
+    pub wire_to_label: Vec<usize>,
+    pub custom_gates_used: Option<CustomGatesUsedData>,
+    pub custom_gates_applied: Option<CustomGatesAppliedData>,
+}
+
+// little endian reader of the binary formats of iden3
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    pub(crate) fn take(&mut self, size: usize) -> Result<&'a [u8], ()> {
+        let end = self.position.checked_add(size).ok_or(())?;
+        let taken = self.bytes.get(self.position..end).ok_or(())?;
+        self.position = end;
+        Result::Ok(taken)
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<usize, ()> {
+        let bytes = self.take(4)?;
+        Result::Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<usize, ()> {
+        let bytes = self.take(8)?;
+        usize::try_from(u64::from_le_bytes(bytes.try_into().unwrap())).map_err(|_err| {})
+    }
+
+    pub(crate) fn read_field_element(&mut self, field_size: usize) -> Result<BigInt, ()> {
+        let bytes = self.take(field_size)?;
+        Result::Ok(BigInt::from_bytes_le(Sign::Plus, bytes))
+    }
+
+    pub(crate) fn read_string(&mut self) -> Result<String, ()> {
+        let rest = &self.bytes[self.position..];
+        let length = rest.iter().position(|byte| *byte == 0).ok_or(())?;
+        let string = String::from_utf8(rest[..length].to_vec()).map_err(|_err| {})?;
+        self.position += length + 1;
+        Result::Ok(string)
+    }
+}
+
+fn read_header(section: &[u8]) -> Result<(usize, HeaderData), ()> {
+    let mut reader = Reader::new(section);
+    let field_size = reader.read_u32()?;
+    let field = reader.read_field_element(field_size)?;
+    let header = HeaderData {
+        field,
+        total_wires: reader.read_u32()?,
+        public_outputs: reader.read_u32()?,
+        public_inputs: reader.read_u32()?,
+        private_inputs: reader.read_u32()?,
+        number_of_labels: reader.read_u64()?,
+        number_of_constraints: reader.read_u32()?,
+    };
+    Result::Ok((field_size, header))
+}
+
+fn read_linear_combination(
+    reader: &mut Reader,
+    field_size: usize,
+) -> Result<LinearCombination, ()> {
+    let non_zero_factors = reader.read_u32()?;
+    let mut linear_combination = HashMap::new();
+    for _ in 0..non_zero_factors {
+        let signal = reader.read_u32()?;
+        let factor = reader.read_field_element(field_size)?;
+        linear_combination.insert(signal, factor);
+    }
+    Result::Ok(linear_combination)
+}
+
+fn read_constraints(
+    section: &[u8],
+    field_size: usize,
+    number_of_constraints: usize,
+) -> Result<Vec<R1CSConstraint>, ()> {
+    let mut reader = Reader::new(section);
+    let mut constraints = Vec::new();
+    for _ in 0..number_of_constraints {
+        let a = read_linear_combination(&mut reader, field_size)?;
+        let b = read_linear_combination(&mut reader, field_size)?;
+        let c = read_linear_combination(&mut reader, field_size)?;
+        constraints.push(R1CSConstraint { a, b, c });
+    }
+    if reader.is_finished() {
+        Result::Ok(constraints)
+    } else {
+        Result::Err(())
+    }
+}
+
+fn read_wire_to_label(section: &[u8]) -> Result<Vec<usize>, ()> {
+    let mut reader = Reader::new(section);
+    let mut wire_to_label = Vec::new();
+    while !reader.is_finished() {
+        wire_to_label.push(reader.read_u64()?);
+    }
+    Result::Ok(wire_to_label)
+}
+
+fn read_custom_gates_used(section: &[u8], field_size: usize) -> Result<CustomGatesUsedData, ()> {
+    let mut reader = Reader::new(section);
+    let no_custom_gates = reader.read_u32()?;
+    let mut custom_gates = Vec::new();
+    for _ in 0..no_custom_gates {
+        let name = reader.read_string()?;
+        let no_parameters = reader.read_u32()?;
+        let mut parameters = Vec::new();
+        for _ in 0..no_parameters {
+            parameters.push(reader.read_field_element(field_size)?);
+        }
+        custom_gates.push((name, parameters));
+    }
+    Result::Ok(custom_gates)
+}
+
+fn read_custom_gates_applied(section: &[u8]) -> Result<CustomGatesAppliedData, ()> {
+    let mut reader = Reader::new(section);
+    let no_applications = reader.read_u32()?;
+    let mut applications = Vec::new();
+    for _ in 0..no_applications {
+        let custom_gate = reader.read_u32()?;
+        let no_signals = reader.read_u32()?;
+        let mut signals = Vec::new();
+        for _ in 0..no_signals {
+            signals.push(reader.read_u64()?);
+        }
+        applications.push((custom_gate, signals));
+    }
+    Result::Ok(applications)
+}
+
+impl R1CSFile {
+    pub fn from_bytes(bytes: &[u8]) -> Result<R1CSFile, ()> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(4)? != MAGIC || reader.read_u32()? != VERSION {
+            return Result::Err(());
+        }
+        // the sections can appear in any order, the header is needed to read the rest
+        let no_sections = reader.read_u32()?;
+        let mut sections = HashMap::new();
+        for _ in 0..no_sections {
+            let section_type = reader.read_u32()?;
+            let size = reader.read_u64()?;
+            let section = reader.take(size)?;
+            if sections.insert(section_type, section).is_some() {
+                return Result::Err(());
+            }
+        }
+        let (field_size, header) = read_header(sections.get(&HEADER_TYPE).ok_or(())?)?;
+        let constraints = read_constraints(
+            sections.get(&CONSTRAINT_TYPE).ok_or(())?,
+            field_size,
+            header.number_of_constraints,
+        )?;
+        let wire_to_label = read_wire_to_label(sections.get(&WIRE2LABEL_TYPE).ok_or(())?)?;
+        let custom_gates_used = match sections.get(&CUSTOM_GATES_USED_TYPE) {
+            Some(section) => Some(read_custom_gates_used(section, field_size)?),
+            None => None,
+        };
+        let custom_gates_applied = match sections.get(&CUSTOM_GATES_APPLIED_TYPE) {
+            Some(section) => Some(read_custom_gates_applied(section)?),
+            None => None,
+        };
+        Result::Ok(R1CSFile {
+            field_size,
+            header,
+            constraints,
+            wire_to_label,
+            custom_gates_used,
+            custom_gates_applied,
+        })
+    }
+
+    pub fn read(file: &str) -> Result<R1CSFile, ()> {
+        let bytes = std::fs::read(file).map_err(|_err| {})?;
+        R1CSFile::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs_writer::R1CSWriter;
+
+    const MULTIPLIER2: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../examples/multiplier2/multiplier2.r1cs"
+    );
+
+    // writes the file with the sections in the order used by the compiler
+    fn write(r1cs: &R1CSFile, file: &str) -> Result<(), ()> {
+        let custom_gates = r1cs.custom_gates_used.is_some();
+        let writer = R1CSWriter::new(file.to_string(), r1cs.field_size, custom_gates)?;
+        let mut constraint_section = R1CSWriter::start_constraints_section(writer)?;
+        for constraint in &r1cs.constraints {
+            constraint_section.write_constraint_usize(
+                &constraint.a,
+                &constraint.b,
+                &constraint.c,
+            )?;
+        }
+        let writer = constraint_section.end_section()?;
+        let mut header_section = R1CSWriter::start_header_section(writer)?;
+        header_section.write_section(r1cs.header.clone())?;
+        let writer = header_section.end_section()?;
+        let mut signal_section = R1CSWriter::start_signal_section(writer)?;
+        for label in &r1cs.wire_to_label {
+            signal_section.write_signal_usize(*label)?;
+        }
+        let writer = signal_section.end_section()?;
+        if !custom_gates {
+            return R1CSWriter::finish_writing(writer);
+        }
+        let mut used_section = R1CSWriter::start_custom_gates_used_section(writer)?;
+        used_section.write_custom_gates_usages(r1cs.custom_gates_used.clone().unwrap())?;
+        let writer = used_section.end_section()?;
+        let mut applied_section = R1CSWriter::start_custom_gates_applied_section(writer)?;
+        applied_section
+            .write_custom_gates_applications(r1cs.custom_gates_applied.clone().unwrap())?;
+        let writer = applied_section.end_section()?;
+        R1CSWriter::finish_writing(writer)
+    }
+
+    fn temporary_file(name: &str) -> String {
+        let mut path = std::env::temp_dir();
+        path.push(format!("{}_{}.r1cs", name, std::process::id()));
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn reads_the_multiplier_example() {
+        let r1cs = R1CSFile::read(MULTIPLIER2).unwrap();
+        assert_eq!(r1cs.header.field, BigInt::from(2147483647));
+        assert_eq!(r1cs.header.total_wires, 4);
+        assert_eq!(r1cs.header.public_outputs, 1);
+        assert_eq!(r1cs.header.public_inputs, 0);
+        assert_eq!(r1cs.header.private_inputs, 2);
+        assert_eq!(r1cs.header.number_of_constraints, 1);
+        assert_eq!(r1cs.wire_to_label, vec![0, 1, 2, 3]);
+        assert!(r1cs.custom_gates_used.is_none());
+
+        // -a * b - (-c) = 0
+        let minus_one = BigInt::from(2147483646);
+        let constraint = &r1cs.constraints[0];
+        assert_eq!(constraint.a, HashMap::from([(2, minus_one.clone())]));
+        assert_eq!(constraint.b, HashMap::from([(3, BigInt::from(1))]));
+        assert_eq!(constraint.c, HashMap::from([(1, minus_one)]));
+    }
+
+    #[test]
+    fn the_multiplier_example_round_trips() {
+        let original = std::fs::read(MULTIPLIER2).unwrap();
+        let r1cs = R1CSFile::from_bytes(&original).unwrap();
+        let file = temporary_file("multiplier2");
+        write(&r1cs, &file).unwrap();
+        let written = std::fs::read(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(written, original);
+    }
+
+    #[test]
+    fn custom_gates_round_trip() {
+        let r1cs = R1CSFile {
+            field_size: 32,
+            header: HeaderData {
+                field: BigInt::parse_bytes(
+                    b"21888242871839275222246405745257275088548364400416034343698204186575808495617",
+                    10,
+                )
+                .unwrap(),
+                total_wires: 5,
+                public_outputs: 1,
+                public_inputs: 1,
+                private_inputs: 1,
+                number_of_labels: 6,
+                number_of_constraints: 2,
+            },
+            constraints: vec![
+                R1CSConstraint {
+                    a: HashMap::from([(2, BigInt::from(3)), (0, BigInt::from(1))]),
+                    b: HashMap::from([(3, BigInt::from(1))]),
+                    c: HashMap::from([(4, BigInt::from(7))]),
+                },
+                R1CSConstraint {
+                    a: HashMap::new(),
+                    b: HashMap::new(),
+                    c: HashMap::from([(1, BigInt::from(1)), (4, BigInt::from(5))]),
+                },
+            ],
+            wire_to_label: vec![0, 1, 2, 3, 5],
+            custom_gates_used: Some(vec![
+                ("CMul".to_string(), vec![]),
+                ("CAdd".to_string(), vec![BigInt::from(2), BigInt::from(40)]),
+            ]),
+            custom_gates_applied: Some(vec![(1, vec![1, 2, 3]), (0, vec![4])]),
+        };
+        let file = temporary_file("custom_gates");
+        write(&r1cs, &file).unwrap();
+        let read = R1CSFile::read(&file);
+        std::fs::remove_file(&file).unwrap();
+        let read = read.unwrap();
+        assert_eq!(read.field_size, r1cs.field_size);
+        assert_eq!(read.header, r1cs.header);
+        assert_eq!(read.constraints, r1cs.constraints);
+        assert_eq!(read.wire_to_label, r1cs.wire_to_label);
+        assert_eq!(read.custom_gates_used, r1cs.custom_gates_used);
+        assert_eq!(read.custom_gates_applied, r1cs.custom_gates_applied);
+    }
+}
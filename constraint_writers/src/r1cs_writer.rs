@@ -249,6 +249,7 @@ impl R1CSWriter {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HeaderData {
     pub field: BigInt,
     pub total_wires: usize,
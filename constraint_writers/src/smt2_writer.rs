@@ -0,0 +1,138 @@
+use circom_algebra::num_bigint::BigInt;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+
+// Writes the constraint system in the SMT-LIB theory of finite fields (QF_FF).
+// Every signal is declared as a constant named after its symbol, the signal one
+// is the constant 1 and each constraint A * B - C = 0 becomes an assertion.
+// The signals of the main component are listed in the :outputs, :public-inputs
+// and :private-inputs attributes.
+pub struct SMTWriter {
+    writer: BufWriter<File>,
+    names: HashMap<usize, String>,
+}
+
+impl SMTWriter {
+    pub fn new(file: &str, field: &BigInt) -> Result<SMTWriter, ()> {
+        let file = File::create(file).map_err(|_err| {})?;
+        let mut writer = BufWriter::new(file);
+        let header = format!(
+            "; constraints of the circuit over the field of size {}\n(set-logic QF_FF)\n(define-sort F () (_ FiniteField {}))\n",
+            field.to_str_radix(10),
+            field.to_str_radix(10)
+        );
+        writer.write_all(header.as_bytes()).map_err(|_err| {})?;
+        Result::Ok(SMTWriter {
+            writer,
+            names: HashMap::new(),
+        })
+    }
+
+    // signals must be declared before being used in a constraint
+    pub fn declare_signal(&mut self, signal: usize, name: &str) -> Result<(), ()> {
+        let identifier = format!("|{}|", name);
+        let declaration = format!("(declare-const {} F)\n", identifier);
+        self.writer
+            .write_all(declaration.as_bytes())
+            .map_err(|_err| {})?;
+        self.names.insert(signal, identifier);
+        Result::Ok(())
+    }
+
+    pub fn tag_signals(&mut self, tag: &str, signals: Range<usize>) -> Result<(), ()> {
+        let identifiers: Vec<String> = signals
+            .map(|signal| identifier(&self.names, signal))
+            .collect();
+        let info = format!("(set-info :{} ({}))\n", tag, identifiers.join(" "));
+        self.writer.write_all(info.as_bytes()).map_err(|_err| {})
+    }
+
+    pub fn write_constraint(
+        &mut self,
+        a: &HashMap<usize, BigInt>,
+        b: &HashMap<usize, BigInt>,
+        c: &HashMap<usize, BigInt>,
+    ) -> Result<(), ()> {
+        let c = linear_combination_as_smt(c, &self.names);
+        let assertion = if a.is_empty() || b.is_empty() {
+            format!("(assert (= {} {}))\n", c, field_constant(&BigInt::from(0)))
+        } else {
+            let a = linear_combination_as_smt(a, &self.names);
+            let b = linear_combination_as_smt(b, &self.names);
+            format!("(assert (= (ff.mul {} {}) {}))\n", a, b, c)
+        };
+        self.writer
+            .write_all(assertion.as_bytes())
+            .map_err(|_err| {})
+    }
+
+    pub fn end(mut self) -> Result<(), ()> {
+        self.writer.flush().map_err(|_err| {})
+    }
+}
+
+fn identifier(names: &HashMap<usize, String>, signal: usize) -> String {
+    match names.get(&signal) {
+        Some(identifier) => identifier.clone(),
+        None => format!("|signal_{}|", signal),
+    }
+}
+
+fn field_constant(value: &BigInt) -> String {
+    format!("(as ff{} F)", value.to_str_radix(10))
+}
+
+fn linear_combination_as_smt(
+    values: &HashMap<usize, BigInt>,
+    names: &HashMap<usize, String>,
+) -> String {
+    let mut order: Vec<&usize> = values.keys().collect();
+    order.sort();
+    let mut terms = Vec::new();
+    for signal in order {
+        let coefficient = values.get(signal).unwrap();
+        let term = if *signal == 0 {
+            field_constant(coefficient)
+        } else if *coefficient == BigInt::from(1) {
+            identifier(names, *signal)
+        } else {
+            format!(
+                "(ff.mul {} {})",
+                field_constant(coefficient),
+                identifier(names, *signal)
+            )
+        };
+        terms.push(term);
+    }
+    match terms.len() {
+        0 => field_constant(&BigInt::from(0)),
+        1 => terms.pop().unwrap(),
+        _ => format!("(ff.add {})", terms.join(" ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_combinations_use_the_signal_names() {
+        let mut names = HashMap::new();
+        names.insert(1, "|main.out|".to_string());
+        names.insert(2, "|main.in[0]|".to_string());
+
+        let mut lc = HashMap::new();
+        assert_eq!(linear_combination_as_smt(&lc, &names), "(as ff0 F)");
+        lc.insert(2, BigInt::from(1));
+        assert_eq!(linear_combination_as_smt(&lc, &names), "|main.in[0]|");
+        lc.insert(0, BigInt::from(5));
+        lc.insert(1, BigInt::from(3));
+        lc.insert(7, BigInt::from(1));
+        assert_eq!(
+            linear_combination_as_smt(&lc, &names),
+            "(ff.add (as ff5 F) (ff.mul (as ff3 F) |main.out|) |main.in[0]| |signal_7|)"
+        );
+    }
+}
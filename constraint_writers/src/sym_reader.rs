@@ -0,0 +1,28 @@
+use super::sym_writer::SymElem;
+
+fn parse_sym_elem(line: &str) -> Result<SymElem, ()> {
+    let mut fields = line.splitn(4, ',');
+    let mut next_number = || -> Result<i64, ()> {
+        let field = fields.next().ok_or(())?;
+        field.parse::<i64>().map_err(|_err| {})
+    };
+    let original = next_number()?;
+    let witness = next_number()?;
+    let node_id = next_number()?;
+    let symbol = fields.next().ok_or(())?.to_string();
+    Result::Ok(SymElem {
+        original,
+        witness,
+        node_id,
+        symbol,
+    })
+}
+
+pub fn read_sym(file: &str) -> Result<Vec<SymElem>, ()> {
+    let contents = std::fs::read_to_string(file).map_err(|_err| {})?;
+    let mut elems = Vec::new();
+    for line in contents.lines().filter(|line| !line.is_empty()) {
+        elems.push(parse_sym_elem(line)?);
+    }
+    Result::Ok(elems)
+}
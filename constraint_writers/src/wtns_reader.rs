@@ -0,0 +1,55 @@
+use super::r1cs_reader::Reader;
+use circom_algebra::num_bigint::BigInt;
+use std::collections::HashMap;
+
+const MAGIC: &[u8] = b"wtns";
+const VERSION: usize = 2;
+const HEADER_TYPE: usize = 1;
+const WITNESS_TYPE: usize = 2;
+
+// Contents of a witness in the wtns format of iden3
+pub struct WtnsFile {
+    pub field_size: usize,
+    pub field: BigInt,
+    pub witness: Vec<BigInt>,
+}
+
+impl WtnsFile {
+    pub fn from_bytes(bytes: &[u8]) -> Result<WtnsFile, ()> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(4)? != MAGIC || reader.read_u32()? != VERSION {
+            return Result::Err(());
+        }
+        let no_sections = reader.read_u32()?;
+        let mut sections = HashMap::new();
+        for _ in 0..no_sections {
+            let section_type = reader.read_u32()?;
+            let size = reader.read_u64()?;
+            sections.insert(section_type, reader.take(size)?);
+        }
+
+        let mut header = Reader::new(sections.get(&HEADER_TYPE).ok_or(())?);
+        let field_size = header.read_u32()?;
+        let field = header.read_field_element(field_size)?;
+        let no_values = header.read_u32()?;
+
+        let mut values = Reader::new(sections.get(&WITNESS_TYPE).ok_or(())?);
+        let mut witness = Vec::with_capacity(no_values);
+        for _ in 0..no_values {
+            witness.push(values.read_field_element(field_size)?);
+        }
+        if !values.is_finished() {
+            return Result::Err(());
+        }
+        Result::Ok(WtnsFile {
+            field_size,
+            field,
+            witness,
+        })
+    }
+
+    pub fn read(file: &str) -> Result<WtnsFile, ()> {
+        let bytes = std::fs::read(file).map_err(|_err| {})?;
+        WtnsFile::from_bytes(&bytes)
+    }
+}
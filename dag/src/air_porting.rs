@@ -0,0 +1,31 @@
+use super::{Tree, DAG};
+use constraint_writers::air_writer::AIRWriter;
+
+pub fn write(dag: &DAG, output: &str, columns: &str) -> Result<(), ()> {
+    let tree = Tree::new(dag);
+    // adding 1 to include the signal used to represent value 1 in the field (signal one)
+    let wires = count_signals(&tree) + 1;
+    let mut writer = AIRWriter::new(output, columns, &tree.field, wires)?;
+    visit_tree(&tree, &mut writer)?;
+    writer.end()
+}
+
+fn count_signals(tree: &Tree) -> usize {
+    let mut no_signals = tree.signals.len();
+    for edge in Tree::get_edges(tree) {
+        let subtree = Tree::go_to_subtree(tree, edge);
+        no_signals += count_signals(&subtree);
+    }
+    no_signals
+}
+
+fn visit_tree(tree: &Tree, writer: &mut AIRWriter) -> Result<(), ()> {
+    for c in &tree.constraints {
+        writer.write_constraint(c.a(), c.b(), c.c())?;
+    }
+    for edge in Tree::get_edges(tree) {
+        let subtree = Tree::go_to_subtree(tree, edge);
+        visit_tree(&subtree, writer)?;
+    }
+    Result::Ok(())
+}
@@ -0,0 +1,314 @@
+use super::{Constraint, Signal, DAG};
+use circom_algebra::modular_arithmetic;
+use circom_algebra::num_bigint::BigInt;
+use program_structure::constants::UsefulConstants;
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::{Report, ReportCollection};
+use program_structure::file_definition::{FileID, FileLocation};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const CONSTANT: Signal = 0;
+
+struct UndeterminedSignal;
+impl UndeterminedSignal {
+    pub fn new(
+        template: &str,
+        examples: &[String],
+        file_id: FileID,
+        location: FileLocation,
+    ) -> Report {
+        let msg = if examples.len() == 1 {
+            format!(
+                "In template \"{}\": Signal {} is assigned with <-- but the constraints do not determine its value from the inputs",
+                template, examples[0]
+            )
+        } else {
+            format!(
+                "In template \"{}\": {} signals assigned with <-- are not determined by the constraints from the inputs",
+                template,
+                examples.len()
+            )
+        };
+        let mut report = Report::warning(msg, ReportCode::UndeterminedSignal);
+        report.add_primary(
+            location,
+            file_id,
+            "other values also satisfy the constraints".to_string(),
+        );
+        if examples.len() > 1 {
+            report.add_note(format!("For example: {}, {}.", examples[0], examples[1]));
+        }
+        report
+    }
+}
+
+// Coefficient of the signal in A * B - C when one of A and B is a constant,
+// None when the constraint is quadratic
+fn linear_coefficient(constraint: &Constraint, signal: Signal, field: &BigInt) -> Option<BigInt> {
+    let zero = BigInt::from(0);
+    let constant_of = |lc: &HashMap<Signal, BigInt>| {
+        if lc.keys().all(|s| *s == CONSTANT) {
+            Some(lc.get(&CONSTANT).cloned().unwrap_or_else(|| zero.clone()))
+        } else {
+            None
+        }
+    };
+    let c = constraint.c().get(&signal).unwrap_or(&zero);
+    let product = match (constant_of(constraint.a()), constant_of(constraint.b())) {
+        (Some(_), Some(_)) => zero.clone(),
+        (Some(k), None) => {
+            modular_arithmetic::mul(&k, constraint.b().get(&signal).unwrap_or(&zero), field)
+        }
+        (None, Some(k)) => {
+            modular_arithmetic::mul(&k, constraint.a().get(&signal).unwrap_or(&zero), field)
+        }
+        (None, None) => return None,
+    };
+    Some(modular_arithmetic::sub(&product, c, field))
+}
+
+// Signal x of constraints of the form k * x * (x - 1) = 0
+fn boolean_signal(constraint: &Constraint, field: &BigInt) -> Option<Signal> {
+    let signals = constraint.take_cloned_signals();
+    let mut non_constant = signals.iter().filter(|s| **s != CONSTANT);
+    let x = *non_constant.next()?;
+    if non_constant.next().is_some() {
+        return None;
+    }
+    let zero = BigInt::from(0);
+    let coefficient = |lc: &HashMap<Signal, BigInt>, s: Signal| lc.get(&s).unwrap_or(&zero).clone();
+    let (a1, a0) = (
+        coefficient(constraint.a(), x),
+        coefficient(constraint.a(), CONSTANT),
+    );
+    let (b1, b0) = (
+        coefficient(constraint.b(), x),
+        coefficient(constraint.b(), CONSTANT),
+    );
+    let (c1, c0) = (
+        coefficient(constraint.c(), x),
+        coefficient(constraint.c(), CONSTANT),
+    );
+    // (a1 x + a0) (b1 x + b0) - (c1 x + c0)
+    let square = modular_arithmetic::mul(&a1, &b1, field);
+    let linear = modular_arithmetic::sub(
+        &modular_arithmetic::add(
+            &modular_arithmetic::mul(&a1, &b0, field),
+            &modular_arithmetic::mul(&a0, &b1, field),
+            field,
+        ),
+        &c1,
+        field,
+    );
+    let constant = modular_arithmetic::sub(&modular_arithmetic::mul(&a0, &b0, field), &c0, field);
+    let is_boolean = square != zero
+        && constant == zero
+        && modular_arithmetic::add(&square, &linear, field) == zero;
+    if is_boolean {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+// The coefficients are, up to a common factor, different powers of two whose
+// sum is smaller than the prime. Then every assignment of bits gives a
+// different value of the linear combination.
+fn is_binary_decomposition(coefficients: &[BigInt], field: &BigInt) -> bool {
+    let one = BigInt::from(1);
+    for pivot in coefficients {
+        let inverse = match modular_arithmetic::div(&one, pivot, field) {
+            Ok(inverse) => inverse,
+            Err(_) => continue,
+        };
+        let mut powers = HashSet::new();
+        let mut sum = BigInt::from(0);
+        let mut valid = true;
+        for coefficient in coefficients {
+            let value = modular_arithmetic::mul(coefficient, &inverse, field);
+            let is_power = value > BigInt::from(0) && (&value & (&value - &one)) == BigInt::from(0);
+            if !is_power || !powers.insert(value.bits()) {
+                valid = false;
+                break;
+            }
+            sum += value;
+        }
+        if valid && sum < *field {
+            return true;
+        }
+    }
+    false
+}
+
+// Signals whose value is fixed by the constraint once the known signals are
+fn determined_by(
+    constraint: &Constraint,
+    known: &HashSet<Signal>,
+    booleans: &HashSet<Signal>,
+    field: &BigInt,
+) -> Vec<Signal> {
+    let mut unknown: Vec<Signal> = constraint
+        .take_cloned_signals()
+        .into_iter()
+        .filter(|s| *s != CONSTANT && !known.contains(s))
+        .collect();
+    unknown.sort();
+    let zero = BigInt::from(0);
+    if unknown.len() == 1 {
+        let x = unknown[0];
+        let determined = match linear_coefficient(constraint, x, field) {
+            Some(coefficient) => coefficient != zero,
+            None => {
+                let in_a = constraint.a().contains_key(&x);
+                let in_b = constraint.b().contains_key(&x);
+                // x * k = c with c a nonzero constant, k cannot be zero
+                let nonzero_product = constraint.c().keys().all(|s| *s == CONSTANT)
+                    && constraint.c().get(&CONSTANT).is_some_and(|c| *c != zero);
+                !(in_a || in_b) || (in_a != in_b && nonzero_product)
+            }
+        };
+        if determined {
+            return unknown;
+        }
+    } else if unknown.len() > 1 && unknown.iter().all(|s| booleans.contains(s)) {
+        let coefficients: Option<Vec<BigInt>> = unknown
+            .iter()
+            .map(|s| linear_coefficient(constraint, *s, field))
+            .collect();
+        if let Some(coefficients) = coefficients {
+            if is_binary_decomposition(&coefficients, field) {
+                return unknown;
+            }
+        }
+    }
+    Vec::new()
+}
+
+// Propagates which signals are uniquely determined starting from the inputs
+// of the node. The outputs of a subcomponent are determined by its inputs.
+fn determined_signals(dag: &DAG, node_id: usize, field: &BigInt) -> HashSet<Signal> {
+    let node = &dag.nodes[node_id];
+    let mut known: HashSet<Signal> = HashSet::new();
+    known.insert(CONSTANT);
+    known.extend((node.outputs_length + 1)..=(node.outputs_length + node.inputs_length));
+    let mut subcomponents = Vec::new();
+    for edge in &dag.adjacency[node_id] {
+        let subcomponent = &dag.nodes[edge.goes_to];
+        let outputs: Vec<Signal> = (1..=subcomponent.outputs_length)
+            .map(|s| edge.in_number + s)
+            .collect();
+        let inputs: Vec<Signal> = (1..=subcomponent.inputs_length)
+            .map(|s| edge.in_number + subcomponent.outputs_length + s)
+            .collect();
+        subcomponents.push((inputs, outputs));
+    }
+    let booleans: HashSet<Signal> = node
+        .constraints
+        .iter()
+        .filter_map(|c| boolean_signal(c, field))
+        .collect();
+    loop {
+        let mut changed = false;
+        for (inputs, outputs) in &subcomponents {
+            if inputs.iter().all(|s| known.contains(s)) {
+                for output in outputs {
+                    changed |= known.insert(*output);
+                }
+            }
+        }
+        for constraint in &node.constraints {
+            for signal in determined_by(constraint, &known, &booleans, field) {
+                changed |= known.insert(signal);
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    known
+}
+
+// Checks, once per template, that the signals assigned with <-- are uniquely
+// determined by the inputs through the constraints of the template. The
+// subcomponents are assumed to determine their outputs.
+pub fn analyse(dag: &DAG) -> ReportCollection {
+    let field = UsefulConstants::new(&dag.prime).get_p().clone();
+    let mut reports = ReportCollection::new();
+    let mut visited: HashSet<String> = HashSet::new();
+    for (node_id, node) in dag.nodes.iter().enumerate() {
+        if node.is_custom_gate()
+            || node.unsafe_assignments.is_empty()
+            || !visited.insert(node.template_name.clone())
+        {
+            continue;
+        }
+        let known = determined_signals(dag, node_id, &field);
+        let names: HashMap<Signal, &String> = node
+            .signal_correspondence
+            .iter()
+            .map(|(name, id)| (*id, name))
+            .collect();
+        let mut undetermined: BTreeMap<(FileID, usize, usize), Vec<String>> = BTreeMap::new();
+        for (signal, file_id, location) in &node.unsafe_assignments {
+            if !known.contains(signal) {
+                let name = names
+                    .get(signal)
+                    .map_or(signal.to_string(), |n| n.to_string());
+                undetermined
+                    .entry((*file_id, location.start, location.end))
+                    .or_default()
+                    .push(name);
+            }
+        }
+        for ((file_id, start, end), examples) in undetermined {
+            reports.push(UndeterminedSignal::new(
+                &node.template_name,
+                &examples,
+                file_id,
+                start..end,
+            ));
+        }
+    }
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use circom_algebra::algebra::ArithmeticExpression;
+
+    // the expressions always hold the constant term
+    fn lc(terms: &[(Signal, i64)]) -> HashMap<Signal, BigInt> {
+        let mut lc: HashMap<Signal, BigInt> =
+            terms.iter().map(|(s, v)| (*s, BigInt::from(*v))).collect();
+        lc.entry(CONSTANT).or_insert_with(|| BigInt::from(0));
+        lc
+    }
+
+    #[test]
+    fn binary_decompositions_determine_the_bits() {
+        let field = BigInt::from(2147483647);
+        let minus = |v: i64| modular_arithmetic::prefix_sub(&BigInt::from(v), &field);
+        // x * (x - 1) = 0
+        let x_times_x_minus_one = ArithmeticExpression::Quadratic {
+            a: lc(&[(1, 1)]),
+            b: lc(&[(1, 1), (0, 2147483646)]),
+            c: lc(&[]),
+        };
+        let bit = ArithmeticExpression::transform_expression_to_constraint_form(
+            x_times_x_minus_one,
+            &field,
+        )
+        .unwrap();
+        assert_eq!(boolean_signal(&bit, &field), Some(1));
+
+        let bits: Vec<BigInt> = (0..30).map(|i| minus(1 << i)).collect();
+        assert!(is_binary_decomposition(&bits, &field));
+        // in M31 the 31 bits of a value are not unique, 0 and p are both valid
+        let bits: Vec<BigInt> = (0..31).map(|i| BigInt::from(1i64 << i)).collect();
+        assert!(!is_binary_decomposition(&bits, &field));
+        let repeated = vec![BigInt::from(2), BigInt::from(2)];
+        assert!(!is_binary_decomposition(&repeated, &field));
+    }
+}
@@ -1,16 +1,26 @@
+mod air_porting;
 mod constraint_correctness_analysis;
+mod determinism_analysis;
 mod json_porting;
 mod map_to_constraint_list;
 mod r1cs_porting;
+mod smt2_porting;
+mod statistics_porting;
 mod sym_porting;
 mod witness_producer;
 use circom_algebra::num_bigint::BigInt;
 use constraint_list::ConstraintList;
 use constraint_writers::debug_writer::DebugWriter;
+use constraint_writers::log_writer::Log;
+use constraint_writers::r1cs_reader::R1CSConstraint;
+use constraint_writers::sym_writer::SymElem;
 use constraint_writers::ConstraintExporter;
 use program_structure::constants::UsefulConstants;
 use program_structure::error_definition::ReportCollection;
+use program_structure::file_definition::{FileID, FileLocation};
 use std::collections::{HashMap, HashSet};
+
+pub use statistics_porting::{Statistics, TemplateStatistics};
 type Signal = usize;
 type Constraint = circom_algebra::algebra::Constraint<usize>;
 type Substitution = circom_algebra::algebra::Substitution<usize>;
@@ -181,6 +191,7 @@ pub struct Node {
     io_signals: Vec<usize>,
     constraints: Vec<Constraint>,
     underscored_signals: Vec<usize>,
+    unsafe_assignments: Vec<(Signal, FileID, FileLocation)>,
     is_parallel: bool,
     has_parallel_sub_cmp: bool,
     is_custom_gate: bool,
@@ -255,6 +266,10 @@ impl Node {
         self.underscored_signals.push(signal)
     }
 
+    fn add_unsafe_assignment(&mut self, signal: usize, file_id: FileID, location: FileLocation) {
+        self.unsafe_assignments.push((signal, file_id, location))
+    }
+
     fn set_number_of_subcomponents_indexes(&mut self, number_scmp: usize) {
         self.number_of_subcomponents_indexes = number_scmp
     }
@@ -332,7 +347,7 @@ pub struct DAG {
 }
 
 impl ConstraintExporter for DAG {
-    fn r1cs(&self, out: &str, custom_gates: bool) -> Result<(), ()> {
+    fn r1cs(&self, out: &str, custom_gates: bool) -> Result<Log, ()> {
         DAG::generate_r1cs_output(self, out, custom_gates)
     }
 
@@ -343,6 +358,22 @@ impl ConstraintExporter for DAG {
     fn sym(&self, out: &str) -> Result<(), ()> {
         DAG::generate_sym_output(self, out)
     }
+
+    fn r1cs_constraints(&self) -> Vec<R1CSConstraint> {
+        r1cs_porting::constraints(self)
+    }
+
+    fn sym_elems(&self) -> Vec<SymElem> {
+        sym_porting::sym_elems(self)
+    }
+
+    fn air(&self, out: &str, columns: &str) -> Result<(), ()> {
+        DAG::generate_air_output(self, out, columns)
+    }
+
+    fn smt2(&self, out: &str) -> Result<(), ()> {
+        DAG::generate_smt2_output(self, out)
+    }
 }
 
 impl DAG {
@@ -451,6 +482,17 @@ impl DAG {
         }
     }
 
+    pub fn add_unsafe_assignment(
+        &mut self,
+        signal: usize,
+        file_id: FileID,
+        location: FileLocation,
+    ) {
+        if let Option::Some(node) = self.get_mut_main() {
+            node.add_unsafe_assignment(signal, file_id, location);
+        }
+    }
+
     pub fn set_number_of_subcomponents_indexes(&mut self, number_scmp: usize) {
         if let Option::Some(node) = self.get_mut_main() {
             node.set_number_of_subcomponents_indexes(number_scmp);
@@ -504,11 +546,15 @@ impl DAG {
         }
     }
 
+    pub fn determinism_analysis(&self) -> ReportCollection {
+        determinism_analysis::analyse(self)
+    }
+
     pub fn clean_constraints(&mut self) {
         constraint_correctness_analysis::clean_constraints(&mut self.nodes);
     }
 
-    pub fn generate_r1cs_output(&self, output_file: &str, custom_gates: bool) -> Result<(), ()> {
+    pub fn generate_r1cs_output(&self, output_file: &str, custom_gates: bool) -> Result<Log, ()> {
         r1cs_porting::write(self, output_file, custom_gates)
     }
 
@@ -516,6 +562,14 @@ impl DAG {
         sym_porting::write(self, output_file)
     }
 
+    pub fn generate_air_output(&self, output_file: &str, columns_file: &str) -> Result<(), ()> {
+        air_porting::write(self, output_file, columns_file)
+    }
+
+    pub fn generate_smt2_output(&self, output_file: &str) -> Result<(), ()> {
+        smt2_porting::write(self, output_file)
+    }
+
     pub fn generate_json_constraints(&self, debug: &DebugWriter) -> Result<(), ()> {
         json_porting::port_constraints(self, debug)
     }
@@ -1,10 +1,11 @@
 use super::{Constraint, Tree, DAG};
 use constraint_writers::log_writer::Log;
+use constraint_writers::r1cs_reader::R1CSConstraint;
 use constraint_writers::r1cs_writer::{
     ConstraintSection, CustomGatesAppliedData, HeaderData, R1CSWriter,
 };
 
-pub fn write(dag: &DAG, output: &str, custom_gates: bool) -> Result<(), ()> {
+pub fn write(dag: &DAG, output: &str, custom_gates: bool) -> Result<Log, ()> {
     let tree = Tree::new(dag);
     let field_size = if tree.field.bits() % 64 == 0 {
         tree.field.bits() / 4
@@ -111,8 +112,27 @@ pub fn write(dag: &DAG, output: &str, custom_gates: bool) -> Result<(), ()> {
         R1CSWriter::finish_writing(r1cs)?;
     }
 
-    Log::print(&log);
-    Result::Ok(())
+    Result::Ok(log)
+}
+
+// the constraints in the order of the r1cs file
+pub fn constraints(dag: &DAG) -> Vec<R1CSConstraint> {
+    fn visit_tree(tree: &Tree, constraints: &mut Vec<R1CSConstraint>) {
+        for c in &tree.constraints {
+            constraints.push(R1CSConstraint {
+                a: c.a().clone(),
+                b: c.b().clone(),
+                c: c.c().clone(),
+            });
+        }
+        for edge in Tree::get_edges(tree) {
+            let subtree = Tree::go_to_subtree(tree, edge);
+            visit_tree(&subtree, constraints);
+        }
+    }
+    let mut constraints = Vec::new();
+    visit_tree(&Tree::new(dag), &mut constraints);
+    constraints
 }
 
 fn write_constraint_section(
@@ -0,0 +1,41 @@
+use super::{Tree, DAG};
+use constraint_writers::smt2_writer::SMTWriter;
+use std::collections::HashMap;
+
+pub fn write(dag: &DAG, output: &str) -> Result<(), ()> {
+    let tree = Tree::new(dag);
+    let mut writer = SMTWriter::new(output, &tree.field)?;
+    declare_signals(&tree, &mut writer)?;
+    // the signals of the main component are the first ones after the signal one
+    let outputs = 1 + dag.public_outputs();
+    let public_inputs = outputs + dag.public_inputs();
+    let private_inputs = public_inputs + dag.private_inputs();
+    writer.tag_signals("outputs", 1..outputs)?;
+    writer.tag_signals("public-inputs", outputs..public_inputs)?;
+    writer.tag_signals("private-inputs", public_inputs..private_inputs)?;
+    write_constraints(&tree, &mut writer)?;
+    writer.end()
+}
+
+fn declare_signals(tree: &Tree, writer: &mut SMTWriter) -> Result<(), ()> {
+    for signal in &tree.signals {
+        let name = HashMap::get(&tree.id_to_name, signal).unwrap();
+        writer.declare_signal(*signal, &format!("{}.{}", tree.path, name))?;
+    }
+    for edge in Tree::get_edges(tree) {
+        let subtree = Tree::go_to_subtree(tree, edge);
+        declare_signals(&subtree, writer)?;
+    }
+    Result::Ok(())
+}
+
+fn write_constraints(tree: &Tree, writer: &mut SMTWriter) -> Result<(), ()> {
+    for c in &tree.constraints {
+        writer.write_constraint(c.a(), c.b(), c.c())?;
+    }
+    for edge in Tree::get_edges(tree) {
+        let subtree = Tree::go_to_subtree(tree, edge);
+        write_constraints(&subtree, writer)?;
+    }
+    Result::Ok(())
+}
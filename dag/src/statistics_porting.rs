@@ -1,71 +1,273 @@
 use super::DAG;
-use constraint_writers::debug_writer::DebugWriter;
-
-struct SlotStatistics {
-    number_of_constraints: usize,
-    number_of_intermediates: usize,
-    number_of_inputs: usize,
-    number_of_outputs: usize,
-    number_of_components: usize,
-    times_reached: usize
+use constraint_list::{ConstraintList, EncodingIterator};
+use program_structure::diagnostics::print_progress;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+// Statistics of the instances of a template with the same parameters
+pub struct TemplateStatistics {
+    // name of the instance, the template followed by its parameters
+    pub name: String,
+    pub instances: usize,
+    // per instance, before the simplification
+    pub constraints: usize,
+    pub inputs: usize,
+    pub outputs: usize,
+    pub intermediates: usize,
+    pub subcomponents: usize,
+    // of all the instances, None when the constraints are not simplified
+    pub simplified_constraints: Option<usize>,
 }
-impl Default for SlotStatistics {
-    fn default() -> Self {
-        SlotStatistics {
-            number_of_constraints: 0,
-            number_of_intermediates: 0,
-            number_of_inputs: 0,
-            number_of_outputs: 0,
-            number_of_components: 0,
-            times_reached: 0
-        }
+
+impl TemplateStatistics {
+    // of all the instances, before the simplification
+    pub fn generated_constraints(&self) -> usize {
+        self.constraints * self.instances
+    }
+
+    // the constraints that end up in the r1cs
+    pub fn final_constraints(&self) -> usize {
+        self.simplified_constraints
+            .unwrap_or_else(|| self.generated_constraints())
     }
 }
 
-pub fn write_statistics(dag: &DAG,debug: &DebugWriter) -> Result<(),()>{
-    use std::io::Write;
-    let mut writer = debug.build_statistics_file()?;
-    let mut node_statistics = Vec::with_capacity(dag.number_of_nodes());
-    for _i in 0..dag.number_of_nodes() {
-        node_statistics.push(SlotStatistics::default());
+pub struct Statistics {
+    pub templates: Vec<TemplateStatistics>,
+    // template of each node of the dag
+    node_to_template: Vec<usize>,
+}
+
+impl Statistics {
+    pub fn new(dag: &DAG) -> Statistics {
+        // the children of a node are always before it, so the instances of a
+        // node are known when it is reached from the main component
+        let mut instances = vec![0; dag.number_of_nodes()];
+        if !instances.is_empty() {
+            instances[dag.main_id()] = 1;
+        }
+        for i in (0..dag.number_of_nodes()).rev() {
+            for edge in &dag.adjacency[i] {
+                instances[edge.goes_to] += instances[i];
+            }
+        }
+
+        let mut templates: Vec<TemplateStatistics> = Vec::new();
+        let mut positions = HashMap::new();
+        let mut node_to_template = Vec::with_capacity(dag.number_of_nodes());
+        for (i, node) in dag.nodes.iter().enumerate() {
+            let key = (node.template_name.clone(), node.parameters.clone());
+            let position = *positions.entry(key).or_insert_with(|| {
+                templates.push(TemplateStatistics {
+                    name: node.template_name.clone(),
+                    instances: 0,
+                    constraints: node.constraints().len(),
+                    inputs: node.number_of_inputs(),
+                    outputs: node.number_of_outputs(),
+                    intermediates: node.number_of_intermediates(),
+                    subcomponents: dag.adjacency[i].len(),
+                    simplified_constraints: None,
+                });
+                templates.len() - 1
+            });
+            templates[position].instances += instances[i];
+            node_to_template.push(position);
+        }
+        Statistics {
+            templates,
+            node_to_template,
+        }
+    }
+
+    // A simplified constraint is counted in the instance that emitted it. The
+    // constraints that the linear simplification derives have no single origin,
+    // those are counted in the instance that owns the first of their signals,
+    // the outermost one as the signals of a component are numbered before the
+    // ones of its subcomponents
+    pub fn add_simplification(&mut self, list: &ConstraintList) {
+        let mut owners = HashMap::new();
+        let iterator = EncodingIterator::new(&list.dag_encoding);
+        collect_owners(iterator, &mut owners);
+        for template in &mut self.templates {
+            template.simplified_constraints = Some(0);
+        }
+        for c_id in list.constraints.get_ids() {
+            let owner = list.constraints.read_origin(c_id).or_else(|| {
+                let constraint = list.constraints.read_constraint(c_id).unwrap();
+                constraint
+                    .take_cloned_signals_ordered()
+                    .iter()
+                    .find_map(|signal| owners.get(signal).cloned())
+            });
+            if let Some(node) = owner {
+                let template = &mut self.templates[self.node_to_template[node]];
+                template.simplified_constraints = template.simplified_constraints.map(|c| c + 1);
+            }
+        }
+    }
+
+    pub fn total_constraints(&self) -> usize {
+        self.templates.iter().map(|t| t.final_constraints()).sum()
+    }
+
+    // percentage of the constraints of the circuit, the final ones of the
+    // template over the final ones of the circuit
+    fn share(&self, template: &TemplateStatistics) -> f64 {
+        let total = self.total_constraints();
+        if total == 0 {
+            0.0
+        } else {
+            100.0 * template.final_constraints() as f64 / total as f64
+        }
     }
-    for i in 0..dag.number_of_nodes() {
-        node_statistics[i].number_of_outputs = dag.nodes[i].number_of_outputs();
-        node_statistics[i].number_of_inputs = dag.nodes[i].number_of_inputs();
-        node_statistics[i].number_of_intermediates = dag.nodes[i].number_of_intermediates();
-        node_statistics[i].number_of_constraints = dag.nodes[i].constraints().len();
-        node_statistics[i].number_of_components = dag.adjacency[i].len();
-        for arrow in &dag.adjacency[i] {
-            let pointer = arrow.goes_to;
-            node_statistics[pointer].times_reached += 1;
+
+    // the templates with more constraints first
+    fn sorted(&self) -> Vec<&TemplateStatistics> {
+        let mut sorted: Vec<_> = self.templates.iter().collect();
+        sorted.sort_by(|a, b| {
+            b.final_constraints()
+                .cmp(&a.final_constraints())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        sorted
+    }
+
+    pub fn write_json(&self, file: &str) -> Result<(), ()> {
+        let mut templates = json::JsonValue::new_array();
+        for template in self.sorted() {
+            // the constraints and the share are the final ones, the generated
+            // constraints are the ones before the simplification
+            let entry = json::object! {
+                "template" => template.name.clone(),
+                "instances" => template.instances,
+                "generated_constraints_per_instance" => template.constraints,
+                "generated_constraints" => template.generated_constraints(),
+                "constraints" => template.final_constraints(),
+                "share" => (self.share(template) * 100.0).round() / 100.0,
+                "inputs" => template.inputs,
+                "outputs" => template.outputs,
+                "intermediates" => template.intermediates,
+                "subcomponents" => template.subcomponents,
+            };
+            templates.push(entry).map_err(|_err| {})?;
         }
+        let stats = json::object! {
+            "constraints" => self.total_constraints(),
+            "templates" => templates,
+        };
+        let file = File::create(file).map_err(|_err| {})?;
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(stats.pretty(4).as_bytes())
+            .map_err(|_err| {})?;
+        writer.flush().map_err(|_err| {})
     }
-    for (index,node_statistic) in node_statistics.iter().enumerate(){
-        let splitter = "***************************************\n".to_string();
-        let title = format!("NODE NUMBER: {}\n",index);
-        let number_of_constraints = format!("Number of constraints: {}\n",node_statistic.number_of_constraints);
-        let number_of_intermediates = format!("Number of intermediates: {}\n", node_statistic.number_of_intermediates);
-        let number_of_inputs = format!("Number of inputs: {}\n",node_statistic.number_of_inputs);
-        let number_of_outputs = format!("Number of outputs: {}\n",node_statistic.number_of_outputs);
-        let number_of_components = format!("Number of components: {}\n",node_statistic.number_of_components);
-        let times_reached = format!("Times reached: {}\n",node_statistic.times_reached);
-
-        writer.write_all(splitter.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(title.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_constraints.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_intermediates.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_inputs.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_outputs.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_components.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(times_reached.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
+
+    // the generated columns are before the simplification, the constraints
+    // and the share after it
+    pub fn print(&self) {
+        let mut rows = vec![[
+            "template",
+            "instances",
+            "generated per instance",
+            "generated",
+            "constraints",
+            "share",
+            "inputs",
+            "outputs",
+            "intermediates",
+            "subcomponents",
+        ]
+        .iter()
+        .map(|header| header.to_string())
+        .collect::<Vec<_>>()];
+        for template in self.sorted() {
+            rows.push(vec![
+                template.name.clone(),
+                template.instances.to_string(),
+                template.constraints.to_string(),
+                template.generated_constraints().to_string(),
+                template.final_constraints().to_string(),
+                format!("{:.2}%", self.share(template)),
+                template.inputs.to_string(),
+                template.outputs.to_string(),
+                template.intermediates.to_string(),
+                template.subcomponents.to_string(),
+            ]);
+        }
+        let mut widths = vec![0; rows[0].len()];
+        for row in &rows {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.len());
+            }
+        }
+        for row in &rows {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i == 0 {
+                        format!("{:<width$}", cell, width = widths[i])
+                    } else {
+                        format!("{:>width$}", cell, width = widths[i])
+                    }
+                })
+                .collect();
+            print_progress(&cells.join("  "));
+        }
     }
-    Result::Ok(())
-}
\ No newline at end of file
+}
+
+fn collect_owners(mut iterator: EncodingIterator, owners: &mut HashMap<usize, usize>) {
+    let (signals, _) = EncodingIterator::take(&mut iterator);
+    for signal in signals {
+        owners.insert(signal.id, iterator.node_id);
+    }
+    for edge in EncodingIterator::edges(&iterator) {
+        let next = EncodingIterator::next(&iterator, edge);
+        collect_owners(next, owners);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use circom_algebra::num_bigint::BigInt;
+
+    #[test]
+    fn instances_are_counted_through_the_subcomponents() {
+        let mut dag = DAG::new(&"m31".to_string());
+        dag.add_node(
+            "Leaf(1)".to_string(),
+            vec![BigInt::from(1)],
+            vec![],
+            false,
+            false,
+        );
+        dag.add_input("in".to_string(), false);
+        dag.add_output("out".to_string());
+        dag.add_node("Mid()".to_string(), vec![], vec![], false, false);
+        dag.add_edge(0, "a", false);
+        dag.add_edge(0, "b", false);
+        dag.add_node("Main()".to_string(), vec![], vec![], false, false);
+        dag.add_edge(1, "left", false);
+        dag.add_edge(1, "right", false);
+        dag.add_edge(0, "leaf", false);
+
+        let statistics = Statistics::new(&dag);
+        let summary: Vec<(&str, usize, usize, usize)> = statistics
+            .templates
+            .iter()
+            .map(|t| (t.name.as_str(), t.instances, t.inputs, t.subcomponents))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Leaf(1)", 5, 1, 0),
+                ("Mid()", 2, 0, 2),
+                ("Main()", 1, 0, 3)
+            ]
+        );
+    }
+}
@@ -4,15 +4,23 @@ use constraint_writers::sym_writer::*;
 use std::collections::HashMap;
 
 pub fn write(dag: &DAG, file_name: &str) -> Result<(), ()> {
-    let tree = Tree::new(dag);
     let mut dot_sym = SymFile::new(file_name)?;
-    visit_tree(&tree, &mut dot_sym)?;
+    for sym_elem in sym_elems(dag) {
+        SymFile::write_sym_elem(&mut dot_sym, sym_elem)?;
+    }
     SymFile::finish_writing(dot_sym)?;
     //SymFile::close(dot_sym);
     Ok(())
 }
 
-fn visit_tree(tree: &Tree, dot_sym: &mut SymFile) -> Result<(), ()> {
+pub fn sym_elems(dag: &DAG) -> Vec<SymElem> {
+    let tree = Tree::new(dag);
+    let mut elems = Vec::new();
+    visit_tree(&tree, &mut elems);
+    elems
+}
+
+fn visit_tree(tree: &Tree, elems: &mut Vec<SymElem>) {
     for signal in &tree.signals {
         let name = HashMap::get(&tree.id_to_name, signal).unwrap();
         let symbol = format!("{}.{}", tree.path, name);
@@ -25,11 +33,10 @@ fn visit_tree(tree: &Tree, dot_sym: &mut SymFile) -> Result<(), ()> {
             node_id,
             symbol,
         };
-        SymFile::write_sym_elem(dot_sym, sym_elem)?;
+        elems.push(sym_elem);
     }
     for edge in Tree::get_edges(tree) {
         let subtree = Tree::go_to_subtree(tree, edge);
-        visit_tree(&subtree, dot_sym)?;
+        visit_tree(&subtree, elems);
     }
-    Ok(())
 }
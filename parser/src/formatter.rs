@@ -0,0 +1,885 @@
+use super::parser_logic::{parse_file, preprocess};
+use program_structure::ast::*;
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::{Report, ReportCollection};
+use program_structure::file_definition::FileID;
+
+const INDENT: &str = "    ";
+
+// Formats a circom file in the canonical style, keeping its comments. The
+// parser already removes some syntactic sugar (for loops, compound
+// assignments, ++, --> and declarations of several symbols), the printer
+// recovers the original form from the locations of the nodes. The result is
+// parsed again and rejected if it is not the same program.
+pub fn format_source(src: &str, file_id: FileID) -> Result<String, ReportCollection> {
+    let program = parse_file(src, file_id)?;
+    let preprocessed = preprocess(src, file_id)?;
+    let mut printer = Printer::new(src, &preprocessed, false);
+    printer.program(&program);
+    let formatted = printer.out;
+
+    let unstable = || {
+        let report = Report::error(
+            "The formatted code is not equivalent to the original one, the file is left as it is"
+                .to_string(),
+            ReportCode::UnstableFormat,
+        );
+        vec![report]
+    };
+    let reparsed = parse_file(&formatted, file_id).map_err(|_| unstable())?;
+    let preprocessed_formatted = preprocess(&formatted, file_id)?;
+    if shape(src, &preprocessed, &program) != shape(&formatted, &preprocessed_formatted, &reparsed)
+    {
+        return Result::Err(unstable());
+    }
+    Result::Ok(formatted)
+}
+
+// The program printed with every operation in parentheses and no comments,
+// two programs with the same shape have the same syntax tree
+fn shape(src: &str, preprocessed: &str, program: &AST) -> String {
+    let mut printer = Printer::new(src, preprocessed, true);
+    printer.program(program);
+    printer.out
+}
+
+struct Comment {
+    start: usize,
+    end: usize,
+}
+
+// Same comments the preprocessing of the parser removes
+fn collect_comments(src: &str) -> Vec<Comment> {
+    let bytes = src.as_bytes();
+    let mut comments = Vec::new();
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        let end = match (bytes[i], bytes[i + 1]) {
+            (b'/', b'/') => src[i..].find('\n').map_or(src.len(), |j| i + j),
+            (b'/', b'*') => src[i + 2..].find("*/").map_or(src.len(), |j| i + j + 4),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        comments.push(Comment { start: i, end });
+        i = end;
+    }
+    comments
+}
+
+struct Printer<'a> {
+    src: &'a str,
+    // the source with the comments replaced by spaces
+    preprocessed: &'a str,
+    // with every operation in parentheses and without the comments
+    shape: bool,
+    comments: Vec<Comment>,
+    next_comment: usize,
+    last_end: usize,
+    indent: usize,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    fn new(src: &'a str, preprocessed: &'a str, shape: bool) -> Printer<'a> {
+        Printer {
+            src,
+            preprocessed,
+            shape,
+            comments: if shape {
+                Vec::new()
+            } else {
+                collect_comments(src)
+            },
+            next_comment: 0,
+            last_end: 0,
+            indent: 0,
+            out: String::new(),
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    // Keeps one empty line where the source has at least one
+    fn separate(&mut self, position: usize) {
+        if self.shape || self.last_end >= position {
+            return;
+        }
+        let gap: Vec<&str> = self.src[self.last_end..position].split('\n').collect();
+        let has_empty_line =
+            gap.len() > 2 && gap[1..gap.len() - 1].iter().any(|l| l.trim().is_empty());
+        if has_empty_line
+            && !self.out.is_empty()
+            && !self.out.ends_with("{\n")
+            && !self.out.ends_with("\n\n")
+        {
+            self.out.push('\n');
+        }
+    }
+
+    // Prints in their own lines the comments that start before position
+    fn comments_before(&mut self, position: usize) {
+        while self.next_comment < self.comments.len()
+            && self.comments[self.next_comment].start < position
+        {
+            let (start, end) = (
+                self.comments[self.next_comment].start,
+                self.comments[self.next_comment].end,
+            );
+            self.separate(start);
+            let text = self.src[start..end].trim_end().to_string();
+            self.line(&text);
+            self.next_comment += 1;
+            self.last_end = end;
+        }
+    }
+
+    // A comment in the same line as the end of a statement stays after it
+    fn trailing_comment(&mut self, end: usize) -> String {
+        self.last_end = self.last_end.max(end);
+        if let Some(comment) = self.comments.get(self.next_comment) {
+            let gap = self.src.get(end..comment.start).unwrap_or("\n");
+            if comment.start >= end
+                && gap
+                    .chars()
+                    .all(|c| c == ';' || (c.is_whitespace() && c != '\n'))
+            {
+                let text = format!(" {}", self.src[comment.start..comment.end].trim_end());
+                self.last_end = comment.end;
+                self.next_comment += 1;
+                return text;
+            }
+        }
+        String::new()
+    }
+
+    fn program(&mut self, program: &AST) {
+        let main_start = program.main_component.as_ref().map(|(_, call)| {
+            let start = call.get_meta().start;
+            self.src[..start].rfind("component").unwrap_or(start)
+        });
+        let header_end = program
+            .definitions
+            .first()
+            .map(|d| definition_meta(d).start)
+            .or(main_start)
+            .unwrap_or(self.src.len());
+        self.header(program, header_end);
+        for definition in &program.definitions {
+            self.top_level_item(definition_meta(definition).start);
+            self.definition(definition);
+        }
+        if let (Some((public, call)), Some(start)) = (&program.main_component, main_start) {
+            self.top_level_item(start);
+            let public = if public.is_empty() {
+                String::new()
+            } else {
+                format!(" {{public [{}]}}", public.join(", "))
+            };
+            let text = format!("component main{} = {};", public, self.expression(call));
+            let trailing = self.trailing_comment(call.get_meta().end);
+            self.line(&(text + &trailing));
+        }
+        self.comments_before(usize::MAX);
+        while self.out.ends_with("\n\n") {
+            self.out.pop();
+        }
+    }
+
+    // Start of the comments in the lines right above position, with no empty
+    // line between them, which document the item at position
+    fn attached_comments(&self, position: usize) -> usize {
+        let mut start = position;
+        for comment in self.comments[self.next_comment..].iter().rev() {
+            if comment.end > start {
+                continue;
+            }
+            let gap = &self.src[comment.end..start];
+            let own_line = self.src[..comment.start]
+                .rsplit('\n')
+                .next()
+                .map_or(true, |l| l.trim().is_empty());
+            if !own_line || !gap.trim().is_empty() || gap.matches('\n').count() > 1 {
+                break;
+            }
+            start = comment.start;
+        }
+        start
+    }
+
+    // The definitions and the main component are separated by an empty line,
+    // which goes above the comments that document them
+    fn top_level_item(&mut self, start: usize) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+        self.comments_before(start);
+        self.separate(start);
+    }
+
+    // Pragmas and includes, which the syntax tree keeps without locations
+    fn header(&mut self, program: &AST, header_end: usize) {
+        if self.shape {
+            if let Some((major, minor, patch)) = program.compiler_version {
+                self.line(&format!("pragma circom {}.{}.{};", major, minor, patch));
+            }
+            if program.custom_gates {
+                self.line("pragma custom_templates;");
+            }
+            for include in &program.includes {
+                self.line(&format!("include \"{}\";", include));
+            }
+            return;
+        }
+        let mut position = 0;
+        loop {
+            let rest = &self.preprocessed[position..header_end];
+            let start = match rest.find(|c: char| !c.is_whitespace()) {
+                Some(offset) => position + offset,
+                None => break,
+            };
+            let end = match self.preprocessed[start..header_end].find(';') {
+                Some(offset) => start + offset,
+                None => break,
+            };
+            self.comments_before(start);
+            self.separate(start);
+            let directive: Vec<&str> = self.preprocessed[start..end].split_whitespace().collect();
+            let text = format!("{};", directive.join(" "));
+            self.comments_before(end);
+            let trailing = self.trailing_comment(end + 1);
+            self.line(&(text + &trailing));
+            position = end + 1;
+        }
+        let attached = self.attached_comments(header_end);
+        self.comments_before(attached);
+    }
+
+    fn definition(&mut self, definition: &Definition) {
+        let (meta, header, body) = match definition {
+            Definition::Template {
+                meta,
+                name,
+                args,
+                body,
+                parallel,
+                is_custom_gate,
+                ..
+            } => {
+                let custom = if *is_custom_gate { "custom " } else { "" };
+                let parallel = if *parallel { "parallel " } else { "" };
+                let header = format!(
+                    "template {}{}{}({})",
+                    custom,
+                    parallel,
+                    name,
+                    args.join(", ")
+                );
+                (meta, header, body)
+            }
+            Definition::Function {
+                meta,
+                name,
+                args,
+                body,
+                ..
+            } => (
+                meta,
+                format!("function {}({})", name, args.join(", ")),
+                body,
+            ),
+            Definition::Test { meta, name, body } => (meta, format!("test \"{}\"", name), body),
+        };
+        let pending = self.open(&header, body);
+        self.close(pending, meta.end);
+    }
+
+    // Prints the header of a compound statement and its body, without the
+    // closing brace of the body, which is pending when the result is true
+    fn open(&mut self, header: &str, body: &Statement) -> bool {
+        match body {
+            Statement::Block { meta, stmts } if !is_for_loop(body) => {
+                self.line(&format!("{} {{", header));
+                self.indent += 1;
+                for stmt in stmts {
+                    self.statement(stmt);
+                }
+                self.comments_before(meta.end - 1);
+                self.indent -= 1;
+                true
+            }
+            _ => {
+                self.line(header);
+                self.indent += 1;
+                self.statement(body);
+                self.indent -= 1;
+                false
+            }
+        }
+    }
+
+    fn close(&mut self, pending: bool, end: usize) {
+        if pending {
+            let trailing = self.trailing_comment(end);
+            self.line(&format!("}}{}", trailing));
+        } else {
+            self.last_end = self.last_end.max(end);
+        }
+    }
+
+    fn statement(&mut self, stmt: &Statement) {
+        use Statement::*;
+        let meta = stmt.get_meta();
+        self.comments_before(meta.start);
+        self.separate(meta.start);
+        match stmt {
+            IfThenElse {
+                cond,
+                if_case,
+                else_case,
+                ..
+            } => {
+                let mut header = format!("if ({})", self.expression(cond));
+                let mut if_case = if_case;
+                let mut else_case = else_case;
+                loop {
+                    let pending = self.open(&header, if_case);
+                    let prefix = if pending { "} else" } else { "else" };
+                    match else_case.as_deref() {
+                        None => {
+                            self.close(pending, meta.end);
+                            break;
+                        }
+                        Some(IfThenElse {
+                            cond,
+                            if_case: next_if,
+                            else_case: next_else,
+                            ..
+                        }) => {
+                            header = format!("{} if ({})", prefix, self.expression(cond));
+                            if_case = next_if;
+                            else_case = next_else;
+                        }
+                        Some(other) => {
+                            let pending = self.open(prefix, other);
+                            self.close(pending, meta.end);
+                            break;
+                        }
+                    }
+                }
+            }
+            While {
+                cond, stmt: body, ..
+            } => {
+                let header = format!("while ({})", self.expression(cond));
+                let pending = self.open(&header, body);
+                self.close(pending, meta.end);
+            }
+            Block { stmts, .. } if is_for_loop(stmt) => {
+                if let (
+                    init,
+                    While {
+                        cond, stmt: body, ..
+                    },
+                ) = (&stmts[0], &stmts[1])
+                {
+                    if let Block {
+                        stmts: loop_body, ..
+                    } = body.as_ref()
+                    {
+                        let header = format!(
+                            "for ({}; {}; {})",
+                            self.simple_statement(init),
+                            self.expression(cond),
+                            self.simple_statement(&loop_body[1])
+                        );
+                        let pending = self.open(&header, &loop_body[0]);
+                        self.close(pending, meta.end);
+                    }
+                }
+            }
+            Block { stmts, .. } => {
+                self.line("{");
+                self.indent += 1;
+                for stmt in stmts {
+                    self.statement(stmt);
+                }
+                self.comments_before(meta.end - 1);
+                self.indent -= 1;
+                self.close(true, meta.end);
+            }
+            _ => {
+                // the comments inside the statement go before it
+                self.comments_before(meta.end);
+                let text = format!("{};", self.simple_statement(stmt));
+                let trailing = self.trailing_comment(meta.end);
+                self.line(&(text + &trailing));
+            }
+        }
+    }
+
+    // Statements that fit in a line, without the semicolon
+    fn simple_statement(&self, stmt: &Statement) -> String {
+        use Statement::*;
+        match stmt {
+            InitializationBlock {
+                meta,
+                xtype,
+                initializations,
+            } => self.declaration(meta, xtype, initializations),
+            Declaration {
+                xtype,
+                name,
+                dimensions,
+                ..
+            } => format!(
+                "{} {}{}",
+                variable_type(xtype),
+                name,
+                self.dimensions(dimensions)
+            ),
+            Substitution {
+                meta,
+                var,
+                access,
+                op,
+                rhe,
+            } => {
+                let target = format!("{}{}", var, self.accesses(access));
+                if let Some(compound) = self.compound_assignment(meta, *op, rhe) {
+                    return format!("{}{}", target, compound);
+                }
+                if *op != AssignOp::AssignVar && rhe.get_meta().start == meta.start {
+                    format!("{} {} {}", self.expression(rhe), reversed_op(*op), target)
+                } else {
+                    format!("{} {} {}", target, assign_op(*op), self.expression(rhe))
+                }
+            }
+            MultSubstitution { meta, lhe, op, rhe } => match lhe {
+                // anonymous component used as a statement
+                Expression::Tuple { values, .. }
+                    if values.is_empty() && lhe.get_meta().start == meta.start =>
+                {
+                    self.expression(rhe)
+                }
+                _ if rhe.get_meta().start < lhe.get_meta().start => {
+                    format!(
+                        "{} {} {}",
+                        self.expression(rhe),
+                        reversed_op(*op),
+                        self.expression(lhe)
+                    )
+                }
+                _ => format!(
+                    "{} {} {}",
+                    self.expression(lhe),
+                    assign_op(*op),
+                    self.expression(rhe)
+                ),
+            },
+            UnderscoreSubstitution { op, rhe, .. } => {
+                format!("_ {} {}", assign_op(*op), self.expression(rhe))
+            }
+            ConstraintEquality { lhe, rhe, .. } => {
+                format!("{} === {}", self.expression(lhe), self.expression(rhe))
+            }
+            Return { value, .. } => format!("return {}", self.expression(value)),
+            LogCall { args, .. } => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        LogArgument::LogStr(string) => format!("\"{}\"", string),
+                        LogArgument::LogExp(expression) => self.expression(expression),
+                    })
+                    .collect();
+                format!("log({})", args.join(", "))
+            }
+            Assert { arg, .. } => format!("assert({})", self.expression(arg)),
+            IfThenElse { .. } | While { .. } | Block { .. } => String::new(),
+        }
+    }
+
+    // x += e, x++ and x-- are parsed as x = x op e with the location of the
+    // whole statement in the operation
+    fn compound_assignment(&self, meta: &Meta, op: AssignOp, rhe: &Expression) -> Option<String> {
+        if op != AssignOp::AssignVar {
+            return None;
+        }
+        if let Expression::InfixOp {
+            meta: op_meta,
+            lhe,
+            infix_op,
+            rhe: value,
+        } = rhe
+        {
+            let is_shortcut =
+                op_meta.start == meta.start && matches!(lhe.as_ref(), Expression::Variable { .. });
+            if !is_shortcut {
+                return None;
+            }
+            let is_step = matches!(value.as_ref(), Expression::Number(number_meta, _) if number_meta.start == meta.start);
+            return match (infix_op, is_step) {
+                (ExpressionInfixOpcode::Add, true) => Some("++".to_string()),
+                (ExpressionInfixOpcode::Sub, true) => Some("--".to_string()),
+                _ => Some(format!(
+                    " {}= {}",
+                    infix_symbol(*infix_op),
+                    self.expression(value)
+                )),
+            };
+        }
+        None
+    }
+
+    // The parser splits a declaration into the declaration of each symbol
+    // and its initialization. The variables without initialization get a
+    // zero with the location of the whole declaration, those are skipped.
+    fn declaration(
+        &self,
+        meta: &Meta,
+        xtype: &VariableType,
+        initializations: &[Statement],
+    ) -> String {
+        let mut symbols: Vec<(String, String)> = Vec::new();
+        let mut tuple = None;
+        for initialization in initializations {
+            match initialization {
+                Statement::Declaration {
+                    name, dimensions, ..
+                } => {
+                    symbols.push((
+                        name.clone(),
+                        format!("{}{}", name, self.dimensions(dimensions)),
+                    ));
+                }
+                Statement::Substitution { var, op, rhe, .. } => {
+                    let value_meta = rhe.get_meta();
+                    if value_meta.start == meta.start && value_meta.end == meta.end {
+                        continue;
+                    }
+                    if let Some(symbol) = symbols.iter_mut().rev().find(|(name, _)| name == var) {
+                        symbol.1 =
+                            format!("{} {} {}", symbol.1, assign_op(*op), self.expression(rhe));
+                    }
+                }
+                Statement::MultSubstitution { op, rhe, .. } => tuple = Some((*op, rhe)),
+                _ => {}
+            }
+        }
+        let symbols: Vec<String> = symbols.into_iter().map(|(_, text)| text).collect();
+        match tuple {
+            Some((op, rhe)) => format!(
+                "{} ({}) {} {}",
+                variable_type(xtype),
+                symbols.join(", "),
+                assign_op(op),
+                self.expression(rhe)
+            ),
+            None => format!("{} {}", variable_type(xtype), symbols.join(", ")),
+        }
+    }
+
+    fn dimensions(&self, dimensions: &[Expression]) -> String {
+        dimensions
+            .iter()
+            .map(|d| format!("[{}]", self.expression(d)))
+            .collect()
+    }
+
+    fn accesses(&self, access: &[Access]) -> String {
+        access
+            .iter()
+            .map(|a| match a {
+                Access::ArrayAccess(index) => format!("[{}]", self.expression(index)),
+                Access::ComponentAccess(name) => format!(".{}", name),
+            })
+            .collect()
+    }
+
+    fn list(&self, expressions: &[Expression]) -> String {
+        let list: Vec<String> = expressions.iter().map(|e| self.expression(e)).collect();
+        list.join(", ")
+    }
+
+    // An operand is in parentheses when its precedence is lower than the one
+    // the grammar accepts in its position
+    fn operand(&self, expression: &Expression, max_precedence: usize) -> String {
+        let precedence = precedence(expression);
+        let text = self.expression(expression);
+        if precedence > max_precedence || (self.shape && precedence > 1) {
+            format!("({})", text)
+        } else {
+            text
+        }
+    }
+
+    fn expression(&self, expression: &Expression) -> String {
+        use Expression::*;
+        match expression {
+            InfixOp {
+                lhe, infix_op, rhe, ..
+            } => {
+                let level = precedence(expression);
+                format!(
+                    "{} {} {}",
+                    self.operand(lhe, level),
+                    infix_symbol(*infix_op),
+                    self.operand(rhe, level - 1)
+                )
+            }
+            PrefixOp { prefix_op, rhe, .. } => {
+                let symbol = match prefix_op {
+                    ExpressionPrefixOpcode::Sub => "-",
+                    ExpressionPrefixOpcode::BoolNot => "!",
+                    ExpressionPrefixOpcode::Complement => "~",
+                };
+                format!("{}{}", symbol, self.operand(rhe, 1))
+            }
+            InlineSwitchOp {
+                cond,
+                if_true,
+                if_false,
+                ..
+            } => format!(
+                "{} ? {} : {}",
+                self.operand(cond, 12),
+                self.operand(if_true, 12),
+                self.operand(if_false, 12)
+            ),
+            ParallelOp { rhe, .. } => format!("parallel {}", self.operand(rhe, 13)),
+            Variable { name, access, .. } => format!("{}{}", name, self.accesses(access)),
+            Number(meta, value) => {
+                if self.src[meta.start..].starts_with("0x") {
+                    format!("0x{}", value.to_str_radix(16))
+                } else {
+                    value.to_string()
+                }
+            }
+            Call { id, args, .. } => format!("{}({})", id, self.list(args)),
+            AnonymousComp {
+                id,
+                is_parallel,
+                params,
+                signals,
+                names,
+                ..
+            } => {
+                let signals = match names {
+                    Some(names) => {
+                        let named: Vec<String> = names
+                            .iter()
+                            .zip(signals)
+                            .map(|((op, name), signal)| {
+                                format!("{} {} {}", name, assign_op(*op), self.expression(signal))
+                            })
+                            .collect();
+                        named.join(", ")
+                    }
+                    None => self.list(signals),
+                };
+                let parallel = if *is_parallel { "parallel " } else { "" };
+                format!("{}{}({})({})", parallel, id, self.list(params), signals)
+            }
+            ArrayInLine { values, .. } => format!("[{}]", self.list(values)),
+            Tuple { values, .. } => format!("({})", self.list(values)),
+            // only built by the parser for the variables without initialization
+            UniformArray { meta, .. } => self.src[meta.location.clone()].to_string(),
+        }
+    }
+}
+
+fn definition_meta(definition: &Definition) -> &Meta {
+    match definition {
+        Definition::Template { meta, .. }
+        | Definition::Function { meta, .. }
+        | Definition::Test { meta, .. } => meta,
+    }
+}
+
+// for (init; cond; step) body is parsed as { init; while (cond) { body; step; } }
+// where the loop has the location of the whole block
+fn is_for_loop(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Block { meta, stmts } if stmts.len() == 2 => match &stmts[1] {
+            Statement::While {
+                meta: loop_meta,
+                stmt,
+                ..
+            } => {
+                loop_meta.start == meta.start
+                    && matches!(stmt.as_ref(), Statement::Block { stmts, .. } if stmts.len() == 2)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// Levels of the grammar, the lower the tighter
+fn precedence(expression: &Expression) -> usize {
+    use ExpressionInfixOpcode::*;
+    match expression {
+        Expression::ParallelOp { .. } => 14,
+        Expression::InlineSwitchOp { .. } => 13,
+        Expression::InfixOp { infix_op, .. } => match infix_op {
+            BoolOr => 12,
+            BoolAnd => 11,
+            Eq | NotEq | Lesser | Greater | LesserEq | GreaterEq => 10,
+            BitOr => 9,
+            BitXor => 8,
+            BitAnd => 7,
+            ShiftL | ShiftR => 6,
+            Add | Sub => 5,
+            Mul | Div | IntDiv | Mod => 4,
+            Pow => 3,
+        },
+        Expression::PrefixOp { .. } => 2,
+        _ => 1,
+    }
+}
+
+fn infix_symbol(op: ExpressionInfixOpcode) -> &'static str {
+    use ExpressionInfixOpcode::*;
+    match op {
+        Mul => "*",
+        Div => "/",
+        Add => "+",
+        Sub => "-",
+        Pow => "**",
+        IntDiv => "\\",
+        Mod => "%",
+        ShiftL => "<<",
+        ShiftR => ">>",
+        LesserEq => "<=",
+        GreaterEq => ">=",
+        Lesser => "<",
+        Greater => ">",
+        Eq => "==",
+        NotEq => "!=",
+        BoolOr => "||",
+        BoolAnd => "&&",
+        BitOr => "|",
+        BitAnd => "&",
+        BitXor => "^",
+    }
+}
+
+fn assign_op(op: AssignOp) -> &'static str {
+    match op {
+        AssignOp::AssignVar => "=",
+        AssignOp::AssignSignal => "<--",
+        AssignOp::AssignConstraintSignal => "<==",
+    }
+}
+
+fn reversed_op(op: AssignOp) -> &'static str {
+    match op {
+        AssignOp::AssignSignal => "-->",
+        _ => "==>",
+    }
+}
+
+fn variable_type(xtype: &VariableType) -> String {
+    match xtype {
+        VariableType::Var => "var".to_string(),
+        VariableType::Component | VariableType::AnonymousComponent => "component".to_string(),
+        VariableType::Signal(signal_type, tags) => {
+            let signal_type = match signal_type {
+                SignalType::Input => " input",
+                SignalType::Output => " output",
+                SignalType::Intermediate => "",
+            };
+            let tags = if tags.is_empty() {
+                String::new()
+            } else {
+                format!(" {{{}}}", tags.join(", "))
+            };
+            format!("signal{}{}", signal_type, tags)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNFORMATTED: &str = "pragma circom   2.0.0 ;
+// bits of a number
+include \"bits.circom\";
+// converts a number to bits
+template   Num2Bits(n){
+  signal input in;signal output {binary} out[n]; // the bits
+  var lc1=0;
+  var e2 = 1, k;
+
+
+  for (var i = 0; i<n; i++) {
+    out[i] <-- (in >> i) & 1;
+    out[i] * (out[i] -1 ) === 0;
+    lc1 += out[i] * e2;
+    e2 = e2+e2;
+  }
+  lc1 ==> in;
+  if (n > 2) k = (n - 1) * (2 - n); else { k = -(-n); }
+  /* the end */
+}
+// a function
+function f(a) { return a > 1 ? (a ? 1 : 2) : 0x1F; }
+test   \"eight bits\" {component c = Num2Bits(8); c.in <== 5; assert(c.out[0]==1);}
+component main {public [in]} = Num2Bits(8);
+";
+
+    const FORMATTED: &str = "pragma circom 2.0.0;
+// bits of a number
+include \"bits.circom\";
+
+// converts a number to bits
+template Num2Bits(n) {
+    signal input in;
+    signal output {binary} out[n]; // the bits
+    var lc1 = 0;
+    var e2 = 1, k;
+
+    for (var i = 0; i < n; i++) {
+        out[i] <-- in >> i & 1;
+        out[i] * (out[i] - 1) === 0;
+        lc1 += out[i] * e2;
+        e2 = e2 + e2;
+    }
+    lc1 ==> in;
+    if (n > 2)
+        k = (n - 1) * (2 - n);
+    else {
+        k = -(-n);
+    }
+    /* the end */
+}
+
+// a function
+function f(a) {
+    return a > 1 ? (a ? 1 : 2) : 0x1f;
+}
+
+test \"eight bits\" {
+    component c = Num2Bits(8);
+    c.in <== 5;
+    assert(c.out[0] == 1);
+}
+
+component main {public [in]} = Num2Bits(8);
+";
+
+    #[test]
+    fn formats_in_the_canonical_style() {
+        let formatted = format_source(UNFORMATTED, 0).ok().unwrap();
+        assert_eq!(formatted, FORMATTED);
+        assert_eq!(format_source(&formatted, 0).ok().unwrap(), formatted);
+    }
+}
@@ -1,4 +1,5 @@
 use program_structure::ast::produce_report_with_message;
+use program_structure::builtin_library::{builtin_path, builtin_source};
 use program_structure::error_code::ReportCode;
 use program_structure::error_definition::Report;
 use std::collections::{HashMap, HashSet};
@@ -46,6 +47,13 @@ impl FileStack {
                 }
             }
         }
+        if let Some(path) = builtin_path(&name) {
+            let path = PathBuf::from(path);
+            if !f_stack.black_paths.contains(&path) {
+                f_stack.stack.push(path.clone());
+            }
+            return Result::Ok(path.to_str().unwrap().to_string());
+        }
         Result::Err(produce_report_with_message(
             ReportCode::IncludeNotFound,
             name,
@@ -100,8 +108,13 @@ impl IncludesGraph {
     pub fn add_edge(&mut self, old_path: String) -> Result<(), Report> {
         let mut crr = PathBuf::new();
         crr.push(old_path.clone());
-        let path = std::fs::canonicalize(crr)
-            .map_err(|_e| produce_report_with_message(ReportCode::FileOs, old_path))?;
+        // builtin files are not in the file system
+        let path = if builtin_source(&old_path).is_some() {
+            crr
+        } else {
+            std::fs::canonicalize(crr)
+                .map_err(|_e| produce_report_with_message(ReportCode::FileOs, old_path))?
+        };
         let edges = self.adjacency.entry(path).or_insert(vec![]);
         edges.push(self.nodes.len() - 1);
         Ok(())
@@ -7,20 +7,27 @@ extern crate lalrpop_util;
 
 lalrpop_mod!(pub lang);
 
+mod formatter;
 mod include_logic;
 mod parser_logic;
 mod syntax_sugar_remover;
 
+pub use formatter::format_source;
 use include_logic::{FileStack, IncludesGraph};
+// parses a single file without following its includes, used by the tooling
+// that works on files which are not the main of a project
+pub use parser_logic::{parse_file, parse_file_with_recovery};
 use program_structure::ast::{
-    produce_compiler_version_report, produce_report, produce_report_with_message,
-    produce_version_warning_report, Expression,
+    build_template, produce_compiler_version_report, produce_report, produce_report_with_message,
+    produce_version_warning_report, Definition, Expression, MainComponent, Meta,
 };
 use program_structure::error_code::ReportCode;
 use program_structure::error_definition::Report;
 use program_structure::error_definition::ReportCollection;
-use program_structure::file_definition::FileLibrary;
+use program_structure::expression_builders::build_call;
+use program_structure::file_definition::{FileID, FileLibrary};
 use program_structure::program_archive::ProgramArchive;
+use program_structure::statement_builders::build_block;
 use std::path::{Path, PathBuf};
 use syntax_sugar_remover::apply_syntactic_sugar;
 
@@ -59,29 +66,73 @@ pub fn find_file(
     (found, path, src, crr_str_file, reports)
 }
 
-pub fn run_parser(
+type ParserResult = Result<(ProgramArchive, ReportCollection), (FileLibrary, ReportCollection)>;
+
+pub fn run_parser(file: String, version: &str, link_libraries: Vec<PathBuf>) -> ParserResult {
+    parse_project(file, None, version, link_libraries, false)
+}
+
+// Same as run_parser but the file may be a library without a main component.
+// Then the main is an empty template, so the definitions of the files can
+// still be checked.
+pub fn run_parser_for_library(
+    file: String,
+    version: &str,
+    link_libraries: Vec<PathBuf>,
+) -> ParserResult {
+    parse_project(file, None, version, link_libraries, true)
+}
+
+// Same as run_parser but the main file is not read from disk, its content is
+// given by src. The includes are still looked up relative to file.
+pub fn run_parser_from_source(
+    file: String,
+    src: String,
+    version: &str,
+    link_libraries: Vec<PathBuf>,
+) -> ParserResult {
+    parse_project(file, Some(src), version, link_libraries, false)
+}
+
+// The files of a project, read following the includes from the main file
+struct ProjectFiles {
+    file_library: FileLibrary,
+    definitions: Vec<(FileID, Vec<Definition>)>,
+    main_components: Vec<(FileID, MainComponent, bool)>,
+    includes_graph: IncludesGraph,
+    // the main file uses custom templates
+    custom_gates: bool,
+    warnings: ReportCollection,
+}
+
+fn read_project(
     file: String,
+    mut main_source: Option<String>,
     version: &str,
     link_libraries: Vec<PathBuf>,
-) -> Result<(ProgramArchive, ReportCollection), (FileLibrary, ReportCollection)> {
+) -> Result<ProjectFiles, (FileLibrary, ReportCollection)> {
     let mut file_library = FileLibrary::new();
     let mut definitions = Vec::new();
     let mut main_components = Vec::new();
     let mut file_stack = FileStack::new(PathBuf::from(file));
     let mut includes_graph = IncludesGraph::new();
     let mut warnings = Vec::new();
+    let mut custom_gates = None;
     let mut link_libraries2 = link_libraries.clone();
     let mut ext_link_libraries = vec![Path::new("").to_path_buf()];
     ext_link_libraries.append(&mut link_libraries2);
     while let Some(crr_file) = FileStack::take_next(&mut file_stack) {
-        let (found, path, src, crr_str_file, reports) =
-            find_file(crr_file, ext_link_libraries.clone());
+        let (found, path, src, crr_str_file, reports) = match main_source.take() {
+            Some(src) => (true, format!("{:?}", crr_file), src, crr_file, Vec::new()),
+            None => find_file(crr_file, ext_link_libraries.clone()),
+        };
         if !found {
             return Result::Err((file_library.clone(), reports));
         }
         let file_id = file_library.add_file(path.clone(), src.clone());
         let program =
             parser_logic::parse_file(&src, file_id).map_err(|e| (file_library.clone(), e))?;
+        custom_gates.get_or_insert(program.custom_gates);
         if let Some(main) = program.main_component {
             main_components.push((file_id, main, program.custom_gates));
         }
@@ -117,6 +168,50 @@ pub fn run_parser(
             .map_err(|e| (file_library.clone(), vec![e]))?
         }
     }
+    Result::Ok(ProjectFiles {
+        file_library,
+        definitions,
+        main_components,
+        includes_graph,
+        custom_gates: custom_gates.unwrap_or(false),
+        warnings,
+    })
+}
+
+fn parse_project(
+    file: String,
+    main_source: Option<String>,
+    version: &str,
+    link_libraries: Vec<PathBuf>,
+    library: bool,
+) -> ParserResult {
+    let ProjectFiles {
+        file_library,
+        mut definitions,
+        mut main_components,
+        includes_graph,
+        custom_gates,
+        mut warnings,
+    } = read_project(file, main_source, version, link_libraries)?;
+
+    if main_components.is_empty() && library {
+        let meta = Meta::new(0, 0);
+        // not an identifier, so it does not collide with the templates
+        let template_name = "library main".to_string();
+        let template = build_template(
+            meta.clone(),
+            template_name.clone(),
+            Vec::new(),
+            0..0,
+            build_block(meta.clone(), Vec::new()),
+            false,
+            false,
+        );
+        let (main_id, main_definitions) = &mut definitions[0];
+        main_definitions.push(template);
+        let main_component = (Vec::new(), build_call(meta, template_name, Vec::new()));
+        main_components.push((*main_id, main_component, custom_gates));
+    }
 
     if main_components.len() == 0 {
         let report = produce_report(ReportCode::NoMainFoundInProject, 0..0, 0);
@@ -127,47 +222,130 @@ pub fn run_parser(
         warnings.push(report);
         Err((file_library, warnings))
     } else {
-        let mut errors: ReportCollection = includes_graph.get_problematic_paths().iter().map(|path|
-            Report::error(
-                format!(
-                    "Missing custom templates pragma in file {} because of the following chain of includes {}",
-                    path.last().unwrap().display(),
-                    IncludesGraph::display_path(path)
-                ),
-                ReportCode::CustomGatesPragmaError
-            )
-        ).collect();
+        let mut errors = custom_gates_errors(&includes_graph);
         if errors.len() > 0 {
             warnings.append(&mut errors);
             Err((file_library, warnings))
         } else {
             let (main_id, main_component, custom_gates) = main_components.pop().unwrap();
-            let result_program_archive = ProgramArchive::new(
+            build_program(
                 file_library,
                 main_id,
                 main_component,
                 definitions,
                 custom_gates,
+            )
+            .map(|program_archive| (program_archive, warnings.clone()))
+            .map_err(|(lib, mut reports)| {
+                warnings.append(&mut reports);
+                (lib, warnings)
+            })
+        }
+    }
+}
+
+type TestsResult =
+    Result<(Vec<(String, ProgramArchive)>, ReportCollection), (FileLibrary, ReportCollection)>;
+
+// Builds a program for each test of the file, where the body of the test is
+// the main template. The tests of the included files and the main component
+// of the file are ignored.
+pub fn run_parser_for_tests(
+    file: String,
+    version: &str,
+    link_libraries: Vec<PathBuf>,
+) -> TestsResult {
+    let ProjectFiles {
+        file_library,
+        definitions,
+        includes_graph,
+        custom_gates,
+        mut warnings,
+        ..
+    } = read_project(file, None, version, link_libraries)?;
+    let mut errors = custom_gates_errors(&includes_graph);
+    if !errors.is_empty() {
+        warnings.append(&mut errors);
+        return Err((file_library, warnings));
+    }
+    let mut tests = Vec::new();
+    let mut callables = Vec::new();
+    for (file_id, file_definitions) in definitions {
+        let (file_tests, file_callables): (Vec<_>, Vec<_>) = file_definitions
+            .into_iter()
+            .partition(|definition| matches!(definition, Definition::Test { .. }));
+        // the main file is the first one that is read
+        if callables.is_empty() {
+            tests = file_tests;
+        }
+        callables.push((file_id, file_callables));
+    }
+    let main_id = callables[0].0;
+    let mut programs = Vec::new();
+    for test in tests {
+        if let Definition::Test { meta, name, body } = test {
+            // not an identifier, so it does not collide with the templates
+            let template_name = format!("test \"{}\"", name);
+            let location = body.get_meta().location.clone();
+            let template = build_template(
+                meta.clone(),
+                template_name.clone(),
+                Vec::new(),
+                location.start..location.start,
+                body,
+                false,
+                false,
             );
-            match result_program_archive {
-                Err((lib, mut rep)) => {
-                    warnings.append(&mut rep);
-                    Err((lib, warnings))
-                }
-                Ok(mut program_archive) => {
-                    let lib = program_archive.get_file_library().clone();
-                    let program_archive_result = apply_syntactic_sugar(&mut program_archive);
-                    match program_archive_result {
-                        Result::Err(v) => {
-                            warnings.push(v);
-                            Result::Err((lib, warnings))
-                        }
-                        Result::Ok(_) => Ok((program_archive, warnings)),
-                    }
-                }
-            }
+            let mut contents = callables.clone();
+            contents[0].1.push(template);
+            let main_component = (Vec::new(), build_call(meta, template_name, Vec::new()));
+            let program_archive = build_program(
+                file_library.clone(),
+                main_id,
+                main_component,
+                contents,
+                custom_gates,
+            )
+            .map_err(|(lib, mut reports)| {
+                warnings.append(&mut reports);
+                (lib, warnings.clone())
+            })?;
+            programs.push((name, program_archive));
         }
     }
+    Ok((programs, warnings))
+}
+
+fn custom_gates_errors(includes_graph: &IncludesGraph) -> ReportCollection {
+    includes_graph.get_problematic_paths().iter().map(|path|
+        Report::error(
+            format!(
+                "Missing custom templates pragma in file {} because of the following chain of includes {}",
+                path.last().unwrap().display(),
+                IncludesGraph::display_path(path)
+            ),
+            ReportCode::CustomGatesPragmaError
+        )
+    ).collect()
+}
+
+fn build_program(
+    file_library: FileLibrary,
+    main_id: FileID,
+    main_component: MainComponent,
+    definitions: Vec<(FileID, Vec<Definition>)>,
+    custom_gates: bool,
+) -> Result<ProgramArchive, (FileLibrary, ReportCollection)> {
+    let mut program_archive = ProgramArchive::new(
+        file_library,
+        main_id,
+        main_component,
+        definitions,
+        custom_gates,
+    )?;
+    let lib = program_archive.get_file_library().clone();
+    apply_syntactic_sugar(&mut program_archive).map_err(|report| (lib, vec![report]))?;
+    Ok(program_archive)
 }
 
 fn produce_report_with_main_components(
@@ -195,8 +373,12 @@ fn produce_report_with_main_components(
 }
 
 fn open_file(path: PathBuf) -> Result<(String, String), Report> /* path, src */ {
+    use program_structure::builtin_library::builtin_source;
     use std::fs::read_to_string;
     let path_str = format!("{:?}", path);
+    if let Some(src) = path.to_str().and_then(builtin_source) {
+        return Result::Ok((path_str, src.to_string()));
+    }
     read_to_string(path)
         .map(|contents| (path_str.clone(), contents))
         .map_err(|_| produce_report_with_message(ReportCode::FileOs, path_str.clone()))
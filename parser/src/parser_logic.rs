@@ -1,4 +1,5 @@
 use super::lang;
+use lalrpop_util::ParseError;
 use program_structure::ast::produce_report;
 use program_structure::ast::AST;
 use program_structure::error_code::ReportCode;
@@ -86,33 +87,45 @@ pub fn preprocess(expr: &str, file_id: FileID) -> Result<String, ReportCollectio
 }
 
 pub fn parse_file(src: &str, file_id: FileID) -> Result<AST, ReportCollection> {
-    use lalrpop_util::ParseError::*;
+    match parse_file_with_recovery(src, file_id) {
+        (Some(ast), errors) if errors.is_empty() => Ok(ast),
+        (_, errors) => Err(errors),
+    }
+}
 
+// The grammar recovers from the errors in a statement, in the name of a
+// definition and from missing semicolons, so all of them are reported in a
+// single run. The syntax tree is returned whenever the parser reaches the end
+// of the file, even if there are errors, for the tooling that works on
+// incomplete code. An error the parser cannot recover from is reported after
+// the ones found before it.
+pub fn parse_file_with_recovery(src: &str, file_id: FileID) -> (Option<AST>, ReportCollection) {
     let mut errors = Vec::new();
-    let preprocess = preprocess(src, file_id)?;
+    let preprocess = match preprocess(src, file_id) {
+        Ok(preprocess) => preprocess,
+        Err(reports) => return (None, reports),
+    };
 
-    let ast = lang::ParseAstParser::new()
-        .parse(file_id, &mut errors, &preprocess)
-        // TODO: is this always fatal?
-        .map_err(|parse_error| match parse_error {
-            InvalidToken { location } => {
-                produce_generic_report(format!("{:?}", parse_error), location..location, file_id)
-            }
-            UnrecognizedToken { ref token, .. } => {
-                produce_generic_report(format!("{:?}", parse_error), token.0..token.2, file_id)
-            }
-            ExtraToken { ref token } => {
-                produce_generic_report(format!("{:?}", parse_error), token.0..token.2, file_id)
-            }
-            _ => produce_generic_report(format!("{:?}", parse_error), 0..0, file_id),
-        })
-        .map_err(|e| vec![e])?;
-
-    if !errors.is_empty() {
-        return Err(errors.into_iter().collect());
+    let result = lang::ParseAstParser::new().parse(file_id, &mut errors, &preprocess);
+    match result {
+        Ok(ast) => (Some(ast), errors),
+        Err(parse_error) => {
+            let location = error_location(&parse_error);
+            let report = produce_generic_report(format!("{:?}", parse_error), location, file_id);
+            errors.push(report);
+            (None, errors)
+        }
     }
+}
 
-    Ok(ast)
+// Location of the token where the parser found the error
+pub fn error_location<T, E>(error: &ParseError<usize, T, E>) -> std::ops::Range<usize> {
+    use lalrpop_util::ParseError::*;
+    match error {
+        InvalidToken { location } | UnrecognizedEOF { location, .. } => *location..*location,
+        UnrecognizedToken { token, .. } | ExtraToken { token } => token.0..token.2,
+        User { .. } => 0..0,
+    }
 }
 
 fn produce_generic_report(format: String, token: std::ops::Range<usize>, file_id: usize) -> Report {
@@ -120,3 +133,67 @@ fn produce_generic_report(format: String, token: std::ops::Range<usize>, file_id
     report.add_primary(token, file_id, "here".to_string());
     report
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use program_structure::ast::Definition;
+
+    #[test]
+    fn reports_every_recoverable_error() {
+        let src = "template A(n) {
+    signal input a;
+    signal output b
+    var x = 1 + * 2;
+    b <== a;
+}
+template (m) {
+    signal 3;
+}
+component main = A(1);
+";
+        let (ast, errors) = parse_file_with_recovery(src, 0);
+        let codes: Vec<String> = errors
+            .iter()
+            .map(|e| format!("{:?}", e.get_code()))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                "MissingSemicolon",
+                "IllegalExpression",
+                "ExpectedIdentifier",
+                "ExpectedIdentifier"
+            ]
+        );
+        let ast = ast.unwrap();
+        assert_eq!(ast.definitions.len(), 2);
+        assert!(ast.main_component.is_some());
+        assert!(parse_file(src, 0).is_err());
+    }
+
+    #[test]
+    fn parses_test_blocks() {
+        let src = "template A() {
+    signal input a;
+    var test = a;
+}
+test \"a is five\" {
+    component c = A();
+    c.a <== 5;
+}
+";
+        let ast = parse_file(src, 0).ok().unwrap();
+        assert!(matches!(
+            &ast.definitions[1],
+            Definition::Test { name, .. } if name == "a is five"
+        ));
+
+        let (_, errors) = parse_file_with_recovery(&src.replace("test \"", "tset \""), 0);
+        let codes: Vec<String> = errors
+            .iter()
+            .map(|e| format!("{:?}", e.get_code()))
+            .collect();
+        assert_eq!(codes, vec!["UnrecognizedDefinition"]);
+    }
+}
@@ -174,6 +174,13 @@ pub enum Definition {
         arg_location: FileLocation,
         body: Statement,
     },
+    // test "name" { body }, run by circom test with the body as the main
+    // template, it is not callable
+    Test {
+        meta: Meta,
+        name: String,
+        body: Statement,
+    },
 }
 pub fn build_template(
     meta: Meta,
@@ -211,6 +218,10 @@ pub fn build_function(
     }
 }
 
+pub fn build_test(meta: Meta, name: String, body: Statement) -> Definition {
+    Definition::Test { meta, name, body }
+}
+
 #[derive(Clone)]
 pub enum Statement {
     IfThenElse {
@@ -572,6 +583,18 @@ pub fn produce_report(error_code: ReportCode, location: FileLocation, file_id: F
             );
             report
         }
+        UnrecognizedDefinition => {
+            let mut report = Report::error(
+                "unrecognized definition".to_string(),
+                ReportCode::UnrecognizedDefinition,
+            );
+            report.add_primary(
+                location,
+                file_id,
+                "Expected function, template or test".to_string(),
+            );
+            report
+        }
         _ => unreachable!(),
     };
     report
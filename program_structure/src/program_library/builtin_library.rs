@@ -0,0 +1,34 @@
+// Circuits shipped with the compiler. They are included as any other file,
+// e.g. `include "qm31.circom";`, when no file with that name is found in the
+// include paths.
+use super::file_definition::{FileID, FileLibrary};
+
+pub const BUILTIN_PREFIX: &str = "<builtin>/";
+pub const QM31_LIBRARY: &str = "qm31.circom";
+pub const QM31_FUNCTIONS: [&str; 4] = ["qm31_add", "qm31_sub", "qm31_mul", "qm31_inv"];
+
+pub fn builtin_path(name: &str) -> Option<String> {
+    if name == QM31_LIBRARY {
+        Some(format!("{}{}", BUILTIN_PREFIX, name))
+    } else {
+        None
+    }
+}
+
+pub fn builtin_source(path: &str) -> Option<&'static str> {
+    match path.strip_prefix(BUILTIN_PREFIX) {
+        Some(QM31_LIBRARY) => Some(include_str!("builtins/qm31.circom")),
+        _ => None,
+    }
+}
+
+pub fn is_builtin_file(file_library: &FileLibrary, file_id: FileID) -> bool {
+    file_library
+        .get_file_name(file_id)
+        .is_some_and(|name| name.trim_matches('"').starts_with(BUILTIN_PREFIX))
+}
+
+// QM31 functions of the builtin library, evaluated natively
+pub fn is_qm31_function(name: &str, file_library: &FileLibrary, file_id: FileID) -> bool {
+    QM31_FUNCTIONS.contains(&name) && is_builtin_file(file_library, file_id)
+}
@@ -0,0 +1,119 @@
+pragma circom 2.0.0;
+
+// Arithmetic over QM31, the degree 4 extension of M31 used by Circle-STARKs:
+// CM31 = M31[i] / (i^2 + 1) and QM31 = CM31[u] / (u^2 - 2 - i).
+// The element (a0 + a1 * i) + (a2 + a3 * i) * u is represented as [a0, a1, a2, a3].
+// The functions are evaluated natively by the compiler and the C witness generator.
+
+function cm31_mul(a0, a1, b0, b1) {
+    var r[2];
+    r[0] = a0 * b0 - a1 * b1;
+    r[1] = a0 * b1 + a1 * b0;
+    return r;
+}
+
+function qm31_add(a, b) {
+    var r[4];
+    for (var k = 0; k < 4; k++) {
+        r[k] = a[k] + b[k];
+    }
+    return r;
+}
+
+function qm31_sub(a, b) {
+    var r[4];
+    for (var k = 0; k < 4; k++) {
+        r[k] = a[k] - b[k];
+    }
+    return r;
+}
+
+function qm31_mul(a, b) {
+    var xx[2] = cm31_mul(a[0], a[1], b[0], b[1]);
+    var yy[2] = cm31_mul(a[2], a[3], b[2], b[3]);
+    var xy[2] = cm31_mul(a[0], a[1], b[2], b[3]);
+    var yx[2] = cm31_mul(a[2], a[3], b[0], b[1]);
+    var r[4];
+    r[0] = xx[0] + 2 * yy[0] - yy[1];
+    r[1] = xx[1] + 2 * yy[1] + yy[0];
+    r[2] = xy[0] + yx[0];
+    r[3] = xy[1] + yx[1];
+    return r;
+}
+
+function qm31_inv(a) {
+    // (x + y * u)^-1 = (x - y * u) / (x^2 - (2 + i) * y^2)
+    var xx[2] = cm31_mul(a[0], a[1], a[0], a[1]);
+    var yy[2] = cm31_mul(a[2], a[3], a[2], a[3]);
+    var d0 = xx[0] - 2 * yy[0] + yy[1];
+    var d1 = xx[1] - 2 * yy[1] - yy[0];
+    // (d0 + d1 * i)^-1 = (d0 - d1 * i) / (d0^2 + d1^2)
+    var norm = d0 * d0 + d1 * d1;
+    var x[2] = cm31_mul(a[0], a[1], d0 / norm, -d1 / norm);
+    var y[2] = cm31_mul(a[2], a[3], d0 / norm, -d1 / norm);
+    var r[4];
+    r[0] = x[0];
+    r[1] = x[1];
+    r[2] = -y[0];
+    r[3] = -y[1];
+    return r;
+}
+
+template CM31Mul() {
+    signal input a[2];
+    signal input b[2];
+    signal output out[2];
+    signal p0 <== a[0] * b[0];
+    signal p1 <== a[1] * b[1];
+    signal p2 <== (a[0] + a[1]) * (b[0] + b[1]);
+    out[0] <== p0 - p1;
+    out[1] <== p2 - p0 - p1;
+}
+
+template QM31Add() {
+    signal input a[4];
+    signal input b[4];
+    signal output out[4];
+    for (var k = 0; k < 4; k++) {
+        out[k] <== a[k] + b[k];
+    }
+}
+
+template QM31Sub() {
+    signal input a[4];
+    signal input b[4];
+    signal output out[4];
+    for (var k = 0; k < 4; k++) {
+        out[k] <== a[k] - b[k];
+    }
+}
+
+// Karatsuba over CM31, 9 quadratic constraints
+template QM31Mul() {
+    signal input a[4];
+    signal input b[4];
+    signal output out[4];
+    component xx = CM31Mul();
+    xx.a <== [a[0], a[1]];
+    xx.b <== [b[0], b[1]];
+    component yy = CM31Mul();
+    yy.a <== [a[2], a[3]];
+    yy.b <== [b[2], b[3]];
+    component s = CM31Mul();
+    s.a <== [a[0] + a[2], a[1] + a[3]];
+    s.b <== [b[0] + b[2], b[1] + b[3]];
+    out[0] <== xx.out[0] + 2 * yy.out[0] - yy.out[1];
+    out[1] <== xx.out[1] + 2 * yy.out[1] + yy.out[0];
+    out[2] <== s.out[0] - xx.out[0] - yy.out[0];
+    out[3] <== s.out[1] - xx.out[1] - yy.out[1];
+}
+
+template QM31Inv() {
+    signal input in[4];
+    signal output out[4];
+    out <-- qm31_inv(in);
+    component check = QM31Mul();
+    check.a <== in;
+    check.b <== out;
+    check.out === [1, 0, 0, 0];
+}
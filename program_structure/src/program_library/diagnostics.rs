@@ -0,0 +1,280 @@
+use super::error_definition::Report;
+use super::file_definition::{FileID, FileLibrary};
+use codespan_reporting::diagnostic::Label;
+use codespan_reporting::files::Files;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::sync::Mutex;
+
+// Human reports are printed by Report::print_reports as soon as they are
+// produced. With the other formats print_reports stores them, resolved to
+// lines and columns, and write_diagnostics outputs all of them in a single
+// document once the compiler is done.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DiagnosticsFormat {
+    Human,
+    Json,
+    Sarif,
+}
+
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+struct Span {
+    file: String,
+    start: Position,
+    end: Position,
+    message: String,
+}
+
+struct Diagnostic {
+    is_error: bool,
+    code: String,
+    name: String,
+    message: String,
+    primary: Vec<Span>,
+    secondary: Vec<Span>,
+    notes: Vec<String>,
+}
+
+struct Sink {
+    format: DiagnosticsFormat,
+    output: Option<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+static SINK: Mutex<Sink> = Mutex::new(Sink {
+    format: DiagnosticsFormat::Human,
+    output: None,
+    diagnostics: Vec::new(),
+});
+
+// output is the file the document is written to, None for stdout
+pub fn set_diagnostics_format(format: DiagnosticsFormat, output: Option<String>) {
+    let mut sink = SINK.lock().unwrap();
+    sink.format = format;
+    sink.output = output;
+}
+
+pub fn diagnostics_format() -> DiagnosticsFormat {
+    SINK.lock().unwrap().format
+}
+
+// When the document goes to stdout the progress messages of the compiler are
+// printed to stderr, so that stdout only holds the document
+pub fn print_progress(message: &str) {
+    let to_stderr = {
+        let sink = SINK.lock().unwrap();
+        sink.format != DiagnosticsFormat::Human && sink.output.is_none()
+    };
+    if to_stderr {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+pub(crate) fn collect(reports: &[Report], file_library: &FileLibrary) {
+    let mut diagnostics: Vec<Diagnostic> = reports
+        .iter()
+        .map(|r| to_diagnostic(r, file_library))
+        .collect();
+    SINK.lock().unwrap().diagnostics.append(&mut diagnostics);
+}
+
+pub fn write_diagnostics() -> Result<(), ()> {
+    let sink = SINK.lock().unwrap();
+    let document = match sink.format {
+        DiagnosticsFormat::Human => return Result::Ok(()),
+        DiagnosticsFormat::Json => to_json(&sink.diagnostics),
+        DiagnosticsFormat::Sarif => to_sarif(&sink.diagnostics),
+    };
+    let text = serde_json::to_string_pretty(&document).map_err(|_| {})?;
+    match &sink.output {
+        Some(file) => {
+            let mut file = File::create(file).map_err(|_| {})?;
+            writeln!(file, "{}", text).map_err(|_| {})?;
+            file.flush().map_err(|_| {})
+        }
+        None => {
+            println!("{}", text);
+            Result::Ok(())
+        }
+    }
+}
+
+fn to_diagnostic(report: &Report, file_library: &FileLibrary) -> Diagnostic {
+    let code = report.get_code();
+    let name = format!("{:?}", code);
+    let to_span = |label: &Label<FileID>| to_span(label, file_library);
+    Diagnostic {
+        is_error: report.is_error(),
+        code: Report::error_code_to_diagnostic_code(code),
+        // the variants carrying data only keep their name
+        name: name.split('(').next().unwrap().to_string(),
+        message: report.get_message().clone(),
+        primary: report.get_primary().iter().map(to_span).collect(),
+        secondary: report.get_secondary().iter().map(to_span).collect(),
+        notes: report.get_notes().clone(),
+    }
+}
+
+fn to_span(label: &Label<FileID>, file_library: &FileLibrary) -> Span {
+    // the file names are stored as the debug output of their paths
+    let file = file_library
+        .get_file_name(label.file_id)
+        .map_or(String::new(), |name| name.trim_matches('"').to_string());
+    Span {
+        file,
+        start: to_position(file_library, label.file_id, label.range.start),
+        end: to_position(file_library, label.file_id, label.range.end),
+        message: label.message.clone(),
+    }
+}
+
+fn to_position(file_library: &FileLibrary, file_id: FileID, byte_index: usize) -> Position {
+    match file_library.to_storage().location(file_id, byte_index) {
+        Some(location) => Position {
+            line: location.line_number,
+            column: location.column_number,
+        },
+        None => Position { line: 0, column: 0 },
+    }
+}
+
+fn severity(diagnostic: &Diagnostic) -> &'static str {
+    if diagnostic.is_error {
+        "error"
+    } else {
+        "warning"
+    }
+}
+
+fn to_json(diagnostics: &[Diagnostic]) -> Value {
+    let span = |span: &Span| {
+        json!({
+            "file": span.file,
+            "start": { "line": span.start.line, "column": span.start.column },
+            "end": { "line": span.end.line, "column": span.end.column },
+            "message": span.message,
+        })
+    };
+    let reports: Vec<Value> = diagnostics
+        .iter()
+        .map(|d| {
+            json!({
+                "severity": severity(d),
+                "code": d.code,
+                "name": d.name,
+                "message": d.message,
+                "primary": d.primary.iter().map(span).collect::<Vec<_>>(),
+                "secondary": d.secondary.iter().map(span).collect::<Vec<_>>(),
+                "notes": d.notes,
+            })
+        })
+        .collect();
+    json!({ "diagnostics": reports })
+}
+
+// SARIF 2.1.0, a run of the tool with one rule per report code
+fn to_sarif(diagnostics: &[Diagnostic]) -> Value {
+    let location = |span: &Span| {
+        let mut location = json!({
+            "physicalLocation": {
+                "artifactLocation": { "uri": span.file },
+                "region": {
+                    "startLine": span.start.line,
+                    "startColumn": span.start.column,
+                    "endLine": span.end.line,
+                    "endColumn": span.end.column,
+                },
+            },
+        });
+        if !span.message.is_empty() {
+            location["message"] = json!({ "text": span.message });
+        }
+        location
+    };
+    let mut rules: BTreeMap<&String, &String> = BTreeMap::new();
+    let mut results = Vec::new();
+    for diagnostic in diagnostics {
+        rules.insert(&diagnostic.code, &diagnostic.name);
+        let mut text = diagnostic.message.clone();
+        for note in &diagnostic.notes {
+            text.push('\n');
+            text.push_str(note);
+        }
+        results.push(json!({
+            "ruleId": diagnostic.code,
+            "level": severity(diagnostic),
+            "message": { "text": text },
+            "locations": diagnostic.primary.iter().map(location).collect::<Vec<_>>(),
+            "relatedLocations": diagnostic.secondary.iter().map(location).collect::<Vec<_>>(),
+        }));
+    }
+    let rules: Vec<Value> = rules
+        .into_iter()
+        .map(|(code, name)| json!({ "id": code, "name": name }))
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "circom",
+                    "informationUri": "https://docs.circom.io",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::error_code::ReportCode;
+    use super::*;
+
+    #[test]
+    fn reports_are_resolved_to_lines_and_columns() {
+        let mut file_library = FileLibrary::new();
+        let file_id = file_library.add_file(
+            "\"main.circom\"".to_string(),
+            "pragma circom 2.0.0;\nsignal input a;\n".to_string(),
+        );
+        let mut report = Report::warning("unused".to_string(), ReportCode::UnusedOutput);
+        report.add_primary(28..35, file_id, "here".to_string());
+        report.add_note("a note".to_string());
+        let diagnostic = to_diagnostic(&report, &file_library);
+        assert_eq!(diagnostic.name, "UnusedOutput");
+        assert_eq!(diagnostic.primary[0].file, "main.circom");
+        assert_eq!(
+            (
+                diagnostic.primary[0].start.line,
+                diagnostic.primary[0].start.column
+            ),
+            (2, 8)
+        );
+
+        let document = to_json(&[diagnostic]);
+        assert_eq!(document["diagnostics"][0]["severity"], "warning");
+        assert_eq!(
+            document["diagnostics"][0]["primary"][0]["end"]["column"],
+            15
+        );
+        let diagnostic = to_diagnostic(&report, &file_library);
+        let sarif = to_sarif(&[diagnostic]);
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["message"]["text"], "unused\na note");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            2
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use core::fmt;
 use std::fmt::Formatter;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum ReportCode {
     //Parse Errors
     UnclosedComment,
@@ -13,11 +13,13 @@ pub enum ReportCode {
     UnrecognizedVersion,
     UnrecognizedPragma,
     ExpectedIdentifier,
+    UnrecognizedDefinition,
     IncludeNotFound,
     IllegalExpression,
     MultiplePragma,
     NoCompilerVersionWarning,
     CompilerVersionError,
+    UnstableFormat,
     WrongTypesInAssignOperationOperatorSignal,
     WrongTypesInAssignOperationOperatorNoSignal,
     WrongTypesInAssignOperationTemplate,
@@ -52,6 +54,8 @@ pub enum ReportCode {
     NonExistentSymbol,
     MainComponentWithTags,
     IllegalMainExpression,
+    FieldOverflow,
+    MeaninglessComparison,
     TemplateCallAsArgument,
     TemplateWrongNumberOfArguments,
     TemplateWithReturnStatement,
@@ -96,8 +100,14 @@ pub enum ReportCode {
     UnconstrainedIOSignal,
     UnusedInput,
     UnusedOutput,
+    UndeterminedSignal,
 
     ErrorWat2Wasm,
+    // Command line and output codes
+    InvalidOption,
+    OutputError,
+    WitnessError,
+    UnsatisfiedConstraint,
     CustomGateIntermediateSignalWarning,
     CustomGateConstraintError,
     CustomGateSubComponentError,
@@ -134,6 +144,8 @@ impl fmt::Display for ReportCode {
             MultiplePragma => "P1013",
             IncludeNotFound => "P1014",
             ExpectedIdentifier => "P1015",
+            UnstableFormat => "P1016",
+            UnrecognizedDefinition => "P1017",
             UndefinedFunction => "T2001",
             UndefinedTemplate => "T2002",
             UninitializedSymbolInExpression => "T2003",
@@ -194,6 +206,8 @@ impl fmt::Display for ReportCode {
             MainComponentWithTags => "T2051",
             NonValidTagAssignment => "T2052",
             IllegalMainExpression => "T2053",
+            FieldOverflow => "T2054",
+            MeaninglessComparison => "T2055",
             RuntimeError => "T3001",
             RuntimeWarning => "T3002",
             UnknownDimension => "T20460",
@@ -208,7 +222,12 @@ impl fmt::Display for ReportCode {
             UnconstrainedIOSignal => "CA02",
             UnusedInput => "CA03",
             UnusedOutput => "CA04",
+            UndeterminedSignal => "CA05",
             ErrorWat2Wasm => "W01",
+            InvalidOption => "IO01",
+            OutputError => "IO02",
+            WitnessError => "IO03",
+            UnsatisfiedConstraint => "IO04",
             CustomGateIntermediateSignalWarning => "CG01",
             CustomGateConstraintError => "CG02",
             CustomGateSubComponentError => "CG03",
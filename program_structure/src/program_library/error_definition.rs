@@ -1,3 +1,4 @@
+use super::diagnostics::{self, DiagnosticsFormat};
 use super::error_code::ReportCode;
 use super::file_definition::{FileID, FileLibrary, FileLocation};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
@@ -52,6 +53,10 @@ impl Report {
     }
     pub fn print_reports(reports: &[Report], file_library: &FileLibrary) {
         use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+        if diagnostics::diagnostics_format() != DiagnosticsFormat::Human {
+            diagnostics::collect(reports, file_library);
+            return;
+        }
         let writer = StandardStream::stderr(ColorChoice::Always);
         let config = term::Config::default();
         let mut diagnostics = Vec::new();
@@ -66,6 +71,11 @@ impl Report {
             }
         }
     }
+    // Reports an error that is not located in any file, such as an invalid
+    // option or an output that cannot be written
+    pub fn print_error(error_message: String, code: ReportCode) {
+        Report::print_reports(&[Report::error(error_message, code)], &FileLibrary::new());
+    }
     pub fn error_code_to_diagnostic_code(error_code: &ReportCode) -> DiagnosticCode {
         error_code.to_string()
     }
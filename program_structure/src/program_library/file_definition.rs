@@ -33,6 +33,12 @@ impl FileLibrary {
             None => None,
         }
     }
+    pub fn get_file_name(&self, file_id: FileID) -> Option<&str> {
+        self.files.get(file_id).map(|file| file.name().as_str())
+    }
+    pub fn get_source(&self, file_id: FileID) -> Option<&str> {
+        self.files.get(file_id).map(|file| file.source().as_str())
+    }
     pub fn to_storage(&self) -> &FileStorage {
         &self.get_files()
     }
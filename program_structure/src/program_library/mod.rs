@@ -1,4 +1,6 @@
 use super::ast;
+pub mod builtin_library;
+pub mod diagnostics;
 pub mod error_code;
 pub mod error_definition;
 pub mod file_definition;
@@ -83,6 +83,8 @@ impl Merger {
                         (Option::None, meta)
                     }
                 }
+                // the tests are turned into templates by circom test
+                Definition::Test { .. } => continue,
             };
             if let Option::Some(definition_name) = name {
                 let mut report = Report::error(
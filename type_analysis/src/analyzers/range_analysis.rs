@@ -0,0 +1,682 @@
+use num_bigint::BigInt;
+use num_traits::{One, ToPrimitive, Zero};
+use program_structure::ast::*;
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::{Report, ReportCollection};
+use program_structure::file_definition::FileID;
+use program_structure::program_archive::ProgramArchive;
+use std::collections::{HashMap, HashSet};
+
+// Shifts and exponents beyond this size make any non trivial value overflow
+const MAX_EXPONENT: usize = 4096;
+
+// Bounds [lower, upper] of the integer representative of a value, unknown
+// values have no bounds
+#[derive(Clone, PartialEq, Eq)]
+struct Range {
+    lower: BigInt,
+    upper: BigInt,
+}
+
+impl Range {
+    fn new(lower: BigInt, upper: BigInt) -> Range {
+        Range { lower, upper }
+    }
+    fn constant(value: BigInt) -> Range {
+        Range::new(value.clone(), value)
+    }
+    fn bits(bits: usize) -> Range {
+        Range::new(BigInt::zero(), (BigInt::one() << bits) - 1)
+    }
+    fn boolean() -> Range {
+        Range::bits(1)
+    }
+    fn join(&self, other: &Range) -> Range {
+        Range::new(
+            self.lower.clone().min(other.lower.clone()),
+            self.upper.clone().max(other.upper.clone()),
+        )
+    }
+    fn meet(&self, other: &Range) -> Range {
+        Range::new(
+            self.lower.clone().max(other.lower.clone()),
+            self.upper.clone().min(other.upper.clone()),
+        )
+    }
+    fn as_constant(&self) -> Option<usize> {
+        if self.lower == self.upper {
+            self.upper.to_usize()
+        } else {
+            None
+        }
+    }
+}
+
+type Bound = Option<Range>;
+
+fn join(left: &Bound, right: &Bound) -> Bound {
+    match (left, right) {
+        (Some(l), Some(r)) => Some(l.join(r)),
+        _ => None,
+    }
+}
+
+// Names without a bound are unknown. Signals and arrays that have not been
+// assigned yet are not in the state, so that the first assignment sets their
+// bound and the rest are joined to it.
+type State = HashMap<String, Bound>;
+
+// maxbit value that the call sites give to each input of each template, None
+// when a call site connects a signal whose maxbit is not known
+type Seeds = HashMap<(String, String), Option<usize>>;
+
+fn add_seed(seeds: &mut Seeds, key: (String, String), maxbit: Option<usize>) {
+    let joined = match seeds.get(&key) {
+        Some(previous) => previous.zip(maxbit).map(|(l, r)| l.max(r)),
+        None => maxbit,
+    };
+    seeds.insert(key, joined);
+}
+
+fn join_states(left: &State, right: &State) -> State {
+    let mut state = left.clone();
+    for (name, bound) in right {
+        let joined = match left.get(name) {
+            Some(other) => join(other, bound),
+            None => bound.clone(),
+        };
+        state.insert(name.clone(), joined);
+    }
+    state
+}
+
+struct RangeAnalysis<'a> {
+    program_archive: &'a ProgramArchive,
+    field: &'a BigInt,
+    half_field: BigInt,
+    file_id: FileID,
+    signals: HashSet<String>,
+    // bounds given to signals through their tags
+    tag_bounds: HashMap<String, Range>,
+    maxbits: HashMap<String, usize>,
+    // template of each component
+    components: HashMap<String, String>,
+    // maxbit values of the inputs of the analysed template
+    input_seeds: HashMap<String, usize>,
+    call_sites: Seeds,
+    state: State,
+    reporting: bool,
+    reports: ReportCollection,
+}
+
+impl<'a> RangeAnalysis<'a> {
+    fn new(
+        program_archive: &'a ProgramArchive,
+        field: &'a BigInt,
+        file_id: FileID,
+        reporting: bool,
+    ) -> RangeAnalysis<'a> {
+        RangeAnalysis {
+            program_archive,
+            field,
+            half_field: (field - 1) / 2,
+            file_id,
+            signals: HashSet::new(),
+            tag_bounds: HashMap::new(),
+            maxbits: HashMap::new(),
+            components: HashMap::new(),
+            input_seeds: HashMap::new(),
+            call_sites: Seeds::new(),
+            state: State::new(),
+            reporting,
+            reports: ReportCollection::new(),
+        }
+    }
+
+    fn analyse_statement(&mut self, stmt: &Statement) {
+        use Statement::*;
+        match stmt {
+            IfThenElse {
+                cond,
+                if_case,
+                else_case,
+                ..
+            } => {
+                self.analyse_expression(cond);
+                let before = self.state.clone();
+                self.analyse_statement(if_case);
+                let after_if = std::mem::replace(&mut self.state, before);
+                if let Some(else_case) = else_case {
+                    self.analyse_statement(else_case);
+                }
+                self.state = join_states(&after_if, &self.state);
+            }
+            While { cond, stmt, .. } => {
+                // the loop is run once without reporting, the values that are
+                // modified by the body are unknown in the next iterations
+                let reporting = self.reporting;
+                let before = self.state.clone();
+                self.reporting = false;
+                self.analyse_expression(cond);
+                self.analyse_statement(stmt);
+                let after = std::mem::replace(&mut self.state, before.clone());
+                for (name, bound) in after {
+                    if self.state.get(&name) != Some(&bound) {
+                        self.state.insert(name, None);
+                    }
+                }
+                self.reporting = reporting;
+                self.analyse_expression(cond);
+                self.analyse_statement(stmt);
+                // the body may not run at all
+                self.state = join_states(&before, &self.state);
+            }
+            Return { value, .. } => {
+                self.analyse_expression(value);
+            }
+            InitializationBlock {
+                initializations, ..
+            } => {
+                for stmt in initializations {
+                    self.analyse_statement(stmt);
+                }
+            }
+            Declaration {
+                xtype,
+                name,
+                dimensions,
+                ..
+            } => {
+                for dimension in dimensions {
+                    self.analyse_expression(dimension);
+                }
+                self.tag_bounds.remove(name);
+                self.maxbits.remove(name);
+                self.components.remove(name);
+                match xtype {
+                    VariableType::Var => {
+                        self.signals.remove(name);
+                        self.state
+                            .insert(name.clone(), Some(Range::constant(BigInt::zero())));
+                    }
+                    VariableType::Signal(_, tags) => {
+                        self.signals.insert(name.clone());
+                        self.state.remove(name);
+                        if tags.iter().any(|tag| tag == "binary") {
+                            self.tag_bounds.insert(name.clone(), Range::boolean());
+                        }
+                        let seed = self.input_seeds.get(name).cloned();
+                        match seed {
+                            Some(bits) if tags.iter().any(|tag| tag == "maxbit") => {
+                                self.set_maxbit(name, bits);
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {
+                        self.signals.remove(name);
+                        self.state.insert(name.clone(), None);
+                    }
+                }
+            }
+            Substitution {
+                var, access, rhe, ..
+            } => {
+                let value = self.analyse_expression(rhe);
+                self.analyse_access(access);
+                let tag = access.iter().find_map(|acc| match acc {
+                    Access::ComponentAccess(tag) => Some(tag),
+                    _ => None,
+                });
+                let instance = self.is_instance(rhe);
+                match (tag, instance) {
+                    (Some(tag), _) if self.signals.contains(var) => {
+                        self.assign_tag(var, tag, &value);
+                    }
+                    (Some(input), _) if self.components.contains_key(var) => {
+                        self.connect(var, input, rhe);
+                    }
+                    (Some(_), _) => {}
+                    (None, Some(template)) => {
+                        self.components.insert(var.clone(), template);
+                    }
+                    (None, None) if access.is_empty() => {
+                        self.state.insert(var.clone(), value);
+                    }
+                    (None, None) => {
+                        let joined = match self.state.get(var) {
+                            Some(previous) => join(previous, &value),
+                            None => value,
+                        };
+                        self.state.insert(var.clone(), joined);
+                    }
+                }
+            }
+            MultSubstitution { lhe, rhe, .. } => {
+                self.analyse_expression(rhe);
+                self.forget_assigned(lhe);
+            }
+            UnderscoreSubstitution { rhe, .. } => {
+                self.analyse_expression(rhe);
+            }
+            ConstraintEquality { lhe, rhe, .. } => {
+                self.analyse_expression(lhe);
+                self.analyse_expression(rhe);
+            }
+            LogCall { args, .. } => {
+                for arg in args {
+                    if let LogArgument::LogExp(arg) = arg {
+                        self.analyse_expression(arg);
+                    }
+                }
+            }
+            Block { stmts, .. } => {
+                for stmt in stmts {
+                    self.analyse_statement(stmt);
+                }
+            }
+            Assert { arg, .. } => {
+                self.analyse_expression(arg);
+            }
+        }
+    }
+
+    fn assign_tag(&mut self, signal: &str, tag: &str, value: &Bound) {
+        let bits = value.as_ref().and_then(|range| range.as_constant());
+        match bits {
+            Some(bits) if tag == "maxbit" => self.set_maxbit(signal, bits),
+            _ => {}
+        }
+    }
+
+    fn set_maxbit(&mut self, signal: &str, bits: usize) {
+        if bits < MAX_EXPONENT {
+            self.tag_bounds
+                .insert(signal.to_string(), Range::bits(bits));
+            self.maxbits.insert(signal.to_string(), bits);
+        }
+    }
+
+    fn is_instance(&self, rhe: &Expression) -> Option<String> {
+        match rhe {
+            Expression::Call { id, .. } if self.program_archive.contains_template(id) => {
+                Some(id.clone())
+            }
+            _ => None,
+        }
+    }
+
+    // The input of the component takes the maxbit value of the signal that is
+    // connected to it
+    fn connect(&mut self, component: &str, input: &str, rhe: &Expression) {
+        let maxbit = match rhe {
+            Expression::Variable { name, access, .. } => {
+                let is_field = access
+                    .iter()
+                    .any(|acc| matches!(acc, Access::ComponentAccess(_)));
+                if is_field {
+                    None
+                } else {
+                    self.maxbits.get(name).cloned()
+                }
+            }
+            _ => None,
+        };
+        let template = self.components.get(component).unwrap().clone();
+        add_seed(&mut self.call_sites, (template, input.to_string()), maxbit);
+    }
+
+    fn forget_assigned(&mut self, lhe: &Expression) {
+        match lhe {
+            Expression::Variable { name, .. } => {
+                self.state.insert(name.clone(), None);
+            }
+            Expression::Tuple { values, .. } => {
+                for value in values {
+                    self.forget_assigned(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn analyse_access(&mut self, access: &[Access]) {
+        for acc in access {
+            if let Access::ArrayAccess(index) = acc {
+                self.analyse_expression(index);
+            }
+        }
+    }
+
+    fn analyse_expression(&mut self, expr: &Expression) -> Bound {
+        use Expression::*;
+        match expr {
+            InfixOp {
+                meta,
+                lhe,
+                infix_op,
+                rhe,
+            } => {
+                let left = self.analyse_expression(lhe);
+                let right = self.analyse_expression(rhe);
+                if is_comparison(*infix_op) {
+                    self.check_comparison(meta, &left);
+                    self.check_comparison(meta, &right);
+                }
+                match (left, right) {
+                    (Some(left), Some(right)) => {
+                        let result = infix_range(*infix_op, &left, &right);
+                        self.check_overflow(meta, result)
+                    }
+                    _ if is_boolean(*infix_op) => Some(Range::boolean()),
+                    _ => None,
+                }
+            }
+            PrefixOp { prefix_op, rhe, .. } => {
+                let value = self.analyse_expression(rhe);
+                match prefix_op {
+                    ExpressionPrefixOpcode::BoolNot => Some(Range::boolean()),
+                    ExpressionPrefixOpcode::Sub => value.filter(|range| range.upper.is_zero()),
+                    ExpressionPrefixOpcode::Complement => None,
+                }
+            }
+            InlineSwitchOp {
+                cond,
+                if_true,
+                if_false,
+                ..
+            } => {
+                self.analyse_expression(cond);
+                let if_true = self.analyse_expression(if_true);
+                let if_false = self.analyse_expression(if_false);
+                join(&if_true, &if_false)
+            }
+            ParallelOp { rhe, .. } => self.analyse_expression(rhe),
+            Variable { name, access, .. } => {
+                self.analyse_access(access);
+                let is_tag_or_field = access
+                    .iter()
+                    .any(|acc| matches!(acc, Access::ComponentAccess(_)));
+                if is_tag_or_field {
+                    return None;
+                }
+                let value = self.state.get(name).cloned().flatten();
+                match (self.tag_bounds.get(name), value) {
+                    (Some(tag_bound), Some(value)) => Some(tag_bound.meet(&value)),
+                    (Some(tag_bound), None) => Some(tag_bound.clone()),
+                    (None, value) => value,
+                }
+            }
+            Number(_, value) => {
+                if *value < *self.field {
+                    Some(Range::constant(value.clone()))
+                } else {
+                    None
+                }
+            }
+            Call { args, .. } => {
+                for arg in args {
+                    self.analyse_expression(arg);
+                }
+                None
+            }
+            AnonymousComp {
+                params, signals, ..
+            } => {
+                for arg in params.iter().chain(signals.iter()) {
+                    self.analyse_expression(arg);
+                }
+                None
+            }
+            ArrayInLine { values, .. } => {
+                let mut bound = None;
+                for (i, value) in values.iter().enumerate() {
+                    let value = self.analyse_expression(value);
+                    bound = if i == 0 { value } else { join(&bound, &value) };
+                }
+                bound
+            }
+            Tuple { values, .. } => {
+                for value in values {
+                    self.analyse_expression(value);
+                }
+                None
+            }
+            UniformArray {
+                value, dimension, ..
+            } => {
+                self.analyse_expression(dimension);
+                self.analyse_expression(value)
+            }
+        }
+    }
+
+    fn check_overflow(&mut self, meta: &Meta, result: Bound) -> Bound {
+        let range = result?;
+        if range.upper < *self.field {
+            return Some(range);
+        }
+        if self.reporting {
+            let mut warning = Report::warning(
+                "The value of this expression can exceed the size of the field".to_string(),
+                ReportCode::FieldOverflow,
+            );
+            warning.add_primary(
+                meta.location.clone(),
+                meta.file_id.unwrap_or(self.file_id),
+                format!(
+                    "can be as large as {} bits but the field has {} bits, the value is reduced modulo the prime",
+                    range.upper.bits(),
+                    self.field.bits()
+                ),
+            );
+            self.reports.push(warning);
+        }
+        // the value wraps around, reporting it once is enough
+        None
+    }
+
+    fn check_comparison(&mut self, meta: &Meta, operand: &Bound) {
+        let range = match operand {
+            Some(range) if range.upper > self.half_field => range,
+            _ => return,
+        };
+        if self.reporting {
+            let mut warning = Report::warning(
+                "Comparison of values that can be larger than half of the field".to_string(),
+                ReportCode::MeaninglessComparison,
+            );
+            warning.add_primary(
+                meta.location.clone(),
+                meta.file_id.unwrap_or(self.file_id),
+                format!(
+                    "an operand can be as large as {} bits, values over (p-1)/2 are compared as negative numbers",
+                    range.upper.bits()
+                ),
+            );
+            self.reports.push(warning);
+        }
+    }
+}
+
+fn is_comparison(op: ExpressionInfixOpcode) -> bool {
+    use ExpressionInfixOpcode::*;
+    matches!(op, Lesser | Greater | LesserEq | GreaterEq)
+}
+
+fn is_boolean(op: ExpressionInfixOpcode) -> bool {
+    use ExpressionInfixOpcode::*;
+    is_comparison(op) || matches!(op, Eq | NotEq | BoolOr | BoolAnd)
+}
+
+fn mask(value: &BigInt) -> BigInt {
+    (BigInt::one() << value.bits()) - 1
+}
+
+// Bound of the result of the operation on the integer representatives before
+// it is reduced modulo the prime
+fn infix_range(op: ExpressionInfixOpcode, left: &Range, right: &Range) -> Bound {
+    use ExpressionInfixOpcode::*;
+    match op {
+        Add => Some(Range::new(
+            &left.lower + &right.lower,
+            &left.upper + &right.upper,
+        )),
+        Sub if left.lower >= right.upper => Some(Range::new(
+            &left.lower - &right.upper,
+            &left.upper - &right.lower,
+        )),
+        Mul => Some(Range::new(
+            &left.lower * &right.lower,
+            &left.upper * &right.upper,
+        )),
+        Pow => {
+            let lower = power(&left.lower, &right.lower)?;
+            let upper = power(&left.upper, &right.upper)?;
+            Some(Range::new(lower.min(upper.clone()), upper))
+        }
+        ShiftL => {
+            let lower = right.lower.to_usize().filter(|s| *s < MAX_EXPONENT)?;
+            let upper = right.upper.to_usize().filter(|s| *s < MAX_EXPONENT)?;
+            Some(Range::new(&left.lower << lower, &left.upper << upper))
+        }
+        ShiftR => {
+            let lower = right.upper.to_usize().unwrap_or(MAX_EXPONENT);
+            let upper = right.lower.to_usize().unwrap_or(MAX_EXPONENT);
+            Some(Range::new(&left.lower >> lower, &left.upper >> upper))
+        }
+        IntDiv if !right.upper.is_zero() => {
+            let divisor = right.lower.clone().max(BigInt::one());
+            Some(Range::new(
+                &left.lower / &right.upper,
+                &left.upper / divisor,
+            ))
+        }
+        Mod if !right.upper.is_zero() => Some(Range::new(
+            BigInt::zero(),
+            left.upper.clone().min(&right.upper - 1),
+        )),
+        BitAnd => Some(Range::new(
+            BigInt::zero(),
+            left.upper.clone().min(right.upper.clone()),
+        )),
+        BitOr => Some(Range::new(
+            left.lower.clone().max(right.lower.clone()),
+            mask(&left.upper.clone().max(right.upper.clone())),
+        )),
+        BitXor => Some(Range::new(
+            BigInt::zero(),
+            mask(&left.upper.clone().max(right.upper.clone())),
+        )),
+        LesserEq | GreaterEq | Lesser | Greater | Eq | NotEq | BoolOr | BoolAnd => {
+            Some(Range::boolean())
+        }
+        _ => None,
+    }
+}
+
+fn power(base: &BigInt, exponent: &BigInt) -> Option<BigInt> {
+    if base <= &BigInt::one() || exponent.is_zero() {
+        return Some(if exponent.is_zero() {
+            BigInt::one()
+        } else {
+            base.clone()
+        });
+    }
+    let exponent = exponent.to_usize().filter(|e| *e < MAX_EXPONENT)?;
+    if base.bits() * exponent > MAX_EXPONENT {
+        // an upper bound that is large enough to overflow any field
+        return Some(BigInt::one() << MAX_EXPONENT);
+    }
+    Some(num_traits::pow(base.clone(), exponent))
+}
+
+fn analyse_template<'a>(
+    program_archive: &'a ProgramArchive,
+    field: &'a BigInt,
+    name: &str,
+    seeds: &Seeds,
+    reporting: bool,
+) -> RangeAnalysis<'a> {
+    let template_data = program_archive.get_template_data(name);
+    let mut analysis = RangeAnalysis::new(
+        program_archive,
+        field,
+        template_data.get_file_id(),
+        reporting,
+    );
+    for ((template, input), maxbit) in seeds {
+        if let (true, Some(maxbit)) = (template == name, maxbit) {
+            analysis.input_seeds.insert(input.clone(), *maxbit);
+        }
+    }
+    analysis.analyse_statement(template_data.get_body());
+    analysis
+}
+
+// Infers bounds of the values of the expressions of every template and
+// function, warns when a value can be larger than the prime or when a
+// comparison is applied to values that are compared as negative numbers.
+// The inputs with a maxbit tag are bounded by the maxbit values of the
+// signals connected to them in every call site.
+pub fn range_analysis(program_archive: &ProgramArchive, field: &BigInt) -> ReportCollection {
+    let mut reports = ReportCollection::new();
+    let mut template_names: Vec<&String> = program_archive.get_template_names().iter().collect();
+    template_names.sort();
+    // the call sites can forward the maxbit values of their own inputs, the
+    // values are propagated until they do not change
+    let mut seeds = Seeds::new();
+    for _ in 0..=template_names.len() {
+        let mut call_sites = Seeds::new();
+        for name in &template_names {
+            let analysis = analyse_template(program_archive, field, name, &seeds, false);
+            for (key, maxbit) in analysis.call_sites {
+                add_seed(&mut call_sites, key, maxbit);
+            }
+        }
+        if call_sites == seeds {
+            break;
+        }
+        seeds = call_sites;
+    }
+    for name in template_names {
+        let mut analysis = analyse_template(program_archive, field, name, &seeds, true);
+        reports.append(&mut analysis.reports);
+    }
+    let mut function_names: Vec<&String> = program_archive.get_function_names().iter().collect();
+    function_names.sort();
+    for name in function_names {
+        let function_data = program_archive.get_function_data(name);
+        let mut analysis =
+            RangeAnalysis::new(program_archive, field, function_data.get_file_id(), true);
+        analysis.analyse_statement(function_data.get_body());
+        reports.append(&mut analysis.reports);
+    }
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(lower: u64, upper: u64) -> Range {
+        Range::new(BigInt::from(lower), BigInt::from(upper))
+    }
+
+    #[test]
+    fn bounds_of_the_operations() {
+        use ExpressionInfixOpcode::*;
+        let byte = range(0, 255);
+        let constant = range(3, 3);
+        assert!(infix_range(Mul, &byte, &byte) == Some(range(0, 65025)));
+        assert!(infix_range(Add, &byte, &constant) == Some(range(3, 258)));
+        assert!(infix_range(Sub, &byte, &constant).is_none());
+        assert!(infix_range(Sub, &constant, &range(1, 2)) == Some(range(1, 2)));
+        assert!(infix_range(ShiftL, &byte, &constant) == Some(range(0, 2040)));
+        assert!(infix_range(ShiftR, &byte, &constant) == Some(range(0, 31)));
+        assert!(infix_range(Pow, &range(2, 2), &range(16, 16)) == Some(range(65536, 65536)));
+        assert!(infix_range(BitXor, &byte, &constant) == Some(range(0, 255)));
+        assert!(infix_range(Mod, &byte, &constant) == Some(range(0, 2)));
+        assert!(infix_range(Div, &byte, &constant).is_none());
+    }
+}
@@ -1,238 +1,775 @@
-// use program_structure::ast::{
-//     Access, AssignOp, Expression, ExpressionPrefixOpcode, Meta, SignalElementType, SignalType,
-//     Statement, VariableType,
-// };
-// use program_structure::environment::CircomEnvironment;
-// use program_structure::error_code::ReportCode;
-// use program_structure::error_definition::{Report, ReportCollection};
-// use program_structure::file_definition::{generate_file_location, FileID};
-// use program_structure::program_archive::ProgramArchive;
-// use program_structure::template_data::TemplateData;
-// use std::collections::HashMap;
-
-// type TemplateInfo = HashMap<String, TemplateData>;
-// type Environment = CircomEnvironment<Option<String>, SignalElementType, SignalElementType>;
-// enum ExpressionResult {
-//     ArithmeticExpression(SignalElementType),
-//     Template(Option<String>),
-// }
-
-// pub fn tag_analysis(
-//     template_name: &str,
-//     program_archive: &ProgramArchive,
-// ) -> Result<(), ReportCollection> {
-//     let template_body = program_archive.get_template_data(template_name).get_body_as_vec();
-//     let file_id = program_archive.get_template_data(template_name).get_file_id();
-//     let template_info = program_archive.get_templates();
-
-//     let mut environment = Environment::new();
-//     let args = program_archive.get_template_data(template_name).get_name_of_params().clone();
-//     for arg in args.iter() {
-//         environment.add_variable(arg, SignalElementType::FieldElement);
-//     }
-
-//     let mut reports = ReportCollection::new();
-//     treat_sequence_of_statements(
-//         template_body,
-//         file_id,
-//         template_info,
-//         &mut reports,
-//         &mut environment,
-//     );
-//     if reports.is_empty() {
-//         Result::Ok(())
-//     } else {
-//         Result::Err(reports)
-//     }
-// }
-
-// fn statement_inspection(
-//     stmt: &Statement,
-//     file_id: FileID,
-//     template_info: &TemplateInfo,
-//     reports: &mut ReportCollection,
-//     environment: &mut Environment,
-// ) {
-//     use Statement::*;
-//     match stmt {
-//         IfThenElse { if_case, else_case, .. } => {
-//             statement_inspection(if_case, file_id, template_info, reports, environment);
-//             if let Option::Some(else_stmt) = else_case {
-//                 statement_inspection(else_stmt, file_id, template_info, reports, environment);
-//             }
-//         }
-//         While { stmt, .. } => {
-//             statement_inspection(stmt, file_id, template_info, reports, environment);
-//         }
-//         Block { stmts, .. } => {
-//             environment.add_variable_block();
-//             treat_sequence_of_statements(stmts, file_id, template_info, reports, environment);
-//             environment.remove_variable_block();
-//         }
-//         InitializationBlock { initializations, .. } => {
-//             treat_sequence_of_statements(
-//                 initializations,
-//                 file_id,
-//                 template_info,
-//                 reports,
-//                 environment,
-//             );
-//         }
-//         Declaration { xtype, name, meta, .. } => {
-//             use SignalType::*;
-//             use VariableType::*;
-//             match xtype {
-//                 Var => environment.add_variable(name, SignalElementType::FieldElement),
-//                 Component => environment.add_component(name, meta.component_inference.clone()),
-//                 Signal(signal_type, signal_element) => match signal_type {
-//                     Output => environment.add_output(name, *signal_element),
-//                     Intermediate => environment.add_intermediate(name, *signal_element),
-//                     Input => environment.add_input(name, *signal_element),
-//                 },
-//             }
-//         }
-//         Substitution { meta, var, access, rhe, op, .. } => {
-//             use ExpressionResult::*;
-//             let var_info = variable_inspection(var, access, environment, template_info);
-//             let rhe_info = expression_inspection(rhe, template_info, reports, environment);
-//             match (var_info, rhe_info) {
-//                 (Template(_), Template(assign)) => {
-//                     *environment.get_mut_component_or_break(var, file!(), line!()) = assign;
-//                 }
-//                 (ArithmeticExpression(tag_0), ArithmeticExpression(tag_1))
-//                     if tag_0 == SignalElementType::Binary
-//                         && tag_1 == SignalElementType::FieldElement
-//                         && *op == AssignOp::AssignConstraintSignal =>
-//                 {
-//                     add_report(ReportCode::WrongSignalTags, meta, file_id, reports)
-//                 }
-//                 _ => {}
-//             }
-//         }
-//         _ => {}
-//     }
-// }
-
-      
-// fn expression_inspection(
-//     expr: &Expression,
-//     template_info: &TemplateInfo,
-//     reports: &mut ReportCollection,
-//     environment: &Environment,
-// ) -> ExpressionResult {
-//     use Expression::*;
-//     match expr {
-//         InfixOp { .. } => ExpressionResult::ArithmeticExpression(SignalElementType::FieldElement),
-//         PrefixOp { rhe, prefix_op, .. } => {
-//             let rhe_info = expression_inspection(rhe, template_info, reports, environment);
-//             match prefix_op {
-//                 ExpressionPrefixOpcode::BoolNot => rhe_info,
-//                 _ => ExpressionResult::ArithmeticExpression(SignalElementType::FieldElement),
-//             }
-//         }
-//         InlineSwitchOp { if_true, if_false, .. } => {
-//             let if_true_info = expression_inspection(if_true, template_info, reports, environment);
-//             let if_false_info =
-//                 expression_inspection(if_false, template_info, reports, environment);
-//             match (&if_true_info, &if_false_info) {
-//                 (
-//                     ExpressionResult::ArithmeticExpression(tag_0),
-//                     ExpressionResult::ArithmeticExpression(tag_1),
-//                 ) => {
-//                     if let SignalElementType::FieldElement = tag_0 {
-//                         if_true_info
-//                     } else if let SignalElementType::FieldElement = tag_1 {
-//                         if_false_info
-//                     } else {
-//                         if_true_info
-//                     }
-//                 }
-//                 _ => if_true_info,
-//             }
-//         }
-//         Variable { name, access, .. } => {
-//             variable_inspection(name, access, environment, template_info)
-//         }
-//         Number(..) => ExpressionResult::ArithmeticExpression(SignalElementType::FieldElement),
-//         Call { id, .. } => {
-//             if template_info.contains_key(id) {
-//                 ExpressionResult::Template(Option::Some(id.clone()))
-//             } else {
-//                 ExpressionResult::ArithmeticExpression(SignalElementType::FieldElement)
-//             }
-//         }
-//         ArrayInLine { .. } => {
-//             ExpressionResult::ArithmeticExpression(SignalElementType::FieldElement)
-//         }
-//         UniformArray { .. } => {
-//             ExpressionResult::ArithmeticExpression(SignalElementType::FieldElement)
-//         }
-//         _ => {unreachable!("Anonymous calls should not be reachable at this point."); }
-//     }
-// }
-
-// //************************************************* Statement support *************************************************
-// fn treat_sequence_of_statements(
-//     stmts: &[Statement],
-//     file_id: FileID,
-//     template_info: &TemplateInfo,
-//     reports: &mut ReportCollection,
-//     environment: &mut Environment,
-// ) {
-//     for stmt in stmts {
-//         statement_inspection(stmt, file_id, template_info, reports, environment);
-//     }
-// }
-// //************************************************* Expression support *************************************************
-
-// fn variable_inspection(
-//     symbol: &str,
-//     accesses: &[Access],
-//     environment: &Environment,
-//     template_info: &TemplateInfo,
-// ) -> ExpressionResult {
-//     use ExpressionResult::*;
-//     let mut result = if environment.has_component(symbol) {
-//         Template(environment.get_component_or_break(symbol, file!(), line!()).clone())
-//     } else if environment.has_signal(symbol) {
-//         ArithmeticExpression(*environment.get_signal_or_break(symbol, file!(), line!()))
-//     } else {
-//         ArithmeticExpression(SignalElementType::FieldElement)
-//     };
-
-//     for access in accesses {
-//         if let Access::ComponentAccess(signal) = access {
-//             let template =
-//                 environment.get_component_or_break(symbol, file!(), line!()).clone().unwrap();
-//             let input = template_info.get(&template).unwrap().get_input_info(signal);
-//             let output = template_info.get(&template).unwrap().get_output_info(signal);
-//             match (input, output) {
-//                 (Some((_, tag)), _) | (_, Some((_, tag))) => {
-//                     result = ArithmeticExpression(*tag);
-//                 }
-//                 _ => {
-//                     unreachable!()
-//                 }
-//             }
-//         }
-//     }
-//     result
-// }
-
-// //************************************************* Report support *************************************************
-// fn add_report(
-//     error_code: ReportCode,
-//     meta: &Meta,
-//     file_id: FileID,
-//     reports: &mut ReportCollection,
-// ) {
-//     use ReportCode::*;
-//     let mut report = Report::error("Typing error found".to_string(), error_code);
-//     let location = generate_file_location(meta.start, meta.end);
-//     let message = match error_code {
-//         WrongSignalTags => "Can not assign Field values to signals tagged as binary".to_string(),
-//         _ => panic!("Unimplemented error code"),
-//     };
-//     report.add_primary(location, file_id, message);
-//     reports.push(report);
-// }
+use program_structure::ast::{
+    Access, AssignOp, Expression, LogArgument, Meta, SignalType, Statement, VariableType,
+};
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::{Report, ReportCollection};
+use program_structure::file_definition::{generate_file_location, FileID};
+use program_structure::program_archive::ProgramArchive;
+use program_structure::template_data::TagInfo;
+use std::collections::HashMap;
+
+// What is known about the tags of a signal of the template at a point of the
+// body. The "may" sets hold in some path reaching the point and the "must"
+// sets in all of them, errors are only reported when they happen in every path.
+#[derive(Clone)]
+struct SignalState {
+    is_input: bool,
+    declared_tags: TagInfo,
+    // tags received from the signals assigned to it with <==
+    propagated_tags: TagInfo,
+    initialized_may: bool,
+    initialized_must: bool,
+    assigned_tags_may: TagInfo,
+    assigned_tags_must: TagInfo,
+}
+
+impl SignalState {
+    fn join(&self, other: &SignalState) -> SignalState {
+        SignalState {
+            is_input: self.is_input,
+            declared_tags: self.declared_tags.clone(),
+            propagated_tags: self
+                .propagated_tags
+                .union(&other.propagated_tags)
+                .cloned()
+                .collect(),
+            initialized_may: self.initialized_may || other.initialized_may,
+            initialized_must: self.initialized_must && other.initialized_must,
+            assigned_tags_may: self
+                .assigned_tags_may
+                .union(&other.assigned_tags_may)
+                .cloned()
+                .collect(),
+            assigned_tags_must: self
+                .assigned_tags_must
+                .intersection(&other.assigned_tags_must)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn tags(&self) -> TagInfo {
+        self.declared_tags
+            .union(&self.propagated_tags)
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+struct ComponentState {
+    template: Option<String>,
+    created_may: bool,
+}
+
+#[derive(Clone, Default)]
+struct Environment {
+    signals: HashMap<String, SignalState>,
+    components: HashMap<String, ComponentState>,
+}
+
+impl Environment {
+    fn join(&self, other: &Environment) -> Environment {
+        let mut environment = self.clone();
+        for (name, state) in &other.signals {
+            let joined = match self.signals.get(name) {
+                Some(previous) => previous.join(state),
+                None => state.clone(),
+            };
+            environment.signals.insert(name.clone(), joined);
+        }
+        for (name, state) in &other.components {
+            let joined = match self.components.get(name) {
+                Some(previous) => ComponentState {
+                    template: if previous.template == state.template {
+                        state.template.clone()
+                    } else {
+                        None
+                    },
+                    created_may: previous.created_may || state.created_may,
+                },
+                None => state.clone(),
+            };
+            environment.components.insert(name.clone(), joined);
+        }
+        environment
+    }
+}
+
+struct TagAnalysis<'a> {
+    program_archive: &'a ProgramArchive,
+    file_id: FileID,
+    reports: ReportCollection,
+}
+
+// Follows the tags of the signals through the body of the template and reports
+// the misuses that are otherwise only found during the constraint generation:
+// tags assigned to inputs, after the signal is initialized or twice, tags read
+// before they get a value, tags of subcomponent signals modified outside their
+// template and subcomponent inputs assigned values without the tags they
+// require
+pub fn tag_analysis(
+    template_name: &str,
+    program_archive: &ProgramArchive,
+) -> Result<(), ReportCollection> {
+    let template_data = program_archive.get_template_data(template_name);
+    let mut analysis = TagAnalysis {
+        program_archive,
+        file_id: template_data.get_file_id(),
+        reports: ReportCollection::new(),
+    };
+    let mut environment = Environment::default();
+    for stmt in template_data.get_body_as_vec() {
+        analysis.statement_inspection(stmt, &mut environment);
+    }
+    if analysis.reports.is_empty() {
+        Result::Ok(())
+    } else {
+        Result::Err(analysis.reports)
+    }
+}
+
+impl<'a> TagAnalysis<'a> {
+    fn statement_inspection(&mut self, stmt: &Statement, environment: &mut Environment) {
+        use Statement::*;
+        match stmt {
+            IfThenElse {
+                cond,
+                if_case,
+                else_case,
+                ..
+            } => {
+                self.expression_inspection(cond, environment);
+                let mut else_environment = environment.clone();
+                self.statement_inspection(if_case, environment);
+                if let Option::Some(else_stmt) = else_case {
+                    self.statement_inspection(else_stmt, &mut else_environment);
+                }
+                *environment = environment.join(&else_environment);
+            }
+            While { cond, stmt, .. } => {
+                // the body may not be executed
+                self.expression_inspection(cond, environment);
+                let mut body_environment = environment.clone();
+                self.statement_inspection(stmt, &mut body_environment);
+                *environment = environment.join(&body_environment);
+            }
+            Block { stmts, .. } => {
+                for stmt in stmts {
+                    self.statement_inspection(stmt, environment);
+                }
+            }
+            InitializationBlock {
+                initializations, ..
+            } => {
+                for stmt in initializations {
+                    self.statement_inspection(stmt, environment);
+                }
+            }
+            Declaration {
+                xtype,
+                name,
+                dimensions,
+                ..
+            } => {
+                for dimension in dimensions {
+                    self.expression_inspection(dimension, environment);
+                }
+                environment.signals.remove(name);
+                environment.components.remove(name);
+                match xtype {
+                    VariableType::Signal(signal_type, tags) => {
+                        let state = SignalState {
+                            is_input: *signal_type == SignalType::Input,
+                            declared_tags: tags.iter().cloned().collect(),
+                            propagated_tags: TagInfo::new(),
+                            initialized_may: false,
+                            initialized_must: false,
+                            assigned_tags_may: TagInfo::new(),
+                            assigned_tags_must: TagInfo::new(),
+                        };
+                        environment.signals.insert(name.clone(), state);
+                    }
+                    VariableType::Component | VariableType::AnonymousComponent => {
+                        let state = ComponentState {
+                            template: None,
+                            created_may: false,
+                        };
+                        environment.components.insert(name.clone(), state);
+                    }
+                    VariableType::Var => {}
+                }
+            }
+            Substitution {
+                meta,
+                var,
+                access,
+                op,
+                rhe,
+            } => {
+                self.expression_inspection(rhe, environment);
+                self.access_inspection(access, environment);
+                if environment.signals.contains_key(var) {
+                    match component_accesses(access).first() {
+                        Some(tag) => self.tag_assignment(meta, var, tag, environment),
+                        None => {
+                            let tags = if *op == AssignOp::AssignConstraintSignal {
+                                self.tags_of(rhe, environment)
+                            } else {
+                                None
+                            };
+                            let state = environment.signals.get_mut(var).unwrap();
+                            if let Some(tags) = tags {
+                                state.propagated_tags.extend(tags);
+                            }
+                            state.initialized_may = true;
+                            state.initialized_must = true;
+                        }
+                    }
+                } else if environment.components.contains_key(var) {
+                    match component_accesses(access)[..] {
+                        [signal, tag] => {
+                            let message = format!(
+                                "Tag {} of signal {} can only be modified inside the template of component {}",
+                                tag, signal, var
+                            );
+                            add_report(
+                                ReportCode::OutputTagCannotBeModifiedOutside,
+                                meta,
+                                self.file_id,
+                                message,
+                                &mut self.reports,
+                            );
+                        }
+                        [signal, ..] => self.input_assignment(meta, var, signal, rhe, environment),
+                        [] => {
+                            let template = match rhe {
+                                Expression::Call { id, .. }
+                                    if self.program_archive.contains_template(id) =>
+                                {
+                                    Some(id.clone())
+                                }
+                                _ => None,
+                            };
+                            let state = environment.components.get_mut(var).unwrap();
+                            if !state.created_may || state.template == template {
+                                state.template = template;
+                            } else {
+                                state.template = None;
+                            }
+                            state.created_may = true;
+                        }
+                    }
+                }
+            }
+            MultSubstitution { lhe, rhe, .. } => {
+                self.expression_inspection(lhe, environment);
+                self.expression_inspection(rhe, environment);
+            }
+            UnderscoreSubstitution { rhe, .. } => {
+                self.expression_inspection(rhe, environment);
+            }
+            ConstraintEquality { lhe, rhe, .. } => {
+                self.expression_inspection(lhe, environment);
+                self.expression_inspection(rhe, environment);
+            }
+            LogCall { args, .. } => {
+                for arg in args {
+                    if let LogArgument::LogExp(arg) = arg {
+                        self.expression_inspection(arg, environment);
+                    }
+                }
+            }
+            Return { value, .. } => {
+                self.expression_inspection(value, environment);
+            }
+            Assert { arg, .. } => {
+                self.expression_inspection(arg, environment);
+            }
+        }
+    }
+
+    fn tag_assignment(
+        &mut self,
+        meta: &Meta,
+        signal: &str,
+        tag: &str,
+        environment: &mut Environment,
+    ) {
+        let state = environment.signals.get_mut(signal).unwrap();
+        if !state.declared_tags.contains(tag) {
+            // reported by the type checker
+            return;
+        }
+        if state.is_input {
+            let message = format!(
+                "The value of tag {} of input {} is given by the component that uses the template and cannot be assigned",
+                tag, signal
+            );
+            add_report(
+                ReportCode::InvalidTagAccess,
+                meta,
+                self.file_id,
+                message,
+                &mut self.reports,
+            );
+        } else if state.initialized_must {
+            let message = format!(
+                "Tag {} is assigned after signal {} is initialized, tags must be assigned before the signal",
+                tag, signal
+            );
+            add_report(
+                ReportCode::InvalidTagAccess,
+                meta,
+                self.file_id,
+                message,
+                &mut self.reports,
+            );
+        } else if state.assigned_tags_must.contains(tag) {
+            let message = format!("Tag {} of signal {} already got a value", tag, signal);
+            add_report(
+                ReportCode::InvalidTagAccess,
+                meta,
+                self.file_id,
+                message,
+                &mut self.reports,
+            );
+        }
+        state.assigned_tags_may.insert(tag.to_string());
+        state.assigned_tags_must.insert(tag.to_string());
+    }
+
+    fn input_assignment(
+        &mut self,
+        meta: &Meta,
+        component: &str,
+        signal: &str,
+        rhe: &Expression,
+        environment: &Environment,
+    ) {
+        let required = match self.io_tags(component, signal, environment, true) {
+            Some(required) if !required.is_empty() => required,
+            _ => return,
+        };
+        let given = match self.tags_of(rhe, environment) {
+            Some(given) => given,
+            None => return,
+        };
+        let mut missing: Vec<&String> = required.difference(&given).collect();
+        missing.sort();
+        if let Some(tag) = missing.first() {
+            let message = format!(
+                "Input {} of component {} requires tag {}, but the assigned value does not have it",
+                signal, component, tag
+            );
+            add_report(
+                ReportCode::WrongSignalTags,
+                meta,
+                self.file_id,
+                message,
+                &mut self.reports,
+            );
+        }
+    }
+
+    fn expression_inspection(&mut self, expr: &Expression, environment: &Environment) {
+        use Expression::*;
+        match expr {
+            InfixOp { lhe, rhe, .. } => {
+                self.expression_inspection(lhe, environment);
+                self.expression_inspection(rhe, environment);
+            }
+            PrefixOp { rhe, .. } | ParallelOp { rhe, .. } => {
+                self.expression_inspection(rhe, environment);
+            }
+            InlineSwitchOp {
+                cond,
+                if_true,
+                if_false,
+                ..
+            } => {
+                self.expression_inspection(cond, environment);
+                self.expression_inspection(if_true, environment);
+                self.expression_inspection(if_false, environment);
+            }
+            Variable { meta, name, access } => {
+                self.access_inspection(access, environment);
+                self.variable_inspection(meta, name, access, environment);
+            }
+            Number(..) => {}
+            Call { args, .. } => {
+                for arg in args {
+                    self.expression_inspection(arg, environment);
+                }
+            }
+            AnonymousComp {
+                params, signals, ..
+            } => {
+                for arg in params.iter().chain(signals.iter()) {
+                    self.expression_inspection(arg, environment);
+                }
+            }
+            ArrayInLine { values, .. } | Tuple { values, .. } => {
+                for value in values {
+                    self.expression_inspection(value, environment);
+                }
+            }
+            UniformArray {
+                value, dimension, ..
+            } => {
+                self.expression_inspection(value, environment);
+                self.expression_inspection(dimension, environment);
+            }
+        }
+    }
+
+    fn access_inspection(&mut self, access: &[Access], environment: &Environment) {
+        for acc in access {
+            if let Access::ArrayAccess(index) = acc {
+                self.expression_inspection(index, environment);
+            }
+        }
+    }
+
+    // reads of the value of a tag
+    fn variable_inspection(
+        &mut self,
+        meta: &Meta,
+        symbol: &str,
+        access: &[Access],
+        environment: &Environment,
+    ) {
+        let names = component_accesses(access);
+        if let Some(state) = environment.signals.get(symbol) {
+            let tag = match names.first() {
+                Some(tag) => tag,
+                None => return,
+            };
+            let has_value =
+                state.is_input || state.initialized_may || state.assigned_tags_may.contains(*tag);
+            if state.declared_tags.contains(*tag) && !has_value {
+                let message = format!(
+                    "Tag {} of signal {} is read before it gets a value, assign the tag or initialize the signal first",
+                    tag, symbol
+                );
+                add_report(
+                    ReportCode::UnreachableTags,
+                    meta,
+                    self.file_id,
+                    message,
+                    &mut self.reports,
+                );
+            }
+        } else if let Some(state) = environment.components.get(symbol) {
+            if names.len() == 2 && !state.created_may {
+                let message = format!(
+                    "Tag {} of signal {} is read before component {} is created",
+                    names[1], names[0], symbol
+                );
+                add_report(
+                    ReportCode::UnreachableTags,
+                    meta,
+                    self.file_id,
+                    message,
+                    &mut self.reports,
+                );
+            }
+        }
+    }
+
+    // Tags of the signal of a subcomponent, inputs or outputs
+    fn io_tags(
+        &self,
+        component: &str,
+        signal: &str,
+        environment: &Environment,
+        only_inputs: bool,
+    ) -> Option<TagInfo> {
+        let template = environment.components.get(component)?.template.as_ref()?;
+        let template_data = self.program_archive.get_template_data(template);
+        let info = match template_data.get_input_info(signal) {
+            Some(info) => info,
+            None if only_inputs => return None,
+            None => template_data.get_output_info(signal)?,
+        };
+        Some(info.1.clone())
+    }
+
+    // Tags carried by the value of an expression, None if they are not known.
+    // Only accesses to signals keep their tags, the result of any operation
+    // has none.
+    fn tags_of(&self, expr: &Expression, environment: &Environment) -> Option<TagInfo> {
+        use Expression::*;
+        match expr {
+            Variable { name, access, .. } => {
+                let names = component_accesses(access);
+                if let Some(state) = environment.signals.get(name) {
+                    if names.is_empty() {
+                        return Some(state.tags());
+                    }
+                } else if environment.components.contains_key(name) && names.len() == 1 {
+                    return self.io_tags(name, names[0], environment, false);
+                }
+                None
+            }
+            Number(..) | InfixOp { .. } | PrefixOp { .. } => Some(TagInfo::new()),
+            ParallelOp { rhe, .. } => self.tags_of(rhe, environment),
+            _ => None,
+        }
+    }
+}
+
+//************************************************* Expression support *************************************************
+
+fn component_accesses(access: &[Access]) -> Vec<&String> {
+    access
+        .iter()
+        .filter_map(|acc| match acc {
+            Access::ComponentAccess(name) => Some(name),
+            Access::ArrayAccess(_) => None,
+        })
+        .collect()
+}
+
+//************************************************* Report support *************************************************
+fn add_report(
+    error_code: ReportCode,
+    meta: &Meta,
+    file_id: FileID,
+    message: String,
+    reports: &mut ReportCollection,
+) {
+    let mut report = Report::error("Typing error found".to_string(), error_code);
+    let location = generate_file_location(meta.start, meta.end);
+    report.add_primary(location, file_id, message);
+    reports.push(report);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_types::check_types;
+    use program_structure::error_definition::ReportCollection;
+
+    // errors found in the code
+    fn reports(body: &str) -> ReportCollection {
+        let code = format!(
+            "pragma circom 2.1.0;
+
+template Binary() {{
+    signal input {{binary}} in;
+    signal output {{binary}} out;
+    out <== in;
+}}
+
+template Untagged() {{
+    signal input in;
+    signal output out;
+    out <== in;
+}}
+
+template Tested() {{
+{}
+}}
+
+template Main() {{
+    signal input in;
+    signal output out;
+    out <== in;
+}}
+
+component main = Main();
+",
+            body
+        );
+        let (mut program, _) =
+            parser::run_parser_from_source("tags.circom".to_string(), code, "2.1.9", Vec::new())
+                .ok()
+                .unwrap();
+        match check_types(&mut program) {
+            Result::Ok(_) => ReportCollection::new(),
+            Result::Err(reports) => reports,
+        }
+    }
+
+    // messages of the errors found in the code
+    fn errors(body: &str) -> Vec<String> {
+        reports(body)
+            .iter()
+            .flat_map(|r| r.get_primary().iter().map(|l| l.message.clone()))
+            .collect()
+    }
+
+    fn single_error(body: &str) -> String {
+        let errors = errors(body);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        errors[0].clone()
+    }
+
+    // several codes share their number, so they are compared by name
+    fn single_code(body: &str) -> String {
+        let reports = reports(body);
+        assert_eq!(reports.len(), 1);
+        format!("{:?}", reports[0].get_code())
+    }
+
+    #[test]
+    fn tags_must_be_assigned_before_they_are_used() {
+        let valid = "    signal input in;
+    signal output {maxbit} out;
+    out.maxbit = 8;
+    var bits = out.maxbit;
+    out <== in;";
+        assert!(errors(valid).is_empty());
+
+        let after_initialization = "    signal input in;
+    signal output {maxbit} out;
+    out <== in;
+    out.maxbit = 8;";
+        assert!(single_error(after_initialization)
+            .starts_with("Tag maxbit is assigned after signal out is initialized"));
+        assert_eq!(single_code(after_initialization), "InvalidTagAccess");
+
+        let read_before_assignment = "    signal input in;
+    signal output {maxbit} out;
+    var bits = out.maxbit;
+    out.maxbit = 8;
+    out <== in;";
+        assert!(single_error(read_before_assignment)
+            .starts_with("Tag maxbit of signal out is read before it gets a value"));
+        assert_eq!(single_code(read_before_assignment), "UnreachableTags");
+
+        let twice = "    signal input in;
+    signal output {maxbit} out;
+    out.maxbit = 8;
+    out.maxbit = 9;
+    out <== in;";
+        assert_eq!(
+            single_error(twice),
+            "Tag maxbit of signal out already got a value"
+        );
+
+        // a branch that may not assign the tag is not an error
+        let conditional = "    signal input in;
+    signal output {maxbit} out;
+    var n = 3;
+    if (n > 2) {
+        out.maxbit = 8;
+    }
+    out.maxbit = 9;
+    out <== in;";
+        assert!(errors(conditional).is_empty());
+    }
+
+    #[test]
+    fn tags_of_inputs_are_read_only() {
+        let code = "    signal input {maxbit} in;
+    signal output out;
+    var bits = in.maxbit;
+    out <== in;";
+        let errors = errors(&code.replace("in.maxbit;", "in.maxbit;\n    in.maxbit = 8;"));
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(
+            errors[0].starts_with("The value of tag maxbit of input in is given by the component")
+        );
+        assert!(self::errors(code).is_empty());
+    }
+
+    #[test]
+    fn tags_propagate_through_subcomponent_outputs() {
+        let valid = "    signal input in;
+    signal output out;
+    signal {binary} bit;
+    bit <== in;
+    component first = Binary();
+    first.in <== bit;
+    component second = Binary();
+    second.in <== first.out;
+    var is_binary = second.out.binary;
+    out <== second.out;";
+        assert!(errors(valid).is_empty());
+
+        let untagged_output = valid.replace(
+            "component first = Binary();",
+            "component first = Untagged();",
+        );
+        assert_eq!(
+            single_error(&untagged_output),
+            "Input in of component second requires tag binary, but the assigned value does not have it"
+        );
+
+        let before_creation = "    signal input in;
+    signal output out;
+    component c;
+    var is_binary = c.out.binary;
+    c = Untagged();
+    c.in <== in;
+    out <== c.out;";
+        assert_eq!(
+            single_error(before_creation),
+            "Tag binary of signal out is read before component c is created"
+        );
+        assert_eq!(single_code(before_creation), "UnreachableTags");
+
+        // only the template of the component gives values to its tags
+        let modified_outside = valid.replace(
+            "var is_binary = second.out.binary;",
+            "second.out.binary = 1;",
+        );
+        assert_eq!(
+            single_error(&modified_outside),
+            "Tag binary of signal out can only be modified inside the template of component second"
+        );
+        assert_eq!(
+            single_code(&modified_outside),
+            "OutputTagCannotBeModifiedOutside"
+        );
+    }
+
+    #[test]
+    fn arrays_share_their_tags() {
+        let valid = "    signal input in[2];
+    signal output {maxbit} out[2];
+    out.maxbit = 8;
+    out[0] <== in[0];
+    out[1] <== in[1];";
+        assert!(errors(valid).is_empty());
+
+        let after_initialization = "    signal input in[2];
+    signal output {maxbit} out[2];
+    out[0] <== in[0];
+    out.maxbit = 8;
+    out[1] <== in[1];";
+        assert!(single_error(after_initialization)
+            .starts_with("Tag maxbit is assigned after signal out is initialized"));
+    }
+
+    #[test]
+    fn binary_inputs_need_binary_signals() {
+        let tagged = "    signal input in;
+    signal output out;
+    signal {binary} bit;
+    bit <== in;
+    component c = Binary();
+    c.in <== bit;
+    out <== c.out;";
+        assert!(errors(tagged).is_empty());
+
+        // the tags are propagated by <== from the signals
+        let propagated = tagged.replace(
+            "    c.in <== bit;",
+            "    signal copy;\n    copy <== bit;\n    c.in <== copy;",
+        );
+        assert!(errors(&propagated).is_empty());
+
+        let untagged = tagged.replace("c.in <== bit;", "c.in <== in;");
+        assert_eq!(
+            single_error(&untagged),
+            "Input in of component c requires tag binary, but the assigned value does not have it"
+        );
+        let expression = tagged.replace("c.in <== bit;", "c.in <== bit * bit;");
+        assert_eq!(
+            single_error(&expression),
+            "Input in of component c requires tag binary, but the assigned value does not have it"
+        );
+    }
+}
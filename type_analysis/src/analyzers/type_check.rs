@@ -226,15 +226,6 @@ fn type_statement(
                 return;
             };
 
-            if analysis_information.environment.has_component(var) && access_information.2.is_some()
-            {
-                return add_report(
-                    ReportCode::OutputTagCannotBeModifiedOutside,
-                    meta,
-                    &mut analysis_information.reports,
-                );
-            }
-
             let symbol_type_result = apply_access_to_symbol(
                 var,
                 meta,
@@ -1150,7 +1141,6 @@ fn add_report(error_code: ReportCode, meta: &Meta, reports: &mut ReportCollectio
               format!("Must be a single arithmetic expression.\n Found component")
         }
         MustBeArithmetic => "Must be a single arithmetic expression or an array of arithmetic expressions. \n Found component".to_string(),
-        OutputTagCannotBeModifiedOutside => "Output tag from a subcomponent cannot be modified".to_string(),
         MustBeSameDimension(dim_1, dim_2) =>{
             format!("Must be two arrays of the same dimensions.\n Found {} and {} dimensions", dim_1, dim_2)
         }
@@ -1,5 +1,6 @@
 use super::analyzers::*;
 use super::decorators::*;
+use num_bigint::BigInt;
 use program_structure::error_definition::ReportCollection;
 use program_structure::program_archive::ProgramArchive;
 
@@ -67,6 +68,11 @@ pub fn check_types(
     }
 }
 
+// Lints that depend on the prime, they only produce warnings
+pub fn check_ranges(program_archive: &ProgramArchive, field: &BigInt) -> ReportCollection {
+    range_analysis(program_archive, field)
+}
+
 fn program_level_analyses(program_archive: &ProgramArchive, reports: &mut ReportCollection) {
     let symbols_in_body_well_defined_result = check_naming_correctness(program_archive);
     if let Result::Err(mut symbols_in_body_well_defined_reports) =
@@ -77,15 +83,19 @@ fn program_level_analyses(program_archive: &ProgramArchive, reports: &mut Report
 }
 
 fn template_level_analyses(program_archive: &ProgramArchive, reports: &mut ReportCollection) {
-    for template_data in program_archive.get_templates().values() {
+    for (template_name, template_data) in program_archive.get_templates() {
         let no_returns_in_template_result = free_of_returns(template_data);
         let signal_declaration_result = check_signal_correctness(template_data);
+        let tag_analysis_result = tag_analysis(template_name, program_archive);
         if let Result::Err(mut no_returns_reports) = no_returns_in_template_result {
             reports.append(&mut no_returns_reports);
         }
         if let Result::Err(mut signal_declaration_reports) = signal_declaration_result {
             reports.append(&mut signal_declaration_reports);
         }
+        if let Result::Err(mut tag_analysis_reports) = tag_analysis_result {
+            reports.append(&mut tag_analysis_reports);
+        }
     }
 }
 